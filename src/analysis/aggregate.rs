@@ -0,0 +1,108 @@
+use std::fmt::{Display, Formatter};
+
+/**********************************************************************************
+ * Aggregation specifications: reductions over the elements of an array, e.g.     *
+ * `sum($.students[].grades[])`, which are compiled into streamlets that produce  *
+ * one result per array.                                                          *
+ **********************************************************************************/
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reduction {
+    Count,
+    Sum,
+    Min,
+    Max,
+}
+
+impl Reduction {
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Reduction::Count => "count",
+            Reduction::Sum => "sum",
+            Reduction::Min => "min",
+            Reduction::Max => "max",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregation {
+    reduction: Reduction,
+    path: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregateError(String);
+
+impl Display for AggregateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Aggregation {
+    pub fn get_reduction(&self) -> Reduction {
+        self.reduction
+    }
+
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    /// Parse an aggregation of the form `<reduction>(<path>)`
+    pub fn parse(spec: &str) -> Result<Aggregation, AggregateError> {
+        let spec = spec.trim();
+
+        let (name, rest) = spec.split_once('(')
+            .ok_or_else(|| AggregateError(format!("expected '<reduction>(<path>)', found '{}'", spec)))?;
+
+        let path = rest.strip_suffix(')')
+            .ok_or_else(|| AggregateError(format!("expected ')' at the end of '{}'", spec)))?
+            .trim();
+
+        let reduction = match name.trim() {
+            "count" => Reduction::Count,
+            "sum" => Reduction::Sum,
+            "min" => Reduction::Min,
+            "max" => Reduction::Max,
+            name => return Err(AggregateError(format!("unknown reduction '{}', expected count, sum, min or max", name))),
+        };
+
+        // Only the elements of an array have something to reduce over
+        if !path.starts_with('$') || !path.ends_with("[]") {
+            return Err(AggregateError(format!("expected the path of the elements of an array, found '{}'", path)));
+        }
+
+        Ok(Aggregation { reduction, path: path.to_string() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_aggregations() {
+        let aggregation = Aggregation::parse(" max( $.students[].grades[] ) ").unwrap();
+        assert_eq!(aggregation.get_reduction(), Reduction::Max);
+        assert_eq!(aggregation.get_path(), "$.students[].grades[]");
+
+        for (spec, reduction) in [("count($[])", Reduction::Count), ("sum($.a[])", Reduction::Sum), ("min($.a[])", Reduction::Min)] {
+            assert_eq!(Aggregation::parse(spec).unwrap().get_reduction(), reduction);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_aggregations() {
+        for (spec, error) in [
+            ("$.a[]", "expected '<reduction>(<path>)'"),
+            ("sum($.a[]", "expected ')'"),
+            ("avg($.a[])", "unknown reduction 'avg'"),
+            ("sum($.a)", "the path of the elements of an array"),
+            ("sum(a[])", "the path of the elements of an array"),
+        ] {
+            let err = Aggregation::parse(spec).unwrap_err().to_string();
+            assert!(err.contains(error), "'{}' failed with '{}'", spec, err);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::analysis::{components::{JsonComponent, JsonComponentValue, JsonType, Generatable, Value}, aggregate::{Aggregation, Reduction}, types::{TilStreamlet, TilSignal, TilStreamingInterface, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}, til_streamlet::TilImplementationType}};
+
+use super::{Analyzer, AnalyzerError, file_manager::TemplateType, type_manager::StreamType};
+
+/**********************************************************************************
+ * Netlist pass that reduces the values of an array field to one result per      *
+ * array and adds the results to the outputs of the top component.               *
+ **********************************************************************************/
+
+impl Analyzer {
+    pub fn insert_aggregations(&mut self) -> Result<(), AnalyzerError> {
+        for aggregation in self.gen_params.outputs.aggregations.clone() {
+            self.insert_aggregation(&aggregation)?;
+        }
+
+        Ok(())
+    }
+
+    // Find the parser of the values at a path
+    fn find_value(&self, component: &JsonComponent, path: &str) -> Option<Value> {
+        if let JsonComponent::Value(value) = component {
+            if self.json_paths.get(value.get_name()).map(String::as_str) == Some(path) {
+                return Some(value.clone());
+            }
+        }
+
+        component.get_children().iter().find_map(|child| self.find_value(child, path))
+    }
+
+    fn insert_aggregation(&mut self, aggregation: &Aggregation) -> Result<(), AnalyzerError> {
+        let value = self.top_component.as_ref()
+            .and_then(|root| self.find_value(root, aggregation.get_path()))
+            .filter(|value| matches!(value.get_data_type(), JsonType::Integer | JsonType::Boolean))
+            .ok_or_else(|| AnalyzerError::InvalidAggregation(format!("no integer or boolean array elements at path '{}'", aggregation.get_path())))?;
+
+        let input_type = match value.get_data_type() {
+            JsonType::Boolean => StreamType::Bool,
+            _ => value.get_int_type(),
+        };
+
+        // Counts (and sums of booleans) are counters, the others keep the type of the values
+        let output_type = match (aggregation.get_reduction(), &input_type) {
+            (Reduction::Count, _) | (Reduction::Sum, StreamType::Bool) => StreamType::Count,
+            _ => input_type.clone(),
+        };
+
+        // The result belongs to the record that holds the array
+        let dim = value.get_nesting_level() - 1;
+        let name = self.name_reg.register(&format!("{}_aggregator", aggregation.get_reduction().get_name()), dim);
+        let inst_name = format!("{}_inst", name);
+
+        let mut interface = TilStreamingInterface::default();
+
+        let dim_name = "NESTING_LEVEL";
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(dim)));
+        if output_type == StreamType::Count {
+            interface.add_generic(Generic::new("BITWIDTH", GenericType::Positive(self.gen_params.int_width)));
+        }
+
+        interface.add_stream("input", TilStreamDirection::Input,
+            StreamTypeDecl::new(input_type.clone(), Some(StreamDim::new(Some(dim_name.to_string()), dim, 1)))
+        );
+        interface.add_stream("output", TilStreamDirection::Output,
+            StreamTypeDecl::new(output_type.clone(), Some(StreamDim::new(Some(dim_name.to_string()), dim, 0)))
+        );
+
+        // The aggregator taps the output of the parser, next to wherever it already goes
+        self.signal_manager.add_signal(TilSignal::Intermediate {
+            source_inst_name: value.get_instance_name(),
+            source_stream_name: "output".to_owned(),
+            dest_inst_name: inst_name.clone(),
+            dest_stream_name: "input".to_owned(),
+        });
+
+        let output_name = format!("output_{}", inst_name);
+        self.signal_manager.add_signal(TilSignal::Output {
+            source_inst_name: inst_name,
+            source_stream_name: "output".to_owned(),
+            dest_stream_name: output_name.clone(),
+            output_stream: TilStream::new(&output_name, TilStreamDirection::Output,
+                StreamTypeDecl::new(output_type.clone(), Some(StreamDim::new(None, dim, 0)))
+            ),
+        });
+
+        let mut streamlet = TilStreamlet::new(&name);
+        streamlet.set_streaming_interface(interface);
+        streamlet.set_implementation(TilImplementationType::Path("./vhdl_dir".to_string()));
+
+        self.type_manager.register(output_type.clone());
+        self.json_paths.insert(name.clone(), aggregation.get_path().to_owned());
+        self.file_manager.add_entity(TemplateType::Aggregate { reduction: aggregation.get_reduction(), input_type, output_type }, &name);
+        self.entity_list.push(streamlet);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{GeneratorError, checker::check_design, types::stream_types::StreamTypeDecl, analyzer::{AnalyzerError, type_manager::StreamType}, testing::{connections, design_of}};
+
+    #[test]
+    fn reduces_the_elements_of_every_array() {
+        for (spec, parser, counted) in [("count($.a[].b[])", "int_parser", true), ("sum($.a[].c[])", "bool_parser", true), ("max($.a[].b[])", "int_parser", false)] {
+            let design = design_of(r#"{"a": [{"b": [1, 2], "c": [true]}]}"#, |generator| generator.add_aggregation(spec).unwrap()).unwrap();
+
+            let aggregator = design.get_streamlets().iter().find(|streamlet| streamlet.get_name().contains("_aggregator")).unwrap();
+            let input = aggregator.get_streams().get_input_streams()[0].get_type();
+            let output = aggregator.get_streams().get_output_streams()[0].get_type();
+
+            // One result per array instead of one per element
+            let dim = |decl: &StreamTypeDecl| decl.get_stream_dim().as_ref().unwrap().get_true_value();
+            assert_eq!(dim(input), dim(output) + 1);
+            assert_eq!(output.get_stream_type() == StreamType::Count, counted, "{}", spec);
+
+            // The aggregator taps the values of the elements next to their own output, and its result leaves the design
+            let connections = connections(&design.get_top_components()[0]);
+            let inst_name = format!("{}_inst", aggregator.get_name());
+            let source = connections.iter().find(|(_, sink)| *sink == format!("{}.input", inst_name)).map(|(source, _)| source.clone()).unwrap();
+            let tapped = connections.iter().find(|(_, sink)| *sink == source.replace(".output0", ".input")).map(|(source, _)| source.clone()).unwrap();
+            assert!(tapped.starts_with(parser), "{} aggregates {}", spec, tapped);
+            assert!(connections.iter().any(|(source, sink)| *source == format!("{}.output", inst_name) && !sink.contains('.')));
+
+            assert!(check_design(&design).is_empty());
+        }
+    }
+
+    #[test]
+    fn rejects_aggregations_of_other_values() {
+        for spec in ["sum($.names[])", "sum($.missing[])"] {
+            let result = design_of(r#"{"names": ["a", "b"]}"#, |generator| generator.add_aggregation(spec).unwrap());
+            assert!(matches!(result, Err(GeneratorError::AnalyzerError(AnalyzerError::InvalidAggregation(_)))), "{}", spec);
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use json::JsonValue;
+use crate::analysis::{components::{JsonComponent, JsonType, Record, Key, Value, Array, Matcher, Union, Map, Tuple, Raw, Enumeration, Generatable}, schema::Schema};
 
-use crate::analysis::components::{JsonComponent, JsonType, Record, Key, Value, Array, Matcher, Generatable};
+use crate::analysis::params::ComponentParam;
 
 use super::Analyzer;
 
@@ -8,15 +8,19 @@ impl Analyzer {
     // Analyze a record of the JSON object
     // Which results in the creation of 3 components:
     // A matcher, a key and a record
-    pub fn analyze_record(&mut self, key: &str, element: &JsonValue, outer_nesting: usize, inner_nesting: usize) -> (Option<Key>, usize) {
-        let (child, new_inner_nesting) = self.analyze_element(element, outer_nesting + 1, inner_nesting);
+    pub fn analyze_record(&mut self, key: &str, element: &Schema, outer_nesting: usize, inner_nesting: usize, path: &str) -> (Option<Key>, usize) {
+        // The path of the field inside the document
+        let field_path = format!("{}.{}", path, key);
+
+        let (child, new_inner_nesting) = self.analyze_element(element, outer_nesting + 1, inner_nesting, &field_path);
 
         let key_name = self.name_reg.register("key_parser", outer_nesting + 2);
         let matcher_name = self.name_reg.register(&format!("{}_matcher", key), outer_nesting + 2);
 
         // Create a components
         let matcher = Matcher::new(&matcher_name, &key_name, key.to_string(), outer_nesting + 2);
-        let key = Key::new(&key_name, matcher.clone(), outer_nesting + 2, child.map(Box::new));
+        let mut key = Key::new(&key_name, matcher.clone(), outer_nesting + 2, child.map(Box::new));
+        key.set_buffer_depth(self.gen_params.get_component_param(ComponentParam::KeyBufferDepth, &field_path));
         
         // Convert to TilComponent
         let matcher_component = matcher.to_til_streamlet(&self.gen_params);
@@ -38,16 +42,74 @@ impl Analyzer {
         self.file_manager.add_entity(matcher.get_file_type(), matcher.get_name());
         self.file_manager.add_entity(key.get_file_type(), key.get_name());
 
+        // The key belongs to the record, the value it passes on may have fewer lanes
+        let epc = self.gen_params.get_epc(path);
+        self.set_lanes(matcher.get_name(), epc);
+        self.set_lanes(key.get_name(), epc);
+
+        // Register the path the components belong to
+        self.json_paths.insert(matcher.get_name().to_owned(), field_path.clone());
+        self.json_paths.insert(key.get_name().to_owned(), field_path);
 
         // Return the key and keep the same inner nesting level
         (Some(key), new_inner_nesting)  
     }
 
+    // Analyze the values of an object with dynamic keys
+    // Which results in a map parser that splits the entries into keys and values
+    // and a single parser for all values
+    pub fn analyze_map(&mut self, value: &Schema, outer_nesting: usize, inner_nesting: usize, path: &str) -> (Map, usize) {
+        // The path of every value inside the document
+        let value_path = format!("{}.*", path);
+
+        let (child, new_inner_nesting) = self.analyze_element(value, outer_nesting + 1, inner_nesting, &value_path);
+
+        let map = Map::new(&self.name_reg.register("map_parser", outer_nesting + 2), outer_nesting + 2, child.map(Box::new));
+
+        // Add the map parser like any other component
+        self.entity_list.push(map.to_til_streamlet(&self.gen_params));
+        self.type_manager.register_from_component(&map);
+        self.signal_manager.add_multiple_signals(map.get_outgoing_signals());
+        self.file_manager.add_entity(map.get_file_type(), map.get_name());
+        self.json_paths.insert(map.get_name().to_owned(), value_path.clone());
+        self.set_lanes(map.get_name(), self.gen_params.get_epc(&value_path));
+
+        (map, new_inner_nesting)
+    }
+
+    // Analyze the positions of a tuple array
+    // Which results in a demultiplexer that sends every element to the parser of its position
+    pub fn analyze_tuple(&mut self, positions: &[Schema], outer_nesting: usize, inner_nesting: usize, path: &str) -> (Tuple, usize) {
+        let mut children = Vec::new();
+        let mut new_inner_nesting = Vec::new();
+
+        for (idx, position) in positions.iter().enumerate() {
+            let (child, ret_inner_nesting) = self.analyze_element(position, outer_nesting + 1, inner_nesting, &format!("{}[{}]", path, idx));
+
+            children.push(child.map(Box::new));
+            new_inner_nesting.push(ret_inner_nesting);
+        }
+
+        let max_inner_nesting = new_inner_nesting.into_iter().max().unwrap_or(inner_nesting);
+
+        let tuple = Tuple::new(&self.name_reg.register("tuple_demux", outer_nesting + 2), outer_nesting + 2, children);
+
+        // Add the demultiplexer like any other component
+        self.entity_list.push(tuple.to_til_streamlet(&self.gen_params));
+        self.type_manager.register_from_component(&tuple);
+        self.signal_manager.add_multiple_signals(tuple.get_outgoing_signals());
+        self.file_manager.add_entity(tuple.get_file_type(), tuple.get_name());
+        self.json_paths.insert(tuple.get_name().to_owned(), format!("{}[]", path));
+        self.set_lanes(tuple.get_name(), self.gen_params.get_epc(&format!("{}[]", path)));
+
+        (tuple, max_inner_nesting)
+    }
+
     // Analyze the element and recursively call itself if it is an object or array to find nested elements
-    pub fn analyze_element(&mut self, element: &JsonValue, outer_nesting: usize, inner_nesting: usize) -> (Option<JsonComponent>, usize) {
+    pub fn analyze_element(&mut self, element: &Schema, outer_nesting: usize, inner_nesting: usize, path: &str) -> (Option<JsonComponent>, usize) {
         let (component, new_inner_nesting) = match element {
             // Element has string type
-            JsonValue::Short(_) | JsonValue::String(_) => 
+            Schema::String => 
                 (
                     Some(
                         JsonComponent::Value(
@@ -61,23 +123,74 @@ impl Analyzer {
                     // Types don't increase the nesting level
                     inner_nesting
                 ),
-            // Element has integer type
-            JsonValue::Number(_) => 
+            // Element is a string of which the escapes are decoded
+            Schema::Decoded =>
                 (
                     Some(
                         JsonComponent::Value(
                             Value::new(
-                                &self.name_reg.register("int_parser", outer_nesting + 1),
-                                JsonType::Integer,
+                                &self.name_reg.register("string_decoder", outer_nesting),
+                                JsonType::Decoded,
                                 outer_nesting + 1,
                             )
                         )
-                    ), 
+                    ),
                     // Types don't increase the nesting level
                     inner_nesting
                 ),
+            // Element is a string with a fixed set of values
+            Schema::Enum(values) =>
+                (
+                    Some(
+                        JsonComponent::Enumeration(
+                            Enumeration::new(
+                                &self.name_reg.register("enum_matcher", outer_nesting),
+                                outer_nesting + 1,
+                                values.clone(),
+                            )
+                        )
+                    ),
+                    // Types don't increase the nesting level
+                    inner_nesting
+                ),
+            // Element is a string that is parsed in its format
+            Schema::Formatted(format) =>
+                (
+                    Some(
+                        JsonComponent::Value(
+                            Value::new(
+                                &self.name_reg.register(&format.get_parser_name(), outer_nesting + 1),
+                                JsonType::Formatted(*format),
+                                outer_nesting + 1,
+                            )
+                        )
+                    ),
+                    // Types don't increase the nesting level
+                    inner_nesting
+                ),
+            // Element has integer type
+            Schema::Integer => {
+                let mut value = Value::new(
+                    &self.name_reg.register("int_parser", outer_nesting + 1),
+                    JsonType::Integer,
+                    outer_nesting + 1,
+                );
+
+                // The width can be configured per path
+                let int_width = self.gen_params.get_int_width(path);
+                if int_width != self.gen_params.int_width {
+                    value.set_int_width(int_width);
+                }
+                value.set_pipeline_stages(self.gen_params.get_component_param(ComponentParam::PipelineStages, path));
+
+                (
+                    Some(JsonComponent::Value(value)),
+                    // Types don't increase the nesting level
+                    inner_nesting
+                )
+            },
             // Element has boolean type
-            JsonValue::Boolean(_) => 
+            Schema::Boolean => 
                 (
                     Some(
                         JsonComponent::Value(
@@ -92,41 +205,62 @@ impl Analyzer {
                     inner_nesting
                 ),
             // Element is an array
-            JsonValue::Array(arr) => {
-                // If the array is empty, return None
-                if arr.is_empty() {
-                    return (None, inner_nesting + 1);
-                }
+            Schema::Array(child_element) => {
+                // If the array is always empty, return None
+                let child_element = match child_element {
+                    Some(child_element) => child_element,
+                    None => return (None, inner_nesting + 1),
+                };
+
+                // The merged schema of all elements determines the type of the array
+                let (child, new_inner_nesting) = self.analyze_element(child_element, outer_nesting + 1, inner_nesting, &format!("{}[]", path));
 
-                // Get the first element of the array to determine the type of the array
-                let child_element = &arr[0];
-                let (child, new_inner_nesting) = self.analyze_element(child_element, outer_nesting + 1, inner_nesting);
+                let mut array = Array::new(
+                    &self.name_reg.register("array_parser", outer_nesting + 1),
+                    outer_nesting + 1,
+                    new_inner_nesting,
+                    child.map(Box::new),
+                    self.gen_params.outputs.array_length,
+                    self.gen_params.outputs.array_index
+                );
+                array.set_counter_width(self.gen_params.get_component_param(ComponentParam::ElementCounterWidth, path));
 
                 // Return the array with the child element
                 (
-                    Some(
-                        JsonComponent::Array(
-                            Array::new(
-                                &self.name_reg.register("array_parser", outer_nesting + 1),
-                                outer_nesting + 1,
-                                new_inner_nesting,
-                                child.map(Box::new)
-                            )
-                        )
-                    ),
+                    Some(JsonComponent::Array(array)),
+                    // An array increases the inner nesting by 1
+                    new_inner_nesting + 1
+                )
+            },
+            // Element is an array with a type per position
+            Schema::Tuple(positions) => {
+                let (tuple, new_inner_nesting) = self.analyze_tuple(positions, outer_nesting, inner_nesting, path);
+
+                let mut array = Array::new(
+                    &self.name_reg.register("array_parser", outer_nesting + 1),
+                    outer_nesting + 1,
+                    new_inner_nesting,
+                    Some(Box::new(JsonComponent::Tuple(tuple))),
+                    self.gen_params.outputs.array_length,
+                    self.gen_params.outputs.array_index
+                );
+                array.set_counter_width(self.gen_params.get_component_param(ComponentParam::ElementCounterWidth, path));
+
+                (
+                    Some(JsonComponent::Array(array)),
                     // An array increases the inner nesting by 1
                     new_inner_nesting + 1
                 )
             },
             // Element is an object
-            JsonValue::Object(_) => {
+            Schema::Object(fields) => {
                 let mut children: Vec<Key> = Vec::new();
                 let mut new_inner_nesting = Vec::new();
 
                 // Analyze all the records of the object
-                for key in element.entries() {
+                for key in fields {
                     // Analyze the record
-                    let (child, ret_inner_nesting) = self.analyze_record(key.0, key.1, outer_nesting, inner_nesting);
+                    let (child, ret_inner_nesting) = self.analyze_record(&key.0, &key.1, outer_nesting, inner_nesting, path);
                     
                     // Push record if it is not None
                     if let Some(key) = child {
@@ -156,7 +290,69 @@ impl Analyzer {
                     max_inner_nesting + 1
                 )
             },
-            JsonValue::Null => (None, inner_nesting),
+            // Element is an object with dynamic keys
+            Schema::Map(value) => {
+                let (map, new_inner_nesting) = self.analyze_map(value, outer_nesting, inner_nesting, path);
+
+                (
+                    Some(
+                        JsonComponent::Record(
+                            Record::new_map(
+                                &self.name_reg.register("record_parser", outer_nesting + 1),
+                                outer_nesting + 1,
+                                new_inner_nesting,
+                                map
+                            )
+                        )
+                    ),
+                    // An object increases the inner nesting by 1
+                    new_inner_nesting + 1
+                )
+            },
+            // Element has a different type between samples
+            Schema::Union(variants) => {
+                let mut children = Vec::new();
+                let mut new_inner_nesting = Vec::new();
+
+                // Every variant is parsed as if it was the only type of the element
+                for variant in variants {
+                    let (child, ret_inner_nesting) = self.analyze_element(variant, outer_nesting, inner_nesting, path);
+
+                    children.push((variant.get_kind().to_string(), child.map(Box::new)));
+                    new_inner_nesting.push(ret_inner_nesting);
+                }
+
+                let max_inner_nesting = new_inner_nesting.into_iter().max().unwrap_or(inner_nesting);
+
+                (
+                    Some(
+                        JsonComponent::Union(
+                            Union::new(
+                                &self.name_reg.register("union_router", outer_nesting + 1),
+                                outer_nesting + 1,
+                                children
+                            )
+                        )
+                    ),
+                    // The router does not add a level of nesting itself
+                    max_inner_nesting
+                )
+            },
+            // Element is forwarded without being parsed
+            Schema::Raw(schema) =>
+                (
+                    Some(
+                        JsonComponent::Raw(
+                            Raw::new(
+                                &self.name_reg.register("raw_passthrough", outer_nesting + 1),
+                                outer_nesting + 1,
+                            )
+                        )
+                    ),
+                    // The parent still has to track the objects and arrays inside the text
+                    inner_nesting + schema.get_depth()
+                ),
+            Schema::Null => (None, inner_nesting),
         };
 
         // Check if there is a component
@@ -178,6 +374,11 @@ impl Analyzer {
 
             // Add entity to file manager
             self.file_manager.add_entity(gen_component.get_file_type(), gen_component.get_name());
+
+            // Register the path the component belongs to
+            self.json_paths.insert(gen_component.get_name().to_owned(), path.to_owned());
+
+            self.set_lanes(gen_component.get_name(), self.gen_params.get_epc(path));
         }
 
         // Return the component and the new inner nesting level
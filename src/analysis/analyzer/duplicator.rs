@@ -0,0 +1,189 @@
+use crate::analysis::types::{TilStreamlet, TilSignal, TilStreamingInterface, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}, til_streamlet::TilImplementationType};
+
+use super::{Analyzer, file_manager::TemplateType};
+
+/**********************************************************************************
+ * Netlist pass that resolves fan-out: every output that drives more than one     *
+ * sink gets a duplicator streamlet which hands each transfer to all sinks.       *
+ **********************************************************************************/
+
+impl Analyzer {
+    pub fn insert_duplicators(&mut self) {
+        let signals = self.signal_manager.take_signals();
+
+        // Group the signals by the output they are driven by, keeping the original order
+        let mut groups: Vec<((String, String), Vec<TilSignal>)> = Vec::new();
+        for signal in signals {
+            let source = (
+                signal.get_source_inst_name().cloned().unwrap_or_default(),
+                signal.get_source_stream_name().clone(),
+            );
+
+            match groups.iter_mut().find(|(group_source, _)| *group_source == source) {
+                Some((_, group)) => group.push(signal),
+                None => groups.push((source, vec![signal])),
+            }
+        }
+
+        for ((source_inst, source_stream), group) in groups {
+            if group.len() == 1 {
+                self.signal_manager.add_multiple_signals(group);
+                continue;
+            }
+
+            // Find the stream of the source component to give the duplicator the same type
+            let source_component = self.entity_list.iter()
+                .find(|streamlet| format!("{}_inst", streamlet.get_name()) == source_inst)
+                .map(|streamlet| streamlet.get_name().to_owned());
+            let stream = source_component.as_ref().and_then(|component| self.find_stream(component, &source_stream));
+
+            let (source_component, stream) = match (source_component, stream) {
+                (Some(component), Some(stream)) => (component, stream),
+                // Leave unknown sources to the design rule checker
+                _ => {
+                    self.signal_manager.add_multiple_signals(group);
+                    continue;
+                },
+            };
+
+            let duplicator = self.create_duplicator(&stream, group.len());
+            let duplicator_inst = format!("{}_inst", duplicator.get_name());
+
+            self.signal_manager.add_signal(TilSignal::Intermediate {
+                source_inst_name: source_inst,
+                source_stream_name: source_stream,
+                dest_inst_name: duplicator_inst.clone(),
+                dest_stream_name: "input".to_owned(),
+            });
+
+            // Every original sink is now driven by its own output of the duplicator
+            for (idx, signal) in group.into_iter().enumerate() {
+                let output_name = format!("output{}", idx);
+
+                self.signal_manager.add_signal(match signal {
+                    TilSignal::Intermediate { dest_inst_name, dest_stream_name, .. } => TilSignal::Intermediate {
+                        source_inst_name: duplicator_inst.clone(),
+                        source_stream_name: output_name,
+                        dest_inst_name,
+                        dest_stream_name,
+                    },
+                    TilSignal::Output { dest_stream_name, output_stream, .. } => TilSignal::Output {
+                        source_inst_name: duplicator_inst.clone(),
+                        source_stream_name: output_name,
+                        dest_stream_name,
+                        output_stream,
+                    },
+                    input => input,
+                });
+            }
+
+            // The duplicator belongs to the same part of the document as its source
+            if let Some(json_path) = self.json_paths.get(&source_component).cloned() {
+                self.json_paths.insert(duplicator.get_name().to_owned(), json_path);
+            }
+
+            self.entity_list.push(duplicator);
+        }
+    }
+
+    fn find_stream(&self, component_name: &str, stream_name: &str) -> Option<TilStream> {
+        self.entity_list.iter()
+            .find(|streamlet| streamlet.get_name() == component_name)?
+            .get_streams().get_streams().iter()
+            .find(|stream| stream.get_name() == stream_name)
+            .cloned()
+    }
+
+    fn create_duplicator(&mut self, stream: &TilStream, outputs: usize) -> TilStreamlet {
+        let stream_type = stream.get_type().get_stream_type();
+        let true_dim = stream.get_type().get_stream_dim().as_ref().map(|dim| dim.get_true_value());
+
+        let name = self.name_reg.register("duplicator", true_dim.unwrap_or(0));
+
+        let mut interface = TilStreamingInterface::default();
+
+        let physical = stream_type.get_physical_params(&self.gen_params);
+        if physical.lanes > 1 {
+            interface.add_generic(Generic::new("EPC", GenericType::Positive(physical.lanes)));
+        }
+
+        let dim_name = "NESTING_LEVEL";
+        let stream_dim = true_dim.map(|dim| {
+            interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(dim)));
+            StreamDim::new(Some(dim_name.to_string()), dim, 0)
+        });
+
+        interface.add_stream("input", TilStreamDirection::Input, StreamTypeDecl::new(stream_type.clone(), stream_dim.clone()));
+
+        for idx in 0..outputs {
+            interface.add_stream(&format!("output{}", idx), TilStreamDirection::Output, StreamTypeDecl::new(stream_type.clone(), stream_dim.clone()));
+        }
+
+        let mut duplicator = TilStreamlet::new(&name);
+        duplicator.set_streaming_interface(interface);
+        duplicator.set_implementation(TilImplementationType::Path("./vhdl_dir".to_string()));
+
+        self.file_manager.add_entity(TemplateType::Duplicator { stream_type, outputs }, &name);
+
+        duplicator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{GeneratorError, checker::check_design, testing::{design_of, generator_of}, types::til_streamlet::TilImplementationType};
+
+    #[test]
+    fn duplicates_streams_that_drive_several_sinks() {
+        // The record parser drives the key parser of both keys
+        let design = design_of(r#"{"a": 1, "b": "x"}"#, |_| {}).unwrap();
+
+        let duplicators: Vec<_> = design.get_streamlets().iter().filter(|streamlet| streamlet.get_name().starts_with("duplicator")).collect();
+        assert_eq!(duplicators.len(), 1);
+
+        let duplicator = duplicators[0];
+        assert_eq!(duplicator.get_streams().get_output_streams().len(), 2);
+        assert_eq!(design.get_json_path(duplicator.get_name()), Some("$"));
+
+        // The source now only drives the duplicator, which drives both key parsers
+        let Some(TilImplementationType::Inline(implementation)) = design.get_top_components()[0].get_implementation() else {
+            panic!("the top component has no netlist");
+        };
+        let duplicator_inst = format!("{}_inst", duplicator.get_name());
+        let sources: Vec<_> = implementation.get_signals().iter()
+            .filter(|signal| signal.get_source_inst_name() == Some(&"record_parser_L1_00_inst".to_string()))
+            .collect();
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].get_dest_inst_name(), Some(&duplicator_inst));
+
+        let mut sinks: Vec<_> = implementation.get_signals().iter()
+            .filter(|signal| signal.get_source_inst_name() == Some(&duplicator_inst))
+            .map(|signal| signal.get_dest_inst_name().unwrap().clone())
+            .collect();
+        sinks.sort();
+        assert_eq!(sinks, vec!["key_parser_L2_00_inst", "key_parser_L2_01_inst"]);
+
+        assert!(check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn rejects_fan_out_for_point_to_point_backends() {
+        let output = std::env::temp_dir().join(format!("json_til_duplicators_{}", std::process::id()));
+        let output = output.to_str().unwrap();
+
+        let mut generator = generator_of(r#"{"a": 1, "b": "x"}"#, |generator| generator.set_insert_duplicators(false)).unwrap();
+
+        // TydiLang duplicates streams itself
+        generator.select_backends(&["td".to_string()]).unwrap();
+        assert!(generator.generate(output).is_ok());
+
+        generator.select_backends(&["til".to_string()]).unwrap();
+        let result = generator.generate(output);
+        std::fs::remove_dir_all(output).unwrap();
+
+        match result {
+            Err(GeneratorError::DesignRuleViolations(violations)) => assert!(violations.iter().all(|violation| violation.is_fan_out())),
+            _ => panic!("fan-out was not rejected"),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use indoc::formatdoc;
+
+use crate::analysis::{GeneratorParams, analyzer::type_manager::StreamType, aggregate::Reduction};
+
+use super::stream_ports;
+
+pub fn generate_aggregator(comp_name: &str, reduction: Reduction, input_type: &StreamType, output_type: &StreamType, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+    let input_bits = input_type.get_physical_params(gen_params).element_bits;
+
+    let mut generics = vec!["NESTING_LEVEL : natural := 1".to_string()];
+    if *output_type == StreamType::Count {
+        generics.push(format!("BITWIDTH : positive := {}", gen_params.int_width));
+    }
+
+    // The input carries the elements of the array in one more dimension than the result
+    let mut ports = vec![
+        "clk : in std_logic".to_string(),
+        "rst : in std_logic".to_string(),
+        "input_valid : in std_logic".to_string(),
+        "input_ready : out std_logic".to_string(),
+        format!("input_data : in std_logic_vector({}-1 downto 0)", input_bits),
+        "input_last : in std_logic_vector(NESTING_LEVEL downto 0)".to_string(),
+        "input_strb : in std_logic".to_string(),
+    ];
+    ports.extend(stream_ports("output", false, output_type, gen_params));
+
+    let (description, next_acc) = match reduction {
+        Reduction::Count => (
+            "their number",
+            "next_acc <= acc + 1 when input_strb = '1' else acc;",
+        ),
+        Reduction::Sum => (
+            "their sum, which wraps around on overflow",
+            "next_acc <= acc + value when input_strb = '1' else acc;",
+        ),
+        Reduction::Min => (
+            "their minimum",
+            "next_acc <= value when input_strb = '1' and (seen = '0' or value < acc) else acc;",
+        ),
+        Reduction::Max => (
+            "their maximum",
+            "next_acc <= value when input_strb = '1' and (seen = '0' or value > acc) else acc;",
+        ),
+    };
+
+    // An empty array has no smallest or largest element, so it produces an empty transfer
+    let strb = match reduction {
+        Reduction::Count | Reduction::Sum => "'1'",
+        Reduction::Min | Reduction::Max => "next_seen",
+    };
+
+    formatdoc!("
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+
+        library work;
+        use work.{project_name}.all;
+
+        -- Reduces the elements of every array to {description}. The result is
+        -- produced with the transfer that closes the array and keeps its outer
+        -- dimensions.
+        entity {entity} is
+          generic (
+            {generics}
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+          signal acc       : unsigned(output_data'range) := (others => '0');
+          signal seen      : std_logic := '0';
+          signal value     : unsigned(output_data'range);
+          signal next_acc  : unsigned(output_data'range);
+          signal next_seen : std_logic;
+          signal arr_end   : std_logic;
+        begin
+          value     <= resize(unsigned(input_data), output_data'length);
+          arr_end   <= input_last(0);
+          next_seen <= seen or input_strb;
+
+          {next_acc}
+
+          -- Only the end of an array produces a result
+          output_valid <= input_valid and arr_end;
+          input_ready  <= output_ready or not arr_end;
+          output_data  <= std_logic_vector(next_acc);
+          output_last  <= input_last(NESTING_LEVEL downto 1);
+          output_strb  <= {strb};
+
+          state_proc: process (clk) is
+          begin
+            if rising_edge(clk) then
+              if to_x01(input_valid) = '1' and (to_x01(output_ready) = '1' or arr_end = '0') then
+                if arr_end = '1' then
+                  acc  <= (others => '0');
+                  seen <= '0';
+                else
+                  acc  <= next_acc;
+                  seen <= next_seen;
+                end if;
+              end if;
+
+              if to_x01(rst) /= '0' then
+                acc  <= (others => '0');
+                seen <= '0';
+              end if;
+            end if;
+          end process;
+        end behav;
+        ",
+        project_name = gen_params.project_name,
+        generics = generics.join(";\n    "),
+        ports = ports.join(";\n    "),
+    )
+}
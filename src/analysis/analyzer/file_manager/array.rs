@@ -0,0 +1,223 @@
+use indoc::formatdoc;
+
+use crate::analysis::GeneratorParams;
+
+use super::TemplateType;
+
+pub fn generate_array_side_band(comp_name: &str, length: bool, index: bool, counter_width: usize, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+
+    // The array parser itself is instantiated under its own name
+    let elements_name = format!("{}_elements", comp_name);
+    let elements_entity = format!("{}_0_{}_com", gen_params.comp_namespace, elements_name);
+    let elements_template = TemplateType::Array { counter_width };
+    let elements = super::fill_template(&elements_template.get_template().unwrap(), &elements_name, &elements_template.get_params(), gen_params);
+
+    let mut ports = vec![
+        "clk : in std_logic".to_string(),
+        "rst : in std_logic".to_string(),
+        "input_valid : in std_logic".to_string(),
+        "input_ready : out std_logic".to_string(),
+        format!("input_data : in std_logic_vector(EPC*{}-1 downto 0)", gen_params.bit_width),
+        "input_last : in std_logic_vector(((OUTER_NESTING_LEVEL + 1) * EPC) - 1 downto 0)".to_string(),
+        "input_stai : in std_logic_vector(log2ceil(EPC)-1 downto 0)".to_string(),
+        "input_endi : in std_logic_vector(log2ceil(EPC)-1 downto 0)".to_string(),
+        "input_strb : in std_logic_vector(EPC-1 downto 0)".to_string(),
+        "output_valid : out std_logic".to_string(),
+        "output_ready : in std_logic".to_string(),
+        format!("output_data : out std_logic_vector(EPC*{}-1 downto 0)", gen_params.bit_width),
+        "output_last : out std_logic_vector(((OUTER_NESTING_LEVEL + 2) * EPC) - 1 downto 0)".to_string(),
+        "output_stai : out std_logic_vector(log2ceil(EPC)-1 downto 0)".to_string(),
+        "output_endi : out std_logic_vector(log2ceil(EPC)-1 downto 0)".to_string(),
+        "output_strb : out std_logic_vector(EPC-1 downto 0)".to_string(),
+    ];
+
+    // Side-band streams carry one count per array and one index per element
+    let mut assignments = String::new();
+
+    if length {
+        ports.push("length_valid : out std_logic".to_string());
+        ports.push("length_ready : in std_logic".to_string());
+        ports.push("length_data : out std_logic_vector(BITWIDTH-1 downto 0)".to_string());
+        ports.push("length_last : out std_logic_vector(OUTER_NESTING_LEVEL-1 downto 0)".to_string());
+        ports.push("length_strb : out std_logic".to_string());
+
+        assignments.push_str("  length_valid <= len_valid;\n  len_ready    <= length_ready;\n");
+        assignments.push_str("  length_data  <= std_logic_vector(resize(count, BITWIDTH) + 1) when ev_elem = '1' else std_logic_vector(resize(count, BITWIDTH));\n");
+        assignments.push_str("  length_last  <= p_last(DIMS*ev_lane+OUTER_NESTING_LEVEL+1 downto DIMS*ev_lane+2);\n  length_strb  <= '1';\n\n");
+    } else {
+        assignments.push_str("  len_ready <= '1';\n\n");
+    }
+
+    if index {
+        ports.push("index_valid : out std_logic".to_string());
+        ports.push("index_ready : in std_logic".to_string());
+        ports.push("index_data : out std_logic_vector(BITWIDTH-1 downto 0)".to_string());
+        ports.push("index_last : out std_logic_vector(OUTER_NESTING_LEVEL downto 0)".to_string());
+        ports.push("index_strb : out std_logic".to_string());
+
+        assignments.push_str("  index_valid <= idx_valid;\n  idx_ready   <= index_ready;\n");
+        assignments.push_str("  index_data  <= std_logic_vector(resize(count, BITWIDTH));\n");
+        assignments.push_str("  index_last  <= p_last(DIMS*ev_lane+OUTER_NESTING_LEVEL+1 downto DIMS*ev_lane+1);\n  index_strb  <= '1';\n\n");
+    } else {
+        assignments.push_str("  idx_ready <= '1';\n\n");
+    }
+
+    formatdoc!("
+        {elements}
+
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+
+        library work;
+        use work.{project_name}.all;
+        use work.UtilInt_pkg.all;
+
+        -- Array parser with side-band outputs. Every transfer of the parser is
+        -- walked lane by lane: the end of an element reports its index and the
+        -- end of the array reports the number of elements.
+        entity {entity} is
+          generic (
+            EPC : positive := 4;
+            OUTER_NESTING_LEVEL : positive := 2;
+            INNER_NESTING_LEVEL : natural := 0;
+            ELEMENT_COUNTER_BW : natural := {counter_width};
+            BITWIDTH : positive := {int_width}
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+          constant DIMS : positive := OUTER_NESTING_LEVEL + 2;
+
+          signal p_valid   : std_logic;
+          signal p_ready   : std_logic;
+          signal p_data    : std_logic_vector(EPC*{bit_width}-1 downto 0);
+          signal p_last    : std_logic_vector(DIMS*EPC-1 downto 0);
+          signal p_stai    : std_logic_vector(log2ceil(EPC)-1 downto 0);
+          signal p_endi    : std_logic_vector(log2ceil(EPC)-1 downto 0);
+          signal p_strb    : std_logic_vector(EPC-1 downto 0);
+
+          -- Lanes before the pointer have been reported on the side-band streams
+          signal ptr       : natural range 0 to EPC := 0;
+          signal ev_found  : std_logic;
+          signal ev_lane   : natural range 0 to EPC-1;
+          signal ev_elem   : std_logic;
+          signal ev_arr    : std_logic;
+          signal ev_done   : std_logic;
+          signal count     : unsigned(BITWIDTH-1 downto 0) := (others => '0');
+
+          signal out_done  : std_logic := '0';
+          signal idx_done  : std_logic := '0';
+          signal len_done  : std_logic := '0';
+          signal idx_valid : std_logic;
+          signal idx_ready : std_logic;
+          signal len_valid : std_logic;
+          signal len_ready : std_logic;
+        begin
+          elements_inst: entity work.{elements_entity}
+            generic map (
+              EPC => EPC,
+              OUTER_NESTING_LEVEL => OUTER_NESTING_LEVEL,
+              INNER_NESTING_LEVEL => INNER_NESTING_LEVEL,
+              ELEMENT_COUNTER_BW => ELEMENT_COUNTER_BW
+            )
+            port map (
+              clk => clk,
+              rst => rst,
+              input_valid => input_valid,
+              input_ready => input_ready,
+              input_data => input_data,
+              input_last => input_last,
+              input_stai => input_stai,
+              input_endi => input_endi,
+              input_strb => input_strb,
+              output_valid => p_valid,
+              output_ready => p_ready,
+              output_data => p_data,
+              output_last => p_last,
+              output_stai => p_stai,
+              output_endi => p_endi,
+              output_strb => p_strb
+            );
+
+          -- The next lane that ends an element or the array
+          event_proc: process (p_last, ptr) is
+          begin
+            ev_found <= '0';
+            ev_lane  <= 0;
+            for lane in EPC-1 downto 0 loop
+              if lane >= ptr and (p_last(DIMS*lane) = '1' or p_last(DIMS*lane+1) = '1') then
+                ev_found <= '1';
+                ev_lane  <= lane;
+              end if;
+            end loop;
+          end process;
+
+          ev_elem <= p_last(DIMS*ev_lane);
+          ev_arr  <= p_last(DIMS*ev_lane+1);
+
+          idx_valid <= p_valid and ev_found and ev_elem and not idx_done;
+          len_valid <= p_valid and ev_found and ev_arr and not len_done;
+          ev_done   <= (not ev_elem or idx_done or idx_ready) and (not ev_arr or len_done or len_ready);
+
+          -- The transfer is released once it is forwarded and all of its lanes are reported
+          output_valid <= p_valid and not out_done;
+          output_data  <= p_data;
+          output_last  <= p_last;
+          output_stai  <= p_stai;
+          output_endi  <= p_endi;
+          output_strb  <= p_strb;
+          p_ready      <= (out_done or output_ready) and not ev_found;
+
+        {assignments}  state_proc: process (clk) is
+          begin
+            if rising_edge(clk) then
+              if to_x01(p_valid) = '1' then
+                if p_ready = '1' then
+                  out_done <= '0';
+                  ptr      <= 0;
+                else
+                  if to_x01(output_ready) = '1' then
+                    out_done <= '1';
+                  end if;
+
+                  if ev_found = '1' then
+                    if ev_done = '1' then
+                      idx_done <= '0';
+                      len_done <= '0';
+                      ptr      <= ev_lane + 1;
+
+                      if ev_arr = '1' then
+                        count <= (others => '0');
+                      elsif ev_elem = '1' then
+                        count <= count + 1;
+                      end if;
+                    else
+                      idx_done <= idx_done or (idx_valid and idx_ready);
+                      len_done <= len_done or (len_valid and len_ready);
+                    end if;
+                  end if;
+                end if;
+              end if;
+
+              if to_x01(rst) /= '0' then
+                out_done <= '0';
+                idx_done <= '0';
+                len_done <= '0';
+                ptr      <= 0;
+                count    <= (others => '0');
+              end if;
+            end if;
+          end process;
+        end behav;
+        ",
+        project_name = gen_params.project_name,
+        int_width = gen_params.int_width,
+        bit_width = gen_params.bit_width,
+        ports = ports.join(";\n    "),
+    )
+}
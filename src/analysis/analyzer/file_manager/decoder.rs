@@ -0,0 +1,338 @@
+use indoc::formatdoc;
+
+use crate::analysis::GeneratorParams;
+
+pub fn generate_string_decoder(comp_name: &str, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+    let lanes = gen_params.epc;
+
+    let string_parser = super::inner_string_parser(comp_name, gen_params);
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+    ports.extend(string_parser.input_ports);
+    ports.push("output_valid : out std_logic".to_string());
+    ports.push("output_ready : in std_logic".to_string());
+    ports.push("output_data : out std_logic_vector(EPC*8-1 downto 0)".to_string());
+    ports.push("output_last : out std_logic_vector((NESTING_LEVEL+1)*EPC-1 downto 0)".to_string());
+
+    // Single lane streams have no lane indices
+    let lane_ports = if lanes > 1 {
+        ports.push("output_stai : out std_logic_vector(log2ceil(EPC)-1 downto 0)".to_string());
+        ports.push("output_endi : out std_logic_vector(log2ceil(EPC)-1 downto 0)".to_string());
+        ports.push("output_strb : out std_logic_vector(EPC-1 downto 0)".to_string());
+
+        formatdoc!("
+            output_stai <= (others => '0');
+            if count > 0 then
+              output_endi <= std_logic_vector(to_unsigned(count-1, output_endi'length));
+            else
+              output_endi <= (others => '0');
+            end if;
+            for i in 0 to EPC-1 loop
+              output_strb(i) <= od(i).strb;
+            end loop;")
+    } else {
+        ports.push("output_strb : out std_logic".to_string());
+
+        "output_strb <= od(0).strb;".to_string()
+    };
+
+    formatdoc!("
+        {string_parser}
+
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+        use ieee.std_logic_misc.or_reduce;
+
+        library work;
+        use work.{project_name}.all;
+        use work.UtilInt_pkg.all;
+
+        -- Decodes the escapes of every string into UTF-8, including surrogate pairs.
+        -- Unpaired surrogates are dropped and unknown escapes produce the escaped character.
+        -- Strings end with an empty lane that carries the last flags, as they do in the
+        -- output of the string parser.
+        entity {entity} is
+          generic (
+            EPC : positive := {lanes};
+            NESTING_LEVEL : natural := 1
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+          -- Whether the quotes around the strings are part of the output
+          constant KEEP_QUOTES : boolean := {keep_quotes};
+
+          function hex_value(c : std_logic_vector(7 downto 0)) return natural is
+            variable code : natural;
+          begin
+            code := to_integer(unsigned(c));
+            if code >= 48 and code <= 57 then
+              return code - 48;
+            elsif code >= 65 and code <= 70 then
+              return code - 55;
+            elsif code >= 97 and code <= 102 then
+              return code - 87;
+            end if;
+            return 0;
+          end function;
+
+        {signals}
+        begin
+        {instance}
+          decode_proc: process (clk) is
+            type lane_t is record
+              data : std_logic_vector(7 downto 0);
+              last : std_logic_vector(NESTING_LEVEL downto 0);
+              strb : std_logic;
+            end record;
+
+            type lane_array is array (natural range <>) of lane_t;
+            constant EMPTY : lane_t := (data => x\"00\", last => (others => '0'), strb => '0');
+
+            -- Input holding register, which is decoded lane by lane
+            variable id  : lane_array(0 to EPC-1);
+            variable iv  : std_logic := '0';
+            variable ir  : std_logic := '0';
+            variable idx : natural range 0 to EPC-1 := 0;
+
+            -- Output holding register, which is sent when it is full or holds the end of a string
+            variable od    : lane_array(0 to EPC-1) := (others => EMPTY);
+            variable ov    : std_logic := '0';
+            variable count : natural range 0 to EPC := 0;
+            variable done  : std_logic := '0';
+
+            -- Bytes of the current lane that are not in the output holding register yet,
+            -- at most a quote and the four bytes of a character or two quotes and the end
+            variable pend       : lane_array(0 to 5) := (others => EMPTY);
+            variable pend_count : natural range 0 to 6 := 0;
+
+            type state_t is (STATE_CHAR, STATE_ESCAPE, STATE_HEX);
+            variable state    : state_t := STATE_CHAR;
+            variable hex_left : natural range 0 to 4;
+            variable code     : unsigned(15 downto 0);
+            variable point    : unsigned(20 downto 0);
+
+            -- The upper half of a surrogate pair, waiting for the lower half
+            variable high     : unsigned(9 downto 0);
+            variable has_high : std_logic := '0';
+
+            -- No byte of the current string has been decoded yet
+            variable fresh : std_logic := '1';
+
+            variable lane : lane_t;
+            variable c    : std_logic_vector(7 downto 0);
+
+            procedure push(byte : std_logic_vector(7 downto 0)) is
+            begin
+              pend(pend_count) := (data => byte, last => (others => '0'), strb => '1');
+              pend_count := pend_count + 1;
+            end procedure;
+
+            -- Code points of the basic multilingual plane take one to three bytes
+            procedure push_code(value : unsigned(15 downto 0)) is
+            begin
+              if value < 16#80# then
+                push(std_logic_vector(value(7 downto 0)));
+              elsif value < 16#800# then
+                push(\"110\" & std_logic_vector(value(10 downto 6)));
+                push(\"10\" & std_logic_vector(value(5 downto 0)));
+              else
+                push(\"1110\" & std_logic_vector(value(15 downto 12)));
+                push(\"10\" & std_logic_vector(value(11 downto 6)));
+                push(\"10\" & std_logic_vector(value(5 downto 0)));
+              end if;
+            end procedure;
+
+          begin
+            if rising_edge(clk) then
+
+              -- Latch input holding register if we said we would.
+              if to_x01(ir) = '1' then
+                iv := s_valid;
+                idx := 0;
+                for i in 0 to EPC-1 loop
+                  id(i).data := s_data(8*i+7 downto 8*i);
+                  id(i).last := s_last((NESTING_LEVEL+1)*(i+1)-1 downto (NESTING_LEVEL+1)*i);
+                  id(i).strb := s_strb(i);
+                end loop;
+              end if;
+
+              -- Clear output holding register if transfer was accepted.
+              if to_x01(output_ready) = '1' and ov = '1' then
+                ov := '0';
+                od := (others => EMPTY);
+                count := 0;
+                done := '0';
+              end if;
+
+              -- Decode at most one lane per step and move its bytes to the output holding register.
+              if ov = '0' then
+                for step in 0 to EPC-1 loop
+                  if pend_count = 0 and iv = '1' then
+                    lane := id(idx);
+                    pend := (others => EMPTY);
+
+                    if lane.strb = '1' then
+                      c := lane.data;
+
+                      if KEEP_QUOTES and fresh = '1' then
+                        push(x\"22\");
+                      end if;
+                      fresh := '0';
+
+                      case state is
+                        when STATE_CHAR =>
+                          if c = x\"5C\" then
+                            state := STATE_ESCAPE;
+                          else
+                            push(c);
+                          end if;
+
+                        when STATE_ESCAPE =>
+                          state := STATE_CHAR;
+                          case c is
+                            when x\"62\" => push(x\"08\"); -- \\b
+                            when x\"66\" => push(x\"0C\"); -- \\f
+                            when x\"6E\" => push(x\"0A\"); -- \\n
+                            when x\"72\" => push(x\"0D\"); -- \\r
+                            when x\"74\" => push(x\"09\"); -- \\t
+                            when x\"75\" => -- \\u
+                              state := STATE_HEX;
+                              hex_left := 4;
+                              code := (others => '0');
+                            when others => push(c);
+                          end case;
+
+                        when STATE_HEX =>
+                          code := code(11 downto 0) & to_unsigned(hex_value(c), 4);
+                          hex_left := hex_left - 1;
+
+                          if hex_left = 0 then
+                            state := STATE_CHAR;
+
+                            if code(15 downto 10) = \"110110\" then
+                              high := code(9 downto 0);
+                              has_high := '1';
+                            elsif code(15 downto 10) = \"110111\" then
+                              if has_high = '1' then
+                                point := to_unsigned(16#10000#, 21) + (high & code(9 downto 0));
+                                push(\"11110\" & std_logic_vector(point(20 downto 18)));
+                                push(\"10\" & std_logic_vector(point(17 downto 12)));
+                                push(\"10\" & std_logic_vector(point(11 downto 6)));
+                                push(\"10\" & std_logic_vector(point(5 downto 0)));
+                              end if;
+                              has_high := '0';
+                            else
+                              push_code(code);
+                              has_high := '0';
+                            end if;
+                          end if;
+                      end case;
+                    end if;
+
+                    if or_reduce(lane.last) = '1' then
+                      -- The end of a string
+                      if lane.last(0) = '1' then
+                        if KEEP_QUOTES then
+                          if fresh = '1' then
+                            push(x\"22\");
+                          end if;
+                          push(x\"22\");
+                        end if;
+
+                        state := STATE_CHAR;
+                        has_high := '0';
+                        fresh := '1';
+                      end if;
+
+                      pend(pend_count) := (data => x\"00\", last => lane.last, strb => '0');
+                      pend_count := pend_count + 1;
+                    end if;
+
+                    if idx = EPC-1 then
+                      iv := '0';
+                      idx := 0;
+                    else
+                      idx := idx + 1;
+                    end if;
+                  end if;
+
+                  for byte in 0 to 5 loop
+                    if pend_count > 0 and count < EPC and done = '0' then
+                      od(count) := pend(0);
+                      if or_reduce(pend(0).last) = '1' then
+                        done := '1';
+                      end if;
+
+                      pend(0 to 4) := pend(1 to 5);
+                      pend(5) := EMPTY;
+                      pend_count := pend_count - 1;
+                      count := count + 1;
+                    end if;
+                  end loop;
+                end loop;
+
+                if count = EPC or done = '1' then
+                  ov := '1';
+                end if;
+              end if;
+
+              -- Handle rst.
+              if to_x01(rst) /= '0' then
+                iv := '0';
+                ov := '0';
+                od := (others => EMPTY);
+                count := 0;
+                done := '0';
+                pend_count := 0;
+                state := STATE_CHAR;
+                has_high := '0';
+                fresh := '1';
+              end if;
+
+              -- Forward output holding register.
+              output_valid <= to_x01(ov);
+              ir := not iv and not rst;
+              s_ready <= ir and not rst;
+              for i in 0 to EPC-1 loop
+                output_data(8*i+7 downto 8*i) <= od(i).data;
+                output_last((NESTING_LEVEL+1)*(i+1)-1 downto (NESTING_LEVEL+1)*i) <= od(i).last;
+              end loop;
+        {lane_ports}
+            end if;
+          end process;
+        end behav;
+        ",
+        string_parser = string_parser.entity,
+        project_name = gen_params.project_name,
+        keep_quotes = gen_params.formats.keep_quotes,
+        ports = ports.join(";\n    "),
+        signals = string_parser.signals,
+        instance = string_parser.instance,
+        lane_ports = super::indent(&lane_ports, 6).trim_end(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configures_the_quotes_and_lanes_of_the_decoder() {
+        let mut gen_params = GeneratorParams::new(4, 8, 64, "", "test");
+        let vhdl = generate_string_decoder("string_decoder_L2_00", &gen_params);
+        assert!(vhdl.contains("constant KEEP_QUOTES : boolean := false;"));
+        assert!(vhdl.contains("output_stai : out"));
+
+        gen_params.formats.keep_quotes = true;
+        gen_params.epc = 1;
+        let vhdl = generate_string_decoder("string_decoder_L2_00", &gen_params);
+        assert!(vhdl.contains("constant KEEP_QUOTES : boolean := true;"));
+        assert!(!vhdl.contains("output_stai : out"));
+    }
+}
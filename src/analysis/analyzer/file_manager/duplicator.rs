@@ -0,0 +1,139 @@
+use indoc::formatdoc;
+
+use crate::analysis::{GeneratorParams, analyzer::type_manager::StreamType};
+
+use super::{payload_signals, stream_ports};
+
+pub fn generate_duplicator(comp_name: &str, stream_type: &StreamType, outputs: usize, gen_params: &GeneratorParams) -> String {
+    let physical = stream_type.get_physical_params(gen_params);
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+
+    let mut generics = Vec::new();
+    if physical.lanes > 1 {
+        generics.push(format!("EPC : positive := {}", physical.lanes));
+    }
+    if physical.fixed_dimensionality.is_none() {
+        generics.push("NESTING_LEVEL : natural := 1".to_string());
+    }
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+    ports.extend(stream_ports("input", true, stream_type, gen_params));
+    for idx in 0..outputs {
+        ports.extend(stream_ports(&format!("output{}", idx), false, stream_type, gen_params));
+    }
+
+    let generic_block = if generics.is_empty() {
+        String::new()
+    } else {
+        format!("  generic (\n    {}\n  );\n", generics.join(";\n    "))
+    };
+
+    // Every output forwards the payload of the input unchanged
+    let payload = payload_signals(stream_type, gen_params);
+    let mut assignments = String::new();
+    for idx in 0..outputs {
+        assignments.push_str(&format!("  output{idx}_valid <= out_valid({idx});\n  out_ready({idx}) <= output{idx}_ready;\n"));
+
+        for (signal, _) in &payload {
+            assignments.push_str(&format!("  output{idx}_{signal} <= input_{signal};\n"));
+        }
+    }
+
+    formatdoc!("
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+        use ieee.std_logic_misc.and_reduce;
+
+        library work;
+        use work.{project_name}.all;
+        use work.UtilInt_pkg.all;
+
+        -- Hands every transfer on the input to all {outputs} outputs. The input is only
+        -- acknowledged once every output has accepted the transfer.
+        entity {entity} is
+        {generic_block}  port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+          signal done      : std_logic_vector({last_output} downto 0) := (others => '0');
+          signal out_valid : std_logic_vector({last_output} downto 0);
+          signal out_ready : std_logic_vector({last_output} downto 0);
+          signal all_done  : std_logic;
+        begin
+          out_valid   <= (out_valid'range => input_valid) and not done;
+          all_done    <= and_reduce(done or out_ready);
+          input_ready <= all_done;
+
+          done_proc: process (clk) is
+          begin
+            if rising_edge(clk) then
+              if to_x01(input_valid) = '1' then
+                if all_done = '1' then
+                  done <= (others => '0');
+                else
+                  done <= done or (out_valid and out_ready);
+                end if;
+              end if;
+
+              if to_x01(rst) /= '0' then
+                done <= (others => '0');
+              end if;
+            end if;
+          end process;
+
+        {assignments}end behav;
+        ",
+        project_name = gen_params.project_name,
+        ports = ports.join(";\n    "),
+        last_output = outputs - 1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The names of the output ports that the entity declares and that the architecture assigns
+    fn declared_and_assigned(vhdl: &str) -> (Vec<String>, Vec<String>) {
+        let declared = vhdl.lines()
+            .filter_map(|line| line.trim().split_once(" : out "))
+            .map(|(port, _)| port.to_string())
+            .filter(|port| port.starts_with("output"))
+            .collect();
+        let assigned = vhdl.lines()
+            .filter_map(|line| line.trim().split_once(" <= "))
+            .map(|(port, _)| port.to_string())
+            .filter(|port| port.starts_with("output"))
+            .collect();
+
+        (declared, assigned)
+    }
+
+    #[test]
+    fn forwards_the_payload_to_every_output() {
+        let gen_params = GeneratorParams::new(4, 8, 64, "", "test");
+
+        let vhdl = generate_duplicator("duplicator_L2_00", &StreamType::Json, 2, &gen_params);
+        let (declared, assigned) = declared_and_assigned(&vhdl);
+        assert_eq!(declared, assigned);
+        assert!(vhdl.contains("output1_stai <= input_stai;"));
+        assert!(vhdl.contains("output1_strb : out std_logic_vector(EPC-1 downto 0)"));
+
+        // A single lane has no lane indices
+        let vhdl = generate_duplicator("duplicator_L2_01", &StreamType::Int, 3, &gen_params);
+        let (declared, assigned) = declared_and_assigned(&vhdl);
+        assert_eq!(declared, assigned);
+        assert!(!vhdl.contains("_stai"));
+        assert!(vhdl.contains("output2_strb <= input_strb;"));
+
+        // A single lane without dimensions has only data
+        let vhdl = generate_duplicator("duplicator_L0_00", &StreamType::ValidationError, 2, &gen_params);
+        let (declared, assigned) = declared_and_assigned(&vhdl);
+        assert_eq!(declared, assigned);
+        assert_eq!(declared, vec!["output0_valid", "output0_data", "output1_valid", "output1_data"]);
+        assert!(!vhdl.contains("input_last") && !vhdl.contains("input_strb"));
+    }
+}
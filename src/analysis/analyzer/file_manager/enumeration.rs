@@ -0,0 +1,172 @@
+use indoc::formatdoc;
+
+use crate::analysis::{GeneratorParams, analyzer::type_manager::TagType};
+
+pub fn generate_enum_matcher(comp_name: &str, code_type: &TagType, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+    let lanes = gen_params.epc;
+
+    // The last code is the one for unknown strings
+    let variants = code_type.get_variants();
+    let values = &variants[..variants.len() - 1];
+    let code_bits = code_type.get_bits();
+
+    let string_parser = super::inner_string_parser(comp_name, gen_params);
+
+    let max_length = values.iter().map(|value| value.len()).max().unwrap_or(0);
+
+    let mut code_comment = String::new();
+    let mut expected = Vec::new();
+    let mut lengths = Vec::new();
+
+    for (idx, value) in values.iter().enumerate() {
+        code_comment.push_str(&format!("--   {} : {:?}\n", idx, value));
+
+        let bytes: Vec<String> = value.bytes()
+            .enumerate()
+            .map(|(pos, byte)| format!("{} => x\"{:02X}\"", pos, byte))
+            .chain(std::iter::once("others => x\"00\"".to_string()))
+            .collect();
+
+        expected.push(format!("{} => ({})", idx, bytes.join(", ")));
+        lengths.push(format!("{} => {}", idx, value.len()));
+    }
+    code_comment.push_str(&format!("--   {} : any other string\n", values.len()));
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+    ports.extend(string_parser.input_ports);
+    ports.push("output_valid : out std_logic".to_string());
+    ports.push("output_ready : in std_logic".to_string());
+    ports.push(format!("output_data : out std_logic_vector({}-1 downto 0)", code_bits));
+    ports.push("output_last : out std_logic_vector(NESTING_LEVEL-1 downto 0)".to_string());
+    ports.push("output_strb : out std_logic".to_string());
+
+    formatdoc!("
+        {string_parser}
+
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+
+        library work;
+        use work.{project_name}.all;
+        use work.UtilInt_pkg.all;
+
+        -- Replaces every string by the code of the value it is equal to:
+        {code_comment}entity {entity} is
+          generic (
+            EPC : positive := {lanes};
+            NESTING_LEVEL : natural := 1
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+          constant DIMS       : positive := NESTING_LEVEL + 1;
+          constant VALUES     : positive := {value_count};
+          constant MAX_LENGTH : natural := {max_length};
+
+          type byte_array is array (0 to MAX_LENGTH) of std_logic_vector(7 downto 0);
+          type value_array is array (0 to VALUES-1) of byte_array;
+          type length_array is array (0 to VALUES-1) of natural;
+          constant EXPECTED : value_array := ({expected});
+          constant LENGTHS  : length_array := ({lengths});
+
+        {signals}
+          -- Characters of the current string and the values that still match them
+          signal pos        : natural range 0 to MAX_LENGTH := 0;
+          signal matching   : std_logic_vector(VALUES-1 downto 0) := (others => '1');
+
+          signal next_pos   : natural range 0 to MAX_LENGTH;
+          signal next_match : std_logic_vector(VALUES-1 downto 0);
+          signal code       : natural range 0 to VALUES;
+          signal value_end  : std_logic;
+          signal end_last   : std_logic_vector(NESTING_LEVEL-1 downto 0);
+        begin
+        {instance}
+          -- Strings are expected to end at most once per transfer
+          match_proc: process (s_data, s_last, s_strb, pos, matching) is
+            variable v_pos   : natural range 0 to MAX_LENGTH;
+            variable v_match : std_logic_vector(VALUES-1 downto 0);
+          begin
+            v_pos := pos;
+            v_match := matching;
+            value_end <= '0';
+            end_last <= (others => '0');
+
+            for lane in 0 to EPC-1 loop
+              if s_strb(lane) = '1' then
+                for value in 0 to VALUES-1 loop
+                  if v_pos >= LENGTHS(value) or s_data(8*lane+7 downto 8*lane) /= EXPECTED(value)(v_pos) then
+                    v_match(value) := '0';
+                  end if;
+                end loop;
+
+                -- Strings longer than every value can not match any of them
+                if v_pos < MAX_LENGTH then
+                  v_pos := v_pos + 1;
+                else
+                  v_match := (others => '0');
+                end if;
+              end if;
+
+              if s_last(DIMS*lane) = '1' then
+                value_end <= '1';
+                end_last <= s_last(DIMS*lane+NESTING_LEVEL downto DIMS*lane+1);
+              end if;
+            end loop;
+
+            next_pos   <= v_pos;
+            next_match <= v_match;
+          end process;
+
+          code_proc: process (next_pos, next_match) is
+          begin
+            code <= VALUES;
+            for value in VALUES-1 downto 0 loop
+              if next_match(value) = '1' and next_pos = LENGTHS(value) then
+                code <= value;
+              end if;
+            end loop;
+          end process;
+
+          -- Only the end of a string produces a code
+          output_valid <= s_valid and value_end;
+          s_ready      <= output_ready or not value_end;
+          output_data  <= std_logic_vector(to_unsigned(code, output_data'length));
+          output_last  <= end_last;
+          output_strb  <= '1';
+
+          state_proc: process (clk) is
+          begin
+            if rising_edge(clk) then
+              if to_x01(s_valid) = '1' and (to_x01(output_ready) = '1' or value_end = '0') then
+                if value_end = '1' then
+                  pos      <= 0;
+                  matching <= (others => '1');
+                else
+                  pos      <= next_pos;
+                  matching <= next_match;
+                end if;
+              end if;
+
+              if to_x01(rst) /= '0' then
+                pos      <= 0;
+                matching <= (others => '1');
+              end if;
+            end if;
+          end process;
+        end behav;
+        ",
+        string_parser = string_parser.entity,
+        signals = string_parser.signals,
+        instance = string_parser.instance,
+        project_name = gen_params.project_name,
+        value_count = values.len(),
+        expected = expected.join(", "),
+        lengths = lengths.join(", "),
+        ports = ports.join(";\n    "),
+    )
+}
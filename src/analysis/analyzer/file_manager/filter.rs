@@ -0,0 +1,476 @@
+use indoc::formatdoc;
+
+use crate::analysis::{GeneratorParams, analyzer::type_manager::StreamType, filter::{Comparison, Combine, Literal}};
+
+use super::stream_ports;
+
+// Declaration of a JSON stream, which carries one more dimension than the records it belongs to
+fn json_ports(name: &str, is_input: bool, lanes: usize) -> Vec<String> {
+    let (dir, rev) = if is_input { ("in", "out") } else { ("out", "in") };
+
+    let mut ports = vec![
+        format!("{}_valid : {} std_logic", name, dir),
+        format!("{}_ready : {} std_logic", name, rev),
+        format!("{}_data : {} std_logic_vector(EPC*8-1 downto 0)", name, dir),
+        format!("{}_last : {} std_logic_vector((NESTING_LEVEL+1)*EPC-1 downto 0)", name, dir),
+    ];
+
+    if lanes > 1 {
+        ports.push(format!("{}_stai : {} std_logic_vector(log2ceil(EPC)-1 downto 0)", name, dir));
+        ports.push(format!("{}_endi : {} std_logic_vector(log2ceil(EPC)-1 downto 0)", name, dir));
+        ports.push(format!("{}_strb : {} std_logic_vector(EPC-1 downto 0)", name, dir));
+    } else {
+        ports.push(format!("{}_strb : {} std_logic", name, dir));
+    }
+
+    ports
+}
+
+// Indent generated statements to the level of an architecture body
+fn indent(text: &str) -> String {
+    text.lines().map(|line| if line.is_empty() { "\n".to_string() } else { format!("  {}\n", line) }).collect()
+}
+
+// The comparison of the input with an integer, of which a hexadecimal literal is extended to the width of the input
+fn integer_comparison(comparison: Comparison, value: i128, signed: bool) -> String {
+    if signed {
+        // Enough digits for the sign bit, negative values in two's complement
+        let bits = 129 - (if value < 0 { !value } else { value }).leading_zeros() as usize;
+        let digits = bits.div_ceil(4);
+        let mask = if digits >= 32 { u128::MAX } else { (1 << (digits * 4)) - 1 };
+
+        format!("signed(input_data) {} resize(signed'(x\"{:0width$X}\"), input_data'length)", comparison.to_vhdl(), value as u128 & mask, width = digits)
+    } else {
+        format!("unsigned(input_data) {} resize(unsigned'(x\"{:X}\"), input_data'length)", comparison.to_vhdl(), value)
+    }
+}
+
+pub fn generate_filter_compare(comp_name: &str, comparison: Comparison, literal: &Literal, input_type: &StreamType, signed: bool, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+    let lanes = gen_params.epc;
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+
+    let (description, generics, body) = match literal {
+        Literal::Integer(value) => {
+            ports.extend(stream_ports("input", true, input_type, gen_params));
+            ports.extend(stream_ports("output", false, &StreamType::Bool, gen_params));
+
+            (
+                match input_type {
+                    StreamType::Tag(_) => format!("the enum code {}", value),
+                    _ => format!("the {} integer {}", if signed { "signed" } else { "unsigned" }, value),
+                },
+                "NESTING_LEVEL : natural := 1".to_string(),
+                formatdoc!("
+                    begin
+                      output_valid   <= input_valid;
+                      input_ready    <= output_ready;
+                      output_data(0) <= '1' when {compare} else '0';
+                      output_last    <= input_last;
+                      output_strb    <= input_strb;
+                    end behav;",
+                    compare = integer_comparison(comparison, *value, signed),
+                ),
+            )
+        },
+        Literal::Boolean(value) => {
+            ports.extend(stream_ports("input", true, &StreamType::Bool, gen_params));
+            ports.extend(stream_ports("output", false, &StreamType::Bool, gen_params));
+
+            (
+                format!("the boolean {}", value),
+                "NESTING_LEVEL : natural := 1".to_string(),
+                formatdoc!("
+                    begin
+                      output_valid   <= input_valid;
+                      input_ready    <= output_ready;
+                      output_data(0) <= '1' when input_data(0) {op} '{bit}' else '0';
+                      output_last    <= input_last;
+                      output_strb    <= input_strb;
+                    end behav;",
+                    op = comparison.to_vhdl(),
+                    bit = if *value { 1 } else { 0 },
+                ),
+            )
+        },
+        Literal::String(value) => {
+            ports.extend(json_ports("input", true, lanes));
+            ports.extend(stream_ports("output", false, &StreamType::Bool, gen_params));
+
+            let bytes: Vec<String> = value.bytes()
+                .enumerate()
+                .map(|(idx, byte)| format!("{} => x\"{:02X}\"", idx, byte))
+                .chain(std::iter::once("others => x\"00\"".to_string()))
+                .collect();
+
+            let strb = if lanes > 1 { "input_strb(lane)" } else { "input_strb" };
+
+            (
+                format!("the string \"{}\"", value),
+                format!("EPC : positive := {};\n    NESTING_LEVEL : natural := 1", lanes),
+                formatdoc!("
+                      constant DIMS   : positive := NESTING_LEVEL + 1;
+                      constant LENGTH : natural := {length};
+
+                      type byte_array is array (natural range <>) of std_logic_vector(7 downto 0);
+                      constant EXPECTED : byte_array(0 to LENGTH) := ({bytes});
+
+                      -- Characters of the current string that matched so far
+                      signal pos       : natural range 0 to LENGTH := 0;
+                      signal matching  : std_logic := '1';
+
+                      signal next_pos   : natural range 0 to LENGTH;
+                      signal next_match : std_logic;
+                      signal result     : std_logic;
+                      signal value_end  : std_logic;
+                      signal end_last   : std_logic_vector(NESTING_LEVEL-1 downto 0);
+                    begin
+                      -- Strings are expected to end at most once per transfer
+                      match_proc: process (input_data, input_last, input_strb, pos, matching) is
+                        variable v_pos   : natural range 0 to LENGTH;
+                        variable v_match : std_logic;
+                      begin
+                        v_pos := pos;
+                        v_match := matching;
+                        value_end <= '0';
+                        end_last <= (others => '0');
+
+                        for lane in 0 to EPC-1 loop
+                          if {strb} = '1' then
+                            if v_pos < LENGTH and input_data(8*lane+7 downto 8*lane) = EXPECTED(v_pos) then
+                              v_pos := v_pos + 1;
+                            else
+                              v_match := '0';
+                            end if;
+                          end if;
+
+                          if input_last(DIMS*lane) = '1' then
+                            value_end <= '1';
+                            end_last <= input_last(DIMS*lane+NESTING_LEVEL downto DIMS*lane+1);
+                          end if;
+                        end loop;
+
+                        next_pos   <= v_pos;
+                        next_match <= v_match;
+                      end process;
+
+                      -- The whole string has to match
+                      result <= next_match when next_pos = LENGTH else '0';
+
+                      -- Only the end of a string produces a result
+                      output_valid   <= input_valid and value_end;
+                      input_ready    <= output_ready or not value_end;
+                      output_data(0) <= result {negate};
+                      output_last    <= end_last;
+                      output_strb    <= '1';
+
+                      state_proc: process (clk) is
+                      begin
+                        if rising_edge(clk) then
+                          if to_x01(input_valid) = '1' and (to_x01(output_ready) = '1' or value_end = '0') then
+                            if value_end = '1' then
+                              pos      <= 0;
+                              matching <= '1';
+                            else
+                              pos      <= next_pos;
+                              matching <= next_match;
+                            end if;
+                          end if;
+
+                          if to_x01(rst) /= '0' then
+                            pos      <= 0;
+                            matching <= '1';
+                          end if;
+                        end if;
+                      end process;
+                    end behav;",
+                    length = value.len(),
+                    bytes = bytes.join(", "),
+                    negate = if comparison == Comparison::Ne { "xor '1'" } else { "" },
+                ),
+            )
+        },
+    };
+
+    formatdoc!("
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+
+        library work;
+        use work.{project_name}.all;
+        use work.UtilInt_pkg.all;
+
+        -- Compares every value with {description} ({op}) and produces the result
+        -- as a boolean with the dimensions of the value.
+        entity {entity} is
+          generic (
+            {generics}
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+        {body}
+        ",
+        project_name = gen_params.project_name,
+        op = comparison.to_vhdl(),
+        ports = ports.join(";\n    "),
+    )
+}
+
+pub fn generate_filter_combine(comp_name: &str, inputs: usize, combine: Combine, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+    for idx in 0..inputs {
+        ports.extend(stream_ports(&format!("input_{}", idx), true, &StreamType::Bool, gen_params));
+    }
+    ports.extend(stream_ports("output", false, &StreamType::Bool, gen_params));
+
+    let all_valid = (0..inputs).map(|idx| format!("input_{}_valid", idx)).collect::<Vec<_>>().join(" and ");
+    let operator = match combine {
+        Combine::All => " and ",
+        Combine::Any => " or ",
+    };
+    let result = (0..inputs).map(|idx| format!("input_{}_data(0)", idx)).collect::<Vec<_>>().join(operator);
+
+    let mut readies = String::new();
+    for idx in 0..inputs {
+        readies.push_str(&format!("  input_{}_ready <= all_valid and output_ready;\n", idx));
+    }
+
+    formatdoc!("
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+
+        library work;
+        use work.{project_name}.all;
+
+        -- Combines the results of the predicates of one record into a single result.
+        -- A result is only produced once every predicate has produced its result.
+        entity {entity} is
+          generic (
+            NESTING_LEVEL : natural := 1
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+          signal all_valid : std_logic;
+        begin
+          all_valid <= {all_valid};
+
+          output_valid   <= all_valid;
+          output_data(0) <= {result};
+          output_last    <= input_0_last;
+          output_strb    <= input_0_strb;
+
+        {readies}end behav;
+        ",
+        project_name = gen_params.project_name,
+        ports = ports.join(";\n    "),
+    )
+}
+
+pub fn generate_filter_gate(comp_name: &str, stream_type: &StreamType, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+    let physical = stream_type.get_physical_params(gen_params);
+    let lanes = physical.lanes;
+
+    // JSON streams carry the characters of a value in an extra dimension
+    let is_json = *stream_type.get_base() == StreamType::Json;
+
+    let mut generics = Vec::new();
+    if is_json || lanes > 1 {
+        generics.push(format!("EPC : positive := {}", lanes));
+    }
+    generics.push("NESTING_LEVEL : natural := 1".to_string());
+    generics.push("DEPTH : positive := 16".to_string());
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+    if is_json {
+        ports.extend(json_ports("input", true, lanes));
+    } else {
+        ports.extend(stream_ports("input", true, stream_type, gen_params));
+    }
+    ports.extend(stream_ports("select", true, &StreamType::Bool, gen_params));
+    if is_json {
+        ports.extend(json_ports("output", false, lanes));
+    } else {
+        ports.extend(stream_ports("output", false, stream_type, gen_params));
+    }
+
+    // Every signal of a transfer is stored in a memory of its own
+    let mut fields = vec![("data", true), ("last", true)];
+    if lanes > 1 {
+        fields.push(("stai", true));
+        fields.push(("endi", true));
+        fields.push(("strb", true));
+    } else {
+        fields.push(("strb", false));
+    }
+
+    let mut memories = String::new();
+    let mut writes = String::new();
+    let mut heads = String::new();
+    for (field, is_vector) in &fields {
+        let element = if *is_vector { format!("std_logic_vector(input_{}'range)", field) } else { "std_logic".to_string() };
+
+        memories.push_str(&format!("  type {field}_mem_t is array (0 to DEPTH-1) of {element};\n  signal {field}_mem : {field}_mem_t;\n  signal head_{field} : {element};\n"));
+        writes.push_str(&format!("          {field}_mem(wr_ptr) <= input_{field};\n"));
+        heads.push_str(&format!("  head_{field} <= {field}_mem(rd_ptr);\n"));
+    }
+
+    // Decide which transfers hold (the end of) a value and how a rejected value is removed
+    let lane_strb = |lane: &str| if lanes > 1 { format!("head_strb({})", lane) } else { "head_strb".to_string() };
+    let out_strb = |lane: &str| if lanes > 1 { format!("output_strb({})", lane) } else { "output_strb".to_string() };
+
+    let (value_proc, output_proc) = if is_json {
+        (
+            indent(&formatdoc!("
+                  -- A value ends in the lane that closes the innermost dimension
+                  value_proc: process (head_last, head_strb) is
+                  begin
+                    value_end <= '0';
+                    has_data  <= '0';
+                    for lane in 0 to EPC-1 loop
+                      if head_last((NESTING_LEVEL+1)*lane) = '1' then
+                        value_end <= '1';
+                      end if;
+                      if {strb} = '1' then
+                        has_data <= '1';
+                      end if;
+                    end loop;
+                  end process;
+                ",
+                strb = lane_strb("lane"),
+            )),
+            indent(&formatdoc!("
+                  output_proc: process (head_last, head_strb, pass) is
+                  begin
+                    output_last <= head_last;
+                    for lane in 0 to EPC-1 loop
+                      {out_strb} <= {strb} and pass;
+                      output_last((NESTING_LEVEL+1)*lane) <= head_last((NESTING_LEVEL+1)*lane) and pass;
+                    end loop;
+                  end process;
+                ",
+                strb = lane_strb("lane"),
+                out_strb = out_strb("lane"),
+            )),
+        )
+    } else {
+        (
+            "  -- Every strobed transfer is a value\n  value_end <= head_strb;\n  has_data  <= head_strb;\n".to_string(),
+            "  output_last <= head_last;\n  output_strb <= head_strb and pass;\n".to_string(),
+        )
+    };
+
+    let indices = if lanes > 1 { "  output_stai <= head_stai;\n  output_endi <= head_endi;\n" } else { "" };
+
+    formatdoc!("
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+
+        library work;
+        use work.{project_name}.all;
+        use work.UtilInt_pkg.all;
+
+        -- Removes the values of a record for which the filter did not match. Every
+        -- value takes one result from the select stream. Values wait in a buffer of
+        -- DEPTH transfers for their result, so a record may not span more transfers.
+        -- Rejected values keep their outer last flags so the dimensions stay intact.
+        entity {entity} is
+          generic (
+            {generics}
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+        {memories}
+          signal wr_ptr    : natural range 0 to DEPTH-1 := 0;
+          signal rd_ptr    : natural range 0 to DEPTH-1 := 0;
+          signal count     : natural range 0 to DEPTH := 0;
+          signal push      : std_logic;
+          signal pop       : std_logic;
+
+          signal value_end : std_logic;
+          signal has_data  : std_logic;
+          signal needs_sel : std_logic;
+          signal pass      : std_logic;
+        begin
+          input_ready <= '1' when count < DEPTH else '0';
+          push        <= '1' when to_x01(input_valid) = '1' and count < DEPTH else '0';
+
+        {heads}
+        {value_proc}
+          -- Transfers without a value (e.g. only closing outer dimensions) need no result
+          needs_sel <= value_end or has_data;
+          pass      <= select_data(0) when needs_sel = '1' else '1';
+
+          output_valid <= '1' when count > 0 and (needs_sel = '0' or to_x01(select_valid) = '1') else '0';
+          output_data  <= head_data;
+        {indices}{output_proc}
+          pop          <= '1' when count > 0 and (needs_sel = '0' or to_x01(select_valid) = '1') and to_x01(output_ready) = '1' else '0';
+          select_ready <= pop and value_end;
+
+          fifo_proc: process (clk) is
+          begin
+            if rising_edge(clk) then
+              if push = '1' then
+        {writes}          wr_ptr <= (wr_ptr + 1) mod DEPTH;
+              end if;
+
+              if pop = '1' then
+                rd_ptr <= (rd_ptr + 1) mod DEPTH;
+              end if;
+
+              if push = '1' and pop = '0' then
+                count <= count + 1;
+              elsif push = '0' and pop = '1' then
+                count <= count - 1;
+              end if;
+
+              if to_x01(rst) /= '0' then
+                wr_ptr <= 0;
+                rd_ptr <= 0;
+                count  <= 0;
+              end if;
+            end if;
+          end process;
+        end behav;
+        ",
+        project_name = gen_params.project_name,
+        generics = generics.join(";\n    "),
+        ports = ports.join(";\n    "),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_integers_of_any_width() {
+        assert_eq!(integer_comparison(Comparison::Ne, 255, false), "unsigned(input_data) /= resize(unsigned'(x\"FF\"), input_data'length)");
+        assert_eq!(integer_comparison(Comparison::Lt, u64::MAX.into(), false), "unsigned(input_data) < resize(unsigned'(x\"FFFFFFFFFFFFFFFF\"), input_data'length)");
+    }
+
+    #[test]
+    fn sign_extends_signed_literals() {
+        assert_eq!(integer_comparison(Comparison::Ge, -3, true), "signed(input_data) >= resize(signed'(x\"D\"), input_data'length)");
+        assert_eq!(integer_comparison(Comparison::Eq, 8, true), "signed(input_data) = resize(signed'(x\"08\"), input_data'length)");
+        assert_eq!(integer_comparison(Comparison::Lt, i128::MIN, true), format!("signed(input_data) < resize(signed'(x\"8{}\"), input_data'length)", "0".repeat(31)));
+    }
+}
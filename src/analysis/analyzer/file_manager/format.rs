@@ -0,0 +1,419 @@
+use indoc::{formatdoc, indoc};
+
+use crate::analysis::{GeneratorParams, format::StringFormat};
+
+use super::{indent, stream_ports};
+
+// How the characters of a string are parsed into the binary value of a format
+struct FormatLogic {
+    description: &'static str,
+    // The name, type and reset value of every register of the parser
+    state: Vec<(&'static str, String, &'static str)>,
+    functions: &'static str,
+    // Variables that only live during the update of a transfer
+    locals: Vec<(&'static str, String)>,
+    // Updates the `v_` variables of the registers with the character `c` and its value `digit`
+    update: String,
+    // The output value, computed from the `next_` values of the registers
+    result: &'static str,
+    // The error flags of the value, for formats that report errors
+    error: Option<&'static str>,
+}
+
+// Decimal digits of the date fields, shared by dates and datetimes
+const DATE_UPDATE: &str = "
+if v_pos < 4 then
+  v_year := (v_year * 10 + digit) mod 10000;
+elsif v_pos = 5 or v_pos = 6 then
+  v_month := (v_month * 10 + digit) mod 100;
+elsif v_pos = 8 or v_pos = 9 then
+  v_day := (v_day * 10 + digit) mod 100;
+end if;";
+
+const EPOCH_FUNCTION: &str = "
+-- Seconds since the Unix epoch, counting days with the proleptic Gregorian calendar
+function epoch(year, month, day, hour, minute, second : natural; tz_neg : std_logic; tz_hour, tz_minute : natural) return signed is
+  variable y, era, yoe, doy, doe, days, offset : integer;
+begin
+  y := year;
+  if month <= 2 then
+    y := y - 1;
+  end if;
+  era := y / 400;
+  yoe := y - era * 400;
+  if month > 2 then
+    doy := (153 * (month - 3) + 2) / 5 + day - 1;
+  else
+    doy := (153 * (month + 9) + 2) / 5 + day - 1;
+  end if;
+  doe := yoe * 365 + yoe / 4 - yoe / 100 + doy;
+  days := era * 146097 + doe - 719468;
+
+  offset := tz_hour * 3600 + tz_minute * 60;
+  if tz_neg = '1' then
+    offset := -offset;
+  end if;
+
+  return to_signed(days, 32) * to_signed(86400, 32) + to_signed(hour * 3600 + minute * 60 + second - offset, 64);
+end function;
+";
+
+const INTEGER_FUNCTIONS: &str = "
+function to_value(magnitude : unsigned; neg : std_logic) return signed is
+begin
+  if neg = '1' then
+    return -signed(magnitude);
+  end if;
+  return signed(magnitude);
+end function;
+
+-- Whether a magnitude with a sign does not fit in a signed integer of the same width
+function out_of_range(magnitude : unsigned; neg : std_logic) return std_logic is
+begin
+  if magnitude(magnitude'high) = '0' or (neg = '1' and magnitude(magnitude'high-1 downto 0) = 0) then
+    return '0';
+  end if;
+  return '1';
+end function;
+";
+
+fn get_format_logic(format: StringFormat, gen_params: &GeneratorParams) -> FormatLogic {
+    let field = |range: &str| format!("natural range 0 to {}", range);
+
+    match format {
+        StringFormat::Date => FormatLogic {
+            description: "a date (`YYYY-MM-DD`) into a 16 bit year, an 8 bit month and an 8 bit day",
+            state: vec![
+                ("pos", field("10"), "0"),
+                ("year", field("9999"), "0"),
+                ("month", field("99"), "0"),
+                ("day", field("99"), "0"),
+            ],
+            functions: "",
+            update: format!("{}\n\n{}", DATE_UPDATE, indoc!("
+                if v_pos < 10 then
+                  v_pos := v_pos + 1;
+                end if;")),
+            locals: vec![],
+            result: "std_logic_vector(to_unsigned(next_year, 16)) & std_logic_vector(to_unsigned(next_month, 8)) & std_logic_vector(to_unsigned(next_day, 8))",
+            error: None,
+        },
+        StringFormat::DateTime => FormatLogic {
+            description: "a datetime (`YYYY-MM-DDThh:mm:ss`, with an optional fraction and\n-- offset from UTC) into signed seconds since the Unix epoch. Fractions are dropped",
+            state: vec![
+                ("pos", field("19"), "0"),
+                ("year", field("9999"), "0"),
+                ("month", field("99"), "0"),
+                ("day", field("99"), "0"),
+                ("hour", field("99"), "0"),
+                ("minute", field("99"), "0"),
+                ("second", field("99"), "0"),
+                ("tz_part", field("2"), "0"),
+                ("tz_neg", "std_logic".to_string(), "'0'"),
+                ("tz_hour", field("99"), "0"),
+                ("tz_minute", field("99"), "0"),
+            ],
+            functions: EPOCH_FUNCTION,
+            update: format!("{}\n\n{}", DATE_UPDATE, indoc!("
+                if v_pos = 11 or v_pos = 12 then
+                  v_hour := (v_hour * 10 + digit) mod 100;
+                elsif v_pos = 14 or v_pos = 15 then
+                  v_minute := (v_minute * 10 + digit) mod 100;
+                elsif v_pos = 17 or v_pos = 18 then
+                  v_second := (v_second * 10 + digit) mod 100;
+                elsif v_pos < 19 then
+                  null;
+                -- A sign after the time starts the offset, its hours and minutes are separated by a colon
+                elsif c = x\"2B\" or c = x\"2D\" then
+                  v_tz_part := 1;
+                  if c = x\"2D\" then
+                    v_tz_neg := '1';
+                  end if;
+                elsif c = x\"3A\" then
+                  v_tz_part := 2;
+                elsif v_tz_part = 1 then
+                  v_tz_hour := (v_tz_hour * 10 + digit) mod 100;
+                elsif v_tz_part = 2 then
+                  v_tz_minute := (v_tz_minute * 10 + digit) mod 100;
+                end if;
+
+                if v_pos < 19 then
+                  v_pos := v_pos + 1;
+                end if;")),
+            locals: vec![],
+            result: "std_logic_vector(epoch(next_year, next_month, next_day, next_hour, next_minute, next_second, next_tz_neg, next_tz_hour, next_tz_minute))",
+            error: None,
+        },
+        StringFormat::Uuid => FormatLogic {
+            description: "a UUID into 128 bits, ignoring the dashes",
+            state: vec![("value", "unsigned(127 downto 0)".to_string(), "(others => '0')")],
+            functions: "",
+            locals: vec![],
+            update: indoc!("
+                if is_hex(c) then
+                  v_value := v_value(123 downto 0) & to_unsigned(digit, 4);
+                end if;").to_string(),
+            result: "std_logic_vector(next_value)",
+            error: None,
+        },
+        StringFormat::Ipv4 => FormatLogic {
+            description: "an IPv4 address into 32 bits, the first octet in the most significant bits",
+            state: vec![
+                ("value", "unsigned(23 downto 0)".to_string(), "(others => '0')"),
+                ("octet", field("999"), "0"),
+            ],
+            functions: "",
+            locals: vec![],
+            update: indoc!("
+                if c = x\"2E\" then
+                  v_value := v_value(15 downto 0) & to_unsigned(v_octet mod 256, 8);
+                  v_octet := 0;
+                else
+                  v_octet := (v_octet * 10 + digit) mod 1000;
+                end if;").to_string(),
+            result: "std_logic_vector(next_value & to_unsigned(next_octet mod 256, 8))",
+            error: None,
+        },
+        StringFormat::Hex => FormatLogic {
+            description: "a hexadecimal number, with or without `0x` prefix, into an integer",
+            state: vec![("value", format!("unsigned({}-1 downto 0)", gen_params.int_width), "(others => '0')")],
+            functions: "",
+            locals: vec![],
+            update: indoc!("
+                if is_hex(c) then
+                  v_value := v_value(v_value'high-4 downto 0) & to_unsigned(digit, 4);
+                end if;").to_string(),
+            result: "std_logic_vector(next_value)",
+            error: None,
+        },
+        StringFormat::Integer => FormatLogic {
+            description: "a decimal integer with an optional minus sign into a signed integer",
+            state: vec![
+                ("neg", "std_logic".to_string(), "'0'"),
+                ("seen", "std_logic".to_string(), "'0'"),
+                ("value", format!("unsigned({}-1 downto 0)", gen_params.int_width), "(others => '0')"),
+                ("overflow", "std_logic".to_string(), "'0'"),
+                ("invalid", "std_logic".to_string(), "'0'"),
+            ],
+            functions: INTEGER_FUNCTIONS,
+            locals: vec![("wide", format!("unsigned({}+3 downto 0)", gen_params.int_width))],
+            update: indoc!("
+                if c = x\"2D\" and v_seen = '0' and v_neg = '0' then
+                  v_neg := '1';
+                elsif c >= x\"30\" and c <= x\"39\" then
+                  v_seen := '1';
+                  v_wide := v_value * to_unsigned(10, 4) + digit;
+                  if v_wide(v_wide'high downto v_value'length) /= 0 then
+                    v_overflow := '1';
+                  end if;
+                  v_value := v_wide(v_value'range);
+                else
+                  v_invalid := '1';
+                end if;").to_string(),
+            result: "std_logic_vector(to_value(next_value, next_neg))",
+            // Strings without digits are invalid as well
+            error: Some("(next_overflow or out_of_range(next_value, next_neg)) & (next_invalid or not next_seen)"),
+        },
+    }
+}
+
+pub fn generate_format_parser(comp_name: &str, format: StringFormat, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+    let lanes = gen_params.epc;
+    let logic = get_format_logic(format, gen_params);
+
+    let string_parser = super::inner_string_parser(comp_name, gen_params);
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+    ports.extend(string_parser.input_ports);
+    ports.extend(stream_ports("output", false, &format.get_stream_type(), gen_params));
+    if let Some(error_type) = format.get_error_type() {
+        ports.extend(stream_ports("error", false, &error_type, gen_params));
+    }
+
+    // Every register has a variable that is updated character by character and the value it gets next
+    let mut signals = String::new();
+    let mut variables = String::new();
+    let mut loads = String::new();
+    let mut nexts = String::new();
+    let mut resets = String::new();
+    let mut advances = String::new();
+    let mut sensitivity = vec!["s_data".to_string(), "s_last".to_string(), "s_strb".to_string()];
+
+    for (name, vhdl_type, reset) in &logic.state {
+        signals.push_str(&format!("  signal {name} : {vhdl_type} := {reset};\n  signal next_{name} : {vhdl_type};\n"));
+        variables.push_str(&format!("    variable v_{name} : {vhdl_type};\n"));
+        loads.push_str(&format!("    v_{name} := {name};\n"));
+        nexts.push_str(&format!("    next_{name} <= v_{name};\n"));
+        resets.push_str(&format!("{name} <= {reset};\n"));
+        advances.push_str(&format!("{name} <= next_{name};\n"));
+        sensitivity.push(name.to_string());
+    }
+
+    for (name, vhdl_type) in &logic.locals {
+        variables.push_str(&format!("    variable v_{name} : {vhdl_type};\n"));
+    }
+
+    // The value and its error flags are handed over on separate streams that may accept them at different times
+    let (handshake, error_signals, error_state) = match logic.error {
+        Some(error) => (
+            formatdoc!("
+                -- Only the end of a string produces a value and error flags, which are accepted independently
+                output_valid <= s_valid and value_end and not output_sent;
+                error_valid  <= s_valid and value_end and not error_sent;
+                accepted     <= (output_sent or output_ready) and (error_sent or error_ready);
+                s_ready      <= accepted or not value_end;
+                error_data   <= {error};
+                error_last   <= end_last;
+                error_strb   <= '1';"),
+            indent(indoc!("
+                signal output_sent : std_logic := '0';
+                signal error_sent  : std_logic := '0';
+                signal accepted    : std_logic;"), 2),
+            indent(indoc!("
+                -- Remember which stream took the value while the other one stalls
+                if to_x01(s_valid) = '1' and value_end = '1' then
+                  if accepted = '1' then
+                    output_sent <= '0';
+                    error_sent  <= '0';
+                  else
+                    output_sent <= output_sent or output_ready;
+                    error_sent  <= error_sent or error_ready;
+                  end if;
+                end if;
+
+                if to_x01(rst) /= '0' then
+                  output_sent <= '0';
+                  error_sent  <= '0';
+                end if;"), 6),
+        ),
+        None => (
+            formatdoc!("
+                -- Only the end of a string produces a value
+                output_valid <= s_valid and value_end;
+                s_ready      <= output_ready or not value_end;"),
+            String::new(),
+            String::new(),
+        ),
+    };
+
+    // Formats with error flags report malformed strings instead of producing undefined values
+    let expectation = match logic.error {
+        Some(_) => "-- Malformed strings and values that do not fit are reported on the error stream.",
+        None => "-- Strings are expected to be well formed, other strings produce an undefined value.",
+    };
+
+    formatdoc!("
+        {string_parser}
+
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+
+        library work;
+        use work.{project_name}.all;
+        use work.UtilInt_pkg.all;
+
+        -- Parses every string as {description}.
+        {expectation}
+        entity {entity} is
+          generic (
+            EPC : positive := {lanes};
+            NESTING_LEVEL : natural := 1
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+          constant DIMS : positive := NESTING_LEVEL + 1;
+
+          function is_hex(c : std_logic_vector(7 downto 0)) return boolean is
+            variable code : natural;
+          begin
+            code := to_integer(unsigned(c));
+            return (code >= 48 and code <= 57) or (code >= 65 and code <= 70) or (code >= 97 and code <= 102);
+          end function;
+
+          -- The value of a decimal or hexadecimal digit
+          function hex_value(c : std_logic_vector(7 downto 0)) return natural is
+            variable code : natural;
+          begin
+            code := to_integer(unsigned(c));
+            if code >= 48 and code <= 57 then
+              return code - 48;
+            elsif code >= 65 and code <= 70 then
+              return code - 55;
+            elsif code >= 97 and code <= 102 then
+              return code - 87;
+            end if;
+            return 0;
+          end function;
+        {functions}
+        {signals}
+        {string_signals}
+          signal value_end : std_logic;
+          signal end_last  : std_logic_vector(NESTING_LEVEL-1 downto 0);
+        {error_signals}begin
+        {instance}
+          -- Strings are expected to end at most once per transfer
+          parse_proc: process ({sensitivity}) is
+            variable c     : std_logic_vector(7 downto 0);
+            variable digit : natural range 0 to 15;
+        {variables}  begin
+        {loads}    value_end <= '0';
+            end_last <= (others => '0');
+
+            for lane in 0 to EPC-1 loop
+              if s_strb(lane) = '1' then
+                c := s_data(8*lane+7 downto 8*lane);
+                digit := hex_value(c);
+
+        {update}      end if;
+
+              if s_last(DIMS*lane) = '1' then
+                value_end <= '1';
+                end_last <= s_last(DIMS*lane+NESTING_LEVEL downto DIMS*lane+1);
+              end if;
+            end loop;
+
+        {nexts}  end process;
+
+        {handshake}
+          output_data  <= {result};
+          output_last  <= end_last;
+          output_strb  <= '1';
+
+          state_proc: process (clk) is
+          begin
+            if rising_edge(clk) then
+              if to_x01(s_valid) = '1' and to_x01(s_ready) = '1' then
+                if value_end = '1' then
+        {resets}        else
+        {advances}        end if;
+              end if;
+
+              if to_x01(rst) /= '0' then
+        {rst_resets}      end if;
+        {error_state}    end if;
+          end process;
+        end behav;
+        ",
+        string_parser = string_parser.entity,
+        project_name = gen_params.project_name,
+        description = logic.description,
+        handshake = indent(&handshake, 2).trim_end(),
+        error_state = if error_state.is_empty() { String::new() } else { format!("\n{}", error_state) },
+        ports = ports.join(";\n    "),
+        functions = if logic.functions.is_empty() { String::new() } else { format!("\n{}", indent(logic.functions, 2)) },
+        string_signals = string_parser.signals,
+        instance = string_parser.instance,
+        sensitivity = sensitivity.join(", "),
+        update = indent(&logic.update, 8),
+        resets = indent(&resets, 10),
+        rst_resets = indent(&resets, 8),
+        advances = indent(&advances, 10),
+        result = logic.result,
+    )
+}
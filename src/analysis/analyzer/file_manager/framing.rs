@@ -0,0 +1,166 @@
+use indoc::formatdoc;
+
+use crate::analysis::{GeneratorParams, framing::InputFraming, analyzer::type_manager::StreamType};
+
+use super::{indent, shape::shaped_ports};
+
+pub fn generate_framer(comp_name: &str, framing: InputFraming, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+    let lanes = gen_params.epc;
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+    ports.extend(shaped_ports("input", true, &StreamType::Json, 1, gen_params));
+    ports.extend(shaped_ports("output", false, &StreamType::Json, 2, gen_params));
+
+    let header = formatdoc!("
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+
+        library work;
+        use work.{project_name}.all;
+        use work.UtilInt_pkg.all;
+        ",
+        project_name = gen_params.project_name,
+    );
+
+    // The characters are passed on as they are, only their last flags change
+    let mut wires = vec!["output_valid <= input_valid;", "input_ready  <= output_ready;", "output_data  <= input_data;"];
+    if lanes > 1 {
+        wires.push("output_stai  <= input_stai;");
+        wires.push("output_endi  <= input_endi;");
+    }
+
+    match framing {
+        InputFraming::Ndjson => generate_ndjson_splitter(&entity, &header, &ports, &wires, lanes),
+        _ => formatdoc!("
+            {header}
+            -- Frames a single document: the end of the characters is the end of the document.
+            entity {entity} is
+              generic (
+                EPC : positive := {lanes}
+              );
+              port (
+                {ports}
+              );
+            end {entity};
+
+            architecture behav of {entity} is
+            begin
+            {wires}
+              output_strb  <= input_strb;
+
+              lane_gen: for i in 0 to EPC-1 generate
+                output_last(2*i+1 downto 2*i) <= input_last(i) & input_last(i);
+              end generate;
+            end behav;
+            ",
+            ports = ports.join(";\n    "),
+            wires = indent(&wires.join("\n"), 2).trim_end(),
+        ),
+    }
+}
+
+// Every line that holds more than whitespace is a document, the newlines are left out
+fn generate_ndjson_splitter(entity: &str, header: &str, ports: &[String], wires: &[&str], lanes: usize) -> String {
+    let (input_strb, output_strb) = if lanes > 1 {
+        ("input_strb(i)", "output_strb <= strb;")
+    } else {
+        ("input_strb", "output_strb <= strb(0);")
+    };
+
+    // Lanes outside of the lane indices hold nothing
+    let mut input_lane = format!("active := {};\ntext_end := input_last(i);", input_strb);
+    if lanes > 1 {
+        input_lane.push('\n');
+        input_lane.push_str(&formatdoc!("
+            if i < to_integer(unsigned(input_stai)) or i > to_integer(unsigned(input_endi)) then
+              active := '0';
+              text_end := '0';
+            end if;"));
+    }
+
+    formatdoc!("
+        {header}
+        -- Splits newline delimited JSON into documents: every line that holds more than
+        -- whitespace is a document, which ends with its newline or with the end of the
+        -- text. The newlines and the whitespace in front of a document are left out.
+        entity {entity} is
+          generic (
+            EPC : positive := {lanes}
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+          -- A document has started on the current line
+          signal in_document      : std_logic := '0';
+          signal next_in_document : std_logic;
+        begin
+        {wires}
+
+          split_proc: process (input_data, input_last, input_strb, {range_signals}in_document) is
+            variable c        : std_logic_vector(7 downto 0);
+            variable active   : std_logic;
+            variable text_end : std_logic;
+            variable open_doc : std_logic;
+            variable strb     : std_logic_vector(EPC-1 downto 0);
+            variable last     : std_logic_vector(2*EPC-1 downto 0);
+          begin
+            open_doc := in_document;
+            for i in 0 to EPC-1 loop
+              c := input_data(8*i+7 downto 8*i);
+        {input_lane}
+
+              strb(i) := '0';
+              last(2*i+1 downto 2*i) := \"00\";
+              if active = '1' then
+                if c = x\"0A\" then
+                  -- A newline ends the document on its line
+                  if open_doc = '1' then
+                    last(2*i+1 downto 2*i) := \"11\";
+                    open_doc := '0';
+                  end if;
+                elsif open_doc = '0' and (c = x\"20\" or c = x\"09\" or c = x\"0D\") then
+                  -- Whitespace in front of a document, or on a line without one
+                  null;
+                else
+                  strb(i) := '1';
+                  open_doc := '1';
+                end if;
+              end if;
+
+              -- The end of the text ends the document on the last line
+              if text_end = '1' and open_doc = '1' then
+                last(2*i+1 downto 2*i) := \"11\";
+                open_doc := '0';
+              end if;
+            end loop;
+
+            {output_strb}
+            output_last <= last;
+            next_in_document <= open_doc;
+          end process;
+
+          reg_proc: process (clk) is
+          begin
+            if rising_edge(clk) then
+              if to_x01(input_valid) = '1' and to_x01(output_ready) = '1' then
+                in_document <= next_in_document;
+              end if;
+
+              if to_x01(rst) /= '0' then
+                in_document <= '0';
+              end if;
+            end if;
+          end process;
+        end behav;
+        ",
+        ports = ports.join(";\n    "),
+        wires = indent(&wires.join("\n"), 2).trim_end(),
+        range_signals = if lanes > 1 { "input_stai, input_endi, " } else { "" },
+        input_lane = indent(&input_lane, 6).trim_end(),
+    )
+}
@@ -0,0 +1,83 @@
+use indoc::formatdoc;
+
+use crate::analysis::{GeneratorParams, analyzer::type_manager::StreamType};
+
+use super::stream_ports;
+
+pub fn generate_join(comp_name: &str, stream_type: &StreamType, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+
+    let fields = match stream_type {
+        StreamType::Group(group) => group.get_fields().clone(),
+        _ => Vec::new(),
+    };
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+    for (name, field_type) in &fields {
+        ports.extend(stream_ports(&format!("input_{}", name), true, field_type, gen_params));
+    }
+    ports.extend(stream_ports("output", false, stream_type, gen_params));
+
+    let inputs: Vec<String> = fields.iter().map(|(name, _)| format!("input_{}", name)).collect();
+    let first = inputs.first().cloned().unwrap_or_default();
+
+    // The first field ends up in the least significant bits of the group
+    let all_valid = inputs.iter().map(|input| format!("{}_valid", input)).collect::<Vec<_>>().join(" and ");
+    let data = inputs.iter().rev().map(|input| format!("{}_data", input)).collect::<Vec<_>>().join(" & ");
+
+    // A dimension of the group ends when it ends for any of the fields, and an element is only there when all fields have one
+    let any_last = inputs.iter().map(|input| format!("{}_last", input)).collect::<Vec<_>>().join(" or ");
+    let all_strb = inputs.iter().map(|input| format!("{}_strb", input)).collect::<Vec<_>>().join(" and ");
+
+    // The fields of one record close the same dimensions, anything else means they are out of step
+    let same_last = inputs.iter().skip(1).map(|input| format!("{}_last = {}_last", input, first)).collect::<Vec<_>>().join(" and ");
+
+    let mut readies = String::new();
+    for input in &inputs {
+        readies.push_str(&format!("  {}_ready <= all_valid and output_ready;\n", input));
+    }
+
+    formatdoc!("
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+
+        library work;
+        use work.{project_name}.all;
+
+        -- Joins the fields of one record into a single transfer. A transfer is only
+        -- produced once every field is valid, and all fields are acknowledged together.
+        -- The fields have to end the same dimensions, which is checked in simulation.
+        entity {entity} is
+          generic (
+            NESTING_LEVEL : natural := 1
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+          signal all_valid : std_logic;
+        begin
+          all_valid <= {all_valid};
+
+          output_valid <= all_valid;
+          output_data  <= {data};
+          output_last  <= {any_last};
+          output_strb  <= {all_strb};
+
+        {readies}
+          check: process (clk) is
+          begin
+            if rising_edge(clk) then
+              assert all_valid = '0' or ({same_last})
+                report \"the fields of a record end different dimensions\" severity error;
+            end if;
+          end process;
+        end behav;
+        ",
+        project_name = gen_params.project_name,
+        ports = ports.join(";\n    "),
+    )
+}
@@ -0,0 +1,178 @@
+use indoc::formatdoc;
+
+use crate::analysis::GeneratorParams;
+
+// Declaration of a JSON stream with a number of lanes that is set by the given generic
+fn json_ports(name: &str, is_input: bool, epc: &str, lanes: usize) -> Vec<String> {
+    let (dir, rev) = if is_input { ("in", "out") } else { ("out", "in") };
+
+    let mut ports = vec![
+        format!("{}_valid : {} std_logic", name, dir),
+        format!("{}_ready : {} std_logic", name, rev),
+        format!("{}_data : {} std_logic_vector({}*8-1 downto 0)", name, dir, epc),
+        format!("{}_last : {} std_logic_vector((NESTING_LEVEL+1)*{}-1 downto 0)", name, dir, epc),
+    ];
+
+    if lanes > 1 {
+        ports.push(format!("{}_stai : {} std_logic_vector(log2ceil({})-1 downto 0)", name, dir, epc));
+        ports.push(format!("{}_endi : {} std_logic_vector(log2ceil({})-1 downto 0)", name, dir, epc));
+        ports.push(format!("{}_strb : {} std_logic_vector({}-1 downto 0)", name, dir, epc));
+    } else {
+        ports.push(format!("{}_strb : {} std_logic", name, dir));
+    }
+
+    ports
+}
+
+pub fn generate_lane_converter(comp_name: &str, input_lanes: usize, output_lanes: usize, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+    ports.extend(json_ports("input", true, "INPUT_EPC", input_lanes));
+    ports.extend(json_ports("output", false, "OUTPUT_EPC", output_lanes));
+
+    // Single lane streams have no lane indices, every lane of them is in use
+    let input_lane = if input_lanes > 1 {
+        formatdoc!("
+            id(i).strb := input_strb(i);
+            if i < to_integer(unsigned(input_stai)) or i > to_integer(unsigned(input_endi)) then
+              id(i) := EMPTY;
+            end if;")
+    } else {
+        "id(i).strb := input_strb;".to_string()
+    };
+
+    let output_lanes_ports = if output_lanes > 1 {
+        formatdoc!("
+            output_stai <= (others => '0');
+            if count > 0 then
+              output_endi <= std_logic_vector(to_unsigned(count-1, output_endi'length));
+            else
+              output_endi <= (others => '0');
+            end if;
+            for i in 0 to OUTPUT_EPC-1 loop
+              output_strb(i) <= od(i).strb;
+            end loop;")
+    } else {
+        "output_strb <= od(0).strb;".to_string()
+    };
+
+    formatdoc!("
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+        use ieee.std_logic_misc.or_reduce;
+
+        library work;
+        use work.{project_name}.all;
+        use work.UtilInt_pkg.all;
+
+        -- Moves the characters of a JSON stream to a stream with fewer lanes.
+        -- Lanes that carry neither a character nor the end of a sequence are dropped,
+        -- the others keep their order and their last flags. A transfer of the input
+        -- is spread over as many output transfers as its lanes need, and lanes of
+        -- different input transfers never share an output transfer.
+        entity {entity} is
+          generic (
+            INPUT_EPC : positive := {input_lanes};
+            OUTPUT_EPC : positive := {output_lanes};
+            NESTING_LEVEL : natural := 1
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+        begin
+          convert_proc: process (clk) is
+            type lane_t is record
+              data : std_logic_vector(7 downto 0);
+              last : std_logic_vector(NESTING_LEVEL downto 0);
+              strb : std_logic;
+            end record;
+
+            type lane_array is array (natural range <>) of lane_t;
+            constant EMPTY : lane_t := (data => x\"00\", last => (others => '0'), strb => '0');
+
+            -- Input holding register, of which the lanes are moved in order
+            variable id  : lane_array(0 to INPUT_EPC-1);
+            variable iv  : std_logic := '0';
+            variable ir  : std_logic := '0';
+            variable idx : natural range 0 to INPUT_EPC := 0;
+
+            -- Output holding register, which is sent when it is full or holds the rest of an input transfer
+            variable od    : lane_array(0 to OUTPUT_EPC-1) := (others => EMPTY);
+            variable ov    : std_logic := '0';
+            variable count : natural range 0 to OUTPUT_EPC := 0;
+          begin
+            if rising_edge(clk) then
+
+              -- Latch input holding register if we said we would.
+              if to_x01(ir) = '1' then
+                iv := input_valid;
+                idx := 0;
+                for i in 0 to INPUT_EPC-1 loop
+                  id(i).data := input_data(8*i+7 downto 8*i);
+                  id(i).last := input_last((NESTING_LEVEL+1)*(i+1)-1 downto (NESTING_LEVEL+1)*i);
+        {input_lane}
+                end loop;
+              end if;
+
+              -- Clear output holding register if transfer was accepted.
+              if to_x01(output_ready) = '1' and ov = '1' then
+                ov := '0';
+                od := (others => EMPTY);
+                count := 0;
+              end if;
+
+              -- Move the lanes that carry a character or the end of a sequence to the output holding register.
+              if ov = '0' and iv = '1' then
+                for step in 0 to INPUT_EPC-1 loop
+                  if idx < INPUT_EPC and count < OUTPUT_EPC then
+                    if id(idx).strb = '1' or or_reduce(id(idx).last) = '1' then
+                      od(count) := id(idx);
+                      count := count + 1;
+                    end if;
+                    idx := idx + 1;
+                  end if;
+                end loop;
+
+                if idx = INPUT_EPC then
+                  iv := '0';
+                  if count > 0 then
+                    ov := '1';
+                  end if;
+                elsif count = OUTPUT_EPC then
+                  ov := '1';
+                end if;
+              end if;
+
+              -- Handle rst.
+              if to_x01(rst) /= '0' then
+                iv := '0';
+                ov := '0';
+                od := (others => EMPTY);
+                count := 0;
+                idx := 0;
+              end if;
+
+              -- Forward output holding register.
+              output_valid <= to_x01(ov);
+              ir := not iv and not rst;
+              input_ready <= ir and not rst;
+              for i in 0 to OUTPUT_EPC-1 loop
+                output_data(8*i+7 downto 8*i) <= od(i).data;
+                output_last((NESTING_LEVEL+1)*(i+1)-1 downto (NESTING_LEVEL+1)*i) <= od(i).last;
+              end loop;
+        {output_lanes_ports}
+            end if;
+          end process;
+        end behav;
+        ",
+        project_name = gen_params.project_name,
+        ports = ports.join(";\n    "),
+        input_lane = super::indent(&input_lane, 10).trim_end(),
+        output_lanes_ports = super::indent(&output_lanes_ports, 6).trim_end(),
+    )
+}
@@ -3,39 +3,119 @@ use std::{fs::File, collections::HashMap, io::Write};
 use text_template::Template;
 
 mod matcher;
+mod duplicator;
+mod join;
+mod union;
+mod tuple;
+mod array;
+mod filter;
+mod aggregate;
+mod enumeration;
+mod format;
+mod decoder;
+mod validate;
+mod lanes;
+mod shape;
+mod framing;
 
-use crate::analysis::GeneratorParams;
+use indoc::formatdoc;
+
+use crate::analysis::{GeneratorParams, format::StringFormat, framing::InputFraming, analyzer::type_manager::{StreamType, TagType}, filter::{Comparison, Combine, Literal}, aggregate::Reduction, validate::ValueKind, params::ComponentParam};
 
 #[derive(Clone,Debug)]
 pub enum TemplateType {
-    Array,
-    Int,
+    Array { counter_width: usize },
+    ArraySideBand { length: bool, index: bool, counter_width: usize },
+    Int { int_width: Option<usize>, pipeline_stages: usize },
     Bool,
     Record,
-    Key,
+    Key { buffer_depth: usize },
+    Map,
+    Raw,
     String,
-    Matcher(String)
+    Matcher(String),
+    Duplicator { stream_type: StreamType, outputs: usize },
+    Join { stream_type: StreamType },
+    UnionRouter(TagType),
+    EnumMatcher(TagType),
+    Format(StringFormat),
+    StringDecoder,
+    TupleDemux(usize),
+    // The input type is the type of the integers that are compared, the codes of an enum or the integers of a parser,
+    // which are two's complement when signed
+    FilterCompare { comparison: Comparison, literal: Literal, input_type: StreamType, signed: bool },
+    FilterCombine { inputs: usize, combine: Combine },
+    FilterGate { stream_type: StreamType },
+    Aggregate { reduction: Reduction, input_type: StreamType, output_type: StreamType },
+    TypeChecker { kind: ValueKind, path_id: usize },
+    // The text, path id and whether it is required of every key
+    KeyChecker { keys: Vec<(String, usize, bool)>, path_id: usize },
+    DocumentChecker { kind: Option<ValueKind>, max_depth: Option<usize> },
+    ValidationMerger { inputs: usize, paths: Vec<String> },
+    LaneConverter { input_lanes: usize, output_lanes: usize },
+    // The streams on either side with their dimensions, of which the inner ones are kept when flattening
+    StreamAdapter { input_type: StreamType, output_type: StreamType, input_dim: usize, output_dim: usize, inner_dim: usize, repack: bool },
+    Framer(InputFraming),
 }
 
 impl TemplateType {
     pub fn get_template(&self) -> Option<String> {
         match self {
-            TemplateType::Array => Some(String::from(include_str!("templates/array_parser.vhd"))),
-            TemplateType::Int => Some(String::from(include_str!("templates/int_parser.vhd"))),
+            TemplateType::Array { .. } => Some(String::from(include_str!("templates/array_parser.vhd"))),
+            TemplateType::ArraySideBand { .. } => None,
+            TemplateType::Int { .. } => Some(String::from(include_str!("templates/int_parser.vhd"))),
             TemplateType::Bool => Some(String::from(include_str!("templates/bool_parser.vhd"))),
             TemplateType::Record => Some(String::from(include_str!("templates/record_parser.vhd"))),
-            TemplateType::Key => Some(String::from(include_str!("templates/key_parser.vhd"))),
+            TemplateType::Key { .. } => Some(String::from(include_str!("templates/key_parser.vhd"))),
+            TemplateType::Map => Some(String::from(include_str!("templates/map_parser.vhd"))),
+            TemplateType::Raw => Some(String::from(include_str!("templates/raw_passthrough.vhd"))),
             TemplateType::String => Some(String::from(include_str!("templates/string_parser.vhd"))),
             TemplateType::Matcher(_) => None,
+            TemplateType::Duplicator { .. } => None,
+            TemplateType::Join { .. } => None,
+            TemplateType::UnionRouter(_) => None,
+            TemplateType::EnumMatcher(_) => None,
+            TemplateType::Format(_) => None,
+            TemplateType::StringDecoder => None,
+            TemplateType::TupleDemux(_) => None,
+            TemplateType::FilterCompare { .. } => None,
+            TemplateType::FilterCombine { .. } => None,
+            TemplateType::FilterGate { .. } => None,
+            TemplateType::Aggregate { .. } => None,
+            TemplateType::TypeChecker { .. } => None,
+            TemplateType::KeyChecker { .. } => None,
+            TemplateType::DocumentChecker { .. } => None,
+            TemplateType::ValidationMerger { .. } => None,
+            TemplateType::LaneConverter { .. } => None,
+            TemplateType::StreamAdapter { .. } => None,
+            TemplateType::Framer(_) => None,
+        }
+    }
+
+    // The tuning parameters and path specific widths that are filled in as the defaults of the generics of the template
+    pub fn get_params(&self) -> Vec<(&'static str, usize)> {
+        match self {
+            TemplateType::Array { counter_width } | TemplateType::ArraySideBand { counter_width, .. } => vec![(ComponentParam::ElementCounterWidth.get_name(), *counter_width)],
+            TemplateType::Int { int_width, pipeline_stages } => {
+                let mut params = vec![(ComponentParam::PipelineStages.get_name(), *pipeline_stages)];
+                params.extend(int_width.map(|int_width| ("int_width", int_width)));
+                params
+            },
+            TemplateType::Key { buffer_depth } => vec![(ComponentParam::KeyBufferDepth.get_name(), *buffer_depth)],
+            _ => Vec::new(),
         }
     }
 }
 
+#[derive(Clone)]
 struct TemplateInstance {
     pub template_type: TemplateType,
     pub component_name: String,
+    // The number of lanes of the component, if it differs from the EPC of the design
+    pub lanes: Option<usize>,
 }
 
+#[derive(Clone)]
 pub struct FileManager {
     files: Vec<TemplateInstance>,
 }
@@ -51,20 +131,47 @@ impl FileManager {
         self.files.push(TemplateInstance {
             template_type,
             component_name: component_name.to_owned(),
+            lanes: None,
         });
     }
 
-    pub fn generate_toml(&self, output_path: &str, gen_params: &GeneratorParams) {
+    pub fn remove_entities(&mut self, component_names: &[String]) {
+        self.files.retain(|inst| !component_names.contains(&inst.component_name));
+    }
+
+    // What the file of a component is generated from, apart from its name
+    pub fn describe_entity(&self, component_name: &str) -> Option<String> {
+        self.files.iter()
+            .find(|inst| inst.component_name == component_name)
+            .map(|inst| format!("{:?}", inst.template_type))
+    }
+
+    // The file of a component, as it is generated into the VHDL directory
+    #[cfg(test)]
+    pub fn generate_entity(&self, component_name: &str, gen_params: &GeneratorParams) -> Option<String> {
+        self.files.iter()
+            .find(|inst| inst.component_name == component_name)
+            .map(|inst| self.generate_file(inst, gen_params))
+    }
+
+    // Generate the file of a component for another number of lanes than the EPC of the design
+    pub fn set_lanes(&mut self, component_name: &str, lanes: usize) {
+        if let Some(inst) = self.files.iter_mut().find(|inst| inst.component_name == component_name) {
+            inst.lanes = Some(lanes);
+        }
+    }
+
+    // Take over the files of the components of another schema
+    pub fn extend(&mut self, other: FileManager) {
+        self.files.extend(other.files);
+    }
+
+    pub fn generate_toml(&self, output_path: &str, top_names: &[&str], gen_params: &GeneratorParams) {
         // Generate the files
         let file_name = format!("{}/project.toml", output_path);
         let mut file = File::create(file_name).unwrap();
 
-        let template = Template::from(include_str!("templates/toml_template.toml")); 
-
-        let mut templ_values: HashMap<&str, &str> = HashMap::new();
-        templ_values.insert("project_name", &gen_params.project_name);
-
-        let text = template.fill_in(&templ_values).to_string();
+        let text = project_toml(top_names, gen_params);
         file.write_all(text.as_bytes()).unwrap();
     }
 
@@ -78,17 +185,95 @@ impl FileManager {
             let file_name = format!("{}/{}_0_{}.vhd", path, gen_params.comp_namespace, inst.component_name);
             let mut file = File::create(file_name).unwrap();
 
-            let text = self.file_from_template(inst, gen_params);
+            let text = self.generate_file(inst, gen_params);
             file.write_all(text.as_bytes()).unwrap();
         }
     }
 
+    // Components with lanes of their own are generated for their number of lanes
+    fn generate_file(&self, inst: &TemplateInstance, gen_params: &GeneratorParams) -> String {
+        match inst.lanes {
+            Some(lanes) => self.file_from_template(inst, &gen_params.with_epc(lanes)),
+            None => self.file_from_template(inst, gen_params),
+        }
+    }
+
     fn file_from_template(&self, template_inst: &TemplateInstance, gen_params: &GeneratorParams) -> String {
         match template_inst.template_type {
             // Matcher needs to be handled differently as the python script fills in the template
             TemplateType::Matcher(ref matcher_str) => {
                 matcher::generate_matcher(matcher_str, &format!("{}_0_{}_com", gen_params.comp_namespace, template_inst.component_name), &gen_params.project_name).unwrap()
             },
+            // Duplicators are generated for the number of outputs and the stream type they duplicate
+            TemplateType::Duplicator { ref stream_type, outputs } => {
+                duplicator::generate_duplicator(&template_inst.component_name, stream_type, outputs, gen_params)
+            },
+            // Joins are generated for the fields of the group they assemble
+            TemplateType::Join { ref stream_type } => {
+                join::generate_join(&template_inst.component_name, stream_type, gen_params)
+            },
+            // Routers are generated for the variants of the union they dispatch
+            TemplateType::UnionRouter(ref tag_type) => {
+                union::generate_union_router(&template_inst.component_name, tag_type, gen_params)
+            },
+            // Enum matchers are generated for the strings they encode
+            TemplateType::EnumMatcher(ref code_type) => {
+                enumeration::generate_enum_matcher(&template_inst.component_name, code_type, gen_params)
+            },
+            // Format parsers are generated for the format of the strings they parse
+            TemplateType::Format(format) => {
+                format::generate_format_parser(&template_inst.component_name, format, gen_params)
+            },
+            TemplateType::StringDecoder => {
+                decoder::generate_string_decoder(&template_inst.component_name, gen_params)
+            },
+            // Demultiplexers are generated for the number of positions of the tuple
+            TemplateType::TupleDemux(positions) => {
+                tuple::generate_tuple_demux(&template_inst.component_name, positions, gen_params)
+            },
+            // Filters are generated for the predicates and the streams they select
+            TemplateType::FilterCompare { comparison, ref literal, ref input_type, signed } => {
+                filter::generate_filter_compare(&template_inst.component_name, comparison, literal, input_type, signed, gen_params)
+            },
+            TemplateType::FilterCombine { inputs, combine } => {
+                filter::generate_filter_combine(&template_inst.component_name, inputs, combine, gen_params)
+            },
+            TemplateType::FilterGate { ref stream_type } => {
+                filter::generate_filter_gate(&template_inst.component_name, stream_type, gen_params)
+            },
+            // Aggregators are generated for the reduction and the type of the values they reduce
+            TemplateType::Aggregate { reduction, ref input_type, ref output_type } => {
+                aggregate::generate_aggregator(&template_inst.component_name, reduction, input_type, output_type, gen_params)
+            },
+            // Checkers are generated for what they expect of the documents
+            TemplateType::TypeChecker { kind, path_id } => {
+                validate::generate_type_checker(&template_inst.component_name, kind, path_id, gen_params)
+            },
+            TemplateType::KeyChecker { ref keys, path_id } => {
+                validate::generate_key_checker(&template_inst.component_name, keys, path_id, gen_params)
+            },
+            TemplateType::DocumentChecker { kind, max_depth } => {
+                validate::generate_document_checker(&template_inst.component_name, kind, max_depth, gen_params)
+            },
+            TemplateType::ValidationMerger { inputs, ref paths } => {
+                validate::generate_validation_merger(&template_inst.component_name, inputs, paths, gen_params)
+            },
+            // Converters are generated for the number of lanes on either side
+            TemplateType::LaneConverter { input_lanes, output_lanes } => {
+                lanes::generate_lane_converter(&template_inst.component_name, input_lanes, output_lanes, gen_params)
+            },
+            // Adapters are generated for the shapes of the streams on either side
+            TemplateType::StreamAdapter { ref input_type, ref output_type, input_dim, output_dim, inner_dim, repack } => {
+                shape::generate_stream_adapter(&template_inst.component_name, input_type, output_type, (input_dim, output_dim, inner_dim), repack, gen_params)
+            },
+            // Framers are generated for the framing of the input
+            TemplateType::Framer(framing) => {
+                framing::generate_framer(&template_inst.component_name, framing, gen_params)
+            },
+            // Arrays with side-band outputs wrap the array parser
+            TemplateType::ArraySideBand { length, index, counter_width } => {
+                array::generate_array_side_band(&template_inst.component_name, length, index, counter_width, gen_params)
+            },
             _ => {
                 // Get the template
                 let template = template_inst.template_type.get_template();
@@ -98,22 +283,212 @@ impl FileManager {
                     Some(template_str) => template_str,
                     None => todo!("Template for {:?} not implemented", template_inst.template_type),
                 };
-        
-                // Convert to template struct
-                let template = Template::from(template_str.as_str());
-        
-                // Create map of values to fill in
-                let mut templ_values: HashMap<&str, &str> = HashMap::new();
-                templ_values.insert("comp_name", &template_inst.component_name);
-                let bit_width = gen_params.bit_width.to_string();
-                templ_values.insert("bit_width", &bit_width);
-                templ_values.insert("namespace", &gen_params.comp_namespace);
-                templ_values.insert("project_name", &gen_params.project_name);
-
-                // Fill in the template
-                template.fill_in(&templ_values).to_string()
+
+                fill_template(&template_str, &template_inst.component_name, &template_inst.template_type.get_params(), gen_params)
             },
         }
     }
 }
 
+// The project file for TIL-VHDL, the schemas of a project with several of them share the namespace
+// and are listed with the top streamlet of each
+fn project_toml(top_names: &[&str], gen_params: &GeneratorParams) -> String {
+    let template = Template::from(include_str!("templates/toml_template.toml"));
+
+    let mut templ_values: HashMap<&str, &str> = HashMap::new();
+    templ_values.insert("project_name", &gen_params.project_name);
+
+    let text = template.fill_in(&templ_values).to_string();
+
+    if top_names == ["top"] {
+        return text;
+    }
+
+    let tops: Vec<String> = top_names.iter().map(|name| format!("\"{}::{}\"", gen_params.namespace, name)).collect();
+    format!("{}\n\ntops = [ {} ]\n", text.trim_end(), tops.join(", "))
+}
+
+// Fill in the names, widths and tuning parameters of a component template, where the given
+// parameters take precedence over the widths of the project
+fn fill_template(template_str: &str, comp_name: &str, params: &[(&str, usize)], gen_params: &GeneratorParams) -> String {
+    // Convert to template struct
+    let template = Template::from(template_str);
+
+    // Create map of values to fill in
+    let mut templ_values: HashMap<&str, &str> = HashMap::new();
+    templ_values.insert("comp_name", comp_name);
+    let bit_width = gen_params.bit_width.to_string();
+    templ_values.insert("bit_width", &bit_width);
+    let int_width = gen_params.int_width.to_string();
+    templ_values.insert("int_width", &int_width);
+    templ_values.insert("namespace", &gen_params.comp_namespace);
+    templ_values.insert("project_name", &gen_params.project_name);
+    let params: Vec<(&str, String)> = params.iter().map(|(name, value)| (*name, value.to_string())).collect();
+    for (name, value) in &params {
+        templ_values.insert(name, value);
+    }
+
+    // Fill in the template
+    template.fill_in(&templ_values).to_string()
+}
+
+// The signals of a stream besides its handshake with their VHDL types, in the order TIL-VHDL lowers them
+fn payload_signals(stream_type: &StreamType, gen_params: &GeneratorParams) -> Vec<(&'static str, String)> {
+    let physical = stream_type.get_physical_params(gen_params);
+
+    let last_width = match (physical.fixed_dimensionality, physical.per_lane_last) {
+        (Some(dim), true) => format!("{}", dim * physical.lanes),
+        (Some(dim), false) => format!("{}", dim),
+        (None, true) => "NESTING_LEVEL*EPC".to_string(),
+        (None, false) => "NESTING_LEVEL".to_string(),
+    };
+
+    let mut signals = vec![
+        ("data", format!("std_logic_vector({}-1 downto 0)", physical.element_bits * physical.lanes)),
+    ];
+
+    // A single lane stream without dimensions has neither last flags nor a strobe
+    if physical.fixed_dimensionality == Some(0) && physical.lanes == 1 {
+        return signals;
+    }
+
+    signals.push(("last", format!("std_logic_vector({}-1 downto 0)", last_width)));
+
+    if physical.lanes > 1 {
+        signals.push(("stai", "std_logic_vector(log2ceil(EPC)-1 downto 0)".to_string()));
+        signals.push(("endi", "std_logic_vector(log2ceil(EPC)-1 downto 0)".to_string()));
+        signals.push(("strb", "std_logic_vector(EPC-1 downto 0)".to_string()));
+    } else {
+        signals.push(("strb", "std_logic".to_string()));
+    }
+
+    signals
+}
+
+// Declaration of the ports of one stream
+fn stream_ports(name: &str, is_input: bool, stream_type: &StreamType, gen_params: &GeneratorParams) -> Vec<String> {
+    let (dir, rev) = if is_input { ("in", "out") } else { ("out", "in") };
+
+    let mut ports = vec![
+        format!("{}_valid : {} std_logic", name, dir),
+        format!("{}_ready : {} std_logic", name, rev),
+    ];
+
+    for (signal, signal_type) in payload_signals(stream_type, gen_params) {
+        ports.push(format!("{}_{} : {} {}", name, signal, dir, signal_type));
+    }
+
+    ports
+}
+
+// A string parser inside a component that strips the quotes of the input of the component,
+// the characters of the strings are left on the `s_` signals
+struct InnerStringParser {
+    // The parser itself, which precedes the component in its file
+    entity: String,
+    // The ports of the quoted input of the component
+    input_ports: Vec<String>,
+    signals: String,
+    instance: String,
+}
+
+fn inner_string_parser(comp_name: &str, gen_params: &GeneratorParams) -> InnerStringParser {
+    let lanes = gen_params.epc;
+
+    // The string parser is instantiated under its own name
+    let string_name = format!("{}_string", comp_name);
+    let string_entity = format!("{}_0_{}_com", gen_params.comp_namespace, string_name);
+    let entity = fill_template(&TemplateType::String.get_template().unwrap(), &string_name, &[], gen_params);
+
+    let mut input_ports = vec![
+        "input_valid : in std_logic".to_string(),
+        "input_ready : out std_logic".to_string(),
+        "input_data : in std_logic_vector(EPC*8-1 downto 0)".to_string(),
+        "input_last : in std_logic_vector((NESTING_LEVEL+1)*EPC-1 downto 0)".to_string(),
+    ];
+
+    if lanes > 1 {
+        input_ports.push("input_stai : in std_logic_vector(log2ceil(EPC)-1 downto 0)".to_string());
+        input_ports.push("input_endi : in std_logic_vector(log2ceil(EPC)-1 downto 0)".to_string());
+        input_ports.push("input_strb : in std_logic_vector(EPC-1 downto 0)".to_string());
+    } else {
+        input_ports.push("input_strb : in std_logic".to_string());
+    }
+
+    let (stai, endi, strb) = if lanes > 1 {
+        ("input_stai", "input_endi", "input_strb")
+    } else {
+        ("(others => '0')", "(others => '0')", "(others => input_strb)")
+    };
+
+    let signals = formatdoc!("
+          signal s_valid    : std_logic;
+          signal s_ready    : std_logic;
+          signal s_data     : std_logic_vector(EPC*8-1 downto 0);
+          signal s_last     : std_logic_vector((NESTING_LEVEL+1)*EPC-1 downto 0);
+          signal s_strb     : std_logic_vector(EPC-1 downto 0);
+        ");
+
+    let instance = formatdoc!("
+          string_inst: entity work.{string_entity}
+            generic map (
+              EPC => EPC,
+              NESTING_LEVEL => NESTING_LEVEL
+            )
+            port map (
+              clk => clk,
+              rst => rst,
+              input_valid => input_valid,
+              input_ready => input_ready,
+              input_data => input_data,
+              input_last => input_last,
+              input_stai => {stai},
+              input_endi => {endi},
+              input_strb => {strb},
+              output_valid => s_valid,
+              output_ready => s_ready,
+              output_data => s_data,
+              output_last => s_last,
+              output_strb => s_strb
+            );
+        ");
+
+    InnerStringParser { entity, input_ports, signals: indent(&signals, 2), instance: indent(&instance, 2) }
+}
+
+// Indent every line of a block of VHDL
+fn indent(text: &str, spaces: usize) -> String {
+    text.trim_matches('\n')
+        .lines()
+        .map(|line| if line.trim().is_empty() { "\n".to_string() } else { format!("{}{}\n", " ".repeat(spaces), line) })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{Generator, GeneratorParams, config::SchemaConfig};
+
+    use super::project_toml;
+
+    fn tops_of(text: &str) -> Option<Vec<String>> {
+        let project: toml::Table = text.parse().unwrap();
+        assert_eq!(project["name"].as_str(), Some("test"));
+
+        project.get("tops").map(|tops| tops.as_array().unwrap().iter().map(|top| top.as_str().unwrap().to_string()).collect())
+    }
+
+    #[test]
+    fn lists_the_top_of_every_schema_in_the_project_file() {
+        let mut generator = Generator::new("test", 4, 64);
+        generator.add_schema("orders", &[r#"{"id": 1}"#], &SchemaConfig::default()).unwrap();
+        generator.add_schema("users", &[r#"{"name": "a"}"#], &SchemaConfig::default()).unwrap();
+        let design = generator.get_design().unwrap();
+
+        let gen_params = GeneratorParams::new(4, 8, 64, "", "test");
+        let top_names: Vec<&str> = design.get_top_components().iter().map(|top| top.get_name()).collect();
+        assert_eq!(tops_of(&project_toml(&top_names, &gen_params)), Some(vec!["test::orders".to_string(), "test::users".to_string()]));
+
+        // A project with a single schema keeps the project file it always had
+        assert_eq!(tops_of(&project_toml(&["top"], &gen_params)), None);
+    }
+}
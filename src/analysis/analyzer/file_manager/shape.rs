@@ -0,0 +1,326 @@
+use indoc::formatdoc;
+
+use crate::analysis::{GeneratorParams, analyzer::type_manager::StreamType};
+
+use super::indent;
+
+// Declaration of the ports of a stream with a fixed number of dimensions, in the order TIL-VHDL lowers them
+pub(super) fn shaped_ports(name: &str, is_input: bool, stream_type: &StreamType, dim: usize, gen_params: &GeneratorParams) -> Vec<String> {
+    let physical = stream_type.get_physical_params(gen_params);
+    let (dir, rev) = if is_input { ("in", "out") } else { ("out", "in") };
+
+    let last_width = if physical.per_lane_last { dim * physical.lanes } else { dim };
+
+    let mut ports = vec![
+        format!("{}_valid : {} std_logic", name, dir),
+        format!("{}_ready : {} std_logic", name, rev),
+        format!("{}_data : {} std_logic_vector({}-1 downto 0)", name, dir, physical.element_bits * physical.lanes),
+    ];
+
+    // A single lane stream without dimensions has neither last flags nor a strobe
+    if dim == 0 && physical.lanes == 1 {
+        return ports;
+    }
+
+    ports.push(format!("{}_last : {} std_logic_vector({}-1 downto 0)", name, dir, last_width));
+
+    if physical.lanes > 1 {
+        ports.push(format!("{}_stai : {} std_logic_vector(log2ceil(EPC)-1 downto 0)", name, dir));
+        ports.push(format!("{}_endi : {} std_logic_vector(log2ceil(EPC)-1 downto 0)", name, dir));
+        ports.push(format!("{}_strb : {} std_logic_vector(EPC-1 downto 0)", name, dir));
+    } else {
+        ports.push(format!("{}_strb : {} std_logic", name, dir));
+    }
+
+    ports
+}
+
+// The dimensions of the input, the output and the inner dimensions that are kept when flattening
+pub fn generate_stream_adapter(comp_name: &str, input_type: &StreamType, output_type: &StreamType, dims: (usize, usize, usize), repack: bool, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+    let (input_dim, output_dim, inner_dim) = dims;
+    let input = input_type.get_physical_params(gen_params);
+    let output = output_type.get_physical_params(gen_params);
+    let lanes = input.lanes;
+    let element_bits = input.element_bits;
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+    ports.extend(shaped_ports("input", true, input_type, input_dim, gen_params));
+    ports.extend(shaped_ports("output", false, output_type, output_dim, gen_params));
+
+    let has_last = output_dim > 0 || lanes > 1;
+
+    let header = formatdoc!("
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+        use ieee.std_logic_misc.or_reduce;
+
+        library work;
+        use work.{project_name}.all;
+        use work.UtilInt_pkg.all;
+        ",
+        project_name = gen_params.project_name,
+    );
+
+    let generics = [
+        format!("EPC : positive := {}", lanes),
+        format!("INPUT_DIM : natural := {}", input_dim),
+        format!("OUTPUT_DIM : natural := {}", output_dim),
+        format!("INNER_DIM : natural := {}", inner_dim),
+    ].join(";\n    ");
+
+    // The transfers stay the same, only the type of the stream changes
+    if !repack {
+        let mut wires = vec!["output_valid <= input_valid;", "input_ready <= output_ready;", "output_data <= input_data;"];
+        if has_last {
+            wires.push("output_last <= input_last;");
+            wires.push("output_strb <= input_strb;");
+        }
+        if lanes > 1 {
+            wires.push("output_stai <= input_stai;");
+            wires.push("output_endi <= input_endi;");
+        }
+
+        return formatdoc!("
+            {header}
+            -- Passes a stream on with another synchronicity or a higher complexity, which
+            -- its transfers already comply with.
+            entity {entity} is
+              generic (
+                {generics}
+              );
+              port (
+                {ports}
+              );
+            end {entity};
+
+            architecture behav of {entity} is
+            begin
+            {wires}
+            end behav;
+            ",
+            ports = ports.join(";\n    "),
+            wires = indent(&wires.join("\n"), 2).trim_end(),
+        );
+    }
+
+    // The last flags of a lane of the input, a stream with a last signal per transfer ends in its last lane
+    let input_last = match (input_dim > 0 || lanes > 1, input.per_lane_last, lanes > 1) {
+        (false, _, _) => "raw_last := (others => '0');".to_string(),
+        (true, true, _) => "raw_last := input_last(INPUT_DIM*(i+1)-1 downto INPUT_DIM*i);".to_string(),
+        (true, false, true) => formatdoc!("
+            raw_last := (others => '0');
+            if i = to_integer(unsigned(input_endi)) then
+              raw_last := input_last;
+            end if;"),
+        (true, false, false) => "raw_last := input_last;".to_string(),
+    };
+
+    let input_strb = match (input_dim > 0 || lanes > 1, lanes > 1) {
+        (false, _) => "id(i).strb := '1';",
+        (true, true) => "id(i).strb := input_strb(i);",
+        (true, false) => "id(i).strb := input_strb;",
+    };
+
+    // Lanes outside of the lane indices hold nothing
+    let input_range = if lanes > 1 {
+        formatdoc!("
+            if i < to_integer(unsigned(input_stai)) or i > to_integer(unsigned(input_endi)) then
+              id(i).strb := '0';
+              raw_last := (others => '0');
+            end if;")
+    } else {
+        String::new()
+    };
+    let input_lane = format!("{}\n{}\n{}", input_strb, input_last, input_range);
+
+    let output_last = match (has_last, output.per_lane_last) {
+        (false, _) => String::new(),
+        (true, true) => formatdoc!("
+            for i in 0 to EPC-1 loop
+              output_last(OUTPUT_DIM*(i+1)-1 downto OUTPUT_DIM*i) <= od(i).last;
+            end loop;"),
+        (true, false) => formatdoc!("
+            if count > 0 then
+              output_last <= od(count-1).last;
+            else
+              output_last <= (others => '0');
+            end if;"),
+    };
+
+    let output_strb = match (has_last, lanes > 1) {
+        (false, _) => String::new(),
+        (true, true) => formatdoc!("
+            output_stai <= (others => '0');
+            if count > 0 then
+              output_endi <= std_logic_vector(to_unsigned(count-1, output_endi'length));
+            else
+              output_endi <= (others => '0');
+            end if;
+            for i in 0 to EPC-1 loop
+              output_strb(i) <= od(i).strb;
+            end loop;"),
+        (true, false) => "output_strb <= od(0).strb;".to_string(),
+    };
+
+    formatdoc!("
+        {header}
+        -- Repacks a stream for a lower complexity and drops the dimensions of a flattened
+        -- stream: the inner dimensions and the outer dimensions of the documents are kept.
+        -- Lanes that carry neither an element nor the end of a sequence are dropped, and
+        -- the end of a sequence without an element goes along with the element before
+        -- it. The elements are packed from the first lane on, and a transfer is only
+        -- sent when it is full or its last lane holds the end of a sequence, so the last
+        -- flags of a transfer always belong to its last lane. A transfer waits for the
+        -- lane after it before it is sent, unless it ends the outermost dimension.
+        entity {entity} is
+          generic (
+            {generics}
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+        begin
+          adapt_proc: process (clk) is
+            type lane_t is record
+              data : std_logic_vector({element_bits}-1 downto 0);
+              last : std_logic_vector(OUTPUT_DIM-1 downto 0);
+              strb : std_logic;
+            end record;
+
+            type lane_array is array (natural range <>) of lane_t;
+            constant EMPTY : lane_t := (data => (others => '0'), last => (others => '0'), strb => '0');
+
+            -- The outermost dimension of which a lane holds the end, -1 for none
+            function high_dim(last : std_logic_vector) return integer is
+            begin
+              for d in last'high downto last'low loop
+                if last(d) = '1' then
+                  return d;
+                end if;
+              end loop;
+              return -1;
+            end function;
+
+            -- The innermost dimension of which a lane holds the end, the number of dimensions for none
+            function low_dim(last : std_logic_vector) return integer is
+            begin
+              for d in last'low to last'high loop
+                if last(d) = '1' then
+                  return d;
+                end if;
+              end loop;
+              return last'length;
+            end function;
+
+            -- Input holding register, of which the lanes are moved in order
+            variable id       : lane_array(0 to EPC-1);
+            variable iv       : std_logic := '0';
+            variable ir       : std_logic := '0';
+            variable idx      : natural range 0 to EPC := 0;
+            variable raw_last : std_logic_vector(INPUT_DIM-1 downto 0);
+
+            -- Output holding register
+            variable od    : lane_array(0 to EPC-1) := (others => EMPTY);
+            variable ov    : std_logic := '0';
+            variable count : natural range 0 to EPC := 0;
+            variable lane  : lane_t;
+            variable stop  : boolean;
+          begin
+            if rising_edge(clk) then
+
+              -- Latch input holding register if we said we would.
+              if to_x01(ir) = '1' then
+                iv := input_valid;
+                idx := 0;
+                for i in 0 to EPC-1 loop
+                  id(i).data := input_data({element_bits}*(i+1)-1 downto {element_bits}*i);
+        {input_lane}
+                  -- Keep the inner dimensions and the outer dimensions that are not flattened
+                  for d in 0 to OUTPUT_DIM-1 loop
+                    if d < INNER_DIM then
+                      id(i).last(d) := raw_last(d);
+                    else
+                      id(i).last(d) := raw_last(d + INPUT_DIM - OUTPUT_DIM);
+                    end if;
+                  end loop;
+                end loop;
+              end if;
+
+              -- Clear output holding register if transfer was accepted.
+              if to_x01(output_ready) = '1' and ov = '1' then
+                ov := '0';
+                od := (others => EMPTY);
+                count := 0;
+              end if;
+
+              -- Move the lanes to the output holding register.
+              if ov = '0' and iv = '1' then
+                stop := false;
+                for step in 0 to EPC-1 loop
+                  if not stop and idx < EPC then
+                    lane := id(idx);
+                    if lane.strb = '0' and or_reduce(lane.last) = '0' then
+                      -- Lanes without an element or the end of a sequence are dropped
+                      idx := idx + 1;
+                    elsif lane.strb = '0' and count > 0 and high_dim(od(count-1).last) < low_dim(lane.last) then
+                      -- The end of the sequences that hold the one before it goes along with it
+                      od(count-1).last := od(count-1).last or lane.last;
+                      idx := idx + 1;
+                    elsif count = EPC or (count > 0 and or_reduce(od(count-1).last) = '1') then
+                      -- The lane belongs to the next transfer
+                      ov := '1';
+                      stop := true;
+                    else
+                      od(count) := lane;
+                      count := count + 1;
+                      idx := idx + 1;
+                    end if;
+
+                    -- Nothing can go along with the end of the outermost dimension
+                    if not stop and count > 0 then
+                      if (OUTPUT_DIM > 0 and high_dim(od(count-1).last) = OUTPUT_DIM-1) or (OUTPUT_DIM = 0 and count = EPC) then
+                        ov := '1';
+                        stop := true;
+                      end if;
+                    end if;
+                  end if;
+                end loop;
+
+                if idx = EPC then
+                  iv := '0';
+                end if;
+              end if;
+
+              -- Handle rst.
+              if to_x01(rst) /= '0' then
+                iv := '0';
+                ov := '0';
+                od := (others => EMPTY);
+                count := 0;
+                idx := 0;
+              end if;
+
+              -- Forward output holding register.
+              output_valid <= to_x01(ov);
+              ir := not iv and not rst;
+              input_ready <= ir and not rst;
+              for i in 0 to EPC-1 loop
+                output_data({element_bits}*(i+1)-1 downto {element_bits}*i) <= od(i).data;
+              end loop;
+        {output_last}
+        {output_strb}
+            end if;
+          end process;
+        end behav;
+        ",
+        ports = ports.join(";\n    "),
+        input_lane = indent(&input_lane, 10).trim_end(),
+        output_last = indent(&output_last, 6).trim_end(),
+        output_strb = indent(&output_strb, 6).trim_end(),
+    )
+}
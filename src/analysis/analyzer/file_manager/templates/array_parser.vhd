@@ -12,7 +12,7 @@ entity ${namespace}_0_${comp_name}_com is
     EPC : positive := 4;
     OUTER_NESTING_LEVEL : positive := 2;
     INNER_NESTING_LEVEL : natural := 0;
-    ELEMENT_COUNTER_BW : natural := 4
+    ELEMENT_COUNTER_BW : natural := ${element_counter_width}
   );
   port (
     clk : in std_logic;
@@ -11,28 +11,32 @@ entity ${namespace}_0_${comp_name}_com is
   generic (
     EPC : positive := 4;
     NESTING_LEVEL : positive := 3;
-    BITWIDTH : positive := 64;
-    PIPELINE_STAGES : natural := 1
+    BITWIDTH : positive := ${int_width};
+    PIPELINE_STAGES : natural := ${pipeline_stages}
   );
   port (
     clk : in std_logic;
     rst : in std_logic;
     input_valid : in std_logic;
     input_ready : out std_logic;
-    input_data : in std_logic_vector(EPC*${bit_width} downto 0);
+    input_data : in std_logic_vector(EPC*${bit_width}-1 downto 0);
     input_last : in std_logic_vector(((NESTING_LEVEL + 1) * EPC) - 1 downto 0);
     input_stai : in std_logic_vector(log2ceil(EPC)-1 downto 0);
     input_endi : in std_logic_vector(log2ceil(EPC)-1 downto 0);
     input_strb : in std_logic_vector(EPC-1 downto 0);
     output_valid : out std_logic;
     output_ready : in std_logic;
-    output_data : out std_logic_vector(${int_width}-1 downto 0);
+    output_data : out std_logic_vector(BITWIDTH-1 downto 0);
     output_last : out std_logic_vector(NESTING_LEVEL - 1 downto 0);
     output_strb : out std_logic
   );
 end ${namespace}_0_${comp_name}_com;
 
 architecture behav of ${namespace}_0_${comp_name}_com is
+-- The largest integer of BITWIDTH bits has BITWIDTH*log10(2) digits, rounded up.
+constant BCD_DIGITS   : integer := BITWIDTH*30103/100000 + 1;
+constant BCD_WIDTH    : integer := 4*BCD_DIGITS;
+
 -- Input holding register.
 type in_type is record
   data  : std_logic_vector(7 downto 0);
@@ -41,7 +45,7 @@ type in_type is record
 end record;
 
 type dd_stage_t is record
-  bcd   : std_logic_vector(BITWIDTH+(BITWIDTH-4)/3-1 downto 0);
+  bcd   : std_logic_vector(BCD_WIDTH-1 downto 0);
   bin   : std_logic_vector(BITWIDTH-1 downto 0);
   ready : std_logic;
   valid : std_logic;
@@ -49,8 +53,6 @@ type dd_stage_t is record
   last  : std_logic_vector(NESTING_LEVEL-1 downto 0);
 end record;
 
-constant BCD_WIDTH    : integer := BITWIDTH+(BITWIDTH-4)/3;
-
 constant dd_stage_t_init : dd_stage_t := (  bcd => (others => '0'),
                                             bin => (others => '0'),
                                             ready => '0',
@@ -80,7 +82,7 @@ procedure dd_stage (
     constant  BW        : in natural;
     constant  STEPS     : in natural
   ) is
-    variable bcd_shr : std_logic_vector(BW+(BW-4)/3-1 downto 0) := (others => '0');
+    variable bcd_shr : std_logic_vector(BCD_WIDTH-1 downto 0) := (others => '0');
     variable bin_shr : std_logic_vector(BW-1 downto 0) := (others => '0');
 begin
   -- Use the double-dabble alogorithm to convert BCD to binary.
@@ -89,7 +91,7 @@ begin
   for j in 0 to STEPS-1 loop
     bin_shr := bcd_shr(0) & bin_shr(bin_shr'left downto 1);
     bcd_shr := '0' & bcd_shr(bcd_shr'high downto 1);
-    for idx in 0 to (BW+(BW-4)/3)/4-1 loop
+    for idx in 0 to BCD_DIGITS-1 loop
       if to_01(unsigned(bcd_shr(idx*4+3 downto idx*4))) >= 8 then
         bcd_shr(idx*4+3 downto idx*4) := std_logic_vector(unsigned(unsigned(bcd_shr(idx*4+3 downto idx*4)) - 3));
       end if;
@@ -111,7 +113,7 @@ begin
       variable iv   : std_logic := '0';
       variable ir   : std_logic := '0';
 
-      variable in_shr  : std_logic_vector(BITWIDTH+(BITWIDTH-4)/3-1 downto 0) := (others => '0');
+      variable in_shr  : std_logic_vector(BCD_WIDTH-1 downto 0) := (others => '0');
 
       variable dd_in  : dd_stage_t := dd_stage_t_init;
 
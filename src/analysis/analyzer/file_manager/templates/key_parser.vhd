@@ -12,7 +12,7 @@ entity ${namespace}_0_${comp_name}_com is
   generic (
     EPC : positive := 4;
     OUTER_NESTING_LEVEL : positive := 2;
-    DLY_COMP_BUFF_DEPTH  : integer := 5
+    DLY_COMP_BUFF_DEPTH  : integer := ${key_buffer_depth}
   );
   port (
     clk : in std_logic;
@@ -0,0 +1,106 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+use ieee.std_logic_misc.and_reduce;
+
+library work;
+use work.${project_name}.all;
+use work.UtilInt_pkg.all;
+
+-- Splits the output of a record parser into a stream of keys and a stream of
+-- values. Every entry of the map produces one key and one value, so the two
+-- streams stay aligned.
+entity ${namespace}_0_${comp_name}_com is
+  generic (
+    EPC : positive := 4;
+    OUTER_NESTING_LEVEL : positive := 2
+  );
+  port (
+    clk : in std_logic;
+    rst : in std_logic;
+    input_valid : in std_logic;
+    input_ready : out std_logic;
+    input_data : in std_logic_vector(EPC*${bit_width}+EPC-1 downto 0);
+    input_last : in std_logic_vector(((OUTER_NESTING_LEVEL + 1) * EPC) - 1 downto 0);
+    input_stai : in std_logic_vector(log2ceil(EPC)-1 downto 0);
+    input_endi : in std_logic_vector(log2ceil(EPC)-1 downto 0);
+    input_strb : in std_logic_vector(EPC-1 downto 0);
+    key_valid : out std_logic;
+    key_ready : in std_logic;
+    key_data : out std_logic_vector(EPC*${bit_width}-1 downto 0);
+    key_last : out std_logic_vector(((OUTER_NESTING_LEVEL + 1) * EPC) - 1 downto 0);
+    key_stai : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    key_endi : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    key_strb : out std_logic_vector(EPC-1 downto 0);
+    value_valid : out std_logic;
+    value_ready : in std_logic;
+    value_data : out std_logic_vector(EPC*${bit_width}-1 downto 0);
+    value_last : out std_logic_vector(((OUTER_NESTING_LEVEL + 1) * EPC) - 1 downto 0);
+    value_stai : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    value_endi : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    value_strb : out std_logic_vector(EPC-1 downto 0)
+  );
+end ${namespace}_0_${comp_name}_com;
+
+architecture behav of ${namespace}_0_${comp_name}_com is
+  constant DIMS : positive := OUTER_NESTING_LEVEL + 1;
+
+  -- Lanes with a tag of '1' belong to a value, the others to a key
+  signal tag       : std_logic_vector(EPC-1 downto 0);
+
+  signal done      : std_logic_vector(1 downto 0) := (others => '0');
+  signal out_valid : std_logic_vector(1 downto 0);
+  signal out_ready : std_logic_vector(1 downto 0);
+  signal all_done  : std_logic;
+begin
+  tag <= input_data(EPC*${bit_width}+EPC-1 downto EPC*${bit_width});
+
+  -- Every transfer goes to both outputs, with the lanes of the other kind disabled
+  out_valid   <= (out_valid'range => input_valid) and not done;
+  all_done    <= and_reduce(done or out_ready);
+  input_ready <= all_done;
+
+  done_proc: process (clk) is
+  begin
+    if rising_edge(clk) then
+      if to_x01(input_valid) = '1' then
+        if all_done = '1' then
+          done <= (others => '0');
+        else
+          done <= done or (out_valid and out_ready);
+        end if;
+      end if;
+
+      if to_x01(rst) /= '0' then
+        done <= (others => '0');
+      end if;
+    end if;
+  end process;
+
+  key_valid    <= out_valid(0);
+  out_ready(0) <= key_ready;
+  key_data     <= input_data(EPC*${bit_width}-1 downto 0);
+  key_stai     <= input_stai;
+  key_endi     <= input_endi;
+
+  value_valid  <= out_valid(1);
+  out_ready(1) <= value_ready;
+  value_data   <= input_data(EPC*${bit_width}-1 downto 0);
+  value_stai   <= input_stai;
+  value_endi   <= input_endi;
+
+  lane_proc: process (input_last, input_strb, tag) is
+  begin
+    key_last   <= input_last;
+    value_last <= input_last;
+
+    for idx in 0 to EPC-1 loop
+      key_strb(idx)   <= input_strb(idx) and not tag(idx);
+      value_strb(idx) <= input_strb(idx) and tag(idx);
+
+      -- The innermost dimension closes either a key or a value
+      key_last(DIMS*idx)   <= input_last(DIMS*idx) and not tag(idx);
+      value_last(DIMS*idx) <= input_last(DIMS*idx) and tag(idx);
+    end loop;
+  end process;
+end behav;
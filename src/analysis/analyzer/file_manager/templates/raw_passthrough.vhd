@@ -0,0 +1,79 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+
+library work;
+use work.${project_name}.all;
+use work.UtilInt_pkg.all;
+
+-- Forwards the complete JSON text of a value without parsing it. The stream is
+-- passed through a register slice so the output is decoupled from the parser
+-- that produced the value.
+entity ${namespace}_0_${comp_name}_com is
+  generic (
+      EPC                   : positive := 1;
+      NESTING_LEVEL         : positive := 1
+      );
+  port (
+      clk                   : in  std_logic;
+      rst                   : in  std_logic;
+
+      -- Stream(
+      --     Bits(8),
+      --     t=EPC,
+      --     d=NESTING_LEVEL+1,
+      --     c=8
+      -- )
+      input_valid              : in  std_logic;
+      input_ready              : out std_logic;
+      input_data               : in  std_logic_vector(8*EPC-1 downto 0);
+      input_last               : in  std_logic_vector((NESTING_LEVEL+1)*EPC-1 downto 0) := (others => '0');
+      input_stai               : in  std_logic_vector(log2ceil(EPC)-1 downto 0) := (others => '0');
+      input_endi               : in  std_logic_vector(log2ceil(EPC)-1 downto 0) := (others => '1');
+      input_strb               : in  std_logic_vector(EPC-1 downto 0) := (others => '1');
+
+      -- Stream(
+      --     Bits(8),
+      --     t=EPC,
+      --     d=NESTING_LEVEL+1,
+      --     c=8
+      -- )
+      output_valid             : out std_logic;
+      output_ready             : in  std_logic;
+      output_data              : out std_logic_vector(8*EPC-1 downto 0);
+      output_last              : out std_logic_vector((NESTING_LEVEL+1)*EPC-1 downto 0);
+      output_stai              : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+      output_endi              : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+      output_strb              : out std_logic_vector(EPC-1 downto 0)
+  );
+end ${namespace}_0_${comp_name}_com;
+
+architecture behav of ${namespace}_0_${comp_name}_com is
+    signal full : std_logic := '0';
+begin
+    -- A new transfer can be accepted when the slice is empty or being emptied
+    input_ready  <= not full or output_ready;
+    output_valid <= full;
+
+    reg_proc: process (clk) is
+    begin
+      if rising_edge(clk) then
+        if to_x01(output_ready) = '1' then
+          full <= '0';
+        end if;
+
+        if to_x01(input_valid) = '1' and (full = '0' or to_x01(output_ready) = '1') then
+          full        <= '1';
+          output_data <= input_data;
+          output_last <= input_last;
+          output_stai <= input_stai;
+          output_endi <= input_endi;
+          output_strb <= input_strb;
+        end if;
+
+        if to_x01(rst) /= '0' then
+          full <= '0';
+        end if;
+      end if;
+    end process;
+end behav;
@@ -0,0 +1,135 @@
+use indoc::formatdoc;
+
+use crate::analysis::GeneratorParams;
+
+pub fn generate_tuple_demux(comp_name: &str, positions: usize, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+    let lanes = gen_params.epc;
+
+    // Declaration of the JSON streams, the innermost dimension separates the elements
+    let json_ports = |name: &str, dir: &str, rev: &str| {
+        let mut ports = vec![
+            format!("{}_valid : {} std_logic", name, dir),
+            format!("{}_ready : {} std_logic", name, rev),
+            format!("{}_data : {} std_logic_vector(EPC*8-1 downto 0)", name, dir),
+            format!("{}_last : {} std_logic_vector((NESTING_LEVEL+1)*EPC-1 downto 0)", name, dir),
+        ];
+
+        if lanes > 1 {
+            ports.push(format!("{}_stai : {} std_logic_vector(log2ceil(EPC)-1 downto 0)", name, dir));
+            ports.push(format!("{}_endi : {} std_logic_vector(log2ceil(EPC)-1 downto 0)", name, dir));
+            ports.push(format!("{}_strb : {} std_logic_vector(EPC-1 downto 0)", name, dir));
+        } else {
+            ports.push(format!("{}_strb : {} std_logic", name, dir));
+        }
+
+        ports
+    };
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+    ports.extend(json_ports("input", "in", "out"));
+
+    let mut ready_cases = String::new();
+    let mut assignments = String::new();
+
+    for idx in 0..positions {
+        let output = format!("output_{}", idx);
+        ports.extend(json_ports(&output, "out", "in"));
+        ready_cases.push_str(&format!("      when {} => out_ready <= {}_ready;\n", idx, output));
+
+        assignments.push_str(&format!("  {output}_valid <= input_valid when selected = {idx} else '0';\n"));
+        assignments.push_str(&format!("  {output}_data <= input_data;\n  {output}_last <= out_last;\n  {output}_strb <= input_strb;\n"));
+
+        if lanes > 1 {
+            assignments.push_str(&format!("  {output}_stai <= input_stai;\n  {output}_endi <= input_endi;\n"));
+        }
+
+        assignments.push('\n');
+    }
+
+    formatdoc!("
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+
+        library work;
+        use work.{project_name}.all;
+        use work.UtilInt_pkg.all;
+
+        -- Sends every element of a tuple array to the output of its position.
+        -- Elements are expected to start at a transfer boundary. Every output
+        -- closes the array after its element, so it carries one element per array.
+        -- Dimensions outside of the array are closed on the output of the position
+        -- that held the last element. Elements past the last position are dropped.
+        entity {entity} is
+          generic (
+            EPC : positive := {lanes};
+            NESTING_LEVEL : natural := 1
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+          constant DIMS : positive := NESTING_LEVEL + 1;
+
+          signal selected    : natural range 0 to {positions};
+          signal element_end : std_logic;
+          signal array_end   : std_logic;
+          signal out_last    : std_logic_vector((NESTING_LEVEL+1)*EPC-1 downto 0);
+          signal out_ready   : std_logic;
+        begin
+          -- Find the end of the element and of the array
+          end_proc: process (input_last) is
+          begin
+            element_end <= '0';
+            array_end <= '0';
+            out_last <= input_last;
+            for lane in 0 to EPC-1 loop
+              if input_last(DIMS*lane) = '1' then
+                element_end <= '1';
+                out_last(DIMS*lane+1) <= '1';
+              end if;
+              if input_last(DIMS*lane+1) = '1' then
+                array_end <= '1';
+              end if;
+            end loop;
+          end process;
+
+          ready_proc: process (selected, {readies}) is
+          begin
+            out_ready <= '1';
+            case selected is
+        {ready_cases}      when others => null;
+            end case;
+          end process;
+
+          input_ready <= out_ready;
+
+        {assignments}  index_proc: process (clk) is
+          begin
+            if rising_edge(clk) then
+              if to_x01(input_valid) = '1' and out_ready = '1' then
+                if array_end = '1' then
+                  selected <= 0;
+                elsif element_end = '1' and selected < {positions} then
+                  selected <= selected + 1;
+                end if;
+              end if;
+
+              if to_x01(rst) /= '0' then
+                selected <= 0;
+              end if;
+            end if;
+          end process;
+        end behav;
+        ",
+        project_name = gen_params.project_name,
+        ports = ports.join(";\n    "),
+        readies = (0..positions)
+            .map(|idx| format!("output_{}_ready", idx))
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
@@ -0,0 +1,215 @@
+use indoc::formatdoc;
+
+use crate::analysis::{GeneratorParams, analyzer::type_manager::TagType};
+
+// The first characters a value of the kind can start with
+fn first_characters(kind: &str) -> Vec<&str> {
+    match kind {
+        "string" => vec!["x\"22\""],
+        "integer" => vec!["x\"2D\"", "x\"30\"", "x\"31\"", "x\"32\"", "x\"33\"", "x\"34\"", "x\"35\"", "x\"36\"", "x\"37\"", "x\"38\"", "x\"39\""],
+        "boolean" => vec!["x\"74\"", "x\"66\""],
+        "null" => vec!["x\"6E\""],
+        "object" => vec!["x\"7B\""],
+        "array" => vec!["x\"5B\""],
+        _ => vec![],
+    }
+}
+
+pub fn generate_union_router(comp_name: &str, tag_type: &TagType, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+    let lanes = gen_params.epc;
+    let variants = tag_type.get_variants();
+    let tag_bits = tag_type.get_bits();
+
+    // Declaration of the JSON streams, which carry one more dimension than the tag
+    let json_ports = |name: &str, dir: &str, rev: &str| {
+        let mut ports = vec![
+            format!("{}_valid : {} std_logic", name, dir),
+            format!("{}_ready : {} std_logic", name, rev),
+            format!("{}_data : {} std_logic_vector(EPC*8-1 downto 0)", name, dir),
+            format!("{}_last : {} std_logic_vector((NESTING_LEVEL+1)*EPC-1 downto 0)", name, dir),
+        ];
+
+        if lanes > 1 {
+            ports.push(format!("{}_stai : {} std_logic_vector(log2ceil(EPC)-1 downto 0)", name, dir));
+            ports.push(format!("{}_endi : {} std_logic_vector(log2ceil(EPC)-1 downto 0)", name, dir));
+            ports.push(format!("{}_strb : {} std_logic_vector(EPC-1 downto 0)", name, dir));
+        } else {
+            ports.push(format!("{}_strb : {} std_logic", name, dir));
+        }
+
+        ports
+    };
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+    ports.extend(json_ports("input", "in", "out"));
+
+    let mut detection = String::new();
+    let mut ready_cases = String::new();
+    let mut assignments = String::new();
+    let mut tag_comment = String::new();
+
+    for (idx, kind) in variants.iter().enumerate() {
+        tag_comment.push_str(&format!("--   {} : {}\n", idx, kind));
+        detection.push_str(&format!("      when {} => detected <= {};\n", first_characters(kind).join(" | "), idx));
+
+        // Null values carry no data and are dropped after being reported on the tag stream
+        if kind == "null" {
+            ready_cases.push_str(&format!("      when {} => out_ready <= '1';\n", idx));
+            continue;
+        }
+
+        let output = format!("output_{}", kind);
+        ports.extend(json_ports(&output, "out", "in"));
+        ready_cases.push_str(&format!("      when {} => out_ready <= {}_ready;\n", idx, output));
+
+        assignments.push_str(&format!("  {output}_valid <= input_valid and not blocked when selected = {idx} else '0';\n"));
+        assignments.push_str(&format!("  {output}_data <= input_data;\n  {output}_last <= input_last;\n  {output}_strb <= input_strb;\n"));
+
+        if lanes > 1 {
+            assignments.push_str(&format!("  {output}_stai <= input_stai;\n  {output}_endi <= input_endi;\n"));
+        }
+
+        assignments.push('\n');
+    }
+
+    ports.push("tag_valid : out std_logic".to_string());
+    ports.push("tag_ready : in std_logic".to_string());
+    ports.push(format!("tag_data : out std_logic_vector({}-1 downto 0)", tag_bits));
+    ports.push("tag_last : out std_logic_vector(NESTING_LEVEL-1 downto 0)".to_string());
+    ports.push("tag_strb : out std_logic".to_string());
+
+    let strb = if lanes > 1 { "strb <= input_strb;" } else { "strb(0) <= input_strb;" };
+
+    formatdoc!("
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+
+        library work;
+        use work.{project_name}.all;
+        use work.UtilInt_pkg.all;
+
+        -- Routes every value to the output of its type, which is decided by the first
+        -- character of the value. Values are expected to start at a transfer boundary.
+        -- Once a value is complete its variant is reported on the tag stream:
+        {tag_comment}entity {entity} is
+          generic (
+            EPC : positive := {lanes};
+            NESTING_LEVEL : natural := 1
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+          constant DIMS : positive := NESTING_LEVEL + 1;
+
+          signal strb         : std_logic_vector(EPC-1 downto 0);
+          signal first_char   : std_logic_vector(7 downto 0);
+          signal detected     : natural range 0 to {last_variant};
+          signal selected     : natural range 0 to {last_variant};
+          signal current      : natural range 0 to {last_variant} := 0;
+          signal active       : std_logic := '0';
+          signal value_end    : std_logic;
+          signal end_last     : std_logic_vector(NESTING_LEVEL-1 downto 0);
+          signal tag_full     : std_logic := '0';
+          signal tag_reg      : std_logic_vector({tag_bits}-1 downto 0);
+          signal tag_last_reg : std_logic_vector(NESTING_LEVEL-1 downto 0);
+          signal blocked      : std_logic;
+          signal out_ready    : std_logic;
+          signal handshake    : std_logic;
+        begin
+          {strb}
+
+          -- The first strobed lane holds the first character of a new value
+          first_char_proc: process (input_data, strb) is
+          begin
+            first_char <= input_data(7 downto 0);
+            for lane in EPC-1 downto 0 loop
+              if strb(lane) = '1' then
+                first_char <= input_data(8*lane+7 downto 8*lane);
+              end if;
+            end loop;
+          end process;
+
+          detect_proc: process (first_char) is
+          begin
+            detected <= 0;
+            case first_char is
+        {detection}      when others => null;
+            end case;
+          end process;
+
+          -- A value ends in the lane that closes the innermost dimension
+          end_proc: process (input_last) is
+          begin
+            value_end <= '0';
+            end_last <= (others => '0');
+            for lane in 0 to EPC-1 loop
+              if input_last(DIMS*lane) = '1' then
+                value_end <= '1';
+                end_last <= input_last(DIMS*lane+NESTING_LEVEL downto DIMS*lane+1);
+              end if;
+            end loop;
+          end process;
+
+          selected <= current when active = '1' else detected;
+
+          -- The last transfer of a value needs room for its tag
+          blocked <= value_end and tag_full;
+
+          ready_proc: process (selected, {readies}) is
+          begin
+            out_ready <= '0';
+            case selected is
+        {ready_cases}      when others => null;
+            end case;
+          end process;
+
+          input_ready <= out_ready and not blocked;
+          handshake   <= input_valid and out_ready and not blocked;
+
+        {assignments}  tag_valid <= tag_full;
+          tag_data  <= tag_reg;
+          tag_last  <= tag_last_reg;
+          tag_strb  <= '1';
+
+          state_proc: process (clk) is
+          begin
+            if rising_edge(clk) then
+              if tag_full = '1' and to_x01(tag_ready) = '1' then
+                tag_full <= '0';
+              end if;
+
+              if handshake = '1' then
+                if value_end = '1' then
+                  active       <= '0';
+                  tag_full     <= '1';
+                  tag_reg      <= std_logic_vector(to_unsigned(selected, tag_reg'length));
+                  tag_last_reg <= end_last;
+                else
+                  active  <= '1';
+                  current <= selected;
+                end if;
+              end if;
+
+              if to_x01(rst) /= '0' then
+                active   <= '0';
+                tag_full <= '0';
+              end if;
+            end if;
+          end process;
+        end behav;
+        ",
+        project_name = gen_params.project_name,
+        ports = ports.join(";\n    "),
+        last_variant = variants.len() - 1,
+        readies = variants.iter()
+            .filter(|kind| *kind != "null")
+            .map(|kind| format!("output_{}_ready", kind))
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
@@ -0,0 +1,643 @@
+use indoc::formatdoc;
+
+use crate::analysis::{GeneratorParams, analyzer::type_manager::StreamType, validate::{ValueKind, ErrorKind}};
+
+use super::{indent, stream_ports};
+
+// How the lanes of the tapped stream are checked
+struct CheckLogic {
+    description: String,
+    // Declarations of the architecture, e.g. the expected keys
+    constants: String,
+    // Variables that are kept between transfers
+    locals: String,
+    // Errors that are reported before the next lane is checked, one per transfer
+    pending: String,
+    // Checks the lane in `lane` with its character in `c`
+    lane: String,
+    reset: String,
+}
+
+// The codes of the errors and the functions shared by all checkers
+fn error_constants() -> String {
+    let mut constants: Vec<String> = ErrorKind::ALL.iter()
+        .map(|kind| format!("constant {} : natural := {};", kind_constant(*kind), kind.get_code()))
+        .collect();
+
+    constants.push(formatdoc!("
+
+        function is_space(c : std_logic_vector(7 downto 0)) return boolean is
+        begin
+          return c = x\"20\" or c = x\"09\" or c = x\"0A\" or c = x\"0D\";
+        end function;"));
+
+    constants.join("\n")
+}
+
+fn with_article(kind: ValueKind) -> String {
+    match kind {
+        ValueKind::Object | ValueKind::Array | ValueKind::Integer => format!("an {}", kind.get_name()),
+        ValueKind::String | ValueKind::Boolean => format!("a {}", kind.get_name()),
+    }
+}
+
+fn kind_constant(kind: ErrorKind) -> String {
+    format!("KIND_{}", kind.get_name().to_uppercase().replace(' ', "_"))
+}
+
+// Whether a character is one a value of the kind can start with
+fn expected_function(kind: Option<ValueKind>) -> String {
+    let condition = match kind {
+        Some(kind) => kind.get_first_chars().iter()
+            .map(|c| format!("c = x\"{:02X}\"", c))
+            .collect::<Vec<String>>()
+            .join(" or "),
+        None => "true".to_string(),
+    };
+
+    formatdoc!("
+        function expected(c : std_logic_vector(7 downto 0)) return boolean is
+        begin
+          return {condition};
+        end function;")
+}
+
+// A checker that taps a stream of the parsers and reports the errors it finds in it
+fn generate_checker(comp_name: &str, input_type: &StreamType, check: CheckLogic, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+    let lanes = gen_params.epc;
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+    ports.extend(stream_ports("input", true, input_type, gen_params));
+    ports.extend(stream_ports("error", false, &StreamType::ValidationError, gen_params));
+
+    // Records carry the tags of the lanes after the characters
+    let tag = match input_type {
+        StreamType::Record => "input_data(8*EPC+i)",
+        _ => "'0'",
+    };
+    let strb = if lanes > 1 { "input_strb(i)" } else { "input_strb" };
+
+    // Pending errors are followed by a blank line before the lane is checked
+    let pending = if check.pending.is_empty() {
+        " ".repeat(8)
+    } else {
+        format!("{}\n{}", indent(&check.pending, 8), " ".repeat(8))
+    };
+
+    formatdoc!("
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+        use ieee.std_logic_misc.or_reduce;
+
+        library work;
+        use work.{project_name}.all;
+
+        -- {description}
+        -- Every error is reported with the index of its document, counted from 0,
+        -- the id of its path and its kind.
+        entity {entity} is
+          generic (
+            EPC : positive := {lanes};
+            NESTING_LEVEL : positive := 1
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+        {constants}
+        begin
+          check_proc: process (clk) is
+            type lane_t is record
+              data : std_logic_vector(7 downto 0);
+              tag  : std_logic;
+              last : std_logic_vector(NESTING_LEVEL-1 downto 0);
+              strb : std_logic;
+            end record;
+
+            type lane_array is array (natural range <>) of lane_t;
+
+            -- Input holding register, which is checked lane by lane
+            variable id  : lane_array(0 to EPC-1);
+            variable iv  : std_logic := '0';
+            variable ir  : std_logic := '0';
+            variable idx : natural range 0 to EPC-1 := 0;
+
+            -- Output holding register with the last error
+            variable od : std_logic_vector(55 downto 0) := (others => '0');
+            variable ov : std_logic := '0';
+
+            -- Index of the current document
+            variable doc : unsigned(31 downto 0) := (others => '0');
+
+            variable lane : lane_t;
+            variable c    : std_logic_vector(7 downto 0);
+        {locals}
+            procedure raise(document : unsigned(31 downto 0); path : natural; kind : natural) is
+            begin
+              od := std_logic_vector(document) & std_logic_vector(to_unsigned(path, 16)) & std_logic_vector(to_unsigned(kind, 8));
+              ov := '1';
+            end procedure;
+
+          begin
+            if rising_edge(clk) then
+
+              -- Latch input holding register if we said we would.
+              if to_x01(ir) = '1' then
+                iv := input_valid;
+                idx := 0;
+                for i in 0 to EPC-1 loop
+                  id(i).data := input_data(8*i+7 downto 8*i);
+                  id(i).tag  := {tag};
+                  id(i).last := input_last(NESTING_LEVEL*(i+1)-1 downto NESTING_LEVEL*i);
+                  id(i).strb := {strb};
+                end loop;
+              end if;
+
+              -- Clear output holding register if transfer was accepted.
+              if to_x01(error_ready) = '1' and ov = '1' then
+                ov := '0';
+              end if;
+
+              -- Check lanes until there is an error to report.
+              for step in 0 to EPC-1 loop
+        {pending}if ov = '0' and iv = '1' then
+                  lane := id(idx);
+                  c := lane.data;
+
+        {lane}
+
+                  -- The end of a document
+                  if lane.last(NESTING_LEVEL-{document_dims}) = '1' then
+                    doc := doc + 1;
+                  end if;
+
+                  if idx = EPC-1 then
+                    iv := '0';
+                    idx := 0;
+                  else
+                    idx := idx + 1;
+                  end if;
+                end if;
+              end loop;
+
+              -- Handle rst.
+              if to_x01(rst) /= '0' then
+                iv := '0';
+                ov := '0';
+                doc := (others => '0');
+        {reset}
+              end if;
+
+              -- Forward output holding register.
+              error_valid <= to_x01(ov);
+              error_data <= od;
+              ir := not iv and not rst;
+              input_ready <= ir and not rst;
+            end if;
+          end process;
+        end behav;
+        ",
+        project_name = gen_params.project_name,
+        description = check.description,
+        ports = ports.join(";\n    "),
+        constants = indent(&check.constants, 2).trim_end(),
+        locals = indent(&check.locals, 4),
+        lane = indent(&check.lane, 10).trim_end(),
+        reset = indent(&check.reset, 8).trim_end(),
+        document_dims = gen_params.framing.get_document_dims(),
+    )
+}
+
+pub fn generate_type_checker(comp_name: &str, kind: ValueKind, path_id: usize, gen_params: &GeneratorParams) -> String {
+    let check = CheckLogic {
+        description: format!("Checks that every value starts like {}.", with_article(kind)),
+        constants: formatdoc!("
+            constant PATH_ID : natural := {path_id};
+            {errors}
+
+            {expected}",
+            errors = error_constants(),
+            expected = expected_function(Some(kind)),
+        ),
+        locals: formatdoc!("
+            -- No character of the current value has been seen yet
+            variable first : std_logic := '1';
+            "),
+        pending: String::new(),
+        lane: formatdoc!("
+            -- The first character of a value decides its kind
+            if lane.strb = '1' and first = '1' and not is_space(c) then
+              first := '0';
+              if not expected(c) then
+                raise(doc, PATH_ID, KIND_TYPE_MISMATCH);
+              end if;
+            end if;
+
+            if or_reduce(lane.last) = '1' then
+              first := '1';
+            end if;"),
+        reset: "first := '1';".to_string(),
+    };
+
+    generate_checker(comp_name, &StreamType::Json, check, gen_params)
+}
+
+// The keys of a record are given as their text in the document, their path id and whether every sample had them
+pub fn generate_key_checker(comp_name: &str, keys: &[(String, usize, bool)], path_id: usize, gen_params: &GeneratorParams) -> String {
+    let max_length = keys.iter().map(|(key, _, _)| key.len()).max().unwrap_or(0);
+
+    let mut key_comment = String::new();
+    let mut expected = Vec::new();
+    let mut lengths = Vec::new();
+    let mut paths = Vec::new();
+    let mut required = String::new();
+
+    for (idx, (key, key_path, key_required)) in keys.iter().enumerate() {
+        key_comment.push_str(&format!("--   {:?}{}\n", key, if *key_required { "" } else { " (optional)" }));
+
+        let bytes: Vec<String> = key.bytes()
+            .enumerate()
+            .map(|(pos, byte)| format!("{} => x\"{:02X}\"", pos, byte))
+            .chain(std::iter::once("others => x\"00\"".to_string()))
+            .collect();
+
+        expected.push(format!("{} => ({})", idx, bytes.join(", ")));
+        lengths.push(format!("{} => {}", idx, key.len()));
+        paths.push(format!("{} => {}", idx, key_path));
+
+        // Key 0 is the least significant bit
+        required.insert(0, if *key_required { '1' } else { '0' });
+    }
+
+    let check = CheckLogic {
+        description: formatdoc!("
+            Checks that every record has the keys of the samples and no others:
+            {key_comment}",
+            key_comment = key_comment.trim_end(),
+        ),
+        constants: formatdoc!("
+            constant PATH_ID    : natural := {path_id};
+            constant KEYS       : positive := {key_count};
+            constant MAX_LENGTH : natural := {max_length};
+            {errors}
+
+            type byte_array is array (0 to MAX_LENGTH) of std_logic_vector(7 downto 0);
+            type key_array is array (0 to KEYS-1) of byte_array;
+            type natural_array is array (0 to KEYS-1) of natural;
+            constant EXPECTED  : key_array := ({expected});
+            constant LENGTHS   : natural_array := ({lengths});
+            constant KEY_PATHS : natural_array := ({paths});
+            constant REQUIRED  : std_logic_vector(KEYS-1 downto 0) := \"{required}\";",
+            key_count = keys.len(),
+            errors = error_constants(),
+            expected = expected.join(", "),
+            lengths = lengths.join(", "),
+            paths = paths.join(", "),
+        ),
+        locals: formatdoc!("
+            -- Characters of the current key and the keys that still match them
+            variable pos      : natural range 0 to MAX_LENGTH := 0;
+            variable matching : std_logic_vector(KEYS-1 downto 0) := (others => '1');
+            variable found    : std_logic;
+
+            -- Keys of the current record and the keys that were missing from the last one
+            variable seen        : std_logic_vector(KEYS-1 downto 0) := (others => '0');
+            variable missing     : std_logic_vector(KEYS-1 downto 0) := (others => '0');
+            variable missing_doc : unsigned(31 downto 0);
+            "),
+        pending: formatdoc!("
+            -- Report the missing keys one by one
+            if ov = '0' and or_reduce(missing) = '1' then
+              for key in 0 to KEYS-1 loop
+                if missing(key) = '1' then
+                  missing(key) := '0';
+                  raise(missing_doc, KEY_PATHS(key), KIND_MISSING_KEY);
+                  exit;
+                end if;
+              end loop;
+            end if;
+            "),
+        lane: formatdoc!("
+            -- Characters of keys have tag 0
+            if lane.tag = '0' and lane.strb = '1' then
+              for key in 0 to KEYS-1 loop
+                if pos >= LENGTHS(key) then
+                  matching(key) := '0';
+                elsif c /= EXPECTED(key)(pos) then
+                  matching(key) := '0';
+                end if;
+              end loop;
+
+              -- Keys longer than every expected key can not match any of them
+              if pos < MAX_LENGTH then
+                pos := pos + 1;
+              else
+                matching := (others => '0');
+              end if;
+            end if;
+
+            -- The end of a key
+            if lane.tag = '0' and lane.last(0) = '1' then
+              found := '0';
+              for key in 0 to KEYS-1 loop
+                if matching(key) = '1' and pos = LENGTHS(key) then
+                  seen(key) := '1';
+                  found := '1';
+                end if;
+              end loop;
+
+              if found = '0' then
+                raise(doc, PATH_ID, KIND_UNKNOWN_KEY);
+              end if;
+
+              pos := 0;
+              matching := (others => '1');
+            end if;
+
+            -- The end of a record
+            if lane.last(1) = '1' then
+              missing := REQUIRED and not seen;
+              missing_doc := doc;
+              seen := (others => '0');
+              pos := 0;
+              matching := (others => '1');
+            end if;"),
+        reset: formatdoc!("
+            pos := 0;
+            matching := (others => '1');
+            seen := (others => '0');
+            missing := (others => '0');"),
+    };
+
+    generate_checker(comp_name, &StreamType::Record, check, gen_params)
+}
+
+// Passes the input of the design on to the root parser while it checks the kind of every document
+// and how deep objects and arrays are nested in it
+pub fn generate_document_checker(comp_name: &str, kind: Option<ValueKind>, max_depth: Option<usize>, gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+    let lanes = gen_params.epc;
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+    ports.extend(stream_ports("input", true, &StreamType::Json, gen_params));
+    ports.extend(stream_ports("output", false, &StreamType::Json, gen_params));
+    ports.extend(stream_ports("error", false, &StreamType::ValidationError, gen_params));
+
+    let (lane_ports, strb) = if lanes > 1 {
+        (
+            formatdoc!("
+                output_stai  <= input_stai;
+                output_endi  <= input_endi;
+                output_strb  <= input_strb;"),
+            "input_strb(i)",
+        )
+    } else {
+        ("output_strb  <= input_strb;".to_string(), "input_strb")
+    };
+
+    let description = match kind {
+        Some(kind) => format!("Checks that every document is {}.", with_article(kind)),
+        None => "Checks documents of any kind.".to_string(),
+    };
+
+    formatdoc!("
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+
+        library work;
+        use work.{project_name}.all;
+
+        -- {description} Objects and arrays may be nested
+        -- at most as deep as in the samples. The documents are passed on unchanged.
+        -- Every document reports at most one error of each kind, with the index of
+        -- the document, counted from 0, and path id 0. A transfer reports at most
+        -- one error.
+        entity {entity} is
+          generic (
+            EPC : positive := {lanes};
+            NESTING_LEVEL : positive := {nesting_level}
+          );
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+          constant PATH_ID     : natural := 0;
+          constant CHECK_DEPTH : boolean := {check_depth};
+          constant MAX_DEPTH   : natural := {max_depth};
+        {errors}
+
+        {expected}
+
+          -- The output holding register is full, which holds back the documents
+          signal blocked : std_logic := '0';
+        begin
+          output_valid <= input_valid and not blocked;
+          input_ready  <= output_ready and not blocked;
+          output_data  <= input_data;
+          output_last  <= input_last;
+        {lane_ports}
+
+          check_proc: process (clk) is
+            variable c : std_logic_vector(7 downto 0);
+
+            -- Output holding register with the last error
+            variable od : std_logic_vector(55 downto 0) := (others => '0');
+            variable ov : std_logic := '0';
+
+            -- Index of the current document
+            variable doc : unsigned(31 downto 0) := (others => '0');
+
+            -- Position in the current document
+            variable first     : std_logic := '1';
+            variable in_string : std_logic := '0';
+            variable escaped   : std_logic := '0';
+            variable depth     : natural range 0 to MAX_DEPTH+1 := 0;
+            variable too_deep  : std_logic := '0';
+
+            procedure raise(kind : natural) is
+            begin
+              if ov = '0' then
+                od := std_logic_vector(doc) & std_logic_vector(to_unsigned(PATH_ID, 16)) & std_logic_vector(to_unsigned(kind, 8));
+                ov := '1';
+              end if;
+            end procedure;
+
+          begin
+            if rising_edge(clk) then
+
+              -- Clear output holding register if transfer was accepted.
+              if to_x01(error_ready) = '1' and ov = '1' then
+                ov := '0';
+              end if;
+
+              if to_x01(input_valid) = '1' and to_x01(output_ready) = '1' and blocked = '0' then
+                for i in 0 to EPC-1 loop
+                  c := input_data(8*i+7 downto 8*i);
+
+                  if {strb} = '1' then
+                    if in_string = '1' then
+                      if escaped = '1' then
+                        escaped := '0';
+                      elsif c = x\"5C\" then
+                        escaped := '1';
+                      elsif c = x\"22\" then
+                        in_string := '0';
+                      end if;
+                    elsif not is_space(c) then
+                      -- The first character of a document decides its kind
+                      if first = '1' then
+                        first := '0';
+                        if not expected(c) then
+                          raise(KIND_TYPE_MISMATCH);
+                        end if;
+                      end if;
+
+                      if c = x\"22\" then
+                        in_string := '1';
+                      elsif c = x\"7B\" or c = x\"5B\" then
+                        if depth <= MAX_DEPTH then
+                          depth := depth + 1;
+                        end if;
+                        if CHECK_DEPTH and depth > MAX_DEPTH and too_deep = '0' then
+                          too_deep := '1';
+                          raise(KIND_NESTING_TOO_DEEP);
+                        end if;
+                      elsif (c = x\"7D\" or c = x\"5D\") and depth > 0 then
+                        depth := depth - 1;
+                      end if;
+                    end if;
+                  end if;
+
+                  -- The end of a document
+                  if input_last(NESTING_LEVEL*(i+1)-{document_dims}) = '1' then
+                    doc := doc + 1;
+                    first := '1';
+                    in_string := '0';
+                    escaped := '0';
+                    depth := 0;
+                    too_deep := '0';
+                  end if;
+                end loop;
+              end if;
+
+              -- Handle rst.
+              if to_x01(rst) /= '0' then
+                ov := '0';
+                doc := (others => '0');
+                first := '1';
+                in_string := '0';
+                escaped := '0';
+                depth := 0;
+                too_deep := '0';
+              end if;
+
+              -- Forward output holding register.
+              error_valid <= to_x01(ov);
+              error_data <= od;
+              blocked <= ov;
+            end if;
+          end process;
+        end behav;
+        ",
+        project_name = gen_params.project_name,
+        ports = ports.join(";\n    "),
+        nesting_level = gen_params.framing.get_document_dims() + 1,
+        document_dims = gen_params.framing.get_document_dims(),
+        check_depth = max_depth.is_some(),
+        max_depth = max_depth.unwrap_or(0),
+        errors = indent(&error_constants(), 2).trim_end(),
+        expected = indent(&expected_function(kind), 2).trim_end(),
+        lane_ports = indent(&lane_ports, 2).trim_end(),
+    )
+}
+
+// Merges the errors of all checkers into one stream, the paths are listed in the order of their ids
+pub fn generate_validation_merger(comp_name: &str, inputs: usize, paths: &[String], gen_params: &GeneratorParams) -> String {
+    let entity = format!("{}_0_{}_com", gen_params.comp_namespace, comp_name);
+
+    let mut ports = vec!["clk : in std_logic".to_string(), "rst : in std_logic".to_string()];
+    for idx in 0..inputs {
+        ports.extend(stream_ports(&format!("input{}", idx), true, &StreamType::ValidationError, gen_params));
+    }
+    ports.extend(stream_ports("output", false, &StreamType::ValidationError, gen_params));
+
+    let path_comment: String = paths.iter()
+        .enumerate()
+        .map(|(idx, path)| format!("--   {} : {}\n", idx, path))
+        .collect();
+
+    let kind_comment: String = ErrorKind::ALL.iter()
+        .map(|kind| format!("--   {} : {}\n", kind.get_code(), kind.get_name()))
+        .collect();
+
+    let mut assignments = Vec::new();
+    for idx in 0..inputs {
+        assignments.push(format!("valids({idx}) <= input{idx}_valid;"));
+        assignments.push(format!("datas({idx})  <= input{idx}_data;"));
+        assignments.push(format!("input{idx}_ready <= output_ready when sel = {idx} else '0';"));
+    }
+
+    formatdoc!("
+        library ieee;
+        use ieee.std_logic_1164.all;
+        use ieee.numeric_std.all;
+
+        library work;
+        use work.{project_name}.all;
+
+        -- Merges the errors of the checkers into one stream. Every error holds a 32 bit
+        -- document index, a 16 bit path id and an 8 bit kind, from the most to the least
+        -- significant bits. Errors of different checkers are not ordered by document.
+        -- The path ids:
+        {path_comment}-- The kinds:
+        {kind_comment}entity {entity} is
+          port (
+            {ports}
+          );
+        end {entity};
+
+        architecture behav of {entity} is
+          constant INPUTS : positive := {inputs};
+
+          type data_array is array (0 to INPUTS-1) of std_logic_vector(55 downto 0);
+
+          signal valids : std_logic_vector(INPUTS-1 downto 0);
+          signal datas  : data_array;
+          signal sel    : natural range 0 to INPUTS-1 := 0;
+        begin
+          {assignments}
+
+          output_valid <= valids(sel);
+          output_data  <= datas(sel);
+
+          -- Visit the inputs in turn, but stay at an input until its error is accepted
+          sel_proc: process (clk) is
+          begin
+            if rising_edge(clk) then
+              if not (valids(sel) = '1' and to_x01(output_ready) = '0') then
+                if sel = INPUTS-1 then
+                  sel <= 0;
+                else
+                  sel <= sel + 1;
+                end if;
+              end if;
+
+              if to_x01(rst) /= '0' then
+                sel <= 0;
+              end if;
+            end if;
+          end process;
+        end behav;
+        ",
+        project_name = gen_params.project_name,
+        ports = ports.join(";\n    "),
+        assignments = assignments.join("\n  "),
+    )
+}
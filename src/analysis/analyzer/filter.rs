@@ -0,0 +1,450 @@
+use crate::analysis::{components::{JsonComponent, JsonComponentValue, JsonType, Generatable}, filter::{Filter, Predicate, Literal}, format::StringFormat, types::{TilStreamlet, TilSignal, TilStreamingInterface, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}, til_streamlet::TilImplementationType}};
+
+use super::{Analyzer, AnalyzerError, file_manager::TemplateType, type_manager::StreamType};
+
+/**********************************************************************************
+ * Netlist pass that compiles filters into comparators on the scalar fields of a  *
+ * record, combines their results and gates the outputs of the record with them. *
+ **********************************************************************************/
+
+// The output stream of a comparator or combiner that selects the values of a record
+struct Selection {
+    inst_name: String,
+    stream_name: String,
+}
+
+fn new_streamlet(name: &str, interface: TilStreamingInterface) -> TilStreamlet {
+    let mut streamlet = TilStreamlet::new(name);
+    streamlet.set_streaming_interface(interface);
+    streamlet.set_implementation(TilImplementationType::Path("./vhdl_dir".to_string()));
+
+    streamlet
+}
+
+// Names of fields are only separated by dots, anything else belongs to a nested value
+fn is_field_of(path: &str, record_path: &str) -> bool {
+    match path.strip_prefix(record_path).and_then(|rest| rest.strip_prefix('.')) {
+        Some(field) => !field.is_empty() && !field.contains(['.', '[', '*']),
+        None => false,
+    }
+}
+
+impl Analyzer {
+    pub fn insert_filters(&mut self) -> Result<(), AnalyzerError> {
+        for filter in self.gen_params.outputs.filters.clone() {
+            self.insert_filter(&filter)?;
+        }
+
+        Ok(())
+    }
+
+    // Find the parsers and enum matchers of the scalar fields of the records at a path
+    fn find_fields(&self, component: &JsonComponent, record_path: &str, fields: &mut Vec<JsonComponent>) {
+        if let JsonComponent::Record(record) = component {
+            if self.json_paths.get(record.get_name()).map(String::as_str) == Some(record_path) {
+                for key in record.get_children() {
+                    if let [JsonComponent::Matcher(_), field @ (JsonComponent::Value(_) | JsonComponent::Enumeration(_))] = key.get_children().as_slice() {
+                        fields.push(field.clone());
+                    }
+                }
+            }
+        }
+
+        for child in component.get_children() {
+            self.find_fields(&child, record_path, fields);
+        }
+    }
+
+    fn insert_filter(&mut self, filter: &Filter) -> Result<(), AnalyzerError> {
+        let record_path = filter.get_predicates()[0].get_record_path().to_string();
+
+        if filter.get_predicates().iter().any(|predicate| predicate.get_record_path() != record_path) {
+            return Err(AnalyzerError::InvalidFilter("all predicates of a filter have to compare fields of the same record".to_string()));
+        }
+
+        let mut fields = Vec::new();
+        if let Some(root) = &self.top_component {
+            self.find_fields(root, &record_path, &mut fields);
+        }
+
+        let mut results = Vec::new();
+        let mut dim = 0;
+
+        for predicate in filter.get_predicates() {
+            let field = fields.iter()
+                .find(|field| self.json_paths.get(field.get_generatable().get_name()).map(String::as_str) == Some(predicate.get_path()))
+                .ok_or_else(|| AnalyzerError::InvalidFilter(format!("no integer, boolean or string field at path '{}'", predicate.get_path())))?;
+
+            // The comparator would wait forever for the values of records that do not have the key
+            if self.optional_keys.iter().any(|key| key == predicate.get_path()) {
+                return Err(AnalyzerError::InvalidFilter(format!("'{}' is missing in some samples, so records without it can not be compared", predicate.get_path())));
+            }
+
+            let (literal, compared_type, signed) = match field {
+                JsonComponent::Value(value) => {
+                    // The integer parser ignores the sign of the numbers it reads and hexadecimal strings have none,
+                    // quoted integers are parsed into signed integers
+                    let (kind, compared_type, signed) = match value.get_data_type() {
+                        JsonType::Integer => ("integer", value.get_int_type(), false),
+                        JsonType::Boolean => ("boolean", StreamType::Bool, false),
+                        JsonType::String => ("string", StreamType::Json, false),
+                        // Only the raw characters of strings are compared
+                        JsonType::Decoded => ("decoded string", StreamType::Json, false),
+                        JsonType::Formatted(StringFormat::Hex) => ("integer", StreamType::Int, false),
+                        JsonType::Formatted(StringFormat::Integer) => ("integer", StreamType::Int, true),
+                        JsonType::Formatted(format) => (format.get_name(), format.get_stream_type(), false),
+                    };
+
+                    if kind != predicate.get_literal().get_kind() {
+                        return Err(AnalyzerError::InvalidFilter(format!("'{}' is of kind {} and can not be compared with a {}", predicate.get_path(), kind, predicate.get_literal().get_kind())));
+                    }
+
+                    if let Literal::Integer(literal) = *predicate.get_literal() {
+                        let int_width = value.get_int_width(&self.gen_params);
+                        let range = match (signed, int_width) {
+                            (true, 128..) => i128::MIN..=i128::MAX,
+                            (true, _) => -(1i128 << (int_width - 1))..=(1i128 << (int_width - 1)) - 1,
+                            (false, 127..) => 0..=i128::MAX,
+                            (false, _) => 0..=(1i128 << int_width) - 1,
+                        };
+
+                        if !range.contains(&literal) {
+                            let sign = if signed { "a signed" } else { "an unsigned" };
+                            return Err(AnalyzerError::InvalidFilter(format!("{} does not fit in {} integer of {} bits", literal, sign, int_width)));
+                        }
+                    }
+
+                    (predicate.get_literal().clone(), compared_type, signed)
+                },
+                // Enums carry the codes of their strings, so a string is compared by its code
+                JsonComponent::Enumeration(enumeration) => {
+                    let Literal::String(literal) = predicate.get_literal() else {
+                        return Err(AnalyzerError::InvalidFilter(format!("'{}' is of kind enum and can not be compared with a {}", predicate.get_path(), predicate.get_literal().get_kind())));
+                    };
+
+                    let code = enumeration.get_code(literal)
+                        .ok_or_else(|| AnalyzerError::InvalidFilter(format!("\"{}\" is not one of the values of the enum at '{}'", literal, predicate.get_path())))?;
+
+                    (Literal::Integer(code as i128), StreamType::Tag(enumeration.get_code_type()), false)
+                },
+                _ => unreachable!("only values and enums are fields"),
+            };
+
+            dim = field.get_generatable().get_nesting_level();
+            results.push(self.create_comparator(field, predicate, literal, compared_type, signed));
+        }
+
+        // A single predicate selects the record on its own
+        let selection = if results.len() > 1 {
+            self.create_combiner(filter, results, dim, &record_path)
+        } else {
+            results.remove(0)
+        };
+
+        self.gate_outputs(&record_path, &selection, dim);
+
+        Ok(())
+    }
+
+    // Compare the values of a field, of the given stream type, with a literal. Integers are two's complement when signed
+    fn create_comparator(&mut self, field: &JsonComponent, predicate: &Predicate, literal: Literal, compared_type: StreamType, signed: bool) -> Selection {
+        let field = field.get_generatable();
+        let dim = field.get_nesting_level();
+        let name = self.name_reg.register("filter_compare", dim);
+        let inst_name = format!("{}_inst", name);
+
+        let mut interface = TilStreamingInterface::default();
+
+        let dim_name = "NESTING_LEVEL";
+        let mut lanes = self.gen_params.epc;
+        let input_type = match compared_type {
+            StreamType::Json => {
+                // The strings of a subtree may have a number of lanes of their own
+                let stream_type = self.find_instance_stream(&field.get_instance_name(), "output")
+                    .map_or(StreamType::Json, |stream| stream.get_type().get_stream_type());
+                lanes = stream_type.get_physical_params(&self.gen_params).lanes;
+
+                interface.add_generic(Generic::new("EPC", GenericType::Positive(lanes)));
+                StreamTypeDecl::new(stream_type, Some(StreamDim::new(Some(dim_name.to_string()), dim, 1)))
+            },
+            ref stream_type => StreamTypeDecl::new(stream_type.clone(), Some(StreamDim::new(Some(dim_name.to_string()), dim, 0))),
+        };
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(dim)));
+
+        interface.add_stream("input", TilStreamDirection::Input, input_type);
+        interface.add_stream("output", TilStreamDirection::Output,
+            StreamTypeDecl::new(StreamType::Bool, Some(StreamDim::new(Some(dim_name.to_string()), dim, 0)))
+        );
+
+        // The comparator taps the output of the parser, next to wherever it already goes
+        self.signal_manager.add_signal(TilSignal::Intermediate {
+            source_inst_name: field.get_instance_name(),
+            source_stream_name: "output".to_owned(),
+            dest_inst_name: inst_name.clone(),
+            dest_stream_name: "input".to_owned(),
+        });
+
+        self.type_manager.register(StreamType::Bool);
+        self.json_paths.insert(name.clone(), predicate.get_path().to_owned());
+        self.file_manager.add_entity(TemplateType::FilterCompare { comparison: predicate.get_comparison(), literal, input_type: compared_type, signed }, &name);
+        if lanes != self.gen_params.epc {
+            self.file_manager.set_lanes(&name, lanes);
+        }
+        self.entity_list.push(new_streamlet(&name, interface));
+
+        Selection { inst_name, stream_name: "output".to_owned() }
+    }
+
+    fn create_combiner(&mut self, filter: &Filter, results: Vec<Selection>, dim: usize, record_path: &str) -> Selection {
+        let name = self.name_reg.register("filter_combine", dim);
+        let inst_name = format!("{}_inst", name);
+
+        let mut interface = TilStreamingInterface::default();
+
+        let dim_name = "NESTING_LEVEL";
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(dim)));
+
+        for (idx, result) in results.into_iter().enumerate() {
+            interface.add_stream(&format!("input_{}", idx), TilStreamDirection::Input,
+                StreamTypeDecl::new(StreamType::Bool, Some(StreamDim::new(Some(dim_name.to_string()), dim, 0)))
+            );
+
+            self.signal_manager.add_signal(TilSignal::Intermediate {
+                source_inst_name: result.inst_name,
+                source_stream_name: result.stream_name,
+                dest_inst_name: inst_name.clone(),
+                dest_stream_name: format!("input_{}", idx),
+            });
+        }
+
+        interface.add_stream("output", TilStreamDirection::Output,
+            StreamTypeDecl::new(StreamType::Bool, Some(StreamDim::new(Some(dim_name.to_string()), dim, 0)))
+        );
+
+        self.json_paths.insert(name.clone(), record_path.to_owned());
+        self.file_manager.add_entity(TemplateType::FilterCombine { inputs: filter.get_predicates().len(), combine: filter.get_combine() }, &name);
+        self.entity_list.push(new_streamlet(&name, interface));
+
+        Selection { inst_name, stream_name: "output".to_owned() }
+    }
+
+    // Put a gate in front of every output that carries one value per record
+    fn gate_outputs(&mut self, record_path: &str, selection: &Selection, dim: usize) {
+        let signals = self.signal_manager.take_signals();
+
+        for signal in signals {
+            let gated = match &signal {
+                TilSignal::Output { source_inst_name, output_stream, .. } => {
+                    let source_path = self.json_paths.get(source_inst_name.trim_end_matches("_inst")).cloned().unwrap_or_default();
+                    let stream_type = output_stream.get_type().get_stream_type();
+                    let stream_dim = output_stream.get_type().get_stream_dim().as_ref().map_or(0, |dim| dim.get_true_value());
+
+                    // Fields of the record, or the group that bundles them. Fields that some records lack
+                    // have fewer values than there are selections, so they are passed on as they are
+                    let belongs = (is_field_of(&source_path, record_path) && !self.optional_keys.contains(&source_path))
+                        || (source_path == record_path && matches!(stream_type, StreamType::Group(_)));
+
+                    // Strings carry their characters in one more dimension
+                    let per_record = match stream_type.get_base() {
+                        StreamType::Json => stream_dim == dim + 1,
+                        _ => stream_dim == dim,
+                    };
+
+                    belongs && per_record
+                },
+                _ => false,
+            };
+
+            match signal {
+                TilSignal::Output { source_inst_name, source_stream_name, dest_stream_name, output_stream } if gated => {
+                    let source_path = self.json_paths.get(source_inst_name.trim_end_matches("_inst")).cloned().unwrap_or_default();
+                    let gate_inst = self.create_gate(&output_stream, selection, dim, &source_path);
+
+                    self.signal_manager.add_signal(TilSignal::Intermediate {
+                        source_inst_name,
+                        source_stream_name,
+                        dest_inst_name: gate_inst.clone(),
+                        dest_stream_name: "input".to_owned(),
+                    });
+
+                    // The output keeps its name, only its values are filtered
+                    self.signal_manager.add_signal(TilSignal::Output {
+                        source_inst_name: gate_inst,
+                        source_stream_name: "output".to_owned(),
+                        dest_stream_name,
+                        output_stream,
+                    });
+                },
+                signal => self.signal_manager.add_signal(signal),
+            }
+        }
+    }
+
+    fn create_gate(&mut self, output_stream: &TilStream, selection: &Selection, dim: usize, json_path: &str) -> String {
+        let name = self.name_reg.register("filter_gate", dim);
+        let inst_name = format!("{}_inst", name);
+
+        let stream_type = output_stream.get_type().get_stream_type();
+
+        let mut interface = TilStreamingInterface::default();
+
+        let dim_name = "NESTING_LEVEL";
+        let additive = match stream_type.get_base() {
+            StreamType::Json => {
+                interface.add_generic(Generic::new("EPC", GenericType::Positive(stream_type.get_physical_params(&self.gen_params).lanes)));
+                1
+            },
+            _ => 0,
+        };
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(dim)));
+
+        interface.add_stream("input", TilStreamDirection::Input,
+            StreamTypeDecl::new(stream_type.clone(), Some(StreamDim::new(Some(dim_name.to_string()), dim, additive)))
+        );
+        interface.add_stream("select", TilStreamDirection::Input,
+            StreamTypeDecl::new(StreamType::Bool, Some(StreamDim::new(Some(dim_name.to_string()), dim, 0)))
+        );
+        interface.add_stream("output", TilStreamDirection::Output,
+            StreamTypeDecl::new(stream_type.clone(), Some(StreamDim::new(Some(dim_name.to_string()), dim, additive)))
+        );
+
+        self.signal_manager.add_signal(TilSignal::Intermediate {
+            source_inst_name: selection.inst_name.clone(),
+            source_stream_name: selection.stream_name.clone(),
+            dest_inst_name: inst_name.clone(),
+            dest_stream_name: "select".to_owned(),
+        });
+
+        self.json_paths.insert(name.clone(), json_path.to_owned());
+        self.file_manager.add_entity(TemplateType::FilterGate { stream_type }, &name);
+        self.entity_list.push(new_streamlet(&name, interface));
+
+        inst_name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{Generator, GeneratorError, analyzer::{AnalyzerError, type_manager::StreamType}, testing::{connections, design_of_samples, generator_of_samples}};
+
+    // `extra` is a key that only some records have
+    const SAMPLES: [&str; 2] = [r#"[{"m": 3, "name": "a", "ok": true, "extra": 1}]"#, r#"[{"m": 5, "name": "b", "ok": false}]"#];
+
+    // The connections of the top component of the samples with a filter
+    fn filtered(spec: &str) -> Vec<(String, String)> {
+        let design = design_of_samples(&SAMPLES, |generator| generator.add_filter(spec).unwrap()).unwrap();
+
+        connections(&design.get_top_components()[0])
+    }
+
+    fn invalid_filter(spec: &str) -> String {
+        match generator_of_samples(&SAMPLES, |generator| generator.add_filter(spec).unwrap()) {
+            Err(GeneratorError::AnalyzerError(AnalyzerError::InvalidFilter(err))) => err,
+            Err(err) => panic!("'{}' failed with {}", spec, err),
+            Ok(_) => panic!("'{}' was accepted", spec),
+        }
+    }
+
+    #[test]
+    fn gates_records_with_comparators() {
+        let connections = filtered(r#"$[].m >= 2 && $[].name == "a""#);
+
+        // The results of both comparators are combined into the selection of the record
+        let combined: Vec<&str> = connections.iter()
+            .filter(|(_, sink)| sink.starts_with("filter_combine"))
+            .map(|(source, _)| source.as_str())
+            .collect();
+        assert_eq!(combined, vec!["filter_compare_L3_00_inst.output", "filter_compare_L3_01_inst.output"]);
+
+        // Only the fields that every record has are gated, the others keep all of their values
+        let outputs: Vec<(&str, &str)> = connections.iter()
+            .filter(|(_, sink)| !sink.contains('.'))
+            .map(|(source, sink)| (source.as_str(), sink.as_str()))
+            .collect();
+        assert_eq!(outputs.iter().filter(|(source, _)| source.starts_with("filter_gate")).count(), 3);
+        assert!(outputs.iter().any(|(source, _)| source.starts_with("int_parser")));
+    }
+
+    #[test]
+    fn checks_the_kind_and_range_of_literals() {
+        assert!(invalid_filter("$[].ok == 1").contains("boolean"));
+        assert!(invalid_filter("$[].missing == 1").contains("$[].missing"));
+        assert!(invalid_filter("$[].m == 1 && $.m == 1").contains("same record"));
+
+        // Integers are parsed without their sign
+        assert!(invalid_filter("$[].m > -1").contains("unsigned"));
+        assert!(invalid_filter("$[].m < 18446744073709551616").contains("unsigned"));
+        assert!(!filtered("$[].m < 18446744073709551615").is_empty());
+    }
+
+    #[test]
+    fn rejects_comparisons_of_keys_that_some_records_lack() {
+        assert!(invalid_filter("$[].extra == 1").contains("missing in some samples"));
+    }
+
+    #[test]
+    fn compares_enums_with_the_codes_of_their_values() {
+        let samples = [r#"[{"study": "Computer Science", "year": 1}]"#, r#"[{"study": "Mathematics", "year": 2}]"#];
+        let setup = |spec: &'static str| move |generator: &mut Generator| {
+            generator.add_enum("$[].study=Computer Science,Mathematics").unwrap();
+            generator.add_filter(spec).unwrap();
+        };
+
+        // The comparator takes the codes of the enum matcher
+        let design = design_of_samples(&samples, setup(r#"$[].study == "Mathematics""#)).unwrap();
+        let compare = design.get_streamlets().iter().find(|streamlet| streamlet.get_name().starts_with("filter_compare")).unwrap();
+        let input = compare.get_streams().get_input_streams()[0].get_type().get_stream_type();
+        assert!(matches!(input, StreamType::Tag(_)));
+
+        let connections = connections(&design.get_top_components()[0]);
+        let (tap, _) = connections.iter().find(|(_, sink)| *sink == format!("{}_inst.input", compare.get_name())).unwrap();
+        let (source, _) = connections.iter().find(|(_, sink)| *sink == tap.replace(".output0", ".input")).unwrap();
+        assert!(source.starts_with("enum_matcher"), "{}", source);
+
+        // Strings that are not one of the values would never match
+        let result = generator_of_samples(&samples, setup(r#"$[].study == "Physics""#));
+        assert!(matches!(result, Err(GeneratorError::AnalyzerError(AnalyzerError::InvalidFilter(err))) if err.contains("not one of the values")));
+
+        let result = generator_of_samples(&samples, setup("$[].study == 1"));
+        assert!(matches!(result, Err(GeneratorError::AnalyzerError(AnalyzerError::InvalidFilter(err))) if err.contains("kind enum")));
+    }
+
+    #[test]
+    fn compares_hexadecimal_strings_like_integers() {
+        let samples = [r#"[{"flags": "0x1F"}]"#];
+        let setup = |spec: &'static str| move |generator: &mut Generator| {
+            generator.add_format("$[].flags=hex").unwrap();
+            generator.add_filter(spec).unwrap();
+        };
+
+        assert!(design_of_samples(&samples, setup("$[].flags > 15")).is_ok());
+
+        let result = generator_of_samples(&samples, setup(r#"$[].flags == "0x1F""#));
+        assert!(matches!(result, Err(GeneratorError::AnalyzerError(AnalyzerError::InvalidFilter(err))) if err.contains("kind integer")));
+    }
+
+    #[test]
+    fn compares_quoted_integers_as_signed_integers() {
+        // Integers of 8 bits
+        let analyze = |spec: &str| {
+            let mut generator = Generator::new("test", 4, 8);
+            generator.add_format("$[].n=int").unwrap();
+            generator.add_filter(spec).unwrap();
+            generator.analyze_samples(&[r#"[{"n": "-5"}]"#, r#"[{"n": "12"}]"#]).map(|_| generator)
+        };
+
+        // The comparator takes the integers of the parser of the quoted integers
+        let design = analyze("$[].n >= -3").unwrap().get_design().unwrap();
+        let connections = connections(&design.get_top_components()[0]);
+        let (tap, _) = connections.iter().find(|(_, sink)| sink.starts_with("filter_compare")).unwrap();
+        let (source, _) = connections.iter().find(|(_, sink)| *sink == tap.replace(".output0", ".input")).unwrap();
+        assert!(source.starts_with("quoted_int_parser"), "{}", source);
+
+        // Literals have to fit in a signed integer of the integer width
+        assert!(analyze("$[].n > -128").is_ok());
+        for spec in ["$[].n > -129", "$[].n < 128"] {
+            let result = analyze(spec);
+            assert!(matches!(result, Err(GeneratorError::AnalyzerError(AnalyzerError::InvalidFilter(err))) if err.contains("a signed integer of 8 bits")), "{}", spec);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::analysis::{framing::InputFraming, types::{TilStreamlet, TilSignal, TilStreamingInterface, streaming_interface::{Generic, GenericType, TilStreamDirection}, stream_types::{StreamTypeDecl, StreamDim}, til_streamlet::TilImplementationType}};
+
+use super::{Analyzer, AnalyzerError, file_manager::TemplateType, type_manager::StreamType};
+
+/**********************************************************************************
+ * Framing of the input: inputs of which the documents do not end with a last    *
+ * flag of their own get a framer in front of the parsers, which gives the       *
+ * characters the dimension of the documents.                                    *
+ **********************************************************************************/
+
+impl Analyzer {
+    // The input of the top component, with the dimensions of the framing
+    pub(super) fn get_input_type(&self) -> StreamTypeDecl {
+        StreamTypeDecl::new(StreamType::Json, Some(StreamDim::new(None, self.gen_params.framing.get_input_dim(), 0)))
+    }
+
+    pub fn insert_framer(&mut self) -> Result<(), AnalyzerError> {
+        let framing = self.gen_params.framing;
+        if !framing.needs_framer() {
+            return Ok(());
+        }
+
+        let root = self.top_component.as_ref().ok_or(AnalyzerError::NoTop)?;
+
+        // The framer goes in front of whatever reads the input
+        let dest_inst_name = self.entry_inst_name.clone().unwrap_or_else(|| root.get_generatable().get_instance_name());
+
+        let name = match framing {
+            InputFraming::Ndjson => self.name_reg.register("ndjson_splitter", 0),
+            _ => self.name_reg.register("document_framer", 0),
+        };
+        let inst_name = format!("{}_inst", name);
+
+        let lanes = self.gen_params.epc;
+        let output_dim = framing.get_document_dims() + 1;
+
+        let mut interface = TilStreamingInterface::default();
+        if lanes > 1 {
+            interface.add_generic(Generic::new("EPC", GenericType::Positive(lanes)));
+        }
+
+        interface.add_stream("input", TilStreamDirection::Input, self.get_input_type());
+        interface.add_stream("output", TilStreamDirection::Output, StreamTypeDecl::new(StreamType::Json, Some(StreamDim::new(None, output_dim, 0))));
+
+        self.signal_manager.add_signal(TilSignal::Intermediate {
+            source_inst_name: inst_name.clone(),
+            source_stream_name: "output".to_owned(),
+            dest_inst_name,
+            dest_stream_name: "input".to_owned(),
+        });
+        self.entry_inst_name = Some(inst_name);
+
+        let mut streamlet = TilStreamlet::new(&name);
+        streamlet.set_streaming_interface(interface);
+        streamlet.set_implementation(TilImplementationType::Path("./vhdl_dir".to_string()));
+
+        self.type_manager.register(StreamType::Json);
+        self.json_paths.insert(name.clone(), "$".to_string());
+        self.file_manager.add_entity(TemplateType::Framer(framing), &name);
+        self.entity_list.push(streamlet);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{GeneratorError, checker::check_design, framing::InputFraming, testing::{connections, design_of, generator_of}};
+
+    #[test]
+    fn frames_the_input_of_the_top_component() {
+        for framing in InputFraming::ALL {
+            let design = design_of(r#"{"a": 1}"#, |generator| generator.set_framing(framing.get_name()).unwrap()).unwrap();
+
+            let input = design.get_top_components()[0].get_streams().get_input_streams()[0].get_type();
+            assert_eq!(input.get_stream_dim().as_ref().map(|dim| dim.get_true_value()), Some(framing.get_input_dim()));
+
+            // The input goes to the framer, if there is one, and from there to the record parser
+            let links = connections(&design.get_top_components()[0]);
+            let entry = links.iter().find(|(source, _)| source == "input").map(|(_, sink)| sink.clone()).unwrap();
+            match framing {
+                InputFraming::Ndjson => assert!(entry.starts_with("ndjson_splitter"), "{}", entry),
+                InputFraming::Document => assert!(entry.starts_with("document_framer"), "{}", entry),
+                _ => assert!(entry.starts_with("record_parser"), "{}", entry),
+            }
+            if framing.needs_framer() {
+                let framer = entry.trim_end_matches(".input");
+                assert!(links.contains(&(format!("{}.output", framer), "record_parser_L1_00_inst.input".to_string())), "{:?}", links);
+            }
+
+            assert!(check_design(&design).is_empty(), "{}", framing.get_name());
+        }
+    }
+
+    #[test]
+    fn keeps_the_framer_in_front_of_the_root_subtree() {
+        let design = design_of(r#"{"a": {"b": 1}}"#, |generator| {
+            generator.set_hierarchical(true);
+            generator.set_framing("ndjson").unwrap();
+        }).unwrap();
+
+        let links = connections(&design.get_top_components()[0]);
+        let framer = links.iter().find(|(source, _)| source == "input").map(|(_, sink)| sink.trim_end_matches(".input").to_string()).unwrap();
+        assert!(framer.starts_with("ndjson_splitter"), "{:?}", links);
+
+        let root = links.iter().find(|(source, _)| *source == format!("{}.output", framer)).map(|(_, sink)| sink.clone()).unwrap();
+        assert!(design.get_streamlets().iter().any(|streamlet| root == format!("{}_inst.input", streamlet.get_name()) && streamlet.get_name().ends_with("_subtree")), "{:?}", links);
+
+        assert!(check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_framings() {
+        let mut generator = generator_of(r#"{"a": 1}"#, |_| {}).unwrap();
+        assert!(matches!(generator.set_framing("lines"), Err(GeneratorError::InvalidFraming(name)) if name == "lines"));
+    }
+}
@@ -0,0 +1,270 @@
+use crate::analysis::{components::{JsonComponent, JsonComponentValue, JsonType, Generatable}, types::{TilStreamlet, TilSignal, TilStreamingInterface, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}, til_streamlet::TilImplementationType}};
+
+use super::{Analyzer, file_manager::TemplateType, type_manager::{StreamType, GroupType}};
+
+/**********************************************************************************
+ * Netlist pass that bundles the scalar fields of a record into a single output  *
+ * stream with a group element, synchronized by a generated join component.       *
+ **********************************************************************************/
+
+// A scalar field of a record: the field name, the instance of its parser and its element type
+struct ScalarField {
+    name: String,
+    inst_name: String,
+    stream_type: StreamType,
+    dim: usize,
+}
+
+// Turn a JSON key into a name that can be used as a group field and port name
+fn field_name(key: &str, taken: &[ScalarField]) -> String {
+    let mut name: String = key.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name = format!("f_{}", name);
+    }
+
+    let base = name.clone();
+    let mut idx = 1;
+    while taken.iter().any(|field| field.name == name) {
+        name = format!("{}_{}", base, idx);
+        idx += 1;
+    }
+
+    name
+}
+
+impl Analyzer {
+    pub fn group_records(&mut self) {
+        if let Some(root) = self.top_component.clone() {
+            self.group_component(&root);
+        }
+    }
+
+    fn group_component(&mut self, component: &JsonComponent) {
+        if let JsonComponent::Record(record) = component {
+            let mut fields: Vec<ScalarField> = Vec::new();
+
+            // Integer, boolean, formatted and enum fields are fixed size, strings and nested structures stay separate streams. So do
+            // keys that some records do not have, the join would wait for them forever.
+            for key in record.get_children() {
+                let is_optional = match &key {
+                    JsonComponent::Key(key) => self.json_paths.get(key.get_name()).is_some_and(|path| self.optional_keys.contains(path)),
+                    _ => continue,
+                };
+
+                if is_optional {
+                    continue;
+                }
+
+                let children = key.get_children();
+
+                let (matcher, field, stream_type): (_, &dyn Generatable, _) = match children.as_slice() {
+                    [JsonComponent::Matcher(matcher), JsonComponent::Value(value)] => match value.get_data_type() {
+                        JsonType::Integer => (matcher, value, value.get_int_type()),
+                        JsonType::Boolean => (matcher, value, StreamType::Bool),
+                        JsonType::Formatted(format) => (matcher, value, format.get_stream_type()),
+                        JsonType::String | JsonType::Decoded => continue,
+                    },
+                    [JsonComponent::Matcher(matcher), JsonComponent::Enumeration(enumeration)] => (matcher, enumeration, StreamType::Tag(enumeration.get_code_type())),
+                    _ => continue,
+                };
+
+                fields.push(ScalarField {
+                    name: field_name(matcher.get_matcher(), &fields),
+                    inst_name: field.get_instance_name(),
+                    stream_type,
+                    dim: field.get_nesting_level(),
+                });
+            }
+
+            // A single field gains nothing from a group
+            if fields.len() > 1 {
+                self.create_join(record.get_name(), fields);
+            }
+        }
+
+        for child in component.get_children() {
+            self.group_component(&child);
+        }
+    }
+
+    fn create_join(&mut self, record_name: &str, fields: Vec<ScalarField>) {
+        let dim = fields[0].dim;
+        let name = self.name_reg.register("record_join", dim);
+        let inst_name = format!("{}_inst", name);
+
+        let group_type = StreamType::Group(GroupType::new(&name,
+            fields.iter().map(|field| (field.name.clone(), field.stream_type.clone())).collect()
+        ));
+        self.type_manager.register(group_type.clone());
+
+        let mut interface = TilStreamingInterface::default();
+
+        let dim_name = "NESTING_LEVEL";
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(dim)));
+
+        for field in &fields {
+            interface.add_stream(&format!("input_{}", field.name), TilStreamDirection::Input,
+                StreamTypeDecl::new(field.stream_type.clone(), Some(StreamDim::new(Some(dim_name.to_string()), dim, 0)))
+            );
+        }
+
+        interface.add_stream("output", TilStreamDirection::Output,
+            StreamTypeDecl::new(group_type.clone(), Some(StreamDim::new(Some(dim_name.to_string()), dim, 0)))
+        );
+
+        let mut join = TilStreamlet::new(&name);
+        join.set_streaming_interface(interface);
+        join.set_implementation(TilImplementationType::Path("./vhdl_dir".to_string()));
+
+        // The fields no longer leave the design on their own but through the join
+        let signals = self.signal_manager.take_signals();
+        for signal in signals {
+            let field = fields.iter().find(|field| signal.get_source_inst_name() == Some(&field.inst_name) && signal.get_source_stream_name() == "output");
+
+            match (field, signal) {
+                (Some(field), TilSignal::Output { source_inst_name, source_stream_name, .. }) => {
+                    self.signal_manager.add_signal(TilSignal::Intermediate {
+                        source_inst_name,
+                        source_stream_name,
+                        dest_inst_name: inst_name.clone(),
+                        dest_stream_name: format!("input_{}", field.name),
+                    });
+                },
+                (_, signal) => self.signal_manager.add_signal(signal),
+            }
+        }
+
+        let output_name = format!("output_{}", inst_name);
+        self.signal_manager.add_signal(TilSignal::Output {
+            source_inst_name: inst_name,
+            source_stream_name: "output".to_owned(),
+            dest_stream_name: output_name.clone(),
+            output_stream: TilStream::new(&output_name, TilStreamDirection::Output,
+                StreamTypeDecl::new(group_type.clone(), Some(StreamDim::new(None, dim, 0)))
+            ),
+        });
+
+        // The join belongs to the record it bundles
+        if let Some(json_path) = self.json_paths.get(record_name).cloned() {
+            self.json_paths.insert(name.clone(), json_path);
+        }
+
+        self.file_manager.add_entity(TemplateType::Join { stream_type: group_type }, &name);
+        self.entity_list.push(join);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{analyzer::type_manager::StreamType, backend::Design, checker::check_design, testing::{design_of, design_of_samples}};
+
+    fn grouped_design(json: &str) -> Design {
+        design_of(json, |generator| generator.set_group_records(true)).unwrap()
+    }
+
+    // The field names of every group type of the design
+    fn group_fields(design: &Design) -> Vec<Vec<String>> {
+        design.get_stream_types().iter()
+            .filter_map(|stream_type| match stream_type {
+                StreamType::Group(group) => Some(group.get_fields().iter().map(|(name, _)| name.clone()).collect()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // The types of the outputs of the design, where every group is called "Group"
+    fn output_types(design: &Design) -> Vec<String> {
+        let groups: Vec<&str> = design.get_stream_types().iter()
+            .filter(|stream_type| matches!(stream_type, StreamType::Group(_)))
+            .map(|stream_type| stream_type.get_name())
+            .collect();
+
+        design.get_top_components()[0].get_streams().get_output_streams().iter()
+            .map(|stream| stream.get_type().get_name())
+            .map(|name| if groups.contains(&name) { "Group".to_string() } else { name.to_string() })
+            .collect()
+    }
+
+    #[test]
+    fn joins_the_scalar_fields_of_a_record() {
+        let design = grouped_design(r#"[{"time": 1, "value": 2, "valid": true}]"#);
+
+        assert_eq!(group_fields(&design), vec![vec!["time".to_string(), "value".to_string(), "valid".to_string()]]);
+
+        // The fields only leave the design through the join
+        assert_eq!(output_types(&design), ["Group"]);
+
+        assert!(check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn leaves_strings_and_nested_structures_as_separate_streams() {
+        let design = grouped_design(r#"{"name": "a", "age": 3, "active": true, "grades": [1, 2]}"#);
+
+        assert_eq!(group_fields(&design), vec![vec!["age".to_string(), "active".to_string()]]);
+
+        // Next to the group there are the characters of the name and the elements of the array
+        let mut outputs = output_types(&design);
+        outputs.sort();
+        assert_eq!(outputs, ["Group", "IntParserStream", "JSONStream"]);
+
+        assert!(check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn does_not_group_a_single_field() {
+        let design = grouped_design(r#"{"name": "a", "age": 3}"#);
+
+        assert!(group_fields(&design).is_empty());
+        assert_eq!(output_types(&design).len(), 2);
+    }
+
+    #[test]
+    fn leaves_keys_that_some_records_lack_as_separate_streams() {
+        // The join would wait forever for the validity of the second reading
+        let design = design_of_samples(&[r#"[{"time": 1, "value": 2, "valid": true}]"#, r#"[{"time": 3, "value": 4}]"#],
+            |generator| generator.set_group_records(true)).unwrap();
+
+        assert_eq!(group_fields(&design), vec![vec!["time".to_string(), "value".to_string()]]);
+
+        let mut outputs = output_types(&design);
+        outputs.sort();
+        assert_eq!(outputs, ["BoolParserStream", "Group"]);
+
+        assert!(check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn joins_the_codes_of_enums() {
+        let design = design_of(r#"{"name": "a", "study": "CS", "year": 2}"#, |generator| {
+            generator.set_group_records(true);
+            generator.add_enum("$.study=CS,EE").unwrap();
+        }).unwrap();
+
+        assert_eq!(group_fields(&design), vec![vec!["study".to_string(), "year".to_string()]]);
+
+        let mut outputs = output_types(&design);
+        outputs.sort();
+        assert_eq!(outputs, ["Group", "JSONStream"]);
+
+        assert!(check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn joins_formatted_values() {
+        let design = design_of(r#"{"birthdate": "2000-05-15", "id": 3}"#, |generator| generator.add_format("$.birthdate=date").unwrap()).unwrap();
+        assert!(group_fields(&design).is_empty());
+
+        let design = design_of(r#"{"birthdate": "2000-05-15", "id": 3}"#, |generator| {
+            generator.set_group_records(true);
+            generator.add_format("$.birthdate=date").unwrap();
+        }).unwrap();
+
+        assert_eq!(group_fields(&design), vec![vec!["birthdate".to_string(), "id".to_string()]]);
+        assert_eq!(output_types(&design), ["Group"]);
+        assert!(check_design(&design).is_empty());
+    }
+}
@@ -0,0 +1,519 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::analysis::{syntax::til, components::{JsonComponent, JsonComponentValue}, types::{TilStreamlet, TilSignal, streaming_interface::{TilStream, TilStreamDirection, Generic, GenericType}, stream_types::{StreamTypeDecl, StreamDim}, til_streamlet::{TilInlineImplementation, TilImplementationType, TilInstance}}};
+
+use super::{Analyzer, AnalyzerError, type_manager::StreamType};
+
+/**********************************************************************************
+ * Hierarchical assembly: every record and array subtree of the document becomes  *
+ * a streamlet of its own which only exposes the outputs of its fields. Subtrees  *
+ * take their nesting level as a generic and pass it on to the components they    *
+ * instantiate, so identical subtrees at any depth share one streamlet.           *
+ **********************************************************************************/
+
+// The generic of a subtree streamlet which holds the nesting level of its root component
+const NESTING_LEVEL: &str = "NESTING_LEVEL";
+
+struct Subtree {
+    streamlet: TilStreamlet,
+    instances: Vec<TilInstance>,
+    signals: Vec<TilSignal>,
+    root_inst: String,
+    parent: Option<usize>,
+    // The nesting level of the root component, which is the default of the generic
+    level: usize,
+}
+
+impl Subtree {
+    fn new(name: &str, root_inst: String, parent: Option<usize>) -> Subtree {
+        Subtree {
+            streamlet: TilStreamlet::new(&format!("{}_subtree", name)),
+            instances: Vec::new(),
+            signals: Vec::new(),
+            root_inst,
+            parent,
+            level: 0,
+        }
+    }
+
+    fn add_instance(&mut self, component_name: &str) {
+        self.instances.push(TilInstance::new(component_name, &instance_name(component_name)));
+    }
+}
+
+fn is_subtree_root(component: &JsonComponent) -> bool {
+    matches!(component, JsonComponent::Record(_) | JsonComponent::Array(_))
+}
+
+fn instance_name(component_name: &str) -> String {
+    format!("{}_inst", component_name)
+}
+
+// A dimensionality relative to the nesting level of the subtree it is used in
+fn relative_dim(value: usize, level: usize) -> StreamDim {
+    StreamDim::new(Some(NESTING_LEVEL.to_owned()), level, value as isize - level as isize)
+}
+
+// Express the dimension of a stream in the nesting level of a subtree
+fn relative(stream_type: &StreamTypeDecl, level: usize) -> StreamTypeDecl {
+    StreamTypeDecl::new(
+        stream_type.get_stream_type(),
+        stream_type.get_stream_dim().as_ref().map(|dim| relative_dim(dim.get_true_value(), level))
+    )
+}
+
+fn relative_stream(stream: &TilStream, level: usize) -> TilStream {
+    TilStream::new(stream.get_name(), stream.get_direction(), relative(stream.get_type(), level))
+}
+
+impl Analyzer {
+    // Walk the component tree and assign every instance to the subtree it belongs to
+    fn collect_subtrees(&self, component: &JsonComponent, subtree: usize, subtrees: &mut Vec<Subtree>, membership: &mut HashMap<String, usize>) {
+        for child in component.get_children() {
+            let name = child.get_generatable().get_name().to_owned();
+
+            let child_subtree = if is_subtree_root(&child) {
+                subtrees.push(Subtree::new(&name, instance_name(&name), Some(subtree)));
+
+                let child_subtree = subtrees.len() - 1;
+                let subtree_name = subtrees[child_subtree].streamlet.get_name().to_owned();
+                subtrees[subtree].add_instance(&subtree_name);
+
+                child_subtree
+            } else {
+                subtree
+            };
+
+            membership.insert(instance_name(&name), child_subtree);
+            subtrees[child_subtree].add_instance(&name);
+
+            self.collect_subtrees(&child, child_subtree, subtrees, membership);
+        }
+    }
+
+    // Find the declaration of a stream of an instantiated component
+    pub(super) fn find_instance_stream(&self, inst_name: &str, stream_name: &str) -> Option<&TilStream> {
+        self.entity_list.iter()
+            .find(|streamlet| instance_name(streamlet.get_name()) == inst_name)?
+            .get_streams().get_streams().iter()
+            .find(|stream| stream.get_name() == stream_name)
+    }
+
+    fn find_component(&self, component_name: &str) -> Option<&TilStreamlet> {
+        self.entity_list.iter().find(|streamlet| streamlet.get_name() == component_name)
+    }
+
+    // Everything that makes a component behave the way it does, apart from its name and its nesting level
+    fn component_key(&self, component_name: &str) -> String {
+        let mut streamlet = match self.find_component(component_name) {
+            Some(streamlet) => streamlet.to_til(),
+            None => return component_name.to_owned(),
+        };
+
+        streamlet.name.clear();
+        for param in streamlet.params.iter_mut().filter(|param| param.kind == til::GenericKind::Dimensionality) {
+            param.default = 0;
+        }
+
+        format!("{:?} {}", streamlet, self.file_manager.describe_entity(component_name).unwrap_or_default())
+    }
+
+    // The structure of a subtree, in which the instances and the ports are numbered instead of named
+    fn subtree_key(&self, subtree: &Subtree, subtree_names: &HashSet<String>) -> String {
+        let instances: HashMap<&str, usize> = subtree.instances.iter().enumerate()
+            .map(|(idx, instance)| (instance.get_instance_name(), idx))
+            .collect();
+        let ports: HashMap<&str, usize> = subtree.streamlet.get_streams().get_streams().iter().enumerate()
+            .map(|(idx, stream)| (stream.get_name(), idx))
+            .collect();
+
+        let port_key = |instance: Option<&String>, port: &String| match instance {
+            Some(instance) => format!("{}.{}", instances.get(instance.as_str()).map_or(instance.clone(), |idx| idx.to_string()), port),
+            None => format!("self.{}", ports.get(port.as_str()).map_or(port.clone(), |idx| idx.to_string())),
+        };
+
+        let mut key: Vec<String> = subtree.streamlet.to_til().ports.iter()
+            .map(|port| format!("{} {}<{:?}>", port.direction, port.type_name, port.dim))
+            .collect();
+
+        for instance in &subtree.instances {
+            let component = match subtree_names.contains(instance.get_component_name()) {
+                true => instance.get_component_name().to_owned(),
+                false => self.component_key(instance.get_component_name()),
+            };
+            key.push(format!("{:?} {}", instance.to_til().args, component));
+        }
+
+        let mut signals: Vec<String> = subtree.signals.iter()
+            .map(|signal| format!("{} -- {}",
+                port_key(signal.get_source_inst_name(), signal.get_source_stream_name()),
+                port_key(signal.get_dest_inst_name(), signal.get_dest_stream_name())))
+            .collect();
+        signals.sort();
+        key.extend(signals);
+
+        key.join("\n")
+    }
+
+    // Let subtrees with the same structure as one that comes after them use the streamlet of that
+    // one, the connections of their parent move over to the ports of the shared streamlet
+    fn share_subtrees(&self, subtrees: &mut [Subtree]) {
+        let subtree_names: HashSet<String> = subtrees.iter().map(|subtree| subtree.streamlet.get_name().to_owned()).collect();
+        let mut shared: HashMap<String, usize> = HashMap::new();
+
+        // Children come after their parents, so they are shared before the keys of their parents are made
+        for idx in (0..subtrees.len()).rev() {
+            let key = self.subtree_key(&subtrees[idx], &subtree_names);
+
+            let (shared_idx, parent) = match (shared.get(&key), subtrees[idx].parent) {
+                (Some(&shared_idx), Some(parent)) => (shared_idx, parent),
+                _ => {
+                    shared.insert(key, idx);
+                    continue;
+                },
+            };
+
+            let name = subtrees[idx].streamlet.get_name().to_owned();
+            let shared_name = subtrees[shared_idx].streamlet.get_name().to_owned();
+            let inst_name = instance_name(&name);
+
+            // The ports of both streamlets are in the same order
+            let port_names: HashMap<String, String> = subtrees[idx].streamlet.get_streams().get_streams().iter()
+                .zip(subtrees[shared_idx].streamlet.get_streams().get_streams().iter())
+                .map(|(port, shared_port)| (port.get_name().to_owned(), shared_port.get_name().to_owned()))
+                .collect();
+
+            let parent = &mut subtrees[parent];
+            for instance in parent.instances.iter_mut().filter(|instance| instance.get_instance_name() == inst_name) {
+                let mut shared_instance = TilInstance::new(&shared_name, &inst_name);
+                if let Some(level) = instance.get_generic(NESTING_LEVEL) {
+                    shared_instance.add_generic(NESTING_LEVEL, level.clone());
+                }
+                *instance = shared_instance;
+            }
+
+            for signal in parent.signals.iter_mut() {
+                if let TilSignal::Intermediate { source_inst_name: source, source_stream_name: port, .. } | TilSignal::Output { source_inst_name: source, source_stream_name: port, .. } = signal {
+                    if *source == inst_name {
+                        if let Some(shared_port) = port_names.get(port.as_str()) {
+                            *port = shared_port.clone();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Assemble the subtree streamlets and a top component which only instantiates the root subtree.
+    /// Returns the streamlets of the design and the components that are no longer instantiated.
+    pub fn assemble_hierarchy(&mut self) -> Result<(Vec<TilStreamlet>, TilStreamlet, Vec<String>), AnalyzerError> {
+        let root = self.top_component.as_ref().ok_or(AnalyzerError::NoTop)?;
+
+        // A document that is a single value has nothing to structure
+        if !is_subtree_root(root) {
+            return Ok((self.entity_list.clone(), self.assemble_top_component()?, Vec::new()));
+        }
+
+        let root_name = root.get_generatable().get_name().to_owned();
+
+        let mut subtrees = vec![Subtree::new(&root_name, instance_name(&root_name), None)];
+        subtrees[0].add_instance(&root_name);
+
+        let mut membership = HashMap::new();
+        membership.insert(instance_name(&root_name), 0);
+        self.collect_subtrees(root, 0, &mut subtrees, &mut membership);
+
+        let signals: Vec<TilSignal> = self.signal_manager.get_intermediate_signals().iter()
+            .chain(self.signal_manager.get_output_signals().iter())
+            .cloned()
+            .collect();
+
+        // Components that are not part of the tree (e.g. duplicators) belong to the subtree that drives them
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for signal in &signals {
+                if let (Some(source), Some(dest)) = (signal.get_source_inst_name(), signal.get_dest_inst_name()) {
+                    if !membership.contains_key(dest) {
+                        if let Some(&subtree) = membership.get(source) {
+                            membership.insert(dest.clone(), subtree);
+                            subtrees[subtree].add_instance(dest.trim_end_matches("_inst"));
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Input of every subtree is the input of its root component, which sets the nesting level
+        for subtree in subtrees.iter_mut() {
+            let stream = self.find_instance_stream(&subtree.root_inst, "input").ok_or(AnalyzerError::NoTop)?;
+            let input_dim = stream.get_type().get_stream_dim().as_ref().map_or(1, |dim| dim.get_true_value());
+            subtree.level = input_dim.saturating_sub(1);
+
+            let stream_type = relative(stream.get_type(), subtree.level);
+
+            subtree.streamlet.get_streams_mut().add_generic(Generic::new(NESTING_LEVEL, GenericType::Dimensionality(subtree.level)));
+            subtree.streamlet.get_streams_mut().add_stream("input", TilStreamDirection::Input, stream_type);
+            subtree.streamlet.set_parameterized(true);
+            subtree.signals.push(TilSignal::Input {
+                source_stream_name: "input".to_owned(),
+                dest_inst_name: subtree.root_inst.clone(),
+                dest_stream_name: "input".to_owned(),
+            });
+        }
+
+        for signal in signals {
+            let source_inst = signal.get_source_inst_name().cloned().unwrap_or_default();
+            let subtree = match membership.get(&source_inst) {
+                Some(&subtree) => subtree,
+                None => continue,
+            };
+
+            match signal {
+                TilSignal::Intermediate { source_inst_name, source_stream_name, dest_inst_name, dest_stream_name } => {
+                    // Signals into the root of a child subtree enter through the input of that subtree
+                    let child = subtrees.iter().position(|child| child.root_inst == dest_inst_name && child.parent == Some(subtree));
+
+                    match child {
+                        Some(child) => {
+                            let child_inst = instance_name(subtrees[child].streamlet.get_name());
+
+                            subtrees[subtree].signals.push(TilSignal::Intermediate {
+                                source_inst_name,
+                                source_stream_name,
+                                dest_inst_name: child_inst,
+                                dest_stream_name: "input".to_owned(),
+                            });
+                        },
+                        None => subtrees[subtree].signals.push(TilSignal::Intermediate {
+                            source_inst_name,
+                            source_stream_name,
+                            dest_inst_name,
+                            dest_stream_name,
+                        }),
+                    }
+                },
+                TilSignal::Output { source_inst_name, source_stream_name, dest_stream_name, output_stream } => {
+                    let stream = relative_stream(&output_stream, subtrees[subtree].level);
+                    subtrees[subtree].streamlet.get_streams_mut().add_til_stream(stream.clone());
+                    subtrees[subtree].signals.push(TilSignal::Output {
+                        source_inst_name,
+                        source_stream_name,
+                        dest_stream_name: dest_stream_name.clone(),
+                        output_stream: stream,
+                    });
+
+                    // Pass the output on through all enclosing subtrees
+                    let mut current = subtree;
+                    while let Some(parent) = subtrees[current].parent {
+                        let current_inst = instance_name(subtrees[current].streamlet.get_name());
+                        let stream = relative_stream(&output_stream, subtrees[parent].level);
+
+                        subtrees[parent].streamlet.get_streams_mut().add_til_stream(stream.clone());
+                        subtrees[parent].signals.push(TilSignal::Output {
+                            source_inst_name: current_inst,
+                            source_stream_name: dest_stream_name.clone(),
+                            dest_stream_name: dest_stream_name.clone(),
+                            output_stream: stream,
+                        });
+
+                        current = parent;
+                    }
+                },
+                TilSignal::Input { .. } => {},
+            }
+        }
+
+        // Instances get the dimensionalities of the components relative to the nesting level of the subtree
+        let levels: HashMap<String, usize> = subtrees.iter().map(|subtree| (subtree.streamlet.get_name().to_owned(), subtree.level)).collect();
+        for subtree in subtrees.iter_mut() {
+            for instance in subtree.instances.iter_mut() {
+                match levels.get(instance.get_component_name()) {
+                    Some(&level) => instance.add_generic(NESTING_LEVEL, relative_dim(level, subtree.level)),
+                    None => if let Some(component) = self.find_component(instance.get_component_name()) {
+                        for generic in component.get_streams().get_generics() {
+                            if let GenericType::Dimensionality(value) = generic.get_type() {
+                                instance.add_generic(generic.get_name(), relative_dim(*value, subtree.level));
+                            }
+                        }
+                    },
+                }
+            }
+        }
+
+        self.share_subtrees(&mut subtrees);
+
+        // The top component only instantiates the root subtree
+        let mut top_component = TilStreamlet::new(self.gen_params.get_top_name());
+        let mut implementation = TilInlineImplementation::default();
+
+        top_component.get_streams_mut().add_stream("input", TilStreamDirection::Input, self.get_input_type());
+        self.type_manager.register(StreamType::Json);
+
+        let root_subtree_inst = instance_name(subtrees[0].streamlet.get_name());
+        let mut root_instance = TilInstance::new(subtrees[0].streamlet.get_name(), &root_subtree_inst);
+        root_instance.add_generic(NESTING_LEVEL, StreamDim::new(None, subtrees[0].level, 0));
+        implementation.add_til_instance(root_instance);
+
+        // A framer stays in the top component, in front of the root subtree
+        match &self.entry_inst_name {
+            Some(entry_inst_name) => {
+                implementation.add_til_instance(TilInstance::new(entry_inst_name.trim_end_matches("_inst"), entry_inst_name));
+                implementation.add_signal(TilSignal::Input { source_stream_name: "input".to_owned(), dest_inst_name: entry_inst_name.clone(), dest_stream_name: "input".to_owned() });
+                implementation.add_signal(TilSignal::Intermediate {
+                    source_inst_name: entry_inst_name.clone(),
+                    source_stream_name: "output".to_owned(),
+                    dest_inst_name: root_subtree_inst.clone(),
+                    dest_stream_name: "input".to_owned(),
+                });
+            },
+            None => implementation.add_signal(TilSignal::Input { source_stream_name: "input".to_owned(), dest_inst_name: root_subtree_inst.clone(), dest_stream_name: "input".to_owned() }),
+        }
+
+        // The outputs of the top component have the dimensionality of the root subtree
+        for stream in subtrees[0].streamlet.get_streams().get_output_streams() {
+            let dim = stream.get_type().get_stream_dim().as_ref().map(|dim| StreamDim::new(None, dim.get_true_value(), 0));
+            let stream = TilStream::new(stream.get_name(), TilStreamDirection::Output, StreamTypeDecl::new(stream.get_type().get_stream_type(), dim));
+
+            top_component.get_streams_mut().add_til_stream(stream.clone());
+            implementation.add_signal(TilSignal::Output {
+                source_inst_name: root_subtree_inst.clone(),
+                source_stream_name: stream.get_name().to_owned(),
+                dest_stream_name: stream.get_name().to_owned(),
+                output_stream: stream,
+            });
+        }
+
+        top_component.set_implementation(TilImplementationType::Inline(implementation));
+
+        // Only the subtrees that the root subtree reaches are left, shared subtrees are in there once
+        let mut reached = vec![0];
+        let mut pending = vec![0];
+        while let Some(idx) = pending.pop() {
+            for instance in &subtrees[idx].instances {
+                if let Some(child) = subtrees.iter().position(|subtree| subtree.streamlet.get_name() == instance.get_component_name()) {
+                    if !reached.contains(&child) {
+                        reached.push(child);
+                        pending.push(child);
+                    }
+                }
+            }
+        }
+
+        // The components of the subtrees that are left out are no longer needed
+        let mut instantiated = HashSet::new();
+        let mut dropped = Vec::new();
+        for (idx, subtree) in subtrees.iter().enumerate() {
+            for instance in &subtree.instances {
+                if reached.contains(&idx) {
+                    instantiated.insert(instance.get_component_name().to_owned());
+                } else {
+                    dropped.push(instance.get_component_name().to_owned());
+                }
+            }
+        }
+        let unused: Vec<String> = dropped.into_iter().filter(|name| !instantiated.contains(name)).collect();
+
+        // The components are parameterized by the subtrees that instantiate them
+        let mut streamlets: Vec<TilStreamlet> = self.entity_list.iter()
+            .filter(|component| !unused.contains(&component.get_name().to_owned()))
+            .cloned()
+            .collect();
+        for component in streamlets.iter_mut() {
+            component.set_parameterized(true);
+        }
+
+        // Emit children before the subtrees that instantiate them
+        for (idx, subtree) in subtrees.into_iter().enumerate().rev() {
+            if !reached.contains(&idx) {
+                continue;
+            }
+
+            let mut streamlet = subtree.streamlet;
+
+            // Subtrees are reported under the path of their root component
+            if let Some(json_path) = self.json_paths.get(subtree.root_inst.trim_end_matches("_inst")).cloned() {
+                self.json_paths.insert(streamlet.get_name().to_owned(), json_path);
+            }
+
+            let mut implementation = TilInlineImplementation::default();
+            for instance in subtree.instances {
+                implementation.add_til_instance(instance);
+            }
+            implementation.add_multiple_signals(subtree.signals);
+
+            streamlet.set_implementation(TilImplementationType::Inline(implementation));
+            streamlets.push(streamlet);
+        }
+
+        Ok((streamlets, top_component, unused))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{checker::check_design, testing::design_of, types::til_streamlet::TilImplementationType};
+
+    #[test]
+    fn instantiates_a_streamlet_per_record_and_array() {
+        let design = design_of(r#"{"name": "a", "grades": [1, 2]}"#, |generator| generator.set_hierarchical(true)).unwrap();
+
+        // The top component only holds the root subtree, which exposes the outputs of the fields
+        let top = &design.get_top_components()[0];
+        let Some(TilImplementationType::Inline(implementation)) = top.get_implementation() else {
+            panic!("the top component has no netlist");
+        };
+        assert_eq!(implementation.get_instances().len(), 1);
+
+        let root = design.get_streamlet(implementation.get_instances()[0].get_component_name()).unwrap();
+        assert!(root.get_name().starts_with("record_parser") && root.get_name().ends_with("_subtree"));
+        assert_eq!(root.get_streams().get_output_streams().len(), top.get_streams().get_output_streams().len());
+
+        // The array of grades is a subtree of its own inside the root
+        let Some(TilImplementationType::Inline(root_implementation)) = root.get_implementation() else {
+            panic!("the root subtree has no netlist");
+        };
+        assert!(root_implementation.get_instances().iter().any(|instance| instance.get_component_name().starts_with("array_parser") && instance.get_component_name().ends_with("_subtree")));
+
+        assert!(check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn identical_subtrees_share_a_streamlet() {
+        // The arrays under "a", "b.c" and "d" have the same shape at different nesting levels
+        let design = design_of(r#"{"a": [{"x": 1, "y": true}], "b": {"c": [{"x": 2, "y": false}]}, "d": [{"x": 3, "y": true}]}"#,
+            |generator| generator.set_hierarchical(true)).unwrap();
+
+        let subtrees: Vec<_> = design.get_streamlets().iter().filter(|streamlet| streamlet.get_name().ends_with("_subtree")).collect();
+        let array_subtrees: Vec<_> = subtrees.iter().filter(|streamlet| streamlet.get_name().starts_with("array_parser")).collect();
+        assert_eq!(array_subtrees.len(), 1);
+
+        // Every instance passes the nesting level it is instantiated at
+        let shared = array_subtrees[0].get_name();
+        let mut levels = Vec::new();
+        for subtree in subtrees.iter() {
+            if let Some(TilImplementationType::Inline(implementation)) = subtree.get_implementation() {
+                for instance in implementation.get_instances().iter().filter(|instance| instance.get_component_name() == shared) {
+                    levels.push(instance.get_generic(super::NESTING_LEVEL).unwrap().to_dim_expr().to_string());
+                }
+            }
+        }
+        levels.sort();
+        assert_eq!(levels, vec!["NESTING_LEVEL+1", "NESTING_LEVEL+1", "NESTING_LEVEL+1"]);
+
+        assert!(check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn subtree_streamlets_keep_the_nesting_level_generic() {
+        let design = design_of(r#"[{"x": [1, 2]}]"#, |generator| generator.set_hierarchical(true)).unwrap();
+
+        for streamlet in design.get_streamlets().iter().filter(|streamlet| streamlet.get_name().ends_with("_subtree")) {
+            assert!(streamlet.get_params().iter().any(|generic| generic.get_name() == super::NESTING_LEVEL), "{} has no nesting level generic", streamlet.get_name());
+        }
+
+        assert!(check_design(&design).is_empty());
+    }
+}
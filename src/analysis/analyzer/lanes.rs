@@ -0,0 +1,218 @@
+use crate::analysis::types::{TilStreamlet, TilSignal, TilStreamingInterface, streaming_interface::{Generic, GenericType, TilStream, TilStreamDirection}, stream_types::{StreamTypeDecl, StreamDim}, til_streamlet::TilImplementationType};
+
+use super::{Analyzer, AnalyzerError, file_manager::TemplateType, type_manager::StreamType};
+
+/**********************************************************************************
+ * Subtrees with an EPC of their own: their parsers get streams with another      *
+ * number of lanes, and a netlist pass places converters on the JSON streams      *
+ * that cross from one number of lanes to another.                                *
+ **********************************************************************************/
+
+impl Analyzer {
+    // Give a component that was just added another number of lanes than the EPC of the design
+    pub(super) fn set_lanes(&mut self, component_name: &str, lanes: usize) {
+        if lanes == self.gen_params.epc {
+            return;
+        }
+
+        let streamlet = match self.entity_list.iter_mut().find(|streamlet| streamlet.get_name() == component_name) {
+            Some(streamlet) => streamlet,
+            None => return,
+        };
+
+        let mut interface = TilStreamingInterface::default();
+
+        for generic in streamlet.get_streams().get_generics() {
+            match generic.get_name() {
+                "EPC" | "BPC" => interface.add_generic(Generic::new(generic.get_name(), GenericType::Positive(lanes))),
+                _ => interface.add_generic(generic.clone()),
+            }
+        }
+
+        for stream in streamlet.get_streams().get_streams() {
+            let stream_type = stream.get_type().get_stream_type().with_lanes(lanes, &self.gen_params);
+
+            self.type_manager.register(stream_type.clone());
+            interface.add_stream(stream.get_name(), stream.get_direction(), StreamTypeDecl::new(stream_type, stream.get_type().get_stream_dim().clone()));
+        }
+
+        streamlet.set_streaming_interface(interface);
+
+        // The outputs of the component leave the design with its number of lanes
+        let inst_name = format!("{}_inst", component_name);
+        let mut signals = self.signal_manager.take_signals();
+        for signal in signals.iter_mut() {
+            if let TilSignal::Output { source_inst_name, output_stream, .. } = signal {
+                if *source_inst_name == inst_name {
+                    let stream_type = output_stream.get_type();
+                    let laned = StreamTypeDecl::new(stream_type.get_stream_type().with_lanes(lanes, &self.gen_params), stream_type.get_stream_dim().clone());
+
+                    *output_stream = TilStream::new(output_stream.get_name(), TilStreamDirection::Output, laned);
+                }
+            }
+        }
+        self.signal_manager.add_multiple_signals(signals);
+
+        self.file_manager.set_lanes(component_name, lanes);
+    }
+
+    // Every configured number of lanes has to belong to a value of the document
+    pub fn check_epcs(&self) -> Result<(), AnalyzerError> {
+        for (path, _) in &self.gen_params.lanes.epcs {
+            if !self.json_paths.values().any(|component_path| component_path == path) {
+                return Err(AnalyzerError::UnknownPath("value", path.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Place a converter on every JSON stream that goes to a component with another number of lanes
+    pub fn insert_lane_converters(&mut self) -> Result<(), AnalyzerError> {
+        let signals = self.signal_manager.take_signals();
+
+        for signal in signals {
+            let (source_inst_name, source_stream_name, dest_inst_name, dest_stream_name) = match &signal {
+                TilSignal::Intermediate { source_inst_name, source_stream_name, dest_inst_name, dest_stream_name } =>
+                    (source_inst_name.clone(), source_stream_name.clone(), dest_inst_name.clone(), dest_stream_name.clone()),
+                _ => {
+                    self.signal_manager.add_signal(signal);
+                    continue;
+                },
+            };
+
+            let streams = self.find_instance_stream(&source_inst_name, &source_stream_name).cloned()
+                .zip(self.find_instance_stream(&dest_inst_name, &dest_stream_name).cloned());
+
+            // Streams of other types are left to the design rule checker
+            let (source, dest) = match streams {
+                Some((source, dest)) if source.get_type().get_name() != dest.get_type().get_name()
+                    && source.get_type().get_stream_type().get_base() == &StreamType::Json
+                    && dest.get_type().get_stream_type().get_base() == &StreamType::Json => (source, dest),
+                _ => {
+                    self.signal_manager.add_signal(signal);
+                    continue;
+                },
+            };
+
+            let input_lanes = source.get_type().get_stream_type().get_physical_params(&self.gen_params).lanes;
+            let output_lanes = dest.get_type().get_stream_type().get_physical_params(&self.gen_params).lanes;
+            let dest_name = dest_inst_name.trim_end_matches("_inst").to_owned();
+            let path = self.json_paths.get(&dest_name).cloned().unwrap_or_default();
+
+            // A subtree only receives the characters the value that holds it passes on
+            if output_lanes > input_lanes {
+                return Err(AnalyzerError::InvalidConfig(format!("the value at path '{}' can not have more lanes ({}) than the value that holds it ({})", path, output_lanes, input_lanes)));
+            }
+
+            let converter = self.create_lane_converter(&source, &dest, input_lanes, output_lanes);
+            let converter_inst = format!("{}_inst", converter.get_name());
+
+            self.signal_manager.add_signal(TilSignal::Intermediate {
+                source_inst_name,
+                source_stream_name,
+                dest_inst_name: converter_inst.clone(),
+                dest_stream_name: "input".to_owned(),
+            });
+            self.signal_manager.add_signal(TilSignal::Intermediate {
+                source_inst_name: converter_inst,
+                source_stream_name: "output".to_owned(),
+                dest_inst_name,
+                dest_stream_name,
+            });
+
+            // The converter belongs to the subtree it feeds
+            self.json_paths.insert(converter.get_name().to_owned(), path);
+            self.entity_list.push(converter);
+        }
+
+        Ok(())
+    }
+
+    fn create_lane_converter(&mut self, source: &TilStream, dest: &TilStream, input_lanes: usize, output_lanes: usize) -> TilStreamlet {
+        // JSON streams have one more dimension than the nesting level of their components
+        let true_dim = source.get_type().get_stream_dim().as_ref().map_or(1, |dim| dim.get_true_value());
+        let nesting = true_dim.saturating_sub(1);
+
+        let name = self.name_reg.register("lane_converter", true_dim);
+
+        let mut interface = TilStreamingInterface::default();
+
+        let dim_name = "NESTING_LEVEL";
+        interface.add_generic(Generic::new("INPUT_EPC", GenericType::Positive(input_lanes)));
+        interface.add_generic(Generic::new("OUTPUT_EPC", GenericType::Positive(output_lanes)));
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(nesting)));
+
+        interface.add_stream("input", TilStreamDirection::Input,
+            StreamTypeDecl::new(source.get_type().get_stream_type(), Some(StreamDim::new(Some(dim_name.to_string()), nesting, 1)))
+        );
+        interface.add_stream("output", TilStreamDirection::Output,
+            StreamTypeDecl::new(dest.get_type().get_stream_type(), Some(StreamDim::new(Some(dim_name.to_string()), nesting, 1)))
+        );
+
+        let mut converter = TilStreamlet::new(&name);
+        converter.set_streaming_interface(interface);
+        converter.set_implementation(TilImplementationType::Path("./vhdl_dir".to_string()));
+
+        self.file_manager.add_entity(TemplateType::LaneConverter { input_lanes, output_lanes }, &name);
+
+        converter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{GeneratorParams, GeneratorError, checker::check_design, analyzer::AnalyzerError, testing::{connections, design_of_config}, types::{TilStreamlet, streaming_interface::GenericType}};
+
+    fn lanes_of(streamlet: &TilStreamlet) -> Option<usize> {
+        streamlet.get_streams().get_generics().iter().find_map(|generic| match (generic.get_name(), generic.get_type()) {
+            ("EPC", GenericType::Positive(lanes)) => Some(*lanes),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn selects_the_lanes_of_the_closest_subtree() {
+        let mut gen_params = GeneratorParams::new(8, 8, 64, "", "test");
+        gen_params.lanes.epcs = vec![("$.a".to_string(), 4), ("$.a.b[]".to_string(), 1)];
+
+        assert_eq!(gen_params.get_epc("$"), 8);
+        assert_eq!(gen_params.get_epc("$.a"), 4);
+        assert_eq!(gen_params.get_epc("$.a.c"), 4);
+        assert_eq!(gen_params.get_epc("$.a.b[].d"), 1);
+        // A key that only starts with the name of the subtree is not part of it
+        assert_eq!(gen_params.get_epc("$.ab"), 8);
+    }
+
+    #[test]
+    fn converts_the_lanes_where_json_streams_cross_subtrees() {
+        let design = design_of_config(r#"{"a": "x", "b": {"c": "y"}}"#, "[paths.\"$.b\"]\nepc = 1").unwrap();
+
+        let lanes: Vec<_> = design.get_streamlets().iter()
+            .filter(|streamlet| streamlet.get_name().starts_with("string_parser"))
+            .map(|streamlet| (design.get_json_path(streamlet.get_name()).unwrap(), lanes_of(streamlet)))
+            .collect();
+        assert_eq!(lanes, vec![("$.a", Some(4)), ("$.b.c", Some(1))]);
+
+        // The single converter sits between the key of the subtree and the record parser it holds
+        let converters: Vec<_> = design.get_streamlets().iter().filter(|streamlet| streamlet.get_name().starts_with("lane_converter")).collect();
+        assert_eq!(converters.len(), 1);
+
+        let converter = format!("{}_inst", converters[0].get_name());
+        let links = connections(&design.get_top_components()[0]);
+        assert!(links.contains(&("key_parser_L2_01_inst.output".to_string(), format!("{}.input", converter))), "{:?}", links);
+        assert!(links.contains(&(format!("{}.output", converter), "record_parser_L2_00_inst.input".to_string())), "{:?}", links);
+        assert_eq!(design.get_json_path(converters[0].get_name()), Some("$.b"));
+
+        assert!(check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn rejects_lanes_for_unknown_paths() {
+        let result = design_of_config(r#"{"a": 1}"#, "[paths.\"$.z\"]\nepc = 2");
+        assert!(matches!(result, Err(GeneratorError::AnalyzerError(AnalyzerError::UnknownPath("value", path))) if path == "$.z"));
+
+        let result = design_of_config(r#"{"a": {"b": 1}}"#, "[paths.\"$\"]\nepc = 2");
+        assert!(matches!(result, Err(GeneratorError::InvalidPathConfig(path, _)) if path == "$"));
+    }
+}
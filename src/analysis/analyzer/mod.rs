@@ -1,13 +1,23 @@
-use json::JsonValue;
+use std::collections::HashMap;
 
-use crate::analysis::components::JsonComponent;
+use crate::analysis::{components::JsonComponent, schema::Schema};
 
 use self::{file_manager::FileManager, signal_manager::SignalManager};
 
-use super::{types::TilStreamlet, GeneratorParams, analyzer::{name_reg::NameReg, type_manager::{TypeManager, StreamType}}};
+use super::{types::TilStreamlet, GeneratorParams, backend::Design, analyzer::{name_reg::NameReg, type_manager::{TypeManager, StreamType}}};
 
 mod analysis;
 mod name_reg;
+mod duplicator;
+mod group;
+mod filter;
+mod aggregate;
+mod validate;
+mod overrides;
+mod lanes;
+mod shape;
+mod framing;
+mod hierarchy;
 pub mod type_manager;
 pub mod top_component;
 pub mod file_manager;
@@ -26,6 +36,11 @@ pub struct Analyzer {
     gen_params: GeneratorParams,
     signal_manager: SignalManager,
     top_component: Option<JsonComponent>,
+    json_paths: HashMap<String, String>,
+    // Paths of the keys that some samples do not have
+    optional_keys: Vec<String>,
+    // The instance the input of the top component goes to when it is not the root component
+    entry_inst_name: Option<String>,
 }
 
 impl Analyzer {
@@ -38,14 +53,72 @@ impl Analyzer {
             gen_params: GeneratorParams::default(),
             signal_manager: SignalManager::default(),
             top_component: None,
+            json_paths: HashMap::new(),
+            optional_keys: Vec::new(),
+            entry_inst_name: None,
         }
     }
 
-    pub fn analyze(&mut self, root: &JsonValue, gen_params: GeneratorParams) {
+    pub fn set_optional_keys(&mut self, optional_keys: Vec<String>) {
+        self.optional_keys = optional_keys;
+    }
+
+    pub fn analyze(&mut self, root: &Schema, gen_params: GeneratorParams) -> Result<(), AnalyzerError> {
         self.gen_params = gen_params;
 
-        let (root_component, _) = self.analyze_element(root, 0, 0);
+        // The components of a schema of several are named after it
+        if let Some(schema_name) = &self.gen_params.schema_name {
+            self.name_reg = NameReg::with_prefix(schema_name);
+        }
+
+        // The root component is nested in the dimensions of the documents of the input
+        let outer_nesting = self.gen_params.framing.get_document_dims() - 1;
+        let (root_component, _) = self.analyze_element(root, outer_nesting, 0, "$");
         self.top_component = root_component;
+
+        self.check_int_widths()?;
+        self.check_component_params()?;
+        self.check_epcs()?;
+
+        // Subtrees with fewer lanes receive their characters through converters
+        self.insert_lane_converters()?;
+
+        if self.gen_params.netlist.group_records {
+            self.group_records();
+        }
+
+        // Filters gate the outputs after they are grouped, so a group is filtered as a whole
+        self.insert_filters()?;
+
+        // Aggregations read the values before they are filtered
+        self.insert_aggregations()?;
+
+        // Checkers tap the streams before duplicators are inserted for them
+        if self.gen_params.outputs.validate {
+            self.insert_validation(root.get_depth())?;
+        }
+
+        // The framer goes in front of the document checker, which checks framed documents
+        self.insert_framer()?;
+
+        if self.gen_params.netlist.insert_duplicators {
+            self.insert_duplicators();
+        }
+
+        // Adapters go right in front of the outputs, behind the duplicators
+        self.shape_outputs()?;
+
+        self.name_outputs()?;
+
+        Ok(())
+    }
+
+    pub fn get_top_name(&self) -> &str {
+        self.gen_params.get_top_name()
+    }
+
+    pub fn get_schema_name(&self) -> Option<&str> {
+        self.gen_params.schema_name.as_deref()
     }
 
     pub fn get_root(&self) -> Result<&JsonComponent, AnalyzerError> {
@@ -62,8 +135,21 @@ impl Analyzer {
         (stream_types, til_components)
     }
 
-    pub fn get_file_manager(&self) -> &FileManager {
-        &self.file_manager
+    // Collect the definitions, the top component and the files into a design for the backends
+    pub fn get_design(&mut self) -> Result<Design, AnalyzerError> {
+        let (streamlets, top_component, unused) = if self.gen_params.netlist.hierarchical {
+            self.assemble_hierarchy()?
+        } else {
+            (self.entity_list.clone(), self.assemble_top_component()?, Vec::new())
+        };
+
+        let (stream_types, _) = self.get_definitions();
+
+        // Components of subtrees that share the streamlet of another subtree get no files
+        let mut file_manager = self.file_manager.clone();
+        file_manager.remove_entities(&unused);
+
+        Ok(Design::new(stream_types, streamlets, top_component, file_manager, self.json_paths.clone()))
     }
 }
 
@@ -71,4 +157,24 @@ impl Analyzer {
 pub enum AnalyzerError {
     NoTop,
     PythonError(String),
+    InvalidFilter(String),
+    InvalidAggregation(String),
+    InvalidValidation(String),
+    // The kind of value that was expected and the path
+    UnknownPath(&'static str, String),
+    InvalidConfig(String),
+}
+
+impl std::fmt::Display for AnalyzerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalyzerError::NoTop => write!(f, "the document has no top component"),
+            AnalyzerError::PythonError(err) => write!(f, "matcher generation failed: {}", err),
+            AnalyzerError::InvalidFilter(err) => write!(f, "invalid filter: {}", err),
+            AnalyzerError::InvalidAggregation(err) => write!(f, "invalid aggregation: {}", err),
+            AnalyzerError::InvalidValidation(err) => write!(f, "invalid validation: {}", err),
+            AnalyzerError::UnknownPath(kind, path) => write!(f, "no {} at path '{}'", kind, path),
+            AnalyzerError::InvalidConfig(err) => write!(f, "invalid configuration: {}", err),
+        }
+    }
 }
\ No newline at end of file
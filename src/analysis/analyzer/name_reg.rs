@@ -2,17 +2,30 @@ use std::collections::HashMap;
 
 pub struct NameReg {
     name_map: HashMap<String, usize>,
+    // Prefix of all names, which keeps the components of several schemas in one namespace apart
+    prefix: Option<String>,
 }
 
 impl NameReg {
     pub fn new() -> NameReg {
         NameReg {
             name_map: HashMap::new(),
+            prefix: None,
+        }
+    }
+
+    pub fn with_prefix(prefix: &str) -> NameReg {
+        NameReg {
+            name_map: HashMap::new(),
+            prefix: Some(prefix.to_owned()),
         }
     }
 
     pub fn register(&mut self, name: &str, nesting_level: usize) -> String {
-        let mut pref_name = String::from(name);
+        let mut pref_name = match &self.prefix {
+            Some(prefix) => format!("{}_{}", prefix, name),
+            None => String::from(name),
+        };
 
         // Add nesting level to prefered name
         pref_name.push_str(&format!("_L{}_", nesting_level)); 
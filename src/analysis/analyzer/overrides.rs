@@ -0,0 +1,229 @@
+use crate::analysis::{params::ComponentParam, components::{JsonComponent, JsonType, Generatable, JsonComponentValue}, types::{TilSignal, streaming_interface::{TilStream, TilStreamDirection}}};
+
+use super::{Analyzer, AnalyzerError};
+
+/**********************************************************************************
+ * Overrides of the configuration that are checked against the component tree,   *
+ * as their paths can only be resolved once the parsers are known.                *
+ **********************************************************************************/
+
+impl Analyzer {
+    // Every configured integer width has to belong to an integer parser
+    pub fn check_int_widths(&self) -> Result<(), AnalyzerError> {
+        for (path, _) in &self.gen_params.schema.int_widths {
+            if !self.top_component.as_ref().is_some_and(|root| self.has_component(root, path, ComponentParam::PipelineStages)) {
+                return Err(AnalyzerError::UnknownPath("integer", path.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Every tuning parameter of a path has to belong to a component of its kind, and the
+    // conversion of every integer parser has to split evenly over its pipeline stages
+    pub fn check_component_params(&self) -> Result<(), AnalyzerError> {
+        for (path, param, _) in &self.gen_params.tuning.path_params {
+            if !self.top_component.as_ref().is_some_and(|root| self.has_component(root, path, *param)) {
+                return Err(AnalyzerError::UnknownPath(param.get_component_kind(), path.clone()));
+            }
+        }
+
+        match &self.top_component {
+            Some(root) => self.check_pipeline_stages(root),
+            None => Ok(()),
+        }
+    }
+
+    fn check_pipeline_stages(&self, component: &JsonComponent) -> Result<(), AnalyzerError> {
+        if let JsonComponent::Value(value) = component {
+            let int_width = value.get_int_width(&self.gen_params);
+            if matches!(value.get_data_type(), JsonType::Integer) && int_width % value.get_pipeline_stages() != 0 {
+                let path = self.json_paths.get(value.get_name()).cloned().unwrap_or_default();
+                return Err(AnalyzerError::InvalidConfig(format!("the {} bit integers at path '{}' can not be split into {} pipeline stages", int_width, path, value.get_pipeline_stages())));
+            }
+        }
+
+        component.get_children().iter().try_for_each(|child| self.check_pipeline_stages(child))
+    }
+
+    // Whether the component at a path is of the kind the parameter belongs to
+    fn has_component(&self, component: &JsonComponent, path: &str, param: ComponentParam) -> bool {
+        let matches_kind = match (component, param) {
+            (JsonComponent::Value(value), ComponentParam::PipelineStages) => matches!(value.get_data_type(), JsonType::Integer),
+            (JsonComponent::Key(_), ComponentParam::KeyBufferDepth) => true,
+            (JsonComponent::Array(_), ComponentParam::ElementCounterWidth) => true,
+            _ => false,
+        };
+
+        if matches_kind && self.json_paths.get(component.get_generatable().get_name()).map(String::as_str) == Some(path) {
+            return true;
+        }
+
+        component.get_children().iter().any(|child| self.has_component(child, path, param))
+    }
+
+    // Give the outputs of the values at the configured paths the configured name, the outputs are
+    // named after the instance of their component followed by a suffix, e.g. `output_int_parser_L2_00_inst_error`
+    pub fn name_outputs(&mut self) -> Result<(), AnalyzerError> {
+        if self.gen_params.outputs.output_names.is_empty() {
+            return Ok(());
+        }
+
+        let mut signals = self.signal_manager.take_signals();
+
+        for (path, name) in &self.gen_params.outputs.output_names {
+            let prefixes: Vec<String> = self.json_paths.iter()
+                .filter(|(_, component_path)| *component_path == path)
+                .map(|(component, _)| format!("output_{}_inst", component))
+                .collect();
+
+            let mut renamed = false;
+            for signal in signals.iter_mut() {
+                if let TilSignal::Output { dest_stream_name, output_stream, .. } = signal {
+                    let suffix = prefixes.iter()
+                        .filter_map(|prefix| dest_stream_name.strip_prefix(prefix.as_str()))
+                        .find(|suffix| suffix.is_empty() || suffix.starts_with('_'));
+
+                    if let Some(suffix) = suffix {
+                        let new_name = format!("{}{}", name, suffix);
+
+                        *output_stream = TilStream::new(&new_name, TilStreamDirection::Output, output_stream.get_type().clone());
+                        *dest_stream_name = new_name;
+                        renamed = true;
+                    }
+                }
+            }
+
+            if !renamed {
+                return Err(AnalyzerError::UnknownPath("output", path.clone()));
+            }
+        }
+
+        // The names share the interface of the top component with its input
+        let mut names = vec!["input".to_string()];
+        for signal in &signals {
+            if let TilSignal::Output { dest_stream_name, .. } = signal {
+                if names.contains(dest_stream_name) {
+                    return Err(AnalyzerError::InvalidConfig(format!("the output name '{}' is used twice", dest_stream_name)));
+                }
+
+                names.push(dest_stream_name.clone());
+            }
+        }
+
+        self.signal_manager.add_multiple_signals(signals);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{GeneratorError, GeneratorParams, analyzer::AnalyzerError, testing::{connections, design_of_config}, types::{TilStreamlet, streaming_interface::GenericType}};
+
+    fn generic(streamlet: &TilStreamlet, name: &str) -> usize {
+        match streamlet.get_streams().get_generics().iter().find(|generic| generic.get_name() == name).unwrap().get_type() {
+            GenericType::Natural(value) | GenericType::Positive(value) => *value,
+            _ => panic!("{} is no width or count", name),
+        }
+    }
+
+    #[test]
+    fn applies_the_overrides_of_paths() {
+        let design = design_of_config(r#"{"a": 1, "b": 2, "c": "x"}"#, r#"
+            [paths."$.b"]
+            int_width = 8
+            name = "small"
+
+            [paths."$.c"]
+            select = false
+        "#).unwrap();
+
+        // Only the integer at the path is narrower, and its output is renamed
+        let widths: Vec<_> = design.get_streamlets().iter()
+            .filter(|streamlet| streamlet.get_name().starts_with("int_parser"))
+            .map(|streamlet| (design.get_json_path(streamlet.get_name()).unwrap().to_string(), generic(streamlet, "BITWIDTH")))
+            .collect();
+        assert_eq!(widths, vec![("$.a".to_string(), 64), ("$.b".to_string(), 8)]);
+
+        let outputs: Vec<String> = connections(&design.get_top_components()[0]).into_iter()
+            .filter(|(_, sink)| !sink.contains('.'))
+            .map(|(_, sink)| sink)
+            .collect();
+        assert!(outputs.contains(&"small".to_string()), "{:?}", outputs);
+
+        // The field that is not selected has no parser at all
+        assert!(!design.get_streamlets().iter().any(|streamlet| design.get_json_path(streamlet.get_name()) == Some("$.c")));
+    }
+
+    #[test]
+    fn emits_the_widths_of_paths_into_their_parsers() {
+        let design = design_of_config(r#"{"exams": [{"grade": 7, "year": 2020}]}"#, r#"
+            [paths."$.exams[].grade"]
+            int_width = 8
+        "#).unwrap();
+
+        let gen_params = GeneratorParams::new(4, 8, 64, "", "test");
+        let vhdl_of = |path: &str| {
+            let parser = design.get_streamlets().iter()
+                .find(|streamlet| streamlet.get_name().starts_with("int_parser") && design.get_json_path(streamlet.get_name()) == Some(path))
+                .unwrap();
+            design.get_file_manager().generate_entity(parser.get_name(), &gen_params).unwrap()
+        };
+
+        assert!(vhdl_of("$.exams[].grade").contains("BITWIDTH : positive := 8;"));
+        assert!(vhdl_of("$.exams[].year").contains("BITWIDTH : positive := 64;"));
+    }
+
+    #[test]
+    fn sets_the_tuning_parameters_per_path() {
+        let design = design_of_config(r#"{"a": 1, "b": 2, "c": [3]}"#, r#"
+            pipeline_stages = 2
+
+            [paths."$.b"]
+            pipeline_stages = 4
+
+            [paths."$.c"]
+            element_counter_width = 8
+        "#).unwrap();
+
+        let stages: Vec<_> = design.get_streamlets().iter()
+            .filter(|streamlet| streamlet.get_name().starts_with("int_parser"))
+            .map(|streamlet| (design.get_json_path(streamlet.get_name()).unwrap().to_string(), generic(streamlet, "PIPELINE_STAGES")))
+            .collect();
+        assert_eq!(stages, vec![
+            ("$.a".to_string(), 2),
+            ("$.b".to_string(), 4),
+            ("$.c[]".to_string(), 2),
+        ]);
+
+        let array = design.get_streamlets().iter().find(|streamlet| streamlet.get_name().starts_with("array_parser")).unwrap();
+        assert_eq!(generic(array, "ELEMENT_COUNTER_BW"), 8);
+    }
+
+    #[test]
+    fn rejects_tuning_parameters_that_do_not_apply() {
+        // The path holds a string, which has no integer parser
+        let result = design_of_config(r#"{"a": "x"}"#, "[paths.\"$.a\"]\npipeline_stages = 2");
+        assert!(matches!(result, Err(GeneratorError::AnalyzerError(AnalyzerError::UnknownPath("integer", path))) if path == "$.a"));
+
+        // 64 bits do not split evenly over 3 stages
+        let result = design_of_config(r#"{"a": 1}"#, "pipeline_stages = 3");
+        assert!(matches!(result, Err(GeneratorError::AnalyzerError(AnalyzerError::InvalidConfig(err))) if err.contains("3 pipeline stages")));
+
+        let result = design_of_config(r#"{"a": 1}"#, "[paths.\"$.a\"]\nkey_buffer_depth = 0");
+        assert!(matches!(result, Err(GeneratorError::InvalidPathConfig(path, _)) if path == "$.a"));
+    }
+
+    #[test]
+    fn rejects_overrides_that_do_not_apply() {
+        let result = design_of_config(r#"{"a": "x"}"#, "[paths.\"$.a\"]\nint_width = 8");
+        assert!(matches!(result, Err(GeneratorError::AnalyzerError(AnalyzerError::UnknownPath("integer", path))) if path == "$.a"));
+
+        let result = design_of_config(r#"{"a": 1}"#, "[paths.\"$.b\"]\nselect = false");
+        assert!(matches!(result, Err(GeneratorError::UnknownPath("record field", path)) if path == "$.b"));
+
+        let result = design_of_config(r#"{"a": 1, "b": 2}"#, "[paths.\"$.a\"]\nname = \"b\"\n[paths.\"$.b\"]\nname = \"b\"");
+        assert!(matches!(result, Err(GeneratorError::AnalyzerError(AnalyzerError::InvalidConfig(err))) if err.contains("'b' is used twice")));
+    }
+}
@@ -0,0 +1,239 @@
+use crate::analysis::types::{TilStreamlet, TilSignal, TilStreamingInterface, streaming_interface::{Generic, GenericType, TilStream, TilStreamDirection}, stream_types::{StreamTypeDecl, StreamDim}, til_streamlet::TilImplementationType};
+
+use super::{Analyzer, AnalyzerError, file_manager::TemplateType};
+
+/**********************************************************************************
+ * Outputs with another synchronicity or complexity than the components that     *
+ * produce them: a netlist pass places an adapter in front of every such output   *
+ * that drops the flattened dimensions and repacks the transfers.                 *
+ **********************************************************************************/
+
+// The lowest complexity of which the adapters guarantee the rules, below it the valid signal
+// would have to be held within a sequence
+const MIN_ADAPTED_COMPLEXITY: u8 = 3;
+
+impl Analyzer {
+    // The path of the value an output of the top component belongs to
+    fn find_output_path(&self, dest_stream_name: &str) -> Option<String> {
+        self.json_paths.iter()
+            .filter(|(component, _)| {
+                dest_stream_name.strip_prefix(&format!("output_{}_inst", component))
+                    .is_some_and(|suffix| suffix.is_empty() || suffix.starts_with('_'))
+            })
+            .max_by_key(|(component, _)| component.len())
+            .map(|(_, path)| path.clone())
+    }
+
+    pub fn shape_outputs(&mut self) -> Result<(), AnalyzerError> {
+        if self.gen_params.shapes.output_shapes.is_empty() && self.gen_params.shapes.type_shapes.is_empty() {
+            return Ok(());
+        }
+
+        let signals = self.signal_manager.take_signals();
+        let mut shaped_paths = Vec::new();
+        let mut shaped_types = Vec::new();
+
+        for signal in signals {
+            let (source_inst_name, source_stream_name, dest_stream_name, output_stream) = match signal {
+                TilSignal::Output { source_inst_name, source_stream_name, dest_stream_name, output_stream } =>
+                    (source_inst_name, source_stream_name, dest_stream_name, output_stream),
+                signal => {
+                    self.signal_manager.add_signal(signal);
+                    continue;
+                },
+            };
+
+            let path = self.find_output_path(&dest_stream_name).unwrap_or_default();
+            let stream_type = output_stream.get_type().get_stream_type();
+            let type_names = [stream_type.get_name(), stream_type.get_base().get_name()];
+            let shape = self.gen_params.get_stream_shape(&path, &type_names);
+
+            if self.gen_params.shapes.output_shapes.iter().any(|(shape_path, _)| *shape_path == path) {
+                shaped_paths.push(path.clone());
+            }
+            shaped_types.extend(type_names.iter().map(|name| name.to_string()));
+
+            let (native_synchronicity, native_complexity) = stream_type.get_shape(&self.gen_params);
+            let synchronicity = shape.synchronicity.unwrap_or(native_synchronicity);
+
+            // Flat streams keep the dimension of the characters of a value and the dimensions of the documents
+            let stream_dim = output_stream.get_type().get_stream_dim().clone();
+            let input_dim = stream_dim.as_ref().map_or(0, |dim| dim.get_true_value());
+            let inner_dim = if stream_type.is_characters() { 1 } else { 0 };
+            let document_dims = self.gen_params.framing.get_document_dims();
+            let output_dim = if synchronicity.is_flat() { input_dim.min(inner_dim + document_dims) } else { input_dim };
+
+            if synchronicity.is_flat() && stream_type.has_fixed_dimensionality(&self.gen_params) {
+                return Err(AnalyzerError::InvalidConfig(format!("the output '{}' has a fixed number of dimensions and can not be flattened", dest_stream_name)));
+            }
+
+            // Streams of which dimensions are dropped get at least the complexity the adapter guarantees
+            let complexity = match shape.complexity {
+                Some(complexity) => complexity,
+                None if output_dim != input_dim => native_complexity.max(MIN_ADAPTED_COMPLEXITY),
+                None => native_complexity,
+            };
+
+            if (synchronicity, complexity) == (native_synchronicity, native_complexity) {
+                self.signal_manager.add_signal(TilSignal::Output { source_inst_name, source_stream_name, dest_stream_name, output_stream });
+                continue;
+            }
+
+            // Outputs of which the transfers stay the same only get another type
+            let repack = output_dim != input_dim || complexity < native_complexity;
+            if repack && complexity < MIN_ADAPTED_COMPLEXITY {
+                return Err(AnalyzerError::InvalidConfig(format!("the output '{}' can not be given complexity {}, the lowest complexity it can be adapted to is {}", dest_stream_name, complexity, MIN_ADAPTED_COMPLEXITY)));
+            }
+
+            let shaped_type = stream_type.with_shape(synchronicity, complexity, &self.gen_params);
+            let shaped_dim = match stream_dim {
+                Some(_) if output_dim != input_dim => Some(StreamDim::new(None, output_dim - inner_dim, inner_dim as isize)),
+                dim => dim,
+            };
+            self.type_manager.register(shaped_type.clone());
+
+            let input_decl = output_stream.get_type().clone();
+            let output_decl = StreamTypeDecl::new(shaped_type, shaped_dim);
+            let adapter = self.create_stream_adapter(&input_decl, &output_decl, inner_dim, repack);
+            let adapter_inst = format!("{}_inst", adapter.get_name());
+
+            self.signal_manager.add_signal(TilSignal::Intermediate {
+                source_inst_name,
+                source_stream_name,
+                dest_inst_name: adapter_inst.clone(),
+                dest_stream_name: "input".to_owned(),
+            });
+            self.signal_manager.add_signal(TilSignal::Output {
+                source_inst_name: adapter_inst,
+                source_stream_name: "output".to_owned(),
+                output_stream: TilStream::new(output_stream.get_name(), TilStreamDirection::Output, output_decl),
+                dest_stream_name,
+            });
+
+            // The adapter belongs to the value of the output
+            self.json_paths.insert(adapter.get_name().to_owned(), path);
+            self.entity_list.push(adapter);
+        }
+
+        // Every configured shape has to belong to an output
+        for (path, _) in &self.gen_params.shapes.output_shapes {
+            if !shaped_paths.contains(path) {
+                return Err(AnalyzerError::UnknownPath("output", path.clone()));
+            }
+        }
+
+        for (type_name, _) in &self.gen_params.shapes.type_shapes {
+            if !shaped_types.contains(type_name) {
+                return Err(AnalyzerError::InvalidConfig(format!("no output has stream type '{}'", type_name)));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_stream_adapter(&mut self, input: &StreamTypeDecl, output: &StreamTypeDecl, inner_dim: usize, repack: bool) -> TilStreamlet {
+        let input_dim = input.get_stream_dim().as_ref().map_or(0, |dim| dim.get_true_value());
+        let output_dim = output.get_stream_dim().as_ref().map_or(0, |dim| dim.get_true_value());
+        let lanes = input.get_stream_type().get_physical_params(&self.gen_params).lanes;
+
+        let name = self.name_reg.register("stream_adapter", input_dim);
+
+        let mut interface = TilStreamingInterface::default();
+        if lanes > 1 {
+            interface.add_generic(Generic::new("EPC", GenericType::Positive(lanes)));
+        }
+
+        interface.add_stream("input", TilStreamDirection::Input, input.clone());
+        interface.add_stream("output", TilStreamDirection::Output, output.clone());
+
+        let mut adapter = TilStreamlet::new(&name);
+        adapter.set_streaming_interface(interface);
+        adapter.set_implementation(TilImplementationType::Path("./vhdl_dir".to_string()));
+
+        self.file_manager.add_entity(TemplateType::StreamAdapter {
+            input_type: input.get_stream_type(),
+            output_type: output.get_stream_type(),
+            input_dim,
+            output_dim,
+            inner_dim,
+            repack,
+        }, &name);
+
+        adapter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{GeneratorParams, GeneratorError, checker::check_design, types::stream_types::StreamTypeDecl, testing::{connections, design_of_config}, analyzer::{AnalyzerError, type_manager::Synchronicity}};
+
+    const SAMPLE: &str = r#"{"a": [[1, 2]], "b": 3}"#;
+
+    #[test]
+    fn adapts_outputs_to_the_configured_shape() {
+        let design = design_of_config(SAMPLE, "[paths.\"$.a[][]\"]\nsynchronicity = \"Flatten\"").unwrap();
+
+        let adapters: Vec<_> = design.get_streamlets().iter().filter(|streamlet| streamlet.get_name().starts_with("stream_adapter")).collect();
+        assert_eq!(adapters.len(), 1);
+        assert_eq!(design.get_json_path(adapters[0].get_name()), Some("$.a[][]"));
+
+        // The nested arrays are flattened into one sequence per document, only the dimension of the documents is kept
+        let dim = |decl: &StreamTypeDecl| decl.get_stream_dim().as_ref().map(|dim| dim.get_true_value());
+        let input = adapters[0].get_streams().get_input_streams()[0].get_type();
+        let output = adapters[0].get_streams().get_output_streams()[0].get_type();
+        assert_eq!((dim(input), dim(output)), (Some(4), Some(1)));
+        assert_eq!(output.get_stream_type().get_shape(&GeneratorParams::new(4, 8, 64, "", "test")), (Synchronicity::Flatten, 3));
+
+        // The adapter sits between the parser of the path and its output, the other output is left alone
+        let adapter = format!("{}_inst", adapters[0].get_name());
+        let links = connections(&design.get_top_components()[0]);
+        let into_adapter: Vec<_> = links.iter().filter(|(_, sink)| sink.starts_with(&adapter)).collect();
+        let out_of_adapter: Vec<_> = links.iter().filter(|(source, _)| source.starts_with(&adapter)).collect();
+        assert_eq!(into_adapter.len(), 1, "{:?}", links);
+        assert!(into_adapter[0].0.starts_with("int_parser"), "{:?}", links);
+        assert_eq!(out_of_adapter.len(), 1, "{:?}", links);
+        assert!(!out_of_adapter[0].1.contains('.'), "{:?}", links);
+        assert_eq!(links.iter().filter(|(source, sink)| source.starts_with("int_parser") && !sink.contains('.')).count(), 1, "{:?}", links);
+
+        assert!(check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn shapes_the_outputs_of_a_stream_type_unless_their_path_has_a_shape() {
+        let design = design_of_config(SAMPLE, r#"
+            [streams.IntParserStream]
+            complexity = 4
+
+            [paths."$.b"]
+            complexity = 5
+        "#).unwrap();
+
+        let gen_params = GeneratorParams::new(4, 8, 64, "", "test");
+        let mut shapes: Vec<_> = design.get_streamlets().iter()
+            .filter(|streamlet| streamlet.get_name().starts_with("stream_adapter"))
+            .map(|adapter| {
+                let output = adapter.get_streams().get_output_streams()[0].get_type().get_stream_type();
+                (design.get_json_path(adapter.get_name()).unwrap().to_string(), output.get_shape(&gen_params).1)
+            })
+            .collect();
+        shapes.sort();
+        assert_eq!(shapes, vec![("$.a[][]".to_string(), 4), ("$.b".to_string(), 5)]);
+
+        assert!(check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn rejects_shapes_that_can_not_be_applied() {
+        let result = design_of_config(SAMPLE, "[paths.\"$.a[][]\"]\nsynchronicity = \"Sometimes\"");
+        assert!(matches!(result, Err(GeneratorError::InvalidPathConfig(path, err)) if path == "$.a[][]" && err.contains("unknown synchronicity")));
+
+        let result = design_of_config(SAMPLE, "[paths.\"$.a[][]\"]\nsynchronicity = \"Flatten\"\ncomplexity = 2");
+        assert!(matches!(result, Err(GeneratorError::AnalyzerError(AnalyzerError::InvalidConfig(err))) if err.contains("complexity 2")));
+
+        let result = design_of_config(SAMPLE, "[paths.\"$.c\"]\ncomplexity = 4");
+        assert!(matches!(result, Err(GeneratorError::AnalyzerError(AnalyzerError::UnknownPath("output", path))) if path == "$.c"));
+
+        let result = design_of_config(SAMPLE, "[streams.IntParserStream]\ncomplexity = 9");
+        assert!(matches!(result, Err(GeneratorError::InvalidConfig(err)) if err.contains("IntParserStream")));
+    }
+}
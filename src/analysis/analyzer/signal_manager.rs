@@ -32,4 +32,12 @@ impl SignalManager {
     pub fn get_output_signals(&self) -> &Vec<TilSignal> {
         &self.output_signals
     }
+
+    // Remove all signals, e.g. to rewrite them
+    pub fn take_signals(&mut self) -> Vec<TilSignal> {
+        let mut signals = std::mem::take(&mut self.intermediate_signals);
+        signals.append(&mut self.output_signals);
+
+        signals
+    }
 }
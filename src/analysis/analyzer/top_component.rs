@@ -1,18 +1,13 @@
-use crate::analysis::types::{TilStreamlet, streaming_interface::TilStreamDirection, stream_types::{StreamTypeDecl, StreamDim}, TilSignal, til_streamlet::{TilInlineImplementation, TilImplementationType}};
+use crate::analysis::types::{TilStreamlet, streaming_interface::TilStreamDirection, TilSignal, til_streamlet::{TilInlineImplementation, TilImplementationType}};
 
 use super::{Analyzer, AnalyzerError, type_manager::StreamType};
 
 impl Analyzer {
     pub fn assemble_top_component(&mut self) -> Result<TilStreamlet, AnalyzerError> {
-        let mut top_component = TilStreamlet::new("top");
+        let mut top_component = TilStreamlet::new(self.gen_params.get_top_name());
         
         let input_stream_name = "input";
-        top_component.get_streams_mut().add_stream(input_stream_name, TilStreamDirection::Input, 
-            StreamTypeDecl::new(
-                StreamType::Json,
-                Some(StreamDim::new(None, 2, 0))
-            )
-        );
+        top_component.get_streams_mut().add_stream(input_stream_name, TilStreamDirection::Input, self.get_input_type());
         self.type_manager.register(StreamType::Json);
 
         let mut implementation = TilInlineImplementation::default(); 
@@ -24,7 +19,9 @@ impl Analyzer {
             // Get as generatable
             .get_generatable();
 
-        implementation.add_signal(TilSignal::Input { source_stream_name: input_stream_name.to_string(), dest_inst_name: gen_com.get_instance_name(), dest_stream_name: "input".to_owned() });
+        // The input goes to the root component, unless something is placed in front of it
+        let dest_inst_name = self.entry_inst_name.clone().unwrap_or_else(|| gen_com.get_instance_name());
+        implementation.add_signal(TilSignal::Input { source_stream_name: input_stream_name.to_string(), dest_inst_name, dest_stream_name: "input".to_owned() });
 
         // Add all intermediate signals to the implementation
         implementation.add_multiple_signals(self.signal_manager.get_intermediate_signals().to_vec());
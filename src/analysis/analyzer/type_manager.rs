@@ -1,17 +1,15 @@
-use std::fmt::{Display, Formatter};
-use enumset::{EnumSetType, EnumSet};
-use indoc::writedoc;
+use std::collections::BTreeSet;
 
-use crate::analysis::{GeneratorParams, components::Generatable};
+use crate::analysis::{GeneratorParams, components::Generatable, syntax::{til, td, DimExpr}};
 
 pub(super) struct TypeManager {
-    type_list: EnumSet<StreamType>,
+    type_list: BTreeSet<StreamType>,
 }
 
 impl TypeManager {
     pub fn new() -> TypeManager {
         TypeManager {
-            type_list: EnumSet::new(),
+            type_list: BTreeSet::new(),
         }
     }
 
@@ -28,7 +26,7 @@ impl TypeManager {
 
     // Get stream type definitions
     pub fn get_stream_types(&self) -> Vec<StreamType> {
-        self.type_list.iter().collect()
+        self.type_list.iter().cloned().collect()
     }
 }
 
@@ -38,25 +36,246 @@ impl Default for TypeManager {
     }
 }
 
-#[derive(EnumSetType)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum StreamType {
     Json,
     Int,
+    // Integers of another width than the integer width of the design
+    SizedInt(IntType),
+    // Streams of characters with another number of lanes than the EPC of the design
+    Laned(LaneType),
+    // Streams with another synchronicity or complexity than the component that produces them
+    Shaped(ShapeType),
     Bool,
     Record,
     MatcherMatch,
     MatcherStr,
+    Group(GroupType),
+    Tag(TagType),
+    Count,
+    // Parsed string formats
+    Date,
+    Timestamp,
+    Uuid,
+    Ipv4,
+    ParseError,
+    // Strings with their escapes decoded
+    Utf8,
+    // Documents that do not match the schema of the samples
+    ValidationError,
+}
+
+/// The fields of a record bundled into a single element
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GroupType {
+    stream_name: String,
+    element_name: String,
+    fields: Vec<(String, StreamType)>,
+}
+
+impl GroupType {
+    pub fn new(name: &str, fields: Vec<(String, StreamType)>) -> GroupType {
+        GroupType {
+            stream_name: format!("{}_stream", name),
+            element_name: format!("{}_t", name),
+            fields,
+        }
+    }
+
+    pub fn get_fields(&self) -> &Vec<(String, StreamType)> {
+        &self.fields
+    }
+}
+
+/// Integers of a width of their own, e.g. set for the path of a field in the configuration
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IntType {
+    stream_name: String,
+    element_name: String,
+    width: usize,
+}
+
+impl IntType {
+    pub fn new(width: usize) -> IntType {
+        IntType {
+            stream_name: format!("IntParserStream{}", width),
+            element_name: format!("integer{}_t", width),
+            width,
+        }
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+}
+
+/// A stream of characters with a number of lanes of its own, e.g. set for the path of a subtree in the configuration
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LaneType {
+    stream_name: String,
+    base: Box<StreamType>,
+    lanes: usize,
+}
+
+impl LaneType {
+    pub fn get_base(&self) -> &StreamType {
+        &self.base
+    }
+
+    pub fn get_lanes(&self) -> usize {
+        self.lanes
+    }
+}
+
+/// A stream of which the synchronicity or complexity is set in the configuration, e.g. for an output
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShapeType {
+    stream_name: String,
+    base: Box<StreamType>,
+    synchronicity: Synchronicity,
+    complexity: u8,
+}
+
+/// A code for every value out of a fixed list, numbered in the order of the list,
+/// e.g. the variant of every value of a union
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TagType {
+    stream_name: String,
+    element_name: String,
+    variants: Vec<String>,
+}
+
+impl TagType {
+    pub fn new(name: &str, variants: Vec<String>) -> TagType {
+        TagType {
+            stream_name: format!("{}_tag_stream", name),
+            element_name: format!("{}_tag_t", name),
+            variants,
+        }
+    }
+
+    /// The codes of the strings of an enum, the last code is reserved for any other string
+    pub fn new_enum(name: &str, values: &[String]) -> TagType {
+        let mut variants = values.to_vec();
+        variants.push("unknown".to_string());
+
+        TagType {
+            stream_name: format!("{}_stream", name),
+            element_name: format!("{}_t", name),
+            variants,
+        }
+    }
+
+    pub fn get_variants(&self) -> &Vec<String> {
+        &self.variants
+    }
+
+    /// Number of bits needed to encode every variant
+    pub fn get_bits(&self) -> usize {
+        let mut bits = 1;
+        while (1 << bits) < self.variants.len() {
+            bits += 1;
+        }
+
+        bits
+    }
 }
 
 impl StreamType {
+    /// The stream with the given number of lanes, streams with one element per transfer do not change
+    pub fn with_lanes(&self, lanes: usize, gen_params: &GeneratorParams) -> StreamType {
+        let base = match self {
+            StreamType::Laned(laned) => laned.get_base(),
+            _ => self,
+        };
+
+        match base {
+            StreamType::Json | StreamType::Record | StreamType::MatcherMatch | StreamType::MatcherStr | StreamType::Utf8 => {
+                if lanes == gen_params.epc {
+                    base.clone()
+                } else {
+                    StreamType::Laned(LaneType {
+                        stream_name: format!("{}{}", base.get_name(), lanes),
+                        base: Box::new(base.clone()),
+                        lanes,
+                    })
+                }
+            },
+            _ => self.clone(),
+        }
+    }
+
+    /// The stream with the given synchronicity and complexity
+    pub fn with_shape(&self, synchronicity: Synchronicity, complexity: u8, gen_params: &GeneratorParams) -> StreamType {
+        let base = match self {
+            StreamType::Shaped(shaped) => &shaped.base,
+            _ => self,
+        };
+
+        if base.get_shape(gen_params) == (synchronicity, complexity) {
+            return base.clone();
+        }
+
+        let sync_name = match synchronicity {
+            Synchronicity::Sync => "",
+            _ => synchronicity.get_name(),
+        };
+
+        StreamType::Shaped(ShapeType {
+            stream_name: format!("{}{}C{}", base.get_name(), sync_name, complexity),
+            base: Box::new(base.clone()),
+            synchronicity,
+            complexity,
+        })
+    }
+
+    /// The stream without a number of lanes, synchronicity or complexity of its own
+    pub fn get_base(&self) -> &StreamType {
+        match self {
+            StreamType::Laned(laned) => laned.get_base(),
+            StreamType::Shaped(shaped) => shaped.base.get_base(),
+            _ => self,
+        }
+    }
+
+    /// The synchronicity and complexity of the stream
+    pub fn get_shape(&self, gen_params: &GeneratorParams) -> (Synchronicity, u8) {
+        let type_params = self.get_type_params(gen_params);
+
+        (type_params.synchronicity, type_params.complexity)
+    }
+
+    /// Whether the stream carries characters, which have a dimension of their own
+    pub fn is_characters(&self) -> bool {
+        matches!(self.get_base(), StreamType::Json | StreamType::Record | StreamType::MatcherStr | StreamType::Utf8)
+    }
+
+    /// Whether the number of dimensions of the stream is set by the type
+    pub fn has_fixed_dimensionality(&self, gen_params: &GeneratorParams) -> bool {
+        matches!(self.get_type_params(gen_params).dimensionality, Dimensionality::Fixed(_))
+    }
+
     pub fn get_name(&self) -> &str {
         match self {
             StreamType::Json => "JSONStream",
             StreamType::Int => "IntParserStream",
+            StreamType::SizedInt(int) => &int.stream_name,
+            StreamType::Laned(laned) => &laned.stream_name,
+            StreamType::Shaped(shaped) => &shaped.stream_name,
             StreamType::Bool => "BoolParserStream",
             StreamType::Record => "RecordParserStream",
             StreamType::MatcherMatch => "MatcherMatchStream",
             StreamType::MatcherStr => "MatcherStrStream",
+            StreamType::Group(group) => &group.stream_name,
+            StreamType::Tag(tag) => &tag.stream_name,
+            StreamType::Count => "CountStream",
+            StreamType::Date => "DateStream",
+            StreamType::Timestamp => "TimestampStream",
+            StreamType::Uuid => "UuidStream",
+            StreamType::Ipv4 => "Ipv4Stream",
+            StreamType::ParseError => "ParseErrorStream",
+            StreamType::Utf8 => "Utf8Stream",
+            StreamType::ValidationError => "ValidationErrorStream",
         }
     }
 
@@ -64,10 +283,31 @@ impl StreamType {
         match self {
             StreamType::Json =>  StreamParams::new(gen_params.bit_width, gen_params.epc, Dimensionality::Generic, Synchronicity::Sync, 8),
             StreamType::Int => StreamParams::new(gen_params.int_width, 1, Dimensionality::Generic, Synchronicity::Sync, 2),
+            StreamType::SizedInt(int) => StreamParams::new(int.width, 1, Dimensionality::Generic, Synchronicity::Sync, 2),
+            StreamType::Laned(laned) => StreamParams { throughput: laned.lanes, ..laned.base.get_type_params(gen_params) },
+            StreamType::Shaped(shaped) => StreamParams { synchronicity: shaped.synchronicity, complexity: shaped.complexity, ..shaped.base.get_type_params(gen_params) },
             StreamType::Bool => StreamParams::new(1, 1, Dimensionality::Generic, Synchronicity::Sync, 2),
             StreamType::Record => StreamParams::new(gen_params.bit_width + 1, gen_params.epc, Dimensionality::Generic, Synchronicity::Sync, 8),
             StreamType::MatcherMatch => StreamParams::new(1, gen_params.epc, Dimensionality::Fixed(1), Synchronicity::Sync, 8),
             StreamType::MatcherStr => StreamParams::new(gen_params.bit_width, gen_params.epc, Dimensionality::Fixed(1), Synchronicity::Sync, 8),
+            StreamType::Group(group) => {
+                let data_bits = group.fields.iter().map(|(_, field)| field.get_type_params(gen_params).data_bits).sum();
+                StreamParams::new(data_bits, 1, Dimensionality::Generic, Synchronicity::Sync, 2)
+            },
+            StreamType::Tag(tag) => StreamParams::new(tag.get_bits(), 1, Dimensionality::Generic, Synchronicity::Sync, 2),
+            StreamType::Count => StreamParams::new(gen_params.int_width, 1, Dimensionality::Generic, Synchronicity::Sync, 2),
+            // A 16 bit year, an 8 bit month and an 8 bit day, from the most to the least significant bits
+            StreamType::Date => StreamParams::new(32, 1, Dimensionality::Generic, Synchronicity::Sync, 2),
+            // Signed seconds since the Unix epoch
+            StreamType::Timestamp => StreamParams::new(64, 1, Dimensionality::Generic, Synchronicity::Sync, 2),
+            StreamType::Uuid => StreamParams::new(128, 1, Dimensionality::Generic, Synchronicity::Sync, 2),
+            StreamType::Ipv4 => StreamParams::new(32, 1, Dimensionality::Generic, Synchronicity::Sync, 2),
+            // An overflow flag and an invalid character flag, from the most to the least significant bit
+            StreamType::ParseError => StreamParams::new(2, 1, Dimensionality::Generic, Synchronicity::Sync, 2),
+            StreamType::Utf8 => StreamParams::new(8, gen_params.epc, Dimensionality::Generic, Synchronicity::Sync, 8),
+            // A 32 bit document index, a 16 bit path id and an 8 bit error kind, from the most to the least
+            // significant bits, every error is a transfer of its own
+            StreamType::ValidationError => StreamParams::new(56, 1, Dimensionality::Fixed(0), Synchronicity::Sync, 2),
         }
     }
 
@@ -75,31 +315,122 @@ impl StreamType {
         match self {
             StreamType::Json => "byte_t",
             StreamType::Int => "integer_t",
-            StreamType::Bool => "boole_t",
+            StreamType::SizedInt(int) => &int.element_name,
+            StreamType::Laned(laned) => laned.base.get_datatype_name(),
+            StreamType::Shaped(shaped) => shaped.base.get_datatype_name(),
+            StreamType::Bool => "bool_t",
             StreamType::Record => "record_t",
             StreamType::MatcherMatch => "bool_t",
             StreamType::MatcherStr => "byte_t",
+            StreamType::Group(group) => &group.element_name,
+            StreamType::Tag(tag) => &tag.element_name,
+            StreamType::Count => "count_t",
+            StreamType::Date => "date_t",
+            StreamType::Timestamp => "timestamp_t",
+            StreamType::Uuid => "uuid_t",
+            StreamType::Ipv4 => "ipv4_t",
+            StreamType::ParseError => "parse_error_t",
+            StreamType::Utf8 => "utf8_t",
+            StreamType::ValidationError => "validation_error_t",
         }
     }
 
-    pub fn get_type_def_string(&self, gen_params: &GeneratorParams) -> String {
+    pub fn to_til(&self, gen_params: &GeneratorParams) -> til::TypeDef {
         let type_params = self.get_type_params(gen_params);
 
-        let dim_str = match type_params.dimensionality {
-            Dimensionality::Fixed(_) => "".to_string(),
-            Dimensionality::Generic => format!("<{}: dimensionality = 2>", Dimensionality::Generic),
+        // Types of a generic dimensionality get a dimensionality parameter with a default of 2
+        let params = match type_params.dimensionality {
+            Dimensionality::Fixed(_) => vec![],
+            Dimensionality::Generic => vec![til::GenericParam { name: "d".to_string(), kind: til::GenericKind::Dimensionality, default: 2 }],
         };
 
-        format!("type {}{} = {};\n\n", self.get_name(), dim_str, type_params)
+        til::TypeDef {
+            name: self.get_name().to_string(),
+            params,
+            stream: til::Stream {
+                data: self.get_til_data_type(gen_params),
+                throughput: type_params.throughput as f64,
+                dimensionality: type_params.dimensionality.to_dim_expr(),
+                synchronicity: format!("{:?}", type_params.synchronicity),
+                complexity: type_params.complexity,
+            },
+        }
     }
 
-    pub fn get_td_type_def_string(&self, gen_params: &GeneratorParams) -> String {
+    fn get_til_data_type(&self, gen_params: &GeneratorParams) -> til::DataType {
+        match self {
+            StreamType::Group(group) => til::DataType::Group(
+                group.fields.iter()
+                    .map(|(name, field)| til::Field { name: name.clone(), data: field.get_til_data_type(gen_params) })
+                    .collect()
+            ),
+            _ => til::DataType::Bits(self.get_type_params(gen_params).data_bits),
+        }
+    }
+
+    pub fn to_td(&self, gen_params: &GeneratorParams) -> td::StreamDef {
         let type_params = self.get_type_params(gen_params);
 
-        format!("\n{} = {};\n", self.get_name(), type_params.td(self.get_datatype_name()))
+        td::StreamDef {
+            name: self.get_name().to_string(),
+            element: self.get_datatype_name().to_string(),
+            throughput: type_params.throughput as f64,
+            dimension: type_params.dimensionality.to_dim_expr(),
+            synchronicity: format!("{:?}", type_params.synchronicity),
+            complexity: type_params.complexity,
+        }
+    }
+
+    /// The shape of the signals the stream is lowered to in VHDL
+    pub fn get_physical_params(&self, gen_params: &GeneratorParams) -> PhysicalStreamParams {
+        let type_params = self.get_type_params(gen_params);
+
+        PhysicalStreamParams {
+            element_bits: type_params.data_bits,
+            lanes: type_params.throughput,
+            fixed_dimensionality: match type_params.dimensionality {
+                Dimensionality::Fixed(d) => Some(d),
+                Dimensionality::Generic => None,
+            },
+            // From complexity 8 onwards every lane carries its own last flags
+            per_lane_last: type_params.complexity >= 8,
+        }
+    }
+
+    /// The TydiLang declarations of the element type of the stream, e.g. `byte_t = Bit(8);`
+    /// Groups are preceded by the declarations of their fields
+    pub fn get_td_element_types(&self, gen_params: &GeneratorParams) -> Vec<td::Item> {
+        match self {
+            StreamType::Group(group) => {
+                let mut items: Vec<td::Item> = group.fields.iter()
+                    .flat_map(|(_, field)| field.get_td_element_types(gen_params))
+                    .collect();
+
+                items.push(td::Item::Group(td::Group {
+                    name: group.element_name.clone(),
+                    fields: group.fields.iter()
+                        .map(|(name, field)| td::GroupField { name: name.clone(), element: field.get_datatype_name().to_string() })
+                        .collect(),
+                }));
+
+                items
+            },
+            _ => vec![td::Item::Alias(td::Alias {
+                name: self.get_datatype_name().to_string(),
+                bits: self.get_type_params(gen_params).data_bits,
+            })],
+        }
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct PhysicalStreamParams {
+    pub element_bits: usize,
+    pub lanes: usize,
+    pub fixed_dimensionality: Option<usize>,
+    pub per_lane_last: bool,
+}
+
 #[derive(Clone, Copy, Hash, PartialEq, Eq)]
 struct StreamParams {
     pub data_bits: usize,
@@ -119,42 +450,6 @@ impl StreamParams {
             complexity,
         }
     }
-
-    pub fn td(&self, data_name: &str) -> String {
-        format!(
-"Stream(
-    {},
-    throughput = {}.0,
-    dimension = {},
-    synchronicity = \"{:?}\",
-    complexity = {}
-)",
-        data_name,
-        self.throughput,
-        self.dimensionality,
-        self.synchronicity,
-        self.complexity,)
-    }
-}
-
-impl Display for StreamParams {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        writedoc!(
-            f,
-            "Stream (
-                data: Bits({}),
-                throughput: {},
-                dimensionality: {},
-                synchronicity: {:?},
-                complexity: {},
-            )",
-            self.data_bits,
-            self.throughput,
-            self.dimensionality,
-            self.synchronicity,
-            self.complexity,
-        )
-    }
 }
 
 #[derive(Clone, Copy, Hash, PartialEq, Eq)]
@@ -163,24 +458,68 @@ enum Dimensionality {
     Generic
 }
 
-impl std::fmt::Display for Dimensionality {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Dimensionality {
+    fn to_dim_expr(self) -> DimExpr {
         match self {
-            Dimensionality::Fixed(d) => write!(f, "{}", d),
-            Dimensionality::Generic => write!(f, "d"),
+            Dimensionality::Fixed(d) => DimExpr::fixed(d),
+            Dimensionality::Generic => DimExpr::generic("d"),
         }
     }
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-enum Synchronicity {
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Synchronicity {
     Sync,
+    // The dimensions of the arrays that hold the values are left out
     Flatten,
     Desync,
     FlatDesync,
 }
 
+impl Synchronicity {
+    pub const ALL: [Synchronicity; 4] = [Synchronicity::Sync, Synchronicity::Flatten, Synchronicity::Desync, Synchronicity::FlatDesync];
+
+    /// The name in TIL, TydiLang and the configuration
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Synchronicity::Sync => "Sync",
+            Synchronicity::Flatten => "Flatten",
+            Synchronicity::Desync => "Desync",
+            Synchronicity::FlatDesync => "FlatDesync",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Synchronicity> {
+        Synchronicity::ALL.into_iter().find(|synchronicity| synchronicity.get_name() == name)
+    }
+
+    pub fn is_flat(self) -> bool {
+        matches!(self, Synchronicity::Flatten | Synchronicity::FlatDesync)
+    }
+}
+
+/// The synchronicity and complexity that are configured for the outputs at a path or of a stream type,
+/// what is not set is left as the component produces it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamShape {
+    pub synchronicity: Option<Synchronicity>,
+    pub complexity: Option<u8>,
+}
+
+impl StreamShape {
+    pub fn new(synchronicity: Option<Synchronicity>, complexity: Option<u8>) -> StreamShape {
+        StreamShape { synchronicity, complexity }
+    }
+
+    /// The settings of this shape, with those of the other shape for what this one does not set
+    pub fn or(self, other: StreamShape) -> StreamShape {
+        StreamShape {
+            synchronicity: self.synchronicity.or(other.synchronicity),
+            complexity: self.complexity.or(other.complexity),
+        }
+    }
+}
+
 
 // #[derive(Clone)]
 // pub enum StreamType {
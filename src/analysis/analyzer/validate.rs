@@ -0,0 +1,333 @@
+use crate::analysis::{components::{JsonComponent, JsonComponentValue, JsonType, Generatable}, validate::ValueKind, types::{TilStreamlet, TilSignal, TilStreamingInterface, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}, til_streamlet::TilImplementationType}};
+
+use super::{Analyzer, AnalyzerError, file_manager::TemplateType, type_manager::StreamType};
+
+/**********************************************************************************
+ * Netlist pass that adds a validation path: checkers tap the streams of the      *
+ * parsers and report the documents that do not match the samples, and their     *
+ * errors are merged into one output of the top component.                       *
+ **********************************************************************************/
+
+// The kind of value a component parses, if it can be told by the first character of the value
+fn value_kind(component: &JsonComponent) -> Option<ValueKind> {
+    match component {
+        JsonComponent::Value(value) => Some(match value.get_data_type() {
+            JsonType::Integer => ValueKind::Integer,
+            JsonType::Boolean => ValueKind::Boolean,
+            JsonType::String | JsonType::Formatted(_) | JsonType::Decoded => ValueKind::String,
+        }),
+        JsonComponent::Enumeration(_) => Some(ValueKind::String),
+        JsonComponent::Record(_) => Some(ValueKind::Object),
+        JsonComponent::Array(_) | JsonComponent::Tuple(_) => Some(ValueKind::Array),
+        _ => None,
+    }
+}
+
+fn contains_raw(component: &JsonComponent) -> bool {
+    matches!(component, JsonComponent::Raw(_)) || component.get_children().iter().any(contains_raw)
+}
+
+// The id of a path, paths get the next id when they are first seen
+fn path_id(paths: &mut Vec<String>, path: &str) -> usize {
+    match paths.iter().position(|known| known == path) {
+        Some(id) => id,
+        None => {
+            paths.push(path.to_owned());
+            paths.len() - 1
+        },
+    }
+}
+
+impl Analyzer {
+    pub fn insert_validation(&mut self, max_depth: usize) -> Result<(), AnalyzerError> {
+        // The errors of all subtrees are merged in one place
+        if self.gen_params.netlist.hierarchical {
+            return Err(AnalyzerError::InvalidValidation("the validation path needs a flat top component, it can not be combined with hierarchical mode".to_string()));
+        }
+
+        let root = self.top_component.clone().ok_or(AnalyzerError::NoTop)?;
+
+        // Raw values are forwarded however deep they are nested
+        let max_depth = (!contains_raw(&root)).then_some(max_depth);
+
+        // Path id 0 is the document itself
+        let mut paths = vec!["$".to_string()];
+        let mut checkers = vec![self.insert_document_checker(&root, max_depth)];
+        self.insert_checkers(&root, false, &mut paths, &mut checkers);
+
+        self.insert_validation_merger(&checkers, paths);
+
+        Ok(())
+    }
+
+    fn insert_checkers(&mut self, component: &JsonComponent, check_kind: bool, paths: &mut Vec<String>, checkers: &mut Vec<String>) {
+        if check_kind {
+            if let Some(kind) = value_kind(component) {
+                if let Some(checker) = self.insert_type_checker(component, kind, paths) {
+                    checkers.push(checker);
+                }
+            }
+        }
+
+        self.insert_key_checker(component, paths, checkers);
+
+        // The router of a union already sends every value to the parser of its kind
+        for child in component.get_children() {
+            self.insert_checkers(&child, !matches!(component, JsonComponent::Union(_)), paths, checkers);
+        }
+    }
+
+    // Find the stream that feeds the input of a component
+    fn find_input_source(&self, inst_name: &str) -> Option<(String, String)> {
+        self.signal_manager.get_intermediate_signals().iter().find_map(|signal| match signal {
+            TilSignal::Intermediate { source_inst_name, source_stream_name, dest_inst_name, dest_stream_name }
+                if dest_inst_name == inst_name && dest_stream_name == "input" => Some((source_inst_name.clone(), source_stream_name.clone())),
+            _ => None,
+        })
+    }
+
+    // The dimensionality of a stream of a component
+    fn find_stream_dim(&self, component_name: &str, stream_name: &str) -> Option<usize> {
+        self.entity_list.iter()
+            .find(|streamlet| streamlet.get_name() == component_name)?
+            .get_streams().get_streams().iter()
+            .find(|stream| stream.get_name() == stream_name)?
+            .get_type().get_stream_dim().as_ref()
+            .map(|dim| dim.get_true_value())
+    }
+
+    // Add a checker streamlet that taps a stream and has a stream of errors
+    fn add_checker(&mut self, name: &str, input_type: StreamType, dim: usize, source: (String, String), template_type: TemplateType) -> String {
+        let inst_name = format!("{}_inst", name);
+
+        // The tapped stream may belong to a subtree with a number of lanes of its own
+        let input_type = self.find_instance_stream(&source.0, &source.1)
+            .map_or(input_type, |stream| stream.get_type().get_stream_type());
+        let lanes = input_type.get_physical_params(&self.gen_params).lanes;
+
+        let mut interface = TilStreamingInterface::default();
+
+        let dim_name = "NESTING_LEVEL";
+        interface.add_generic(Generic::new("EPC", GenericType::Positive(lanes)));
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(dim)));
+
+        interface.add_stream("input", TilStreamDirection::Input,
+            StreamTypeDecl::new(input_type.clone(), Some(StreamDim::new(Some(dim_name.to_string()), dim, 0)))
+        );
+        interface.add_stream("error", TilStreamDirection::Output, StreamTypeDecl::new(StreamType::ValidationError, None));
+
+        // The checker taps the stream, next to wherever it already goes
+        let (source_inst_name, source_stream_name) = source;
+        self.signal_manager.add_signal(TilSignal::Intermediate {
+            source_inst_name,
+            source_stream_name,
+            dest_inst_name: inst_name.clone(),
+            dest_stream_name: "input".to_owned(),
+        });
+
+        let mut streamlet = TilStreamlet::new(name);
+        streamlet.set_streaming_interface(interface);
+        streamlet.set_implementation(TilImplementationType::Path("./vhdl_dir".to_string()));
+
+        self.type_manager.register(input_type);
+        self.type_manager.register(StreamType::ValidationError);
+        self.file_manager.add_entity(template_type, name);
+        if lanes != self.gen_params.epc {
+            self.file_manager.set_lanes(name, lanes);
+        }
+        self.entity_list.push(streamlet);
+
+        inst_name
+    }
+
+    // Check the first character of every value that is sent to the parser of a component
+    fn insert_type_checker(&mut self, component: &JsonComponent, kind: ValueKind, paths: &mut Vec<String>) -> Option<String> {
+        let is_tuple = matches!(component, JsonComponent::Tuple(_));
+        let component = component.get_generatable();
+        let source = self.find_input_source(&component.get_instance_name())?;
+        let dim = self.find_stream_dim(component.get_name(), "input")?;
+        let mut path = self.json_paths.get(component.get_name())?.clone();
+
+        // Tuples are registered under the path of their elements
+        if is_tuple {
+            path.truncate(path.len() - "[]".len());
+        }
+
+        let path_id = path_id(paths, &path);
+        let name = self.name_reg.register("type_checker", dim);
+        self.json_paths.insert(name.clone(), path);
+
+        Some(self.add_checker(&name, StreamType::Json, dim, source, TemplateType::TypeChecker { kind, path_id }))
+    }
+
+    // Check the keys of every record that is parsed by a record parser
+    fn insert_key_checker(&mut self, component: &JsonComponent, paths: &mut Vec<String>, checkers: &mut Vec<String>) {
+        let record = match component {
+            JsonComponent::Record(record) => record,
+            _ => return,
+        };
+
+        let mut keys = Vec::new();
+        for key in record.get_children() {
+            if let (JsonComponent::Key(key), Some(JsonComponent::Matcher(matcher))) = (&key, key.get_children().first()) {
+                let key_path = self.json_paths.get(key.get_name()).cloned().unwrap_or_default();
+
+                // Keys are compared with their text in the document
+                let text = json::stringify(matcher.get_matcher());
+                let text = text[1..text.len() - 1].to_string();
+
+                let required = !self.optional_keys.contains(&key_path);
+                keys.push((text, path_id(paths, &key_path), required));
+            }
+        }
+
+        // Records of which the keys are data have nothing to check
+        if keys.is_empty() {
+            return;
+        }
+
+        let path = self.json_paths.get(record.get_name()).cloned().unwrap_or_default();
+
+        // Fields that are not selected are left out of the parser but still belong to the record
+        let prefix = format!("{}.", path);
+        for skip_path in &self.gen_params.schema.skip_paths {
+            if let Some(key) = skip_path.strip_prefix(&prefix).filter(|key| !key.contains(['.', '['])) {
+                let text = json::stringify(key);
+                keys.push((text[1..text.len() - 1].to_string(), path_id(paths, skip_path), false));
+            }
+        }
+
+        let dim = match self.find_stream_dim(record.get_name(), "output") {
+            Some(dim) => dim,
+            None => return,
+        };
+
+        let path_id = path_id(paths, &path);
+        let name = self.name_reg.register("key_checker", dim);
+        self.json_paths.insert(name.clone(), path);
+
+        let source = (record.get_instance_name(), "output".to_owned());
+        checkers.push(self.add_checker(&name, StreamType::Record, dim, source, TemplateType::KeyChecker { keys, path_id }));
+    }
+
+    // The document checker is placed in front of the root parser, as the input of the top component
+    // can not be tapped
+    fn insert_document_checker(&mut self, root: &JsonComponent, max_depth: Option<usize>) -> String {
+        let name = self.name_reg.register("document_checker", 0);
+        let inst_name = format!("{}_inst", name);
+
+        let mut interface = TilStreamingInterface::default();
+
+        // The framed documents, of which the characters have a dimension of their own
+        let dim = self.gen_params.framing.get_document_dims() + 1;
+        let dim_name = "NESTING_LEVEL";
+        interface.add_generic(Generic::new("EPC", GenericType::Positive(self.gen_params.epc)));
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(dim)));
+
+        interface.add_stream("input", TilStreamDirection::Input,
+            StreamTypeDecl::new(StreamType::Json, Some(StreamDim::new(Some(dim_name.to_string()), dim, 0)))
+        );
+        interface.add_stream("output", TilStreamDirection::Output,
+            StreamTypeDecl::new(StreamType::Json, Some(StreamDim::new(Some(dim_name.to_string()), dim, 0)))
+        );
+        interface.add_stream("error", TilStreamDirection::Output, StreamTypeDecl::new(StreamType::ValidationError, None));
+
+        self.signal_manager.add_signal(TilSignal::Intermediate {
+            source_inst_name: inst_name.clone(),
+            source_stream_name: "output".to_owned(),
+            dest_inst_name: root.get_generatable().get_instance_name(),
+            dest_stream_name: "input".to_owned(),
+        });
+        self.entry_inst_name = Some(inst_name.clone());
+
+        let mut streamlet = TilStreamlet::new(&name);
+        streamlet.set_streaming_interface(interface);
+        streamlet.set_implementation(TilImplementationType::Path("./vhdl_dir".to_string()));
+
+        self.type_manager.register(StreamType::ValidationError);
+        self.json_paths.insert(name.clone(), "$".to_string());
+        self.file_manager.add_entity(TemplateType::DocumentChecker { kind: value_kind(root), max_depth }, &name);
+        self.entity_list.push(streamlet);
+
+        inst_name
+    }
+
+    fn insert_validation_merger(&mut self, checkers: &[String], paths: Vec<String>) {
+        let name = self.name_reg.register("validation_merger", 0);
+        let inst_name = format!("{}_inst", name);
+
+        let mut interface = TilStreamingInterface::default();
+
+        for (idx, checker) in checkers.iter().enumerate() {
+            let input_name = format!("input{}", idx);
+            interface.add_stream(&input_name, TilStreamDirection::Input, StreamTypeDecl::new(StreamType::ValidationError, None));
+
+            self.signal_manager.add_signal(TilSignal::Intermediate {
+                source_inst_name: checker.clone(),
+                source_stream_name: "error".to_owned(),
+                dest_inst_name: inst_name.clone(),
+                dest_stream_name: input_name,
+            });
+        }
+        interface.add_stream("output", TilStreamDirection::Output, StreamTypeDecl::new(StreamType::ValidationError, None));
+
+        let output_name = format!("output_{}", inst_name);
+        self.signal_manager.add_signal(TilSignal::Output {
+            source_inst_name: inst_name,
+            source_stream_name: "output".to_owned(),
+            dest_stream_name: output_name.clone(),
+            output_stream: TilStream::new(&output_name, TilStreamDirection::Output, StreamTypeDecl::new(StreamType::ValidationError, None)),
+        });
+
+        let mut streamlet = TilStreamlet::new(&name);
+        streamlet.set_streaming_interface(interface);
+        streamlet.set_implementation(TilImplementationType::Path("./vhdl_dir".to_string()));
+
+        self.json_paths.insert(name.clone(), "$".to_string());
+        self.file_manager.add_entity(TemplateType::ValidationMerger { inputs: checkers.len(), paths }, &name);
+        self.entity_list.push(streamlet);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{GeneratorError, analyzer::AnalyzerError, checker::check_design, testing::{connections, design_of_samples, generator_of}};
+
+    #[test]
+    fn reports_the_errors_of_all_checkers_on_one_output() {
+        let design = design_of_samples(&[r#"{"a": 1, "b": [true]}"#, r#"{"a": 2}"#], |generator| generator.set_validation(true)).unwrap();
+        let connections = connections(&design.get_top_components()[0]);
+
+        // The document is checked before it is parsed
+        assert!(connections.contains(&("input".to_string(), "document_checker_L0_00_inst.input".to_string())));
+        assert!(connections.contains(&("document_checker_L0_00_inst.output".to_string(), "record_parser_L1_00_inst.input".to_string())));
+
+        // The keys of the record, the values of `a` and `b` and the elements of `b` are checked next to their parsers
+        let checked: Vec<&str> = connections.iter()
+            .filter(|(_, sink)| sink.starts_with("key_checker") || sink.starts_with("type_checker"))
+            .map(|(_, sink)| sink.as_str())
+            .collect();
+        assert_eq!(checked.len(), 4, "{:?}", checked);
+        assert!(connections.iter().any(|(source, sink)| source.ends_with(".output0") && sink.starts_with("int_parser")));
+
+        // Every checker reports to the merger, whose output is the only error output of the design
+        let merged = connections.iter().filter(|(source, sink)| source.ends_with(".error") && sink.starts_with("validation_merger")).count();
+        assert_eq!(merged, 5);
+        let errors: Vec<&str> = connections.iter()
+            .filter(|(source, sink)| !sink.contains('.') && source.contains("checker") || source.starts_with("validation_merger"))
+            .map(|(source, _)| source.as_str())
+            .collect();
+        assert_eq!(errors, vec!["validation_merger_L0_00_inst.output"]);
+        assert!(check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn rejects_validation_of_hierarchical_designs() {
+        let result = generator_of(r#"{"a": 1}"#, |generator| {
+            generator.set_validation(true);
+            generator.set_hierarchical(true);
+        });
+
+        assert!(matches!(result, Err(GeneratorError::AnalyzerError(AnalyzerError::InvalidValidation(_)))));
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use super::{GeneratorParams, GeneratorError, types::TilStreamlet, analyzer::{type_manager::StreamType, file_manager::FileManager}};
+
+mod til;
+mod td;
+mod vhdl;
+mod project;
+
+pub use self::{til::TilBackend, td::TdBackend, vhdl::VhdlBackend, project::ProjectBackend};
+#[cfg(test)]
+pub use self::{til::generate_til, td::generate_td};
+
+/**********************************************************************************
+ * Backends turn the analyzed design into output files. Every output format is   *
+ * a backend that is registered with the generator and selected by name.          *
+ **********************************************************************************/
+
+/// The analyzed design as handed to every backend
+pub struct Design {
+    stream_types: Vec<StreamType>,
+    streamlets: Vec<TilStreamlet>,
+    // A top component per schema of the project
+    top_components: Vec<TilStreamlet>,
+    file_manager: FileManager,
+    json_paths: HashMap<String, String>,
+}
+
+impl Design {
+    pub fn new(stream_types: Vec<StreamType>, streamlets: Vec<TilStreamlet>, top_component: TilStreamlet, file_manager: FileManager, json_paths: HashMap<String, String>) -> Design {
+        Design {
+            stream_types,
+            streamlets,
+            top_components: vec![top_component],
+            file_manager,
+            json_paths,
+        }
+    }
+
+    pub fn get_stream_types(&self) -> &Vec<StreamType> {
+        &self.stream_types
+    }
+
+    pub fn get_streamlets(&self) -> &Vec<TilStreamlet> {
+        &self.streamlets
+    }
+
+    pub fn get_top_components(&self) -> &Vec<TilStreamlet> {
+        &self.top_components
+    }
+
+    pub fn get_file_manager(&self) -> &FileManager {
+        &self.file_manager
+    }
+
+    /// Add the design of another schema of the project, the stream types they share are declared once
+    pub fn merge(&mut self, other: Design) {
+        self.stream_types.extend(other.stream_types);
+        self.stream_types.sort();
+        self.stream_types.dedup();
+
+        self.streamlets.extend(other.streamlets);
+        self.top_components.extend(other.top_components);
+        self.file_manager.extend(other.file_manager);
+        self.json_paths.extend(other.json_paths);
+    }
+
+    pub fn get_streamlet(&self, name: &str) -> Option<&TilStreamlet> {
+        self.streamlets.iter().find(|streamlet| streamlet.get_name() == name)
+    }
+
+    /// The path in the JSON document (e.g. `$.exams[].grade`) a component was generated for
+    pub fn get_json_path(&self, component_name: &str) -> Option<&str> {
+        self.json_paths.get(component_name).map(|path| path.as_str())
+    }
+}
+
+pub trait Backend {
+    /// The name under which the backend is selected on the command line
+    fn get_name(&self) -> &str;
+
+    /// Emit the design into the project directory (`gen_params.output_dir`)
+    fn generate(&self, design: &Design, gen_params: &GeneratorParams) -> Result<(), GeneratorError>;
+
+    /// Whether every output in the emitted netlist may only drive a single sink
+    fn requires_point_to_point(&self) -> bool {
+        false
+    }
+}
+
+pub struct BackendRegistry {
+    backends: Vec<Box<dyn Backend>>,
+}
+
+impl BackendRegistry {
+    pub fn new() -> BackendRegistry {
+        BackendRegistry {
+            backends: Vec::new(),
+        }
+    }
+
+    /// Register a backend, replacing any earlier backend with the same name
+    pub fn register(&mut self, backend: Box<dyn Backend>) {
+        self.backends.retain(|registered| registered.get_name() != backend.get_name());
+        self.backends.push(backend);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Backend> {
+        self.backends.iter()
+            .find(|backend| backend.get_name() == name)
+            .map(|backend| backend.as_ref())
+    }
+
+    pub fn get_names(&self) -> Vec<&str> {
+        self.backends.iter().map(|backend| backend.get_name()).collect()
+    }
+}
+
+impl Default for BackendRegistry {
+    fn default() -> Self {
+        let mut registry = BackendRegistry::new();
+
+        registry.register(Box::new(TilBackend));
+        registry.register(Box::new(TdBackend));
+        registry.register(Box::new(VhdlBackend));
+        registry.register(Box::new(ProjectBackend));
+
+        registry
+    }
+}
+
+// Write a generated source file to the `src` directory of the project
+fn write_source_file(extension: &str, contents: &str, gen_params: &GeneratorParams) -> Result<(), GeneratorError> {
+    use std::io::Write;
+
+    let src_dir = format!("{}/src", gen_params.output_dir);
+    std::fs::create_dir_all(&src_dir).map_err(GeneratorError::IoError)?;
+
+    let mut file = std::fs::File::create(format!("{}/{}.{}", src_dir, gen_params.project_name, extension)).map_err(GeneratorError::IoError)?;
+    file.write_all(contents.as_bytes()).map_err(GeneratorError::IoError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Generator;
+
+    struct MarkerBackend {
+        name: &'static str,
+    }
+
+    impl Backend for MarkerBackend {
+        fn get_name(&self) -> &str {
+            self.name
+        }
+
+        fn generate(&self, _design: &Design, gen_params: &GeneratorParams) -> Result<(), GeneratorError> {
+            write_source_file(self.name, "generated", gen_params)
+        }
+    }
+
+    #[test]
+    fn registers_and_replaces_backends_by_name() {
+        let mut registry = BackendRegistry::default();
+        assert_eq!(registry.get_names(), vec!["til", "td", "vhdl", "toml"]);
+
+        registry.register(Box::new(MarkerBackend { name: "marker" }));
+        registry.register(Box::new(MarkerBackend { name: "til" }));
+
+        // The replaced backend moves to the end of the list
+        assert_eq!(registry.get_names(), vec!["td", "vhdl", "toml", "marker", "til"]);
+        assert!(registry.get("marker").is_some());
+        assert!(registry.get("xml").is_none());
+    }
+
+    #[test]
+    fn runs_only_the_selected_backends() {
+        let output = std::env::temp_dir().join(format!("json_til_backends_{}", std::process::id()));
+        let output = output.to_str().unwrap();
+
+        let mut generator = Generator::new("test", 4, 64);
+        generator.register_backend(Box::new(MarkerBackend { name: "marker" }));
+        generator.select_backends(&["marker".to_string()]).unwrap();
+        generator.analyze_samples(&[r#"{"a": 1}"#]).unwrap();
+        generator.generate(output).unwrap();
+
+        let src_dir = format!("{}/test/src", output);
+        assert_eq!(std::fs::read_to_string(format!("{}/test.marker", src_dir)).unwrap(), "generated");
+        assert!(!std::path::Path::new(&format!("{}/test.til", src_dir)).exists());
+
+        std::fs::remove_dir_all(output).unwrap();
+    }
+
+    #[test]
+    fn rejects_unknown_backends() {
+        let mut generator = Generator::new("test", 4, 64);
+
+        let result = generator.select_backends(&["til".to_string(), "xml".to_string()]);
+        assert!(matches!(result, Err(GeneratorError::UnknownBackend(name)) if name == "xml"));
+    }
+}
@@ -0,0 +1,19 @@
+use crate::analysis::{GeneratorParams, GeneratorError};
+
+use super::{Backend, Design};
+
+/// Writes the `project.toml` that TIL-VHDL uses to find the generated sources
+pub struct ProjectBackend;
+
+impl Backend for ProjectBackend {
+    fn get_name(&self) -> &str {
+        "toml"
+    }
+
+    fn generate(&self, design: &Design, gen_params: &GeneratorParams) -> Result<(), GeneratorError> {
+        let top_names: Vec<&str> = design.get_top_components().iter().map(|top| top.get_name()).collect();
+        design.get_file_manager().generate_toml(&gen_params.output_dir, &top_names, gen_params);
+
+        Ok(())
+    }
+}
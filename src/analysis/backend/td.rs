@@ -0,0 +1,57 @@
+use crate::analysis::{GeneratorParams, GeneratorError, syntax::{self, td}};
+
+use super::{Backend, Design, write_source_file};
+
+/**********************************************************************************
+ * Set of functions to generate TD code from the analyzed definitions            *
+ **********************************************************************************/
+
+// Name of the template streamlet that holds all stream types
+const TYPE_CONTAINER: &str = "t";
+
+pub fn generate_td(design: &Design, gen_params: &GeneratorParams) -> td::Package {
+    let mut items = Vec::new();
+
+    // Declare every element type used by the stream types once
+    for type_def in design.get_stream_types() {
+        for element_type in type_def.get_td_element_types(gen_params) {
+            if !items.contains(&element_type) {
+                items.push(element_type);
+            }
+        }
+    }
+
+    items.push(td::Item::TypeContainer(td::TypeContainer {
+        name: TYPE_CONTAINER.to_string(),
+        param: "d".to_string(),
+        streams: design.get_stream_types().iter().map(|type_def| type_def.to_td(gen_params)).collect(),
+    }));
+
+    for stream_def in design.get_streamlets() {
+        items.extend(stream_def.to_td(TYPE_CONTAINER));
+    }
+
+    for top_component in design.get_top_components() {
+        items.extend(top_component.to_td(TYPE_CONTAINER));
+    }
+
+    td::Package {
+        name: gen_params.namespace.replace("::", "_"),
+        items,
+    }
+}
+
+pub struct TdBackend;
+
+impl Backend for TdBackend {
+    fn get_name(&self) -> &str {
+        "td"
+    }
+
+    fn generate(&self, design: &Design, gen_params: &GeneratorParams) -> Result<(), GeneratorError> {
+        let package = generate_td(design, gen_params);
+        let td = syntax::print_validated(&package, td::parse).map_err(GeneratorError::SyntaxError)?;
+
+        write_source_file("td", &td, gen_params)
+    }
+}
@@ -0,0 +1,48 @@
+use crate::analysis::{GeneratorParams, GeneratorError, syntax::{self, til}};
+
+use super::{Backend, Design, write_source_file};
+
+/**********************************************************************************
+ * Set of functions to generate TIL code from the analyzed definitions            *
+ **********************************************************************************/
+
+pub fn generate_til(design: &Design, gen_params: &GeneratorParams) -> til::Namespace {
+    let mut items = Vec::new();
+
+    for type_def in design.get_stream_types() {
+        items.push(til::Item::Type(type_def.to_til(gen_params)));
+    }
+
+    for stream_def in design.get_streamlets() {
+        items.push(til::Item::Streamlet(stream_def.to_til()));
+    }
+
+    for top_component in design.get_top_components() {
+        items.push(til::Item::Streamlet(top_component.to_til()));
+    }
+
+    til::Namespace {
+        path: gen_params.namespace.split("::").map(|segment| segment.to_string()).collect(),
+        items,
+    }
+}
+
+pub struct TilBackend;
+
+impl Backend for TilBackend {
+    fn get_name(&self) -> &str {
+        "til"
+    }
+
+    fn generate(&self, design: &Design, gen_params: &GeneratorParams) -> Result<(), GeneratorError> {
+        let namespace = generate_til(design, gen_params);
+        let til = syntax::print_validated(&namespace, til::parse).map_err(GeneratorError::SyntaxError)?;
+
+        write_source_file("til", &til, gen_params)
+    }
+
+    // TIL connects streams point to point, it does not insert duplicators by itself
+    fn requires_point_to_point(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,18 @@
+use crate::analysis::{GeneratorParams, GeneratorError};
+
+use super::{Backend, Design};
+
+/// Fills in the VHDL templates of all components in the design
+pub struct VhdlBackend;
+
+impl Backend for VhdlBackend {
+    fn get_name(&self) -> &str {
+        "vhdl"
+    }
+
+    fn generate(&self, design: &Design, gen_params: &GeneratorParams) -> Result<(), GeneratorError> {
+        design.get_file_manager().generate_files(&gen_params.output_dir, gen_params);
+
+        Ok(())
+    }
+}
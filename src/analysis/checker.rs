@@ -0,0 +1,333 @@
+use std::{collections::HashMap, fmt::{Display, Formatter}};
+
+use super::{backend::Design, types::{TilSignal, TilStreamlet, streaming_interface::{TilStream, TilStreamDirection}, stream_types::StreamDim, til_streamlet::{TilImplementationType, TilInstance}}};
+
+/**********************************************************************************
+ * Design rule checker which verifies the netlist of the top component before    *
+ * it is emitted: every connection has to type-check, every input has to be       *
+ * driven and no output may drive more than one sink.                             *
+ **********************************************************************************/
+
+#[derive(Debug, Clone)]
+pub enum DesignRuleViolation {
+    UnknownStreamlet { instance: String, streamlet: String },
+    UnknownPort { port: String, json_path: Option<String> },
+    Direction { connection: String, json_path: Option<String> },
+    Type { connection: String, source_type: String, sink_type: String, json_path: Option<String> },
+    Dimensionality { connection: String, source_dim: Option<usize>, sink_dim: Option<usize>, json_path: Option<String> },
+    FanOut { source: String, sinks: Vec<String>, json_path: Option<String> },
+    Undriven { port: String, json_path: Option<String> },
+}
+
+impl DesignRuleViolation {
+    pub fn is_fan_out(&self) -> bool {
+        matches!(self, DesignRuleViolation::FanOut { .. })
+    }
+
+    pub fn get_json_path(&self) -> Option<&str> {
+        match self {
+            DesignRuleViolation::UnknownStreamlet { .. } => None,
+            DesignRuleViolation::UnknownPort { json_path, .. } |
+            DesignRuleViolation::Direction { json_path, .. } |
+            DesignRuleViolation::Type { json_path, .. } |
+            DesignRuleViolation::Dimensionality { json_path, .. } |
+            DesignRuleViolation::FanOut { json_path, .. } |
+            DesignRuleViolation::Undriven { json_path, .. } => json_path.as_deref(),
+        }
+    }
+}
+
+fn format_dim(dim: &Option<usize>) -> String {
+    match dim {
+        Some(dim) => dim.to_string(),
+        None => "none".to_string(),
+    }
+}
+
+impl Display for DesignRuleViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DesignRuleViolation::UnknownStreamlet { instance, streamlet } => write!(f, "{} instantiates unknown streamlet {}", instance, streamlet)?,
+            DesignRuleViolation::UnknownPort { port, .. } => write!(f, "port {} does not exist", port)?,
+            DesignRuleViolation::Direction { connection, .. } => write!(f, "{} does not connect an output to an input", connection)?,
+            DesignRuleViolation::Type { connection, source_type, sink_type, .. } =>
+                write!(f, "{} connects a {} to a {}", connection, source_type, sink_type)?,
+            DesignRuleViolation::Dimensionality { connection, source_dim, sink_dim, .. } =>
+                write!(f, "{} connects dimensionality {} to dimensionality {}", connection, format_dim(source_dim), format_dim(sink_dim))?,
+            DesignRuleViolation::FanOut { source, sinks, .. } => write!(f, "{} drives {} sinks ({})", source, sinks.len(), sinks.join(", "))?,
+            DesignRuleViolation::Undriven { port, .. } => write!(f, "{} is not driven", port)?,
+        }
+
+        if let Some(json_path) = self.get_json_path() {
+            write!(f, " [{}]", json_path)?;
+        }
+
+        Ok(())
+    }
+}
+
+// A resolved end point of a connection
+struct PortInfo<'a> {
+    stream: &'a TilStream,
+    // Whether the port belongs to the enclosing streamlet instead of an instance
+    is_boundary: bool,
+    json_path: Option<String>,
+    // The value the instance gives the generic the dimensionality of the port depends on
+    generic: Option<&'a StreamDim>,
+}
+
+impl PortInfo<'_> {
+    // Ports that produce data inside the implementation: instance outputs and boundary inputs
+    fn is_source(&self) -> bool {
+        (self.stream.get_direction() == TilStreamDirection::Output) != self.is_boundary
+    }
+
+    fn get_true_dim(&self) -> Option<usize> {
+        self.stream.get_type().get_stream_dim().as_ref().map(|dim| match self.generic {
+            Some(generic) => (generic.get_true_value() as isize + dim.get_additive()) as usize,
+            None => dim.get_true_value(),
+        })
+    }
+}
+
+fn port_name(instance: Option<&String>, port: &str) -> String {
+    match instance {
+        Some(instance) => format!("{}.{}", instance, port),
+        None => port.to_string(),
+    }
+}
+
+fn describe_signal(signal: &TilSignal) -> String {
+    format!("{} -- {}",
+        port_name(signal.get_source_inst_name(), signal.get_source_stream_name()),
+        port_name(signal.get_dest_inst_name(), signal.get_dest_stream_name()))
+}
+
+struct Netlist<'a> {
+    design: &'a Design,
+    streamlet: &'a TilStreamlet,
+    // Instance name to the instance and its streamlet
+    instances: HashMap<&'a str, (&'a TilInstance, &'a TilStreamlet)>,
+}
+
+impl<'a> Netlist<'a> {
+    fn resolve(&self, instance: Option<&String>, port: &str) -> Result<PortInfo<'a>, DesignRuleViolation> {
+        let (streamlet, til_instance, json_path) = match instance {
+            Some(instance) => match self.instances.get(instance.as_str()) {
+                Some((til_instance, streamlet)) => (*streamlet, Some(*til_instance), self.design.get_json_path(streamlet.get_name()).map(|path| path.to_string())),
+                None => return Err(DesignRuleViolation::UnknownPort { port: port_name(Some(instance), port), json_path: None }),
+            },
+            None => (self.streamlet, None, None),
+        };
+
+        streamlet.get_streams().get_streams().iter()
+            .find(|stream| stream.get_name() == port)
+            .map(|stream| {
+                let generic = til_instance.zip(stream.get_type().get_stream_dim().as_ref())
+                    .and_then(|(til_instance, dim)| til_instance.get_generic(dim.get_name().as_deref()?));
+
+                PortInfo { stream, is_boundary: instance.is_none(), json_path: json_path.clone(), generic }
+            })
+            .ok_or_else(|| DesignRuleViolation::UnknownPort { port: port_name(instance, port), json_path })
+    }
+}
+
+/// Check the netlist of an inline streamlet against the interfaces of the instantiated streamlets
+pub fn check_streamlet(design: &Design, streamlet: &TilStreamlet) -> Vec<DesignRuleViolation> {
+    let mut violations = Vec::new();
+
+    let inline = match streamlet.get_implementation() {
+        Some(TilImplementationType::Inline(inline)) => inline,
+        _ => return violations,
+    };
+
+    let mut instances = HashMap::new();
+    for instance in inline.get_instances() {
+        match design.get_streamlet(instance.get_component_name()) {
+            Some(component) => { instances.insert(instance.get_instance_name(), (instance, component)); },
+            None => violations.push(DesignRuleViolation::UnknownStreamlet {
+                instance: instance.get_instance_name().to_string(),
+                streamlet: instance.get_component_name().to_string(),
+            }),
+        }
+    }
+
+    let netlist = Netlist { design, streamlet, instances };
+
+    // Sinks per source port and the set of driven ports
+    let mut sinks: Vec<(String, Vec<String>, Option<String>)> = Vec::new();
+    let mut driven: Vec<String> = Vec::new();
+
+    for signal in inline.get_signals() {
+        let source_name = port_name(signal.get_source_inst_name(), signal.get_source_stream_name());
+        let sink_name = port_name(signal.get_dest_inst_name(), signal.get_dest_stream_name());
+        let connection = describe_signal(signal);
+
+        let source = netlist.resolve(signal.get_source_inst_name(), signal.get_source_stream_name());
+        let sink = netlist.resolve(signal.get_dest_inst_name(), signal.get_dest_stream_name());
+
+        let (source, sink) = match (source, sink) {
+            (Ok(source), Ok(sink)) => (source, sink),
+            (source, sink) => {
+                violations.extend(source.err());
+                violations.extend(sink.err());
+                continue;
+            },
+        };
+
+        // Problems are reported at the sink, which is where the data ends up wrongly
+        let json_path = sink.json_path.clone().or_else(|| source.json_path.clone());
+
+        if !source.is_source() || sink.is_source() {
+            violations.push(DesignRuleViolation::Direction { connection: connection.clone(), json_path: json_path.clone() });
+        }
+
+        if source.stream.get_type().get_name() != sink.stream.get_type().get_name() {
+            violations.push(DesignRuleViolation::Type {
+                connection: connection.clone(),
+                source_type: source.stream.get_type().get_name().to_string(),
+                sink_type: sink.stream.get_type().get_name().to_string(),
+                json_path: json_path.clone(),
+            });
+        } else if source.get_true_dim() != sink.get_true_dim() {
+            violations.push(DesignRuleViolation::Dimensionality {
+                connection,
+                source_dim: source.get_true_dim(),
+                sink_dim: sink.get_true_dim(),
+                json_path,
+            });
+        }
+
+        match sinks.iter_mut().find(|(name, _, _)| *name == source_name) {
+            Some((_, source_sinks, _)) => source_sinks.push(sink_name.clone()),
+            None => sinks.push((source_name, vec![sink_name.clone()], source.json_path)),
+        }
+
+        driven.push(sink_name);
+    }
+
+    for (source, source_sinks, json_path) in sinks {
+        if source_sinks.len() > 1 {
+            violations.push(DesignRuleViolation::FanOut { source, sinks: source_sinks, json_path });
+        }
+    }
+
+    // All instance inputs and all outputs of the streamlet itself need a driver
+    for instance in inline.get_instances() {
+        if let Some(component) = design.get_streamlet(instance.get_component_name()) {
+            for stream in component.get_streams().get_input_streams() {
+                let name = port_name(Some(&instance.get_instance_name().to_string()), stream.get_name());
+
+                if !driven.contains(&name) {
+                    violations.push(DesignRuleViolation::Undriven { port: name, json_path: design.get_json_path(component.get_name()).map(|path| path.to_string()) });
+                }
+            }
+        }
+    }
+
+    for stream in streamlet.get_streams().get_output_streams() {
+        if !driven.iter().any(|name| name == stream.get_name()) {
+            violations.push(DesignRuleViolation::Undriven { port: stream.get_name().to_string(), json_path: None });
+        }
+    }
+
+    violations
+}
+
+/// Check the top components and every other streamlet with an inline implementation
+pub fn check_design(design: &Design) -> Vec<DesignRuleViolation> {
+    let mut violations = Vec::new();
+
+    for streamlet in design.get_top_components().iter().chain(design.get_streamlets()) {
+        violations.extend(check_streamlet(design, streamlet));
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{testing::design_of, types::{TilSignal, til_streamlet::TilInlineImplementation}};
+
+    // A streamlet with the interface of the top component that instantiates `components` and connects them with `signals`
+    fn netlist(design: &Design, components: &[&str], signals: Vec<TilSignal>) -> TilStreamlet {
+        let mut implementation = TilInlineImplementation::default();
+        for component in components {
+            implementation.add_instance(component.to_string());
+        }
+        implementation.add_multiple_signals(signals);
+
+        let mut streamlet = TilStreamlet::new("hand_built");
+        streamlet.set_streaming_interface(design.get_top_components()[0].get_streams().clone());
+        streamlet.set_implementation(TilImplementationType::Inline(implementation));
+
+        streamlet
+    }
+
+    #[test]
+    fn accepts_generated_designs() {
+        let design = design_of(r#"{"grades": [1, 2]}"#, |_| {}).unwrap();
+
+        assert!(check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn reports_fan_out() {
+        // The record parser drives the key parser of every key
+        let design = design_of(r#"{"a": 1, "b": "x"}"#, |generator| generator.set_insert_duplicators(false)).unwrap();
+
+        let violations = check_design(&design);
+        assert_eq!(violations.len(), 1);
+        assert!(matches!(&violations[0], DesignRuleViolation::FanOut { source, sinks, .. }
+            if source == "record_parser_L1_00_inst.output" && sinks.len() == 2));
+        assert_eq!(violations[0].get_json_path(), Some("$"));
+    }
+
+    #[test]
+    fn reports_connections_of_different_types() {
+        let design = design_of(r#"{"a": 1, "b": "x"}"#, |_| {}).unwrap();
+
+        let streamlet = netlist(&design, &["int_parser_L2_00", "string_parser_L1_00"], vec![TilSignal::Intermediate {
+            source_inst_name: "int_parser_L2_00_inst".to_string(),
+            source_stream_name: "output".to_string(),
+            dest_inst_name: "string_parser_L1_00_inst".to_string(),
+            dest_stream_name: "input".to_string(),
+        }]);
+
+        let violations = check_streamlet(&design, &streamlet);
+        assert!(violations.iter().any(|violation| matches!(violation, DesignRuleViolation::Type { source_type, sink_type, json_path, .. }
+            if source_type == "IntParserStream" && sink_type == "JSONStream" && json_path.as_deref() == Some("$.b"))));
+        assert!(!violations.iter().any(|violation| matches!(violation, DesignRuleViolation::Dimensionality { .. })));
+    }
+
+    #[test]
+    fn reports_connections_of_different_dimensionalities() {
+        let design = design_of(r#"{"a": 1, "b": "x"}"#, |_| {}).unwrap();
+
+        // The document is one level less nested than the values of its keys
+        let streamlet = netlist(&design, &["int_parser_L2_00"], vec![TilSignal::Input {
+            source_stream_name: "input".to_string(),
+            dest_inst_name: "int_parser_L2_00_inst".to_string(),
+            dest_stream_name: "input".to_string(),
+        }]);
+
+        let violations = check_streamlet(&design, &streamlet);
+        assert!(violations.iter().any(|violation| matches!(violation, DesignRuleViolation::Dimensionality { source_dim, sink_dim, json_path, .. }
+            if *source_dim == Some(2) && *sink_dim == Some(3) && json_path.as_deref() == Some("$.a"))));
+        assert!(!violations.iter().any(|violation| matches!(violation, DesignRuleViolation::Type { .. })));
+    }
+
+    #[test]
+    fn reports_unknown_streamlets_and_undriven_outputs() {
+        let design = design_of(r#"{"grades": [1]}"#, |_| {}).unwrap();
+
+        let streamlet = netlist(&design, &["missing"], Vec::new());
+
+        let violations = check_streamlet(&design, &streamlet);
+        let outputs = streamlet.get_streams().get_output_streams().len();
+        assert_eq!(violations.len(), 1 + outputs);
+        assert!(matches!(&violations[0], DesignRuleViolation::UnknownStreamlet { streamlet, .. } if streamlet == "missing"));
+        assert!(violations[1..].iter().all(|violation| matches!(violation, DesignRuleViolation::Undriven { .. })));
+    }
+}
@@ -1,16 +1,40 @@
-use crate::analysis::{types::{TilStreamingInterface, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}, TilSignal}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::TemplateType}};
+use crate::analysis::{types::{TilStreamingInterface, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}, TilSignal}, GeneratorParams, params::ComponentParam, analyzer::{type_manager::StreamType, file_manager::TemplateType}};
 
 use super::{Array, JsonComponent, Generatable, JsonComponentValue};
 
 impl Array {
-    pub fn new(name: &str, outer_nested: usize, inner_nested: usize, value: Option<Box<JsonComponent>>) -> Array {
+    pub fn new(name: &str, outer_nested: usize, inner_nested: usize, value: Option<Box<JsonComponent>>, length: bool, index: bool) -> Array {
         Array {
             name: name.to_string(),
             outer_nested,
             inner_nested,
             value,
+            length,
+            index,
+            counter_width: ComponentParam::ElementCounterWidth.get_default(),
         }
     }
+
+    pub fn set_counter_width(&mut self, counter_width: usize) {
+        self.counter_width = counter_width;
+    }
+
+    // The side-band streams that are enabled, with their dimensionality relative to the outer nesting
+    fn get_side_band_streams(&self) -> Vec<(&str, isize)> {
+        let mut streams = Vec::new();
+
+        // One length per array
+        if self.length {
+            streams.push(("length", 0));
+        }
+
+        // One index per element
+        if self.index {
+            streams.push(("index", 1));
+        }
+
+        streams
+    }
 }
 
 impl Generatable for Array {
@@ -21,7 +45,11 @@ impl Generatable for Array {
         let dim_name = "OUTER_NESTING_LEVEL";
         interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.outer_nested)));
         interface.add_generic(Generic::new("INNER_NESTING_LEVEL", GenericType::Natural(self.inner_nested)));
-        
+        interface.add_generic(ComponentParam::ElementCounterWidth.to_generic(self.counter_width));
+
+        if self.length || self.index {
+            interface.add_generic(Generic::new("BITWIDTH", GenericType::Positive(gen_params.int_width)));
+        }
 
         // Input type
         interface.add_stream("input", TilStreamDirection::Input,
@@ -39,11 +67,24 @@ impl Generatable for Array {
             )
         );
 
+        for (name, additive) in self.get_side_band_streams() {
+            interface.add_stream(name, TilStreamDirection::Output,
+                StreamTypeDecl::new(
+                    StreamType::Count,
+                    Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, additive))
+                )
+            );
+        }
+
         interface
     }
 
     fn get_streaming_types(&self) -> Vec<StreamType> {
-        vec![StreamType::Json]
+        if self.length || self.index {
+            vec![StreamType::Json, StreamType::Count]
+        } else {
+            vec![StreamType::Json]
+        }
     }
 
     fn get_nesting_level(&self) -> usize {
@@ -51,8 +92,22 @@ impl Generatable for Array {
     }
 
     fn get_outgoing_signals(&self) -> Vec<TilSignal> {
+        // Side-band streams always leave the design
+        let side_band = self.get_side_band_streams().into_iter().map(|(name, additive)| {
+            let output_name = format!("output_{}_{}", self.get_instance_name(), name);
+
+            TilSignal::Output {
+                source_inst_name: self.get_instance_name(),
+                source_stream_name: name.to_owned(),
+                dest_stream_name: output_name.clone(),
+                output_stream: TilStream::new(&output_name, TilStreamDirection::Output,
+                    StreamTypeDecl::new(StreamType::Count, Some(StreamDim::new(None, self.outer_nested, additive)))
+                )
+            }
+        });
+
         // First check if there is a child
-        match &self.value {
+        let signals = match &self.value {
             Some(child) => {
                 // Convert child to generatable
                 let child = Box::<dyn Generatable>::from(child.clone());
@@ -83,11 +138,13 @@ impl Generatable for Array {
                     }
                 ]
             },
-        }       
+        };
+
+        signals.into_iter().chain(side_band).collect()
     }
 
     fn num_outgoing_signals(&self) -> usize {
-        1
+        1 + self.get_side_band_streams().len()
     }
 
     fn get_name(&self) -> &str {
@@ -95,7 +152,11 @@ impl Generatable for Array {
     }
 
     fn get_file_type(&self) -> TemplateType {
-        TemplateType::Array
+        if self.length || self.index {
+            TemplateType::ArraySideBand { length: self.length, index: self.index, counter_width: self.counter_width }
+        } else {
+            TemplateType::Array { counter_width: self.counter_width }
+        }
     }
 }
 
@@ -117,4 +178,49 @@ impl JsonComponentValue for Array {
             None => 0,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::testing::{connections, design_of};
+
+    // The ports of the array parsers that leave the design
+    fn side_band_outputs(json: &str, length: bool, index: bool) -> Vec<String> {
+        let design = design_of(json, |generator| generator.set_array_side_band(length, index)).unwrap();
+
+        connections(&design.get_top_components()[0]).into_iter()
+            .filter(|(source, sink)| source.starts_with("array_parser") && !sink.contains('.'))
+            .map(|(source, _)| source)
+            .collect()
+    }
+
+    #[test]
+    fn adds_a_length_per_array_and_an_index_per_element() {
+        let array = Array::new("array_parser_L1_00", 1, 0, None, true, true);
+        let interface = array.get_streaming_interface(&GeneratorParams::new(4, 8, 64, "", "test"));
+
+        // The length has the dimensions of the array, the index those of its elements
+        let dims: Vec<_> = ["output", "length", "index"].iter()
+            .map(|name| {
+                let stream = interface.get_streams().iter().find(|stream| stream.get_name() == *name).unwrap();
+                stream.get_type().get_stream_dim().as_ref().unwrap().get_true_value()
+            })
+            .collect();
+        assert_eq!(dims, vec![3, 1, 2]);
+
+        // Every nested array counts its own elements
+        let outputs = side_band_outputs(r#"{"a": [[1, 2], [3]]}"#, true, false);
+        assert_eq!(outputs, vec!["array_parser_L3_00_inst.length", "array_parser_L2_00_inst.length"]);
+
+        let outputs = side_band_outputs(r#"{"a": [1, 2]}"#, false, true);
+        assert_eq!(outputs, vec!["array_parser_L2_00_inst.index"]);
+    }
+
+    #[test]
+    fn leaves_arrays_without_elements_without_side_band() {
+        // An array that is empty in every sample has no parser to count its elements
+        assert!(side_band_outputs(r#"{"a": [], "b": 1}"#, true, true).is_empty());
+        assert!(side_band_outputs(r#"{"a": [1]}"#, false, false).is_empty());
+    }
+}
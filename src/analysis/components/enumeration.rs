@@ -0,0 +1,103 @@
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::{StreamType, TagType}, file_manager::TemplateType}};
+
+use super::{Enumeration, JsonComponent, Generatable, JsonComponentValue};
+
+impl Enumeration {
+    pub fn new(name: &str, outer_nested: usize, values: Vec<String>) -> Enumeration {
+        Enumeration {
+            name: name.to_string(),
+            outer_nested,
+            values,
+        }
+    }
+
+    // Every string is replaced by its position in the values, or the unknown code
+    pub fn get_code_type(&self) -> TagType {
+        TagType::new_enum(&self.name, &self.values)
+    }
+
+    // The code of a string, if it is one of the values
+    pub fn get_code(&self, value: &str) -> Option<usize> {
+        self.values.iter().position(|known| known == value)
+    }
+}
+
+impl Generatable for Enumeration {
+    fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {
+        let mut interface = TilStreamingInterface::default();
+
+        interface.add_generic(Generic::new("EPC", GenericType::Positive(gen_params.epc)));
+        let dim_name = "NESTING_LEVEL";
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.outer_nested)));
+
+        // Input type
+        interface.add_stream("input", TilStreamDirection::Input,
+            StreamTypeDecl::new(
+                StreamType::Json,
+                Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
+            )
+        );
+
+        // Output type, one code per string
+        interface.add_stream("output", TilStreamDirection::Output,
+            StreamTypeDecl::new(
+                StreamType::Tag(self.get_code_type()),
+                Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 0))
+            )
+        );
+
+        interface
+    }
+
+    fn get_streaming_types(&self) -> Vec<StreamType> {
+        vec![StreamType::Json, StreamType::Tag(self.get_code_type())]
+    }
+
+    fn get_nesting_level(&self) -> usize {
+        self.outer_nested
+    }
+
+    fn get_outgoing_signals(&self) -> Vec<TilSignal> {
+        let output_name = format!("output_{}", self.get_instance_name());
+
+        vec![
+            TilSignal::Output {
+                source_inst_name: self.get_instance_name(),
+                source_stream_name: "output".to_owned(),
+                dest_stream_name: output_name.clone(),
+                output_stream: TilStream::new(&output_name, TilStreamDirection::Output,
+                    StreamTypeDecl::new(
+                        StreamType::Tag(self.get_code_type()),
+                        Some(StreamDim::new(None, self.outer_nested, 0))
+                    )
+                )
+            }
+        ]
+    }
+
+    fn num_outgoing_signals(&self) -> usize {
+        1
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_file_type(&self) -> TemplateType {
+        TemplateType::EnumMatcher(self.get_code_type())
+    }
+}
+
+impl JsonComponentValue for Enumeration {
+    fn to_graph_node(&self) -> String {
+        format!("Enum matcher\nValues: {}\nO: {}", self.values.len(), self.outer_nested)
+    }
+
+    fn get_children(&self) -> Vec<JsonComponent> {
+        vec![]
+    }
+
+    fn num_children(&self) -> usize {
+        0
+    }
+}
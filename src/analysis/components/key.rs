@@ -1,4 +1,4 @@
-use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::TemplateType}};
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, params::ComponentParam, analyzer::{type_manager::StreamType, file_manager::TemplateType}};
 
 use super::{Key, Generatable, JsonComponent, Matcher, JsonComponentValue};
 
@@ -8,9 +8,14 @@ impl Key {
             name: name.to_string(),
             matcher,
             outer_nested,
-            value
+            value,
+            buffer_depth: ComponentParam::KeyBufferDepth.get_default(),
         }
     }
+
+    pub fn set_buffer_depth(&mut self, buffer_depth: usize) {
+        self.buffer_depth = buffer_depth;
+    }
 }
 
 impl Generatable for Key {
@@ -20,6 +25,7 @@ impl Generatable for Key {
         interface.add_generic(Generic::new("EPC", GenericType::Positive(gen_params.epc)));
         let dim_name = "OUTER_NESTING_LEVEL";
         interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.outer_nested)));
+        interface.add_generic(ComponentParam::KeyBufferDepth.to_generic(self.buffer_depth));
 
         // Input type
         interface.add_stream("input", TilStreamDirection::Input, 
@@ -117,7 +123,7 @@ impl Generatable for Key {
     }
 
     fn get_file_type(&self) -> TemplateType {
-        TemplateType::Key
+        TemplateType::Key { buffer_depth: self.buffer_depth }
     }
 }
 
@@ -0,0 +1,141 @@
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::TemplateType}};
+
+use super::{Map, JsonComponent, Generatable, JsonComponentValue};
+
+impl Map {
+    pub fn new(name: &str, outer_nested: usize, value: Option<Box<JsonComponent>>) -> Map {
+        Map {
+            name: name.to_string(),
+            outer_nested,
+            value,
+        }
+    }
+}
+
+impl Generatable for Map {
+    fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {
+        let mut interface = TilStreamingInterface::default();
+
+        interface.add_generic(Generic::new("EPC", GenericType::Positive(gen_params.epc)));
+        let dim_name = "OUTER_NESTING_LEVEL";
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.outer_nested)));
+
+        // Input type
+        interface.add_stream("input", TilStreamDirection::Input,
+            StreamTypeDecl::new(
+                StreamType::Record,
+                Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
+            )
+        );
+
+        // Output types
+        interface.add_stream("key", TilStreamDirection::Output,
+            StreamTypeDecl::new(
+                StreamType::Json,
+                Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
+            )
+        );
+        interface.add_stream("value", TilStreamDirection::Output,
+            StreamTypeDecl::new(
+                StreamType::Json,
+                Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
+            )
+        );
+
+        interface
+    }
+
+    fn get_streaming_types(&self) -> Vec<StreamType> {
+        vec![StreamType::Record, StreamType::Json]
+    }
+
+    fn get_nesting_level(&self) -> usize {
+        self.outer_nested
+    }
+
+    fn get_outgoing_signals(&self) -> Vec<TilSignal> {
+        let key_name = format!("output_{}_key", self.get_instance_name());
+
+        let mut signals = vec![
+            TilSignal::Output {
+                source_inst_name: self.get_instance_name(),
+                source_stream_name: "key".to_owned(),
+                dest_stream_name: key_name.clone(),
+                output_stream: TilStream::new(&key_name, TilStreamDirection::Output,
+                    StreamTypeDecl::new(
+                        StreamType::Json,
+                        Some(StreamDim::new(None, self.outer_nested, 1))
+                    )
+                )
+            }
+        ];
+
+        // All values share a single parser
+        match &self.value {
+            Some(child) => {
+                // Convert child to generatable
+                let child = Box::<dyn Generatable>::from(child.clone());
+
+                signals.push(
+                    TilSignal::Intermediate {
+                        source_inst_name: self.get_instance_name(),
+                        source_stream_name: "value".to_owned(),
+                        dest_inst_name: child.get_instance_name(),
+                        dest_stream_name: "input".to_owned()
+                    }
+                );
+            },
+            None => {
+                let value_name = format!("output_{}_value", self.get_instance_name());
+
+                signals.push(
+                    TilSignal::Output {
+                        source_inst_name: self.get_instance_name(),
+                        source_stream_name: "value".to_owned(),
+                        dest_stream_name: value_name.clone(),
+                        output_stream: TilStream::new(&value_name, TilStreamDirection::Output,
+                            StreamTypeDecl::new(
+                                StreamType::Json,
+                                Some(StreamDim::new(None, self.outer_nested, 1))
+                            )
+                        )
+                    }
+                );
+            }
+        };
+
+        signals
+    }
+
+    fn num_outgoing_signals(&self) -> usize {
+        2
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_file_type(&self) -> TemplateType {
+        TemplateType::Map
+    }
+}
+
+impl JsonComponentValue for Map {
+    fn to_graph_node(&self) -> String {
+        format!("Map parser\nO: {}", self.outer_nested)
+    }
+
+    fn get_children(&self) -> Vec<JsonComponent> {
+        match &self.value {
+            Some(child) => vec![*child.clone()],
+            None => Vec::new(),
+        }
+    }
+
+    fn num_children(&self) -> usize {
+        match &self.value {
+            Some(_) => 1,
+            None => 0,
+        }
+    }
+}
@@ -1,8 +1,14 @@
+use super::format::StringFormat;
+
 #[derive(Debug, Clone)]
 pub enum JsonType {
     String,
     Integer,
     Boolean,
+    // A string that is parsed into the binary value of its format
+    Formatted(StringFormat),
+    // A string of which the escapes are decoded
+    Decoded,
 }
 
 use super::{types::{TilStreamingInterface, TilSignal, TilStreamlet, til_streamlet::TilImplementationType}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::TemplateType}};
@@ -60,6 +66,11 @@ pub enum JsonComponent {
     Record(Record),
     Key(Key),
     Matcher(Matcher),
+    Union(Union),
+    Map(Map),
+    Tuple(Tuple),
+    Raw(Raw),
+    Enumeration(Enumeration),
 }
 
 mod value;
@@ -67,7 +78,10 @@ mod value;
 pub struct Value {
     name: String,
     data_type: JsonType,
-    outer_nested: usize
+    outer_nested: usize,
+    // The width of an integer when it differs from the integer width of the design
+    int_width: Option<usize>,
+    pipeline_stages: usize,
 }
 
 mod array;
@@ -76,7 +90,11 @@ pub struct Array {
     name: String,
     outer_nested: usize,
     inner_nested: usize,
-    value: Option<Box<JsonComponent>>
+    value: Option<Box<JsonComponent>>,
+    // Side-band outputs with the number of elements of every array and the index of every element
+    length: bool,
+    index: bool,
+    counter_width: usize,
 }
 
 mod record;
@@ -85,7 +103,8 @@ pub struct Record {
     name: String,
     outer_nested: usize,
     inner_nested: usize,
-    keys: Vec<Key>
+    keys: Vec<Key>,
+    map: Option<Map>
 }
 
 mod key;
@@ -94,7 +113,8 @@ pub struct Key {
     name: String,
     matcher: Matcher,
     outer_nested: usize,
-    value: Option<Box<JsonComponent>>
+    value: Option<Box<JsonComponent>>,
+    buffer_depth: usize,
 }
 
 mod matcher;
@@ -106,6 +126,48 @@ pub struct Matcher {
     outer_nested: usize
 }
 
+mod union;
+#[derive(Clone)]
+pub struct Union {
+    name: String,
+    outer_nested: usize,
+    // The kind of every variant and its parser, if the variant needs one
+    variants: Vec<(String, Option<Box<JsonComponent>>)>
+}
+
+mod map;
+#[derive(Clone)]
+pub struct Map {
+    name: String,
+    outer_nested: usize,
+    value: Option<Box<JsonComponent>>
+}
+
+mod tuple;
+#[derive(Clone)]
+pub struct Tuple {
+    name: String,
+    outer_nested: usize,
+    // The parser of every position, if the position needs one
+    positions: Vec<Option<Box<JsonComponent>>>
+}
+
+mod raw;
+#[derive(Clone)]
+pub struct Raw {
+    name: String,
+    outer_nested: usize
+}
+
+mod enumeration;
+#[derive(Clone)]
+pub struct Enumeration {
+    name: String,
+    outer_nested: usize,
+    // The strings in the order of their codes
+    values: Vec<String>
+}
+
 impl JsonComponent {
     pub fn get_generatable(&self) -> &dyn Generatable {
         match self {
@@ -113,7 +175,12 @@ impl JsonComponent {
             JsonComponent::Array(array) => array,
             JsonComponent::Record(record) => record,
             JsonComponent::Key(key) => key,
-            JsonComponent::Matcher(matcher) => matcher
+            JsonComponent::Matcher(matcher) => matcher,
+            JsonComponent::Union(union) => union,
+            JsonComponent::Map(map) => map,
+            JsonComponent::Tuple(tuple) => tuple,
+            JsonComponent::Raw(raw) => raw,
+            JsonComponent::Enumeration(enumeration) => enumeration,
         }
     }
 }
@@ -126,7 +193,12 @@ impl JsonComponentValue for JsonComponent {
             JsonComponent::Array(array) => array.get_children(),
             JsonComponent::Record(record) => record.get_children(),
             JsonComponent::Key(key) => key.get_children(),
-            JsonComponent::Matcher(matcher) => matcher.get_children()
+            JsonComponent::Matcher(matcher) => matcher.get_children(),
+            JsonComponent::Union(union) => union.get_children(),
+            JsonComponent::Map(map) => map.get_children(),
+            JsonComponent::Tuple(tuple) => tuple.get_children(),
+            JsonComponent::Raw(raw) => raw.get_children(),
+            JsonComponent::Enumeration(enumeration) => enumeration.get_children(),
         }
     }
 
@@ -136,7 +208,12 @@ impl JsonComponentValue for JsonComponent {
             JsonComponent::Array(array) => array.num_children(),
             JsonComponent::Record(record) => record.num_children(),
             JsonComponent::Key(key) => key.num_children(),
-            JsonComponent::Matcher(matcher) => matcher.num_children()
+            JsonComponent::Matcher(matcher) => matcher.num_children(),
+            JsonComponent::Union(union) => union.num_children(),
+            JsonComponent::Map(map) => map.num_children(),
+            JsonComponent::Tuple(tuple) => tuple.num_children(),
+            JsonComponent::Raw(raw) => raw.num_children(),
+            JsonComponent::Enumeration(enumeration) => enumeration.num_children(),
         }
     }
 
@@ -146,7 +223,12 @@ impl JsonComponentValue for JsonComponent {
             JsonComponent::Array(array) => array.to_graph_node(),
             JsonComponent::Record(record) => record.to_graph_node(),
             JsonComponent::Key(key) => key.to_graph_node(),
-            JsonComponent::Matcher(matcher) => matcher.to_graph_node()
+            JsonComponent::Matcher(matcher) => matcher.to_graph_node(),
+            JsonComponent::Union(union) => union.to_graph_node(),
+            JsonComponent::Map(map) => map.to_graph_node(),
+            JsonComponent::Tuple(tuple) => tuple.to_graph_node(),
+            JsonComponent::Raw(raw) => raw.to_graph_node(),
+            JsonComponent::Enumeration(enumeration) => enumeration.to_graph_node(),
         }
     }
 }
@@ -158,7 +240,12 @@ impl From<Box<JsonComponent>> for Box<dyn Generatable> {
             JsonComponent::Array(array) => Box::new(array),
             JsonComponent::Record(record) => Box::new(record),
             JsonComponent::Key(key) => Box::new(key),
-            JsonComponent::Matcher(matcher) => Box::new(matcher)
+            JsonComponent::Matcher(matcher) => Box::new(matcher),
+            JsonComponent::Union(union) => Box::new(union),
+            JsonComponent::Map(map) => Box::new(map),
+            JsonComponent::Tuple(tuple) => Box::new(tuple),
+            JsonComponent::Raw(raw) => Box::new(raw),
+            JsonComponent::Enumeration(enumeration) => Box::new(enumeration),
         }
     }
 }
\ No newline at end of file
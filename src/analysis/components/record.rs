@@ -1,6 +1,6 @@
 use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::TemplateType}};
 
-use super::{Record, JsonComponent, Generatable, Key, JsonComponentValue};
+use super::{Record, JsonComponent, Generatable, Key, Map, JsonComponentValue};
 
 impl Record {
     pub fn new(name: &str, outer_nested: usize, inner_nested: usize, keys: Vec<Key>) -> Record {
@@ -8,7 +8,19 @@ impl Record {
             name: name.to_string(),
             outer_nested,
             inner_nested,
-            keys
+            keys,
+            map: None,
+        }
+    }
+
+    // A record of which the keys are data instead of fields, all entries go through the map parser
+    pub fn new_map(name: &str, outer_nested: usize, inner_nested: usize, map: Map) -> Record {
+        Record {
+            name: name.to_string(),
+            outer_nested,
+            inner_nested,
+            keys: Vec::new(),
+            map: Some(map),
         }
     }
 }
@@ -63,6 +75,17 @@ impl Generatable for Record {
             );
         }
 
+        if let Some(map) = &self.map {
+            signals.push(
+                TilSignal::Intermediate {
+                    source_inst_name: self.get_instance_name(),
+                    source_stream_name: "output".to_owned(),
+                    dest_inst_name: map.get_instance_name(),
+                    dest_stream_name: "input".to_owned()
+                }
+            );
+        }
+
         signals
     }
 
@@ -91,6 +114,10 @@ impl JsonComponentValue for Record {
             children.push(JsonComponent::Key(key.clone()));
         }
 
+        if let Some(map) = &self.map {
+            children.push(JsonComponent::Map(map.clone()));
+        }
+
         children
     }
 
@@ -0,0 +1,120 @@
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::TemplateType}};
+
+use super::{Tuple, JsonComponent, Generatable, JsonComponentValue};
+
+impl Tuple {
+    pub fn new(name: &str, outer_nested: usize, positions: Vec<Option<Box<JsonComponent>>>) -> Tuple {
+        Tuple {
+            name: name.to_string(),
+            outer_nested,
+            positions,
+        }
+    }
+}
+
+impl Generatable for Tuple {
+    fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {
+        let mut interface = TilStreamingInterface::default();
+
+        interface.add_generic(Generic::new("EPC", GenericType::Positive(gen_params.epc)));
+        let dim_name = "NESTING_LEVEL";
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.outer_nested)));
+
+        // Input type, the elements of the array
+        interface.add_stream("input", TilStreamDirection::Input,
+            StreamTypeDecl::new(
+                StreamType::Json,
+                Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
+            )
+        );
+
+        // One output per position, carrying one element per array
+        for idx in 0..self.positions.len() {
+            interface.add_stream(&format!("output_{}", idx), TilStreamDirection::Output,
+                StreamTypeDecl::new(
+                    StreamType::Json,
+                    Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
+                )
+            );
+        }
+
+        interface
+    }
+
+    fn get_streaming_types(&self) -> Vec<StreamType> {
+        vec![StreamType::Json]
+    }
+
+    fn get_nesting_level(&self) -> usize {
+        self.outer_nested
+    }
+
+    fn get_outgoing_signals(&self) -> Vec<TilSignal> {
+        let mut signals = Vec::new();
+
+        for (idx, child) in self.positions.iter().enumerate() {
+            match child {
+                Some(child) => {
+                    // Convert child to generatable
+                    let child = Box::<dyn Generatable>::from(child.clone());
+
+                    signals.push(
+                        TilSignal::Intermediate {
+                            source_inst_name: self.get_instance_name(),
+                            source_stream_name: format!("output_{}", idx),
+                            dest_inst_name: child.get_instance_name(),
+                            dest_stream_name: "input".to_owned()
+                        }
+                    );
+                },
+                None => {
+                    let output_name = format!("output_{}_{}", self.get_instance_name(), idx);
+
+                    signals.push(
+                        TilSignal::Output {
+                            source_inst_name: self.get_instance_name(),
+                            source_stream_name: format!("output_{}", idx),
+                            dest_stream_name: output_name.clone(),
+                            output_stream: TilStream::new(&output_name, TilStreamDirection::Output,
+                                StreamTypeDecl::new(
+                                    StreamType::Json,
+                                    Some(StreamDim::new(None, self.outer_nested, 1))
+                                )
+                            )
+                        }
+                    );
+                },
+            }
+        }
+
+        signals
+    }
+
+    fn num_outgoing_signals(&self) -> usize {
+        self.positions.len()
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_file_type(&self) -> TemplateType {
+        TemplateType::TupleDemux(self.positions.len())
+    }
+}
+
+impl JsonComponentValue for Tuple {
+    fn to_graph_node(&self) -> String {
+        format!("Tuple demux\n{} positions\nO: {}", self.positions.len(), self.outer_nested)
+    }
+
+    fn get_children(&self) -> Vec<JsonComponent> {
+        self.positions.iter()
+            .filter_map(|child| child.as_ref().map(|child| *child.clone()))
+            .collect()
+    }
+
+    fn num_children(&self) -> usize {
+        self.get_children().len()
+    }
+}
@@ -0,0 +1,155 @@
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::{StreamType, TagType}, file_manager::TemplateType}};
+
+use super::{Union, JsonComponent, Generatable, JsonComponentValue};
+
+impl Union {
+    pub fn new(name: &str, outer_nested: usize, variants: Vec<(String, Option<Box<JsonComponent>>)>) -> Union {
+        Union {
+            name: name.to_string(),
+            outer_nested,
+            variants,
+        }
+    }
+
+    // The tag stream tells for every value which variant it was, in the order of the variants
+    fn get_tag_type(&self) -> TagType {
+        TagType::new(&self.name, self.variants.iter().map(|(kind, _)| kind.clone()).collect())
+    }
+
+    // Null values carry no data, the router only reports them on the tag stream
+    fn get_routed_variants(&self) -> impl Iterator<Item = &(String, Option<Box<JsonComponent>>)> {
+        self.variants.iter().filter(|(kind, _)| kind != "null")
+    }
+}
+
+impl Generatable for Union {
+    fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {
+        let mut interface = TilStreamingInterface::default();
+
+        interface.add_generic(Generic::new("EPC", GenericType::Positive(gen_params.epc)));
+        let dim_name = "NESTING_LEVEL";
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.outer_nested)));
+
+        // Input type
+        interface.add_stream("input", TilStreamDirection::Input,
+            StreamTypeDecl::new(
+                StreamType::Json,
+                Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
+            )
+        );
+
+        // One output per variant, carrying the values of that type unchanged
+        for (kind, _) in self.get_routed_variants() {
+            interface.add_stream(&format!("output_{}", kind), TilStreamDirection::Output,
+                StreamTypeDecl::new(
+                    StreamType::Json,
+                    Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
+                )
+            );
+        }
+
+        interface.add_stream("tag", TilStreamDirection::Output,
+            StreamTypeDecl::new(
+                StreamType::Tag(self.get_tag_type()),
+                Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 0))
+            )
+        );
+
+        interface
+    }
+
+    fn get_streaming_types(&self) -> Vec<StreamType> {
+        vec![StreamType::Json, StreamType::Tag(self.get_tag_type())]
+    }
+
+    fn get_nesting_level(&self) -> usize {
+        self.outer_nested
+    }
+
+    fn get_outgoing_signals(&self) -> Vec<TilSignal> {
+        let mut signals = Vec::new();
+
+        for (kind, child) in self.get_routed_variants() {
+            match child {
+                Some(child) => {
+                    // Convert child to generatable
+                    let child = Box::<dyn Generatable>::from(child.clone());
+
+                    signals.push(
+                        TilSignal::Intermediate {
+                            source_inst_name: self.get_instance_name(),
+                            source_stream_name: format!("output_{}", kind),
+                            dest_inst_name: child.get_instance_name(),
+                            dest_stream_name: "input".to_owned()
+                        }
+                    );
+                },
+                None => {
+                    let output_name = format!("output_{}_{}", self.get_instance_name(), kind);
+
+                    signals.push(
+                        TilSignal::Output {
+                            source_inst_name: self.get_instance_name(),
+                            source_stream_name: format!("output_{}", kind),
+                            dest_stream_name: output_name.clone(),
+                            output_stream: TilStream::new(&output_name, TilStreamDirection::Output,
+                                StreamTypeDecl::new(
+                                    StreamType::Json,
+                                    Some(StreamDim::new(None, self.outer_nested, 1))
+                                )
+                            )
+                        }
+                    );
+                },
+            }
+        }
+
+        let tag_name = format!("output_{}", self.get_instance_name());
+
+        signals.push(
+            TilSignal::Output {
+                source_inst_name: self.get_instance_name(),
+                source_stream_name: "tag".to_owned(),
+                dest_stream_name: tag_name.clone(),
+                output_stream: TilStream::new(&tag_name, TilStreamDirection::Output,
+                    StreamTypeDecl::new(
+                        StreamType::Tag(self.get_tag_type()),
+                        Some(StreamDim::new(None, self.outer_nested, 0))
+                    )
+                )
+            }
+        );
+
+        signals
+    }
+
+    fn num_outgoing_signals(&self) -> usize {
+        self.get_routed_variants().count() + 1
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_file_type(&self) -> TemplateType {
+        TemplateType::UnionRouter(self.get_tag_type())
+    }
+}
+
+impl JsonComponentValue for Union {
+    fn to_graph_node(&self) -> String {
+        let kinds: Vec<&str> = self.variants.iter().map(|(kind, _)| kind.as_str()).collect();
+
+        format!("Union router\n{}\nO: {}", kinds.join(" | "), self.outer_nested)
+    }
+
+    fn get_children(&self) -> Vec<JsonComponent> {
+        self.variants.iter()
+            .filter_map(|(_, child)| child.as_ref().map(|child| *child.clone()))
+            .collect()
+    }
+
+    fn num_children(&self) -> usize {
+        self.get_children().len()
+    }
+}
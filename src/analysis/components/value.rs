@@ -1,4 +1,4 @@
-use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::TemplateType}};
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, params::ComponentParam, analyzer::{type_manager::{StreamType, IntType}, file_manager::TemplateType}};
 
 use super::{JsonComponent, JsonType, Value, Generatable, JsonComponentValue};
 
@@ -8,6 +8,37 @@ impl Value {
             name: name.to_string(),
             data_type,
             outer_nested,
+            int_width: None,
+            pipeline_stages: ComponentParam::PipelineStages.get_default(),
+        }
+    }
+
+    pub fn get_data_type(&self) -> &JsonType {
+        &self.data_type
+    }
+
+    // Parse an integer into another width than the integer width of the design
+    pub fn set_int_width(&mut self, int_width: usize) {
+        self.int_width = Some(int_width);
+    }
+
+    pub fn get_int_width(&self, gen_params: &GeneratorParams) -> usize {
+        self.int_width.unwrap_or(gen_params.int_width)
+    }
+
+    pub fn set_pipeline_stages(&mut self, pipeline_stages: usize) {
+        self.pipeline_stages = pipeline_stages;
+    }
+
+    pub fn get_pipeline_stages(&self) -> usize {
+        self.pipeline_stages
+    }
+
+    // The type of the parsed integers
+    pub fn get_int_type(&self) -> StreamType {
+        match self.int_width {
+            Some(width) => StreamType::SizedInt(IntType::new(width)),
+            None => StreamType::Int,
         }
     }
 }
@@ -40,13 +71,25 @@ impl Generatable for Value {
 
                 interface
             },
+            JsonType::Decoded => {
+                // Output type, the decoded characters
+                interface.add_stream("output", TilStreamDirection::Output,
+                    StreamTypeDecl::new(
+                        StreamType::Utf8,
+                        Some(StreamDim::new(Some(dim_name.to_string()),  self.outer_nested, 1))
+                    )
+                );
+
+                interface
+            },
             JsonType::Integer => {
-                interface.add_generic(Generic::new("BITWIDTH", GenericType::Positive(gen_params.int_width)));
+                interface.add_generic(Generic::new("BITWIDTH", GenericType::Positive(self.get_int_width(gen_params))));
+                interface.add_generic(ComponentParam::PipelineStages.to_generic(self.pipeline_stages));
 
                 // Output type
                 interface.add_stream("output", TilStreamDirection::Output,
                     StreamTypeDecl::new(
-                        StreamType::Int, 
+                        self.get_int_type(), 
                         Some(StreamDim::new(Some(dim_name.to_string()),  self.outer_nested, 0))
                     )
                 );
@@ -62,6 +105,27 @@ impl Generatable for Value {
                     )
                 );
 
+                interface
+            },
+            JsonType::Formatted(format) => {
+                // Output type, one binary value per string
+                interface.add_stream("output", TilStreamDirection::Output,
+                    StreamTypeDecl::new(
+                        format.get_stream_type(),
+                        Some(StreamDim::new(Some(dim_name.to_string()),  self.outer_nested, 0))
+                    )
+                );
+
+                // Error flags, one per string
+                if let Some(error_type) = format.get_error_type() {
+                    interface.add_stream("error", TilStreamDirection::Output,
+                        StreamTypeDecl::new(
+                            error_type,
+                            Some(StreamDim::new(Some(dim_name.to_string()),  self.outer_nested, 0))
+                        )
+                    );
+                }
+
                 interface
             }
         }
@@ -70,8 +134,10 @@ impl Generatable for Value {
     fn get_streaming_types(&self) -> Vec<StreamType> {
         match self.data_type {
             JsonType::String => vec![StreamType::Json],
-            JsonType::Integer => vec![StreamType::Json, StreamType::Int],
+            JsonType::Decoded => vec![StreamType::Json, StreamType::Utf8],
+            JsonType::Integer => vec![StreamType::Json, self.get_int_type()],
             JsonType::Boolean => vec![StreamType::Json, StreamType::Bool],
+            JsonType::Formatted(format) => [StreamType::Json, format.get_stream_type()].into_iter().chain(format.get_error_type()).collect(),
         }
     }
 
@@ -82,7 +148,7 @@ impl Generatable for Value {
     fn get_outgoing_signals(&self) -> Vec<TilSignal> {
         let output_name = format!("output_{}", self.get_instance_name());
 
-        vec![
+        let mut signals = vec![
             TilSignal::Output { 
                 source_inst_name: self.get_instance_name(), 
                 source_stream_name: "output".to_owned(), 
@@ -90,12 +156,32 @@ impl Generatable for Value {
                 output_stream: TilStream::new(&output_name, TilStreamDirection::Output, 
                     match self.data_type {
                         JsonType::String => StreamTypeDecl::new(StreamType::Json, Some(StreamDim::new(None, self.outer_nested, 1))),
-                        JsonType::Integer => StreamTypeDecl::new(StreamType::Int, Some(StreamDim::new(None, self.outer_nested, 0))),
+                        JsonType::Decoded => StreamTypeDecl::new(StreamType::Utf8, Some(StreamDim::new(None, self.outer_nested, 1))),
+                        JsonType::Integer => StreamTypeDecl::new(self.get_int_type(), Some(StreamDim::new(None, self.outer_nested, 0))),
                         JsonType::Boolean => StreamTypeDecl::new(StreamType::Bool, Some(StreamDim::new(None, self.outer_nested, 0))),
+                        JsonType::Formatted(format) => StreamTypeDecl::new(format.get_stream_type(), Some(StreamDim::new(None, self.outer_nested, 0))),
                     }
                 )
             }
-        ]
+        ];
+
+        // The error flags always leave the design
+        if let JsonType::Formatted(format) = self.data_type {
+            if let Some(error_type) = format.get_error_type() {
+                let error_name = format!("output_{}_error", self.get_instance_name());
+
+                signals.push(TilSignal::Output {
+                    source_inst_name: self.get_instance_name(),
+                    source_stream_name: "error".to_owned(),
+                    dest_stream_name: error_name.clone(),
+                    output_stream: TilStream::new(&error_name, TilStreamDirection::Output,
+                        StreamTypeDecl::new(error_type, Some(StreamDim::new(None, self.outer_nested, 0)))
+                    )
+                });
+            }
+        }
+
+        signals
     }
 
     fn get_name(&self) -> &str {
@@ -109,8 +195,10 @@ impl Generatable for Value {
     fn get_file_type(&self) -> TemplateType {
         match self.data_type {
             JsonType::String => TemplateType::String,
-            JsonType::Integer => TemplateType::Int,
+            JsonType::Decoded => TemplateType::StringDecoder,
+            JsonType::Integer => TemplateType::Int { int_width: self.int_width, pipeline_stages: self.pipeline_stages },
             JsonType::Boolean => TemplateType::Bool,
+            JsonType::Formatted(format) => TemplateType::Format(format),
         }
     }
 }
@@ -127,4 +215,23 @@ impl JsonComponentValue for Value {
     fn num_children(&self) -> usize {
         0
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::testing::{connections, design_of};
+
+    #[test]
+    fn reports_the_parse_errors_of_quoted_integers() {
+        let design = design_of(r#"{"id": "-17"}"#, |generator| generator.add_format("$.id=int").unwrap()).unwrap();
+        let top = &design.get_top_components()[0];
+
+        // Both the integer and the errors of the parser leave the design
+        let outputs: Vec<_> = connections(top).into_iter()
+            .filter(|(source, _)| source.starts_with("quoted_int_parser"))
+            .collect();
+        assert_eq!(outputs.len(), 2, "{:?}", outputs);
+        assert!(outputs.iter().any(|(source, _)| source.ends_with(".output")));
+        assert!(outputs.iter().any(|(source, _)| source.ends_with(".error")));
+    }
+}
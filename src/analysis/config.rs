@@ -0,0 +1,201 @@
+use std::{collections::BTreeMap, fmt::{Display, Formatter}};
+
+use serde::Deserialize;
+
+use super::params::ComponentParam;
+
+/**********************************************************************************
+ * Project configuration: the parameters of the generator in a TOML file, with    *
+ * overrides for the values at JSON paths, e.g.                                   *
+ *                                                                                *
+ *   epc = 8                                                                      *
+ *                                                                                *
+ *   [paths."$.students[].grade"]                                                 *
+ *   int_width = 8                                                                *
+ **********************************************************************************/
+
+/// The parameters of a project, named after the options of the command line
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub(super) parser_name: Option<String>,
+    pub(super) epc: Option<usize>,
+    pub(super) auto_epc: bool,
+    pub(super) int_width: Option<usize>,
+    pub(super) backend: Option<Vec<String>>,
+    pub(super) no_duplicators: bool,
+    pub(super) hierarchical: bool,
+    pub(super) group_records: bool,
+    pub(super) map_min_keys: Option<usize>,
+    pub(super) raw_depth: Option<usize>,
+    pub(super) keep_quotes: bool,
+    pub(super) enum_max_values: Option<usize>,
+    pub(super) detect_formats: bool,
+    pub(super) array_length: bool,
+    pub(super) array_index: bool,
+    pub(super) filter: Vec<String>,
+    pub(super) aggregate: Vec<String>,
+    pub(super) validate: bool,
+    pub(super) framing: Option<String>,
+    // The tuning parameters of all components of their kind
+    pub(super) pipeline_stages: Option<usize>,
+    pub(super) key_buffer_depth: Option<usize>,
+    pub(super) element_counter_width: Option<usize>,
+    // The synchronicity and complexity of the outputs of a stream type, e.g. `[streams.IntParserStream]`
+    pub(super) streams: BTreeMap<String, StreamConfig>,
+    // The overrides of every path, e.g. `[paths."$.students[].grade"]`
+    pub(super) paths: BTreeMap<String, PathConfig>,
+    // The schemas of a project with several top components, e.g. `[schemas.orders]`
+    pub(super) schemas: BTreeMap<String, SchemaConfig>,
+}
+
+/// A schema of a project with several of them, which gets a top component named after it
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchemaConfig {
+    // The sample documents of the schema
+    pub(super) input: Vec<String>,
+    pub(super) filter: Vec<String>,
+    pub(super) aggregate: Vec<String>,
+    // The overrides of the paths of the schema, e.g. `[schemas.orders.paths."$.id"]`
+    pub(super) paths: BTreeMap<String, PathConfig>,
+}
+
+impl SchemaConfig {
+    pub fn get_input(&self) -> &Vec<String> {
+        &self.input
+    }
+}
+
+/// The shape of the outputs of a stream type
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StreamConfig {
+    pub(super) synchronicity: Option<String>,
+    pub(super) complexity: Option<u8>,
+}
+
+/// The overrides for the value at a path
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathConfig {
+    // `raw`, `map`, `tuple`, `decode`, `enum` or the name of a string format
+    pub(super) parser: Option<String>,
+    // The values of an enum
+    pub(super) values: Vec<String>,
+    pub(super) int_width: Option<usize>,
+    // The number of characters the parsers of the subtree handle per transfer
+    pub(super) epc: Option<usize>,
+    // Fields that are not selected are not parsed at all
+    pub(super) select: Option<bool>,
+    // The name of the outputs of the value, instead of the name of its parser
+    pub(super) name: Option<String>,
+    // The synchronicity and complexity of the outputs of the value
+    pub(super) synchronicity: Option<String>,
+    pub(super) complexity: Option<u8>,
+    // The tuning parameters of the component of the value
+    pub(super) pipeline_stages: Option<usize>,
+    pub(super) key_buffer_depth: Option<usize>,
+    pub(super) element_counter_width: Option<usize>,
+}
+
+impl PathConfig {
+    pub(super) fn get_component_params(&self) -> Vec<(ComponentParam, usize)> {
+        component_params(self.pipeline_stages, self.key_buffer_depth, self.element_counter_width)
+    }
+}
+
+fn component_params(pipeline_stages: Option<usize>, key_buffer_depth: Option<usize>, element_counter_width: Option<usize>) -> Vec<(ComponentParam, usize)> {
+    ComponentParam::ALL.into_iter()
+        .zip([pipeline_stages, key_buffer_depth, element_counter_width])
+        .filter_map(|(param, value)| Some((param, value?)))
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    IoError(std::io::Error),
+    SyntaxError(toml::de::Error),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::IoError(err) => write!(f, "{}", err),
+            ConfigError::SyntaxError(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Config {
+    pub fn from_file(path: &str) -> Result<Config, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(ConfigError::IoError)?;
+
+        Config::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        toml::from_str(text).map_err(ConfigError::SyntaxError)
+    }
+
+    pub fn get_parser_name(&self) -> Option<&str> {
+        self.parser_name.as_deref()
+    }
+
+    pub fn get_epc(&self) -> Option<usize> {
+        self.epc
+    }
+
+    pub fn get_int_width(&self) -> Option<usize> {
+        self.int_width
+    }
+
+    pub fn get_framing(&self) -> Option<&str> {
+        self.framing.as_deref()
+    }
+
+    pub fn get_schemas(&self) -> &BTreeMap<String, SchemaConfig> {
+        &self.schemas
+    }
+
+    pub(super) fn get_component_params(&self) -> Vec<(ComponentParam, usize)> {
+        component_params(self.pipeline_stages, self.key_buffer_depth, self.element_counter_width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_project_and_path_parameters() {
+        let config = Config::parse(r#"
+            epc = 8
+            hierarchical = true
+            filter = ["$.a > 1"]
+            pipeline_stages = 2
+
+            [paths."$.a"]
+            int_width = 16
+            name = "a"
+            key_buffer_depth = 3
+        "#).unwrap();
+
+        assert_eq!(config.get_epc(), Some(8));
+        assert!(config.hierarchical);
+        assert_eq!(config.filter, vec!["$.a > 1".to_string()]);
+        assert_eq!(config.get_component_params(), vec![(ComponentParam::PipelineStages, 2)]);
+
+        let path = &config.paths["$.a"];
+        assert_eq!(path.int_width, Some(16));
+        assert_eq!(path.name.as_deref(), Some("a"));
+        assert_eq!(path.get_component_params(), vec![(ComponentParam::KeyBufferDepth, 3)]);
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(matches!(Config::parse("epcs = 8"), Err(ConfigError::SyntaxError(_))));
+        assert!(matches!(Config::parse("[paths.\"$.a\"]\nwidth = 8"), Err(ConfigError::SyntaxError(_))));
+        assert!(matches!(Config::parse("epc = \"eight\""), Err(ConfigError::SyntaxError(_))));
+    }
+}
@@ -0,0 +1,220 @@
+use std::fmt::{Display, Formatter};
+
+/**********************************************************************************
+ * Filter specifications: comparisons of scalar record fields with constants,     *
+ * e.g. `$.students[].grade >= 60 && $.students[].study == "Computer Science"`,   *
+ * which are compiled into comparators that gate the outputs of the record.       *
+ **********************************************************************************/
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    // The VHDL operator of the comparison
+    pub fn to_vhdl(self) -> &'static str {
+        match self {
+            Comparison::Eq => "=",
+            Comparison::Ne => "/=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+        }
+    }
+
+    pub fn is_ordering(self) -> bool {
+        !matches!(self, Comparison::Eq | Comparison::Ne)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    // Wide enough for the negative values of signed fields and every unsigned value of up to 127 bits
+    Integer(i128),
+    String(String),
+    Boolean(bool),
+}
+
+impl Literal {
+    /// The kind of value the literal can be compared with, as reported by `Schema::get_kind`
+    pub fn get_kind(&self) -> &str {
+        match self {
+            Literal::Integer(_) => "integer",
+            Literal::String(_) => "string",
+            Literal::Boolean(_) => "boolean",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Predicate {
+    path: String,
+    comparison: Comparison,
+    literal: Literal,
+}
+
+impl Predicate {
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn get_comparison(&self) -> Comparison {
+        self.comparison
+    }
+
+    pub fn get_literal(&self) -> &Literal {
+        &self.literal
+    }
+
+    /// The path of the record the compared field belongs to, e.g. `$.students[]` for `$.students[].grade`
+    pub fn get_record_path(&self) -> &str {
+        match self.path.rfind('.') {
+            Some(idx) => &self.path[..idx],
+            None => &self.path,
+        }
+    }
+}
+
+// How the results of the predicates of a filter are combined
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Combine {
+    All,
+    Any,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    combine: Combine,
+    predicates: Vec<Predicate>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterError(String);
+
+impl Display for FilterError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Filter {
+    pub fn get_combine(&self) -> Combine {
+        self.combine
+    }
+
+    pub fn get_predicates(&self) -> &Vec<Predicate> {
+        &self.predicates
+    }
+
+    /// Parse a filter of comparisons joined by either `&&` or `||`
+    pub fn parse(spec: &str) -> Result<Filter, FilterError> {
+        let mut predicates = Vec::new();
+        let mut combine = None;
+        let mut rest = spec.trim();
+
+        loop {
+            let (predicate, remainder) = parse_predicate(rest)?;
+            predicates.push(predicate);
+
+            let remainder = remainder.trim_start();
+            if remainder.is_empty() {
+                break;
+            }
+
+            let next = if let Some(remainder) = remainder.strip_prefix("&&") {
+                (Combine::All, remainder)
+            } else if let Some(remainder) = remainder.strip_prefix("||") {
+                (Combine::Any, remainder)
+            } else {
+                return Err(FilterError(format!("expected '&&' or '||' before '{}'", remainder)));
+            };
+
+            // Mixing both would need parentheses to be unambiguous
+            if combine.is_some_and(|combine| combine != next.0) {
+                return Err(FilterError("a filter can not mix '&&' and '||'".to_string()));
+            }
+
+            combine = Some(next.0);
+            rest = next.1.trim_start();
+        }
+
+        Ok(Filter { combine: combine.unwrap_or(Combine::All), predicates })
+    }
+}
+
+// Parse `<path> <comparison> <literal>` and return the text after it
+fn parse_predicate(spec: &str) -> Result<(Predicate, &str), FilterError> {
+    let path_end = spec.find(|c: char| c.is_whitespace() || "=!<>".contains(c)).unwrap_or(spec.len());
+    let path = &spec[..path_end];
+
+    if !path.starts_with('$') || !path.contains('.') {
+        return Err(FilterError(format!("expected the path of a record field, found '{}'", path)));
+    }
+
+    let rest = spec[path_end..].trim_start();
+    let (comparison, rest) = [("==", Comparison::Eq), ("!=", Comparison::Ne), ("<=", Comparison::Le), (">=", Comparison::Ge), ("<", Comparison::Lt), (">", Comparison::Gt)]
+        .iter()
+        .find_map(|(op, comparison)| rest.strip_prefix(op).map(|rest| (*comparison, rest.trim_start())))
+        .ok_or_else(|| FilterError(format!("expected a comparison after '{}'", path)))?;
+
+    let (literal, rest) = if let Some(quoted) = rest.strip_prefix('"') {
+        let end = quoted.find('"').ok_or_else(|| FilterError("unterminated string".to_string()))?;
+        (Literal::String(quoted[..end].to_string()), &quoted[end + 1..])
+    } else {
+        let end = rest.find(|c: char| c.is_whitespace() || c == '&' || c == '|').unwrap_or(rest.len());
+        let literal = match &rest[..end] {
+            "true" => Literal::Boolean(true),
+            "false" => Literal::Boolean(false),
+            value => Literal::Integer(value.parse().map_err(|_| FilterError(format!("expected a string, an integer or a boolean, found '{}'", value)))?),
+        };
+        (literal, &rest[end..])
+    };
+
+    if comparison.is_ordering() && !matches!(literal, Literal::Integer(_)) {
+        return Err(FilterError(format!("'{}' can only be ordered against an integer", path)));
+    }
+
+    Ok((Predicate { path: path.to_string(), comparison, literal }, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_comparisons_joined_by_one_operator() {
+        let filter = Filter::parse(r#"$.exams[].grade >= -60 && $.exams[].course == "CS101" && $.exams[].passed != true"#).unwrap();
+        assert_eq!(filter.get_combine(), Combine::All);
+
+        let predicates = filter.get_predicates();
+        assert_eq!(predicates.len(), 3);
+        assert_eq!((predicates[0].get_path(), predicates[0].get_comparison(), predicates[0].get_literal()), ("$.exams[].grade", Comparison::Ge, &Literal::Integer(-60)));
+        assert_eq!(predicates[1].get_literal(), &Literal::String("CS101".to_string()));
+        assert_eq!(predicates[2].get_literal(), &Literal::Boolean(true));
+        assert_eq!(predicates[0].get_record_path(), "$.exams[]");
+
+        assert_eq!(Filter::parse("$.a<1||$.b>2").unwrap().get_combine(), Combine::Any);
+    }
+
+    #[test]
+    fn rejects_malformed_filters() {
+        for spec in [
+            "$.a < 1 && $.b > 2 || $.c == 3",
+            r#"$.a < "x""#,
+            "$.a == 1.5",
+            r#"$.a == "x"#,
+            "a == 1",
+            "$.a ~ 1",
+            "$.a == 1 $.b == 2",
+        ] {
+            assert!(Filter::parse(spec).is_err(), "'{}' was accepted", spec);
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use super::analyzer::type_manager::StreamType;
+
+/**********************************************************************************
+ * Formats of strings that are parsed into binary values instead of being         *
+ * streamed out character by character, e.g. dates and UUIDs.                    *
+ **********************************************************************************/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum StringFormat {
+    // `2000-05-15`, parsed into packed year, month and day fields
+    Date,
+    // `2023-12-10T09:30:00Z`, parsed into seconds since the Unix epoch
+    DateTime,
+    // `123e4567-e89b-12d3-a456-426614174000`, parsed into 128 bits
+    Uuid,
+    // `192.168.0.1`, parsed into 32 bits
+    Ipv4,
+    // `0x1F`, parsed into an integer
+    Hex,
+    // `-17`, parsed into a signed integer
+    Integer,
+}
+
+impl StringFormat {
+    pub fn from_name(name: &str) -> Option<StringFormat> {
+        match name {
+            "date" => Some(StringFormat::Date),
+            "datetime" => Some(StringFormat::DateTime),
+            "uuid" => Some(StringFormat::Uuid),
+            "ipv4" => Some(StringFormat::Ipv4),
+            "hex" => Some(StringFormat::Hex),
+            "int" => Some(StringFormat::Integer),
+            _ => None,
+        }
+    }
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            StringFormat::Date => "date",
+            StringFormat::DateTime => "datetime",
+            StringFormat::Uuid => "uuid",
+            StringFormat::Ipv4 => "ipv4",
+            StringFormat::Hex => "hex",
+            StringFormat::Integer => "int",
+        }
+    }
+
+    // Quoted integers are not named after their format to tell them apart from the parsers of numbers
+    pub fn get_parser_name(self) -> String {
+        match self {
+            StringFormat::Integer => "quoted_int_parser".to_string(),
+            _ => format!("{}_parser", self.get_name()),
+        }
+    }
+
+    /// The type of the stream the parsed values are produced on
+    pub fn get_stream_type(self) -> StreamType {
+        match self {
+            StringFormat::Date => StreamType::Date,
+            StringFormat::DateTime => StreamType::Timestamp,
+            StringFormat::Uuid => StreamType::Uuid,
+            StringFormat::Ipv4 => StreamType::Ipv4,
+            StringFormat::Hex => StreamType::Int,
+            StringFormat::Integer => StreamType::Int,
+        }
+    }
+
+    /// The type of the stream the parse errors of a string are reported on, if the format has one
+    pub fn get_error_type(self) -> Option<StreamType> {
+        match self {
+            StringFormat::Integer => Some(StreamType::ParseError),
+            _ => None,
+        }
+    }
+
+    /// The format every one of the strings has, if they share one
+    /// Hex strings are only detected with a `0x` prefix, and hex strings and integers have to fit in `int_width` bits
+    pub fn detect(strings: &[String], int_width: usize) -> Option<StringFormat> {
+        [StringFormat::Date, StringFormat::DateTime, StringFormat::Uuid, StringFormat::Ipv4, StringFormat::Hex, StringFormat::Integer]
+            .into_iter()
+            .find(|format| !strings.is_empty() && strings.iter().all(|string| format.matches(string, int_width)))
+    }
+
+    fn matches(self, string: &str, int_width: usize) -> bool {
+        match self {
+            StringFormat::Date => is_date(string),
+            StringFormat::DateTime => {
+                string.len() >= 19
+                    && matches!(string.as_bytes()[10], b'T' | b' ')
+                    && is_date(&string[..10])
+                    && is_time(&string[11..])
+            },
+            StringFormat::Uuid => {
+                string.len() == 36
+                    && string.char_indices().all(|(idx, c)| if [8, 13, 18, 23].contains(&idx) { c == '-' } else { c.is_ascii_hexdigit() })
+            },
+            StringFormat::Ipv4 => {
+                let octets: Vec<&str> = string.split('.').collect();
+                octets.len() == 4 && octets.iter().all(|octet| (1..=3).contains(&octet.len()) && octet.chars().all(|c| c.is_ascii_digit()) && octet.parse::<u16>().is_ok_and(|octet| octet < 256))
+            },
+            StringFormat::Hex => match string.strip_prefix("0x").or_else(|| string.strip_prefix("0X")) {
+                Some(digits) => !digits.is_empty() && digits.len() * 4 <= int_width && digits.chars().all(|c| c.is_ascii_hexdigit()),
+                None => false,
+            },
+            StringFormat::Integer => {
+                let digits = string.strip_prefix('-').unwrap_or(string);
+                is_digits(digits) && fits_signed(string, int_width)
+            },
+        }
+    }
+}
+
+// Whether a decimal integer is a signed integer of `width` bits
+fn fits_signed(string: &str, width: usize) -> bool {
+    match string.parse::<i128>() {
+        Ok(_) if width >= 128 => true,
+        Ok(value) => (-(1i128 << (width - 1))..(1i128 << (width - 1))).contains(&value),
+        // Integers beyond 128 bits are only accepted by a rough estimate of their digits
+        Err(_) => width > 128 && string.trim_start_matches('-').len() * 10 < (width - 1) * 3,
+    }
+}
+
+fn is_digits(string: &str) -> bool {
+    !string.is_empty() && string.chars().all(|c| c.is_ascii_digit())
+}
+
+// `YYYY-MM-DD`
+fn is_date(string: &str) -> bool {
+    let parts: Vec<&str> = string.split('-').collect();
+
+    match parts.as_slice() {
+        [year, month, day] if year.len() == 4 && month.len() == 2 && day.len() == 2 && is_digits(year) && is_digits(month) && is_digits(day) => {
+            let (month, day): (u8, u8) = (month.parse().unwrap(), day.parse().unwrap());
+            (1..=12).contains(&month) && (1..=31).contains(&day)
+        },
+        _ => false,
+    }
+}
+
+// `hh:mm:ss`, optionally followed by a fraction and a `Z` or `+hh:mm` offset
+fn is_time(string: &str) -> bool {
+    if string.len() < 8 || !string.is_char_boundary(8) {
+        return false;
+    }
+
+    let (time, mut rest) = string.split_at(8);
+    let parts: Vec<&str> = time.split(':').collect();
+    if parts.len() != 3 || parts.iter().any(|part| part.len() != 2 || !is_digits(part)) {
+        return false;
+    }
+
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.find(|c: char| !c.is_ascii_digit()).unwrap_or(fraction.len());
+        if digits == 0 {
+            return false;
+        }
+        rest = &fraction[digits..];
+    }
+
+    match rest.chars().next() {
+        None => true,
+        Some('Z') => rest.len() == 1,
+        Some('+') | Some('-') => {
+            let offset = &rest[1..];
+            offset.len() == 5 && offset.as_bytes()[2] == b':' && is_digits(&offset[..2]) && is_digits(&offset[3..])
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(strings: &[&str]) -> Option<StringFormat> {
+        StringFormat::detect(&strings.iter().map(|string| string.to_string()).collect::<Vec<_>>(), 64)
+    }
+
+    #[test]
+    fn detects_the_format_all_strings_share() {
+        assert_eq!(detect(&["2000-05-15", "1999-12-31"]), Some(StringFormat::Date));
+        assert_eq!(detect(&["2023-12-10T09:30:00Z", "2023-12-10 09:30:00.25+01:00"]), Some(StringFormat::DateTime));
+        assert_eq!(detect(&["123e4567-e89b-12d3-a456-426614174000"]), Some(StringFormat::Uuid));
+        assert_eq!(detect(&["192.168.0.1", "10.0.0.255"]), Some(StringFormat::Ipv4));
+        assert_eq!(detect(&["0x1F", "0XaB"]), Some(StringFormat::Hex));
+
+        for format in [StringFormat::Date, StringFormat::DateTime, StringFormat::Uuid, StringFormat::Ipv4, StringFormat::Hex] {
+            assert_eq!(StringFormat::from_name(format.get_name()), Some(format));
+        }
+    }
+
+    #[test]
+    fn rejects_strings_that_are_almost_in_a_format() {
+        for strings in [
+            vec!["2000-13-15"],
+            vec!["2000-05-15", "hello"],
+            vec!["2023-12-10T09:30:00X"],
+            vec!["2023-12-10T09:30"],
+            vec!["123e4567-e89b-12d3-a456-42661417400g"],
+            vec!["256.0.0.1"],
+            vec!["1F"],
+            vec!["0x1234567890abcdef0"],
+            vec![],
+        ] {
+            assert_eq!(detect(&strings), None, "{:?}", strings);
+        }
+
+        assert_eq!(StringFormat::from_name("time"), None);
+    }
+
+    #[test]
+    fn detects_quoted_integers_that_fit_signed() {
+        assert_eq!(detect(&["-17", "42", "007"]), Some(StringFormat::Integer));
+        assert_eq!(StringFormat::detect(&["127".to_string(), "-128".to_string()], 8), Some(StringFormat::Integer));
+
+        // Integers beyond 128 bits only fit in wider integers
+        let wide = "1".repeat(50);
+        assert_eq!(StringFormat::detect(std::slice::from_ref(&wide), 256), Some(StringFormat::Integer));
+        assert_eq!(StringFormat::detect(std::slice::from_ref(&wide), 128), None);
+    }
+
+    #[test]
+    fn rejects_quoted_integers_that_overflow_or_are_no_integers() {
+        assert_eq!(StringFormat::detect(&["128".to_string()], 8), None);
+        assert_eq!(StringFormat::detect(&["-129".to_string()], 8), None);
+
+        for strings in [vec!["1.5"], vec!["-"], vec!["+1"], vec!["1e3"], vec!["12", "twelve"]] {
+            assert_eq!(detect(&strings), None, "{:?}", strings);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+/**********************************************************************************
+ * Framing of the input of the top component: how the documents are delimited in *
+ * the characters the parser receives. The outermost dimension of the documents  *
+ * always ends a document, the dimensions around it group the documents.         *
+ **********************************************************************************/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputFraming {
+    // A single document, of which the characters are one sequence
+    Document,
+    // A sequence of documents, each of which ends with a last flag
+    #[default]
+    DocumentStream,
+    // Sequences of batches of documents
+    Batches,
+    // Newline delimited JSON: one sequence of characters with a document on every line
+    Ndjson,
+}
+
+impl InputFraming {
+    pub const ALL: [InputFraming; 4] = [InputFraming::Document, InputFraming::DocumentStream, InputFraming::Batches, InputFraming::Ndjson];
+
+    pub fn from_name(name: &str) -> Option<InputFraming> {
+        InputFraming::ALL.into_iter().find(|framing| framing.get_name() == name)
+    }
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            InputFraming::Document => "document",
+            InputFraming::DocumentStream => "stream",
+            InputFraming::Batches => "batches",
+            InputFraming::Ndjson => "ndjson",
+        }
+    }
+
+    /// The dimensionality of the input of the top component
+    pub fn get_input_dim(self) -> usize {
+        match self {
+            InputFraming::Document | InputFraming::Ndjson => 1,
+            InputFraming::DocumentStream => 2,
+            InputFraming::Batches => 3,
+        }
+    }
+
+    /// The number of dimensions of the documents and the batches around them, which is the
+    /// nesting level of the root component
+    pub fn get_document_dims(self) -> usize {
+        match self {
+            InputFraming::Batches => 2,
+            _ => 1,
+        }
+    }
+
+    // Inputs without a dimension for the documents are framed before they go to the parsers
+    pub fn needs_framer(self) -> bool {
+        self.get_input_dim() <= self.get_document_dims()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_only_inputs_without_a_dimension_for_the_documents() {
+        for framing in InputFraming::ALL {
+            assert_eq!(InputFraming::from_name(framing.get_name()), Some(framing));
+        }
+        assert_eq!(InputFraming::from_name("lines"), None);
+
+        let framed: Vec<_> = InputFraming::ALL.into_iter().filter(|framing| framing.needs_framer()).collect();
+        assert_eq!(framed, vec![InputFraming::Document, InputFraming::Ndjson]);
+    }
+}
@@ -1,34 +1,433 @@
-use super::{visualization, Generator, GeneratorParams, analyzer::Analyzer, GeneratorError};
+use std::collections::HashMap;
+
+use super::{visualization, checker, Generator, GeneratorParams, NetlistParams, SchemaParams, FormatParams, OutputParams, TuningParams, LaneParams, analyzer::{Analyzer, AnalyzerError}, GeneratorError, backend::{Backend, BackendRegistry, Design}, schema::Schema, filter::Filter, aggregate::Aggregation, format::StringFormat, framing::InputFraming, config::{Config, PathConfig, SchemaConfig}, analyzer::type_manager::{StreamShape, Synchronicity}};
 
 impl Generator {
     pub fn new(project_name: &str, epc: usize, int_width: usize) -> Generator {
+        let backends = BackendRegistry::default();
+        let selected_backends = backends.get_names().iter().map(|name| name.to_string()).collect();
+
         Generator {
-            analyzer: Analyzer::new(),
+            analyzers: Vec::new(),
             gen_params: GeneratorParams::new(epc, 8, int_width, "", project_name),
+            backends,
+            selected_backends,
+        }
+    }
+
+    // Make an additional output format available for selection
+    #[allow(dead_code)]
+    pub fn register_backend(&mut self, backend: Box<dyn Backend>) {
+        self.backends.register(backend);
+    }
+
+    // Select which of the registered backends are run by `generate`
+    pub fn select_backends(&mut self, names: &[String]) -> Result<(), GeneratorError> {
+        if let Some(unknown) = names.iter().find(|name| self.backends.get(name).is_none()) {
+            return Err(GeneratorError::UnknownBackend(unknown.clone()));
+        }
+
+        self.selected_backends = names.to_vec();
+
+        Ok(())
+    }
+
+    // Resolve fan-out with explicit duplicator streamlets (enabled by default)
+    pub fn set_insert_duplicators(&mut self, enabled: bool) {
+        self.gen_params.netlist.insert_duplicators = enabled;
+    }
+
+    // Give every record and array its own streamlet instead of one flat top component
+    pub fn set_hierarchical(&mut self, enabled: bool) {
+        self.gen_params.netlist.hierarchical = enabled;
+    }
+
+    // Bundle the integer and boolean fields of every record into one group-typed output stream
+    pub fn set_group_records(&mut self, enabled: bool) {
+        self.gen_params.netlist.group_records = enabled;
+    }
+
+    // Parse the objects at the given paths (e.g. `$.counts`) as maps with dynamic keys,
+    // and any object with at least `min_keys` keys of the same type if it is set
+    pub fn set_maps(&mut self, paths: &[String], min_keys: Option<usize>) {
+        self.gen_params.schema.map_paths = paths.to_vec();
+        self.gen_params.schema.map_min_keys = min_keys;
+    }
+
+    // Parse the arrays at the given paths (e.g. `$.location`) as tuples with a parser per position,
+    // arrays of which the element types differ by position always are
+    pub fn set_tuples(&mut self, paths: &[String]) {
+        self.gen_params.schema.tuple_paths = paths.to_vec();
+    }
+
+    // Forward the values at the given paths (e.g. `$.metadata`) as JSON text instead of parsing them,
+    // and any object or array nested deeper than `max_depth` if it is set
+    pub fn set_raw(&mut self, paths: &[String], max_depth: Option<usize>) {
+        self.gen_params.schema.raw_paths = paths.to_vec();
+        self.gen_params.schema.raw_depth = max_depth;
+    }
+
+    // Decode the escapes of the strings at the given paths (e.g. `$.name`) into UTF-8,
+    // optionally keeping the quotes around the decoded strings
+    pub fn set_decoded_strings(&mut self, paths: &[String], keep_quotes: bool) {
+        self.gen_params.formats.decode_paths = paths.to_vec();
+        self.gen_params.formats.keep_quotes = keep_quotes;
+    }
+
+    // Add an output that reports the documents that do not match the structure of the samples
+    pub fn set_validation(&mut self, enabled: bool) {
+        self.gen_params.outputs.validate = enabled;
+    }
+
+    // Delimit the documents in the input as a single document, a stream of documents, batches of
+    // streams of documents or newline delimited JSON
+    pub fn set_framing(&mut self, name: &str) -> Result<(), GeneratorError> {
+        self.gen_params.framing = InputFraming::from_name(name).ok_or_else(|| GeneratorError::InvalidFraming(name.to_string()))?;
+
+        Ok(())
+    }
+
+    // Add outputs with the number of elements of every array and the index of every element
+    pub fn set_array_side_band(&mut self, length: bool, index: bool) {
+        self.gen_params.outputs.array_length = length;
+        self.gen_params.outputs.array_index = index;
+    }
+
+    // Only let the records through that match the filter, e.g. `$.students[].grade >= 60`
+    pub fn add_filter(&mut self, spec: &str) -> Result<(), GeneratorError> {
+        let filter = Filter::parse(spec).map_err(GeneratorError::InvalidFilter)?;
+        self.gen_params.outputs.filters.push(filter);
+
+        Ok(())
+    }
+
+    // Encode the strings at a path as enum codes, given as `$.study=Computer Science,Mathematics`
+    pub fn add_enum(&mut self, spec: &str) -> Result<(), GeneratorError> {
+        let (path, values) = spec.split_once('=').ok_or_else(|| GeneratorError::InvalidEnum(spec.to_string()))?;
+        let values: Vec<String> = values.split(',').map(|value| value.to_string()).collect();
+
+        if values.iter().any(|value| value.is_empty()) {
+            return Err(GeneratorError::InvalidEnum(spec.to_string()));
+        }
+
+        self.gen_params.formats.enum_values.push((path.trim().to_string(), values));
+
+        Ok(())
+    }
+
+    // Parse the strings at a path in a format, given as `$.birthdate=date`
+    pub fn add_format(&mut self, spec: &str) -> Result<(), GeneratorError> {
+        let (path, format) = spec.split_once('=')
+            .and_then(|(path, format)| Some((path, StringFormat::from_name(format.trim())?)))
+            .ok_or_else(|| GeneratorError::InvalidFormat(spec.to_string()))?;
+
+        self.gen_params.formats.formats.push((path.trim().to_string(), format));
+
+        Ok(())
+    }
+
+    // Parse every string of which all values in the samples have the same format
+    pub fn set_detect_formats(&mut self, detect: bool) {
+        self.gen_params.formats.detect_formats = detect;
+    }
+
+    // Choose fewer lanes for the subtrees that only hold a small part of the characters of the samples
+    pub fn set_auto_epc(&mut self, auto_epc: bool) {
+        self.gen_params.lanes.auto_epc = auto_epc;
+    }
+
+    // Infer enums for the strings that take at most the given number of values over all samples
+    pub fn set_enum_max_values(&mut self, max_values: Option<usize>) {
+        self.gen_params.formats.enum_max_values = max_values;
+    }
+
+    // Reduce the elements of an array to one value per array, e.g. `sum($.students[].grades[])`
+    pub fn add_aggregation(&mut self, spec: &str) -> Result<(), GeneratorError> {
+        let aggregation = Aggregation::parse(spec).map_err(GeneratorError::InvalidAggregation)?;
+        self.gen_params.outputs.aggregations.push(aggregation);
+
+        Ok(())
+    }
+
+    // Apply a project configuration on top of the options of the command line, see `GeneratorParams::merge_features`
+    pub fn apply_config(&mut self, config: &Config) -> Result<(), GeneratorError> {
+        if let Some(backends) = &config.backend {
+            self.select_backends(backends)?;
+        }
+
+        let component_params = config.get_component_params().into_iter()
+            .map(|(param, value)| match value {
+                0 => Err(GeneratorError::InvalidConfig(format!("{} has to be at least 1", param.get_name()))),
+                _ => Ok((param, value)),
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut params = GeneratorParams {
+            netlist: NetlistParams {
+                insert_duplicators: !config.no_duplicators,
+                hierarchical: config.hierarchical,
+                group_records: config.group_records,
+            },
+            schema: SchemaParams {
+                map_min_keys: config.map_min_keys,
+                raw_depth: config.raw_depth,
+                ..Default::default()
+            },
+            formats: FormatParams {
+                enum_max_values: config.enum_max_values,
+                detect_formats: config.detect_formats,
+                keep_quotes: config.keep_quotes,
+                ..Default::default()
+            },
+            outputs: OutputParams {
+                array_length: config.array_length,
+                array_index: config.array_index,
+                filters: parse_filters(&config.filter)?,
+                aggregations: parse_aggregations(&config.aggregate)?,
+                validate: config.validate,
+                ..Default::default()
+            },
+            tuning: TuningParams {
+                component_params,
+                ..Default::default()
+            },
+            lanes: LaneParams {
+                auto_epc: config.auto_epc,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        for (type_name, stream_config) in &config.streams {
+            let shape = stream_shape(stream_config.synchronicity.as_deref(), stream_config.complexity)
+                .map_err(|err| GeneratorError::InvalidConfig(format!("stream type '{}': {}", type_name, err)))?;
+
+            params.shapes.type_shapes.push((type_name.clone(), shape));
+        }
+
+        for (path, path_config) in &config.paths {
+            params.apply_path_config(path, path_config)?;
+        }
+
+        self.gen_params.merge_features(params);
+
+        Ok(())
+    }
+
+    // Analyze several sample documents of the same schema, values whose type differs become unions
+    pub fn analyze_samples(&mut self, samples: &[&str]) -> Result<(), GeneratorError> {
+        let mut schema: Option<Schema> = None;
+        let mut documents = Vec::new();
+
+        for sample in samples {
+            // Deserialize the JSON string
+            let parsed = json::parse(sample)
+            // In case of error, return the error
+            .map_err(GeneratorError::JsonError)?;
+
+            let sample_schema = Schema::from_json(&parsed);
+
+            schema = Some(match schema {
+                Some(schema) => schema.merge(sample_schema),
+                None => sample_schema,
+            });
+            documents.push(parsed);
+        }
+
+        let schema = schema.ok_or(GeneratorError::AnalyzerError(AnalyzerError::NoTop))?;
+
+        // Arrays with a type per position
+        let mut found = Vec::new();
+        let schema = schema.detect_tuples("$", &self.gen_params.schema.tuple_paths, &mut found);
+
+        if let Some(path) = self.gen_params.schema.tuple_paths.iter().find(|path| !found.contains(path)) {
+            return Err(GeneratorError::UnknownPath("fixed length array", path.clone()));
+        }
+
+        // Fields that are not selected, arrays only have a schema for their elements once tuples are detected
+        let mut found = Vec::new();
+        let mut emptied = Vec::new();
+        let schema = schema.deselect("$", &self.gen_params.schema.skip_paths, &mut found, &mut emptied);
+
+        if let Some(path) = self.gen_params.schema.skip_paths.iter().find(|path| !found.contains(path)) {
+            return Err(GeneratorError::UnknownPath("record field", path.clone()));
+        }
+
+        if let Some(path) = emptied.first() {
+            return Err(GeneratorError::InvalidPathConfig(path.clone(), "none of the fields of the record are selected".to_string()));
+        }
+
+        // Objects with dynamic keys
+        let mut found = Vec::new();
+        let mut empty = Vec::new();
+        let schema = schema.detect_maps("$", &self.gen_params.schema.map_paths, self.gen_params.schema.map_min_keys, &mut found, &mut empty);
+
+        if let Some(path) = self.gen_params.schema.map_paths.iter().find(|path| !found.contains(path)) {
+            return Err(GeneratorError::UnknownPath("object", path.clone()));
+        }
+
+        if let Some(path) = empty.first() {
+            return Err(GeneratorError::InvalidPathConfig(path.clone(), "the object is empty in every sample, so the type of the values of the map is unknown".to_string()));
+        }
+
+        // Values that are not parsed
+        let mut found = Vec::new();
+        let schema = schema.detect_raw("$", &self.gen_params.schema.raw_paths, self.gen_params.schema.raw_depth, 0, &mut found);
+
+        if let Some(path) = self.gen_params.schema.raw_paths.iter().find(|path| !found.contains(path)) {
+            return Err(GeneratorError::UnknownPath("value", path.clone()));
+        }
+
+        // Strings that are decoded
+        let mut found = Vec::new();
+        let schema = schema.detect_decoded("$", &self.gen_params.formats.decode_paths, &mut found);
+
+        if let Some(path) = self.gen_params.formats.decode_paths.iter().find(|path| !found.contains(path)) {
+            return Err(GeneratorError::UnknownPath("string", path.clone()));
+        }
+
+        // The values of the strings decide which of them have a format or a small set of values
+        let mut strings = HashMap::new();
+        for document in &documents {
+            schema.collect_strings(document, "$", &mut strings);
+        }
+
+        // Strings in a format that is parsed
+        let mut found = Vec::new();
+        let schema = schema.detect_formats("$", &self.gen_params.formats.formats, &strings, self.gen_params.formats.detect_formats, self.gen_params.int_width, &mut found);
+
+        if let Some((path, _)) = self.gen_params.formats.formats.iter().find(|(path, _)| !found.contains(path)) {
+            return Err(GeneratorError::UnknownPath("string", path.clone()));
+        }
+
+        // Strings with a small set of values
+        let mut found = Vec::new();
+        let schema = schema.detect_enums("$", &self.gen_params.formats.enum_values, &strings, self.gen_params.formats.enum_max_values, &mut found);
+
+        if let Some((path, _)) = self.gen_params.formats.enum_values.iter().find(|(path, _)| !found.contains(path)) {
+            return Err(GeneratorError::UnknownPath("string", path.clone()));
+        }
+
+        // Keys that some samples do not have are not waited for by joins, nor compared by filters or reported as missing
+        let mut optional = Vec::new();
+        for document in &documents {
+            schema.collect_optional_keys(document, "$", &mut optional);
+        }
+
+        // Integers that do not fit would be truncated by their parsers
+        for document in &documents {
+            if let Some((path, value)) = schema.find_wide_integer(document, "$", &|path| self.gen_params.get_int_width(path)) {
+                let int_width = self.gen_params.get_int_width(&path);
+                return Err(GeneratorError::IntegerTooWide(path, value, int_width));
+            }
+        }
+
+        // Subtrees get lanes in proportion to the characters of the samples they hold, as the
+        // parsers of a subtree only receive the characters of their values
+        if self.gen_params.lanes.auto_epc {
+            let mut lengths = HashMap::new();
+            for document in &documents {
+                schema.collect_lengths(document, "$", &mut lengths);
+            }
+
+            let total = lengths.get("$").copied().unwrap_or_default().max(1);
+
+            let mut subtrees = Vec::new();
+            schema.collect_subtrees("$", &mut subtrees);
+
+            for path in subtrees {
+                // Configured subtrees keep their number of lanes
+                if self.gen_params.lanes.epcs.iter().any(|(subtree, _)| *subtree == path) {
+                    continue;
+                }
+
+                let length = lengths.get(&path).copied().unwrap_or_default();
+                let lanes = (self.gen_params.epc * length).div_ceil(total).next_power_of_two();
+
+                if lanes < self.gen_params.get_epc(&path) {
+                    self.gen_params.lanes.epcs.push((path, lanes));
+                }
+            }
+        }
+
+        let mut analyzer = Analyzer::new();
+        analyzer.set_optional_keys(optional);
+        analyzer.analyze(&schema, self.gen_params.clone()).map_err(GeneratorError::AnalyzerError)?;
+        self.analyzers.push(analyzer);
+
+        Ok(())
+    }
+
+    // Analyze the samples of one of several schemas of the project, of which the top component is named
+    // after the schema. The configuration of the schema goes on top of the parameters of the project.
+    pub fn add_schema(&mut self, name: &str, samples: &[&str], config: &SchemaConfig) -> Result<(), GeneratorError> {
+        let in_schema = |err| GeneratorError::SchemaError(name.to_string(), Box::new(err));
+
+        if !is_identifier(name) {
+            return Err(in_schema(GeneratorError::InvalidConfig(format!("'{}' is not a valid name, names start with a letter and consist of letters, digits and single underscores", name))));
+        }
+
+        if self.analyzers.iter().any(|analyzer| analyzer.get_top_name() == name) {
+            return Err(in_schema(GeneratorError::InvalidConfig("the name is used by another schema".to_string())));
         }
+
+        let project_params = self.gen_params.clone();
+        self.gen_params.schema_name = Some(name.to_string());
+
+        let result = self.apply_schema_config(config).and_then(|_| self.analyze_samples(samples));
+        self.gen_params = project_params;
+
+        result.map_err(in_schema)
     }
 
-    // Analyze a JSON string
-    pub fn analyze(&mut self, json: &str) -> Result<(), GeneratorError> {
-        // Deserialize the JSON string
-        let parsed = json::parse(json)
-        // In case of error, return the error
-        .map_err(GeneratorError::JsonError)?; 
+    fn apply_schema_config(&mut self, config: &SchemaConfig) -> Result<(), GeneratorError> {
+        for filter in &config.filter {
+            self.add_filter(filter)?;
+        }
+
+        for aggregation in &config.aggregate {
+            self.add_aggregation(aggregation)?;
+        }
 
-        self.analyzer.analyze(&parsed, self.gen_params.clone());
+        for (path, path_config) in &config.paths {
+            self.gen_params.apply_path_config(path, path_config)?;
+        }
 
         Ok(())
     }
 
-    // Visualize the component tree as a dot file
+    // Visualize the component tree as a dot file, the trees of several schemas go to a file per schema
+    // that is named after it, e.g. `schema_orders.dot`
     pub fn visualize(&self, path: &str) -> Result<(), GeneratorError> {
-        let root = self.analyzer.get_root().map_err(GeneratorError::AnalyzerError)?;
+        for analyzer in &self.analyzers {
+            let root = analyzer.get_root().map_err(GeneratorError::AnalyzerError)?;
 
-        visualization::generate_dot(root, path);
+            match analyzer.get_schema_name() {
+                Some(name) => {
+                    let (stem, extension) = path.rsplit_once('.').unwrap_or((path, "dot"));
+                    visualization::generate_dot(root, &format!("{}_{}.{}", stem, name, extension));
+                },
+                None => visualization::generate_dot(root, path),
+            }
+        }
 
         Ok(())
     }
 
+    // Collect everything the backends need from the analyzers, the schemas share one design
+    pub fn get_design(&mut self) -> Result<Design, GeneratorError> {
+        let mut analyzers = self.analyzers.iter_mut();
+        let mut design = analyzers.next()
+            .ok_or(GeneratorError::AnalyzerError(AnalyzerError::NoTop))?
+            .get_design().map_err(GeneratorError::AnalyzerError)?;
+
+        for analyzer in analyzers {
+            design.merge(analyzer.get_design().map_err(GeneratorError::AnalyzerError)?);
+        }
+
+        Ok(design)
+    }
+
     pub fn generate(&mut self, path: &str) -> Result<(), GeneratorError> {
         // Set the output directory
         self.gen_params.output_dir = format!("{}/{}", path, self.gen_params.project_name);
@@ -37,30 +436,422 @@ impl Generator {
         // Check if directory exists
         if std::path::Path::new(proj_dir).exists() {
             // If it does, delete it
-            std::fs::remove_dir_all(proj_dir).unwrap();
+            std::fs::remove_dir_all(proj_dir).map_err(GeneratorError::IoError)?;
         }
 
         // Create the directory if it doesn't exist
-        std::fs::create_dir_all(format!("{}/src", proj_dir)).unwrap();
+        std::fs::create_dir_all(proj_dir).map_err(GeneratorError::IoError)?;
 
-        // Create the file
-        let mut til_file = std::fs::File::create(format!("{}/src/{}.til", proj_dir, self.gen_params.project_name)).unwrap();
-        let mut td_file = std::fs::File::create(format!("{}/src/{}.td", proj_dir, self.gen_params.project_name)).unwrap();
+        let design = self.get_design()?;
 
-        use std::io::Write;
+        let backends = self.selected_backends.iter()
+            .map(|name| self.backends.get(name).ok_or_else(|| GeneratorError::UnknownBackend(name.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        let til = self.generate_til();
-        let td = self.generate_td();
+        // Check the design before anything is emitted. Fan-out is only a problem for backends
+        // that cannot duplicate streams themselves.
+        let point_to_point = backends.iter().any(|backend| backend.requires_point_to_point());
+        let violations: Vec<_> = checker::check_design(&design).into_iter()
+            .filter(|violation| point_to_point || !violation.is_fan_out())
+            .collect();
 
-        til_file.write_fmt(format_args!("{}", til)).unwrap();
-        td_file.write_fmt(format_args!("{}", td)).unwrap();
+        if !violations.is_empty() {
+            return Err(GeneratorError::DesignRuleViolations(violations));
+        }
+
+        // Run the selected backends in order
+        for backend in backends {
+            backend.generate(&design, &self.gen_params)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl GeneratorParams {
+    // The overrides of a path are checked against the schema when the samples are analyzed
+    fn apply_path_config(&mut self, path: &str, config: &PathConfig) -> Result<(), GeneratorError> {
+        let invalid = |err: &str| GeneratorError::InvalidPathConfig(path.to_string(), err.to_string());
+
+        match config.parser.as_deref() {
+            Some("raw") => self.schema.raw_paths.push(path.to_string()),
+            Some("map") => self.schema.map_paths.push(path.to_string()),
+            Some("tuple") => self.schema.tuple_paths.push(path.to_string()),
+            Some("decode") => self.formats.decode_paths.push(path.to_string()),
+            Some("enum") => {
+                if config.values.is_empty() || config.values.iter().any(|value| value.is_empty()) {
+                    return Err(invalid("an enum needs a list of values that are not empty"));
+                }
 
-        // Generate the files
-        let file_manager = self.analyzer.get_file_manager();
-        file_manager.generate_files(&self.gen_params.output_dir, &self.gen_params);
+                self.formats.enum_values.push((path.to_string(), config.values.clone()));
+            },
+            Some(name) => {
+                let format = StringFormat::from_name(name)
+                    .ok_or_else(|| invalid(&format!("unknown parser '{}', expected raw, map, tuple, decode, enum or a format", name)))?;
+
+                self.formats.formats.push((path.to_string(), format));
+            },
+            None => {},
+        }
+
+        if !config.values.is_empty() && config.parser.as_deref() != Some("enum") {
+            return Err(invalid("values can only be given for an enum"));
+        }
+
+        if let Some(int_width) = config.int_width {
+            if int_width == 0 {
+                return Err(invalid("the integer width has to be at least 1"));
+            }
+
+            self.schema.int_widths.push((path.to_string(), int_width));
+        }
 
-        file_manager.generate_toml(&self.gen_params.output_dir, &self.gen_params);
+        for (param, value) in config.get_component_params() {
+            if value == 0 {
+                return Err(invalid(&format!("{} has to be at least 1", param.get_name())));
+            }
+
+            self.tuning.path_params.push((path.to_string(), param, value));
+        }
+
+        if let Some(epc) = config.epc {
+            if epc == 0 {
+                return Err(invalid("the EPC has to be at least 1"));
+            }
+
+            if path == "$" {
+                return Err(invalid("the EPC of the whole document is set with epc at the top level"));
+            }
+
+            self.lanes.epcs.push((path.to_string(), epc));
+        }
+
+        let shape = stream_shape(config.synchronicity.as_deref(), config.complexity).map_err(|err| invalid(&err))?;
+        if shape != StreamShape::default() {
+            self.shapes.output_shapes.push((path.to_string(), shape));
+        }
+
+        if config.select == Some(false) {
+            self.schema.skip_paths.push(path.to_string());
+        }
+
+        if let Some(name) = &config.name {
+            if !is_identifier(name) {
+                return Err(invalid(&format!("'{}' is not a valid name, names start with a letter and consist of letters, digits and single underscores", name)));
+            }
+
+            self.outputs.output_names.push((path.to_string(), name.clone()));
+        }
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+// Parse the filters of a configuration, see `Filter::parse`
+fn parse_filters(specs: &[String]) -> Result<Vec<Filter>, GeneratorError> {
+    specs.iter().map(|spec| Filter::parse(spec).map_err(GeneratorError::InvalidFilter)).collect()
+}
+
+// Parse the aggregations of a configuration, see `Aggregation::parse`
+fn parse_aggregations(specs: &[String]) -> Result<Vec<Aggregation>, GeneratorError> {
+    specs.iter().map(|spec| Aggregation::parse(spec).map_err(GeneratorError::InvalidAggregation)).collect()
+}
+
+// The synchronicity and complexity of a stream as they are written in the configuration
+fn stream_shape(synchronicity: Option<&str>, complexity: Option<u8>) -> Result<StreamShape, String> {
+    let synchronicity = match synchronicity {
+        Some(name) => Some(Synchronicity::from_name(name)
+            .ok_or_else(|| format!("unknown synchronicity '{}', expected Sync, Flatten, Desync or FlatDesync", name))?),
+        None => None,
+    };
+
+    if let Some(complexity) = complexity {
+        if !(1..=8).contains(&complexity) {
+            return Err(format!("the complexity has to be between 1 and 8, not {}", complexity));
+        }
+    }
+
+    Ok(StreamShape::new(synchronicity, complexity))
+}
+
+// Whether a name can be used for a port in TIL, TydiLang and VHDL
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && !name.ends_with('_')
+        && !name.contains("__")
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::checker;
+    use super::super::testing::{connections, design_of_samples};
+    use super::super::types::streaming_interface::GenericType;
+
+    #[test]
+    fn routes_values_of_different_types_by_type() {
+        let design = design_of_samples(&[r#"{"a": 1}"#, r#"{"a": "one"}"#], |_| {}).unwrap();
+
+        let routers: Vec<_> = design.get_streamlets().iter().filter(|streamlet| streamlet.get_name().starts_with("union_router")).collect();
+        assert_eq!(routers.len(), 1);
+        assert_eq!(design.get_json_path(routers[0].get_name()), Some("$.a"));
+
+        // Every variant goes to the parser of its type, the tag tells which variant a value is
+        let connections = connections(&design.get_top_components()[0]);
+        let router = format!("{}_inst", routers[0].get_name());
+        let sink_of = |port: &str| connections.iter()
+            .find(|(source, _)| *source == format!("{}.{}", router, port))
+            .map(|(_, sink)| sink.clone())
+            .unwrap();
+        assert!(sink_of("output_integer").starts_with("int_parser"));
+        assert!(sink_of("output_string").starts_with("string_parser"));
+        assert!(!sink_of("tag").contains('.'));
+
+        assert!(checker::check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn rejects_missing_and_invalid_samples() {
+        let mut generator = Generator::new("test", 4, 64);
+        assert!(matches!(generator.analyze_samples(&[]), Err(GeneratorError::AnalyzerError(AnalyzerError::NoTop))));
+        assert!(matches!(generator.analyze_samples(&[r#"{"a": 1}"#, r#"{"a": "#]), Err(GeneratorError::JsonError(_))));
+    }
+
+    #[test]
+    fn parses_tuples_with_a_parser_per_position() {
+        let design = design_of_samples(&[r#"{"location": [52, 4]}"#], |generator| generator.set_tuples(&["$.location".to_string()])).unwrap();
+
+        // The demultiplexer sends every position to a parser of its own
+        let routes = connections(&design.get_top_components()[0]);
+        for (idx, parser) in ["int_parser_L3_00_inst.input", "int_parser_L3_01_inst.input"].iter().enumerate() {
+            let output = format!("tuple_demux_L3_00_inst.output_{}", idx);
+            assert!(routes.contains(&(output.clone(), parser.to_string())), "{} does not drive {}", output, parser);
+        }
+
+        assert!(checker::check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn rejects_tuple_paths_without_a_fixed_length_array() {
+        for path in ["$.location", "$.name"] {
+            let mut generator = Generator::new("test", 4, 64);
+            generator.set_tuples(&[path.to_string()]);
+
+            let result = generator.analyze_samples(&[r#"{"location": [52, 4], "name": "a"}"#, r#"{"location": [52], "name": "b"}"#]);
+            assert!(matches!(result, Err(GeneratorError::UnknownPath("fixed length array", unknown)) if unknown == path));
+        }
+    }
+
+    #[test]
+    fn forwards_raw_values_unparsed() {
+        let design = design_of_samples(&[r#"{"id": 1, "meta": {"tags": ["a"], "x": 2}}"#], |generator| generator.set_raw(&["$.meta".to_string()], None)).unwrap();
+
+        let passthroughs: Vec<_> = design.get_streamlets().iter().filter(|streamlet| streamlet.get_name().starts_with("raw_passthrough")).collect();
+        assert_eq!(passthroughs.len(), 1);
+        assert_eq!(design.get_json_path(passthroughs[0].get_name()), Some("$.meta"));
+
+        // Nothing below the raw value gets a parser of its own
+        assert!(!design.get_streamlets().iter().any(|streamlet| design.get_json_path(streamlet.get_name()).is_some_and(|path| path.starts_with("$.meta."))));
+        assert!(checker::check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn rejects_raw_paths_that_are_not_found() {
+        let mut generator = Generator::new("test", 4, 64);
+        generator.set_raw(&["$.missing".to_string()], None);
+
+        let result = generator.analyze_samples(&[r#"{"id": 1}"#]);
+        assert!(matches!(result, Err(GeneratorError::UnknownPath("value", path)) if path == "$.missing"));
+    }
+
+    #[test]
+    fn rejects_invalid_enums() {
+        let mut generator = Generator::new("test", 4, 64);
+        for spec in ["$.study", "$.study=CS,,EE", "$.study="] {
+            assert!(matches!(generator.add_enum(spec), Err(GeneratorError::InvalidEnum(invalid)) if invalid == spec));
+        }
+
+        // Only strings can be encoded
+        generator.add_enum("$.grade=A,B").unwrap();
+        let result = generator.analyze_samples(&[r#"{"study": "CS", "grade": 8}"#]);
+        assert!(matches!(result, Err(GeneratorError::UnknownPath("string", path)) if path == "$.grade"));
+    }
+
+    #[test]
+    fn rejects_invalid_formats() {
+        let mut generator = Generator::new("test", 4, 64);
+        for spec in ["$.birthdate", "$.birthdate=time"] {
+            assert!(matches!(generator.add_format(spec), Err(GeneratorError::InvalidFormat(invalid)) if invalid == spec));
+        }
+
+        // Only strings can be parsed in a format
+        generator.add_format("$.id = uuid").unwrap();
+        let result = generator.analyze_samples(&[r#"{"id": 7}"#]);
+        assert!(matches!(result, Err(GeneratorError::UnknownPath("string", path)) if path == "$.id"));
+    }
+
+    #[test]
+    fn decodes_the_strings_at_the_given_paths() {
+        let mut generator = Generator::new("test", 4, 64);
+        generator.set_decoded_strings(&["$.text".to_string()], false);
+        generator.analyze_samples(&[r#"{"text": "café", "name": "a"}"#]).unwrap();
+        let design = generator.get_design().unwrap();
+
+        let decoders: Vec<_> = design.get_streamlets().iter().filter(|streamlet| streamlet.get_name().starts_with("string_decoder")).collect();
+        assert_eq!(decoders.len(), 1);
+        assert_eq!(design.get_json_path(decoders[0].get_name()), Some("$.text"));
+
+        // The decoder takes the values of the key in place of a string parser, the other string is parsed as before
+        let connections = connections(&design.get_top_components()[0]);
+        let decoder = format!("{}_inst", decoders[0].get_name());
+        assert!(connections.iter().any(|(source, sink)| source.starts_with("key_parser") && sink.starts_with(&decoder)));
+        assert!(connections.iter().any(|(source, sink)| source.starts_with(&decoder) && !sink.contains('.')));
+        assert_eq!(connections.iter().filter(|(source, _)| source.starts_with("string_parser")).count(), 1);
+        assert!(checker::check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn rejects_decode_paths_that_are_no_strings() {
+        for path in ["$.count", "$.missing"] {
+            let mut generator = Generator::new("test", 4, 64);
+            generator.set_decoded_strings(&[path.to_string()], false);
+
+            let result = generator.analyze_samples(&[r#"{"count": 1}"#]);
+            assert!(matches!(result, Err(GeneratorError::UnknownPath("string", unknown)) if unknown == path));
+        }
+    }
+
+    #[test]
+    fn accepts_integers_beyond_64_bits() {
+        let mut generator = Generator::new("test", 4, 128);
+        generator.analyze_samples(&[r#"{"id": 170141183460469231731687303715884105728}"#]).unwrap();
+        let design = generator.get_design().unwrap();
+
+        let parser = design.get_streamlets().iter().find(|streamlet| streamlet.get_name().starts_with("int_parser")).unwrap();
+        assert!(parser.get_streams().get_generics().iter().any(|generic| generic.get_name() == "BITWIDTH" && matches!(generic.get_type(), GenericType::Positive(128))));
+    }
+
+    #[test]
+    fn rejects_integers_that_do_not_fit() {
+        let mut generator = Generator::new("test", 4, 64);
+        let result = generator.analyze_samples(&[r#"{"id": 1}"#, r#"{"id": 18446744073709551615}"#, r#"{"id": [300]}"#]);
+        assert!(result.is_ok());
+
+        // One more than the largest 64 bit integer, of which the last digit is lost when the sample is read
+        let result = generator.analyze_samples(&[r#"{"id": 1}"#, r#"{"id": 18446744073709551616}"#]);
+        assert!(matches!(result, Err(GeneratorError::IntegerTooWide(path, _, 64)) if path == "$.id"));
+    }
+
+    #[test]
+    fn merges_the_configuration_with_the_command_line() {
+        let mut generator = Generator::new("test", 4, 64);
+        generator.set_maps(&["$.a".to_string()], Some(3));
+        generator.set_insert_duplicators(true);
+
+        let config = Config::parse(r#"
+            map_min_keys = 5
+            no_duplicators = true
+            group_records = true
+            filter = ["$.b.x > 1"]
+
+            [paths."$.c"]
+            parser = "map"
+            int_width = 16
+        "#).unwrap();
+        generator.apply_config(&config).unwrap();
+
+        // The command line wins for options, switches set in either are enabled and lists are joined
+        let params = &generator.gen_params;
+        assert_eq!(params.schema.map_min_keys, Some(3));
+        assert!(!params.netlist.insert_duplicators);
+        assert!(params.netlist.group_records);
+        assert_eq!(params.schema.map_paths, vec!["$.a".to_string(), "$.c".to_string()]);
+        assert_eq!(params.outputs.filters.len(), 1);
+        assert_eq!(params.get_int_width("$.c"), 16);
+    }
+
+    #[test]
+    fn rejects_invalid_configurations() {
+        for (text, error) in [
+            ("pipeline_stages = 0", "pipeline_stages has to be at least 1"),
+            ("[paths.\"$.a\"]\nparser = \"xml\"", "unknown parser 'xml'"),
+            ("[paths.\"$.a\"]\nvalues = [\"x\"]", "values can only be given for an enum"),
+            ("[paths.\"$.a\"]\nparser = \"enum\"", "list of values"),
+            ("[paths.\"$.a\"]\nint_width = 0", "at least 1"),
+            ("[paths.\"$.a\"]\nname = \"a__b\"", "not a valid name"),
+        ] {
+            let mut generator = Generator::new("test", 4, 64);
+            let err = generator.apply_config(&Config::parse(text).unwrap()).unwrap_err().to_string();
+            assert!(err.contains(error), "'{}' failed with '{}'", text, err);
+        }
+
+        // Filters of the configuration are parsed when it is applied
+        let mut generator = Generator::new("test", 4, 64);
+        assert!(matches!(generator.apply_config(&Config::parse("filter = [\"$.a ~ 1\"]").unwrap()), Err(GeneratorError::InvalidFilter(_))));
+    }
+
+    #[test]
+    fn gives_every_schema_its_own_top_component() {
+        let config = Config::parse(r#"
+            [schemas.orders]
+            filter = ["$.total > 10"]
+
+            [schemas.users.paths."$.id"]
+            int_width = 32
+        "#).unwrap();
+
+        let mut generator = Generator::new("test", 4, 64);
+        generator.add_schema("orders", &[r#"{"id": 1, "total": 20}"#], &config.get_schemas()["orders"]).unwrap();
+        generator.add_schema("users", &[r#"{"id": 2, "name": "a"}"#], &config.get_schemas()["users"]).unwrap();
+
+        // The configuration of a schema does not carry over to the next one
+        assert!(generator.gen_params.outputs.filters.is_empty());
+        assert!(generator.gen_params.schema.int_widths.is_empty());
+
+        let design = generator.get_design().unwrap();
+        let tops: Vec<_> = design.get_top_components().iter().map(|top| top.get_name()).collect();
+        assert_eq!(tops, vec!["orders", "users"]);
+
+        // Only the orders are filtered, and only the id of the users is narrower
+        let orders = connections(&design.get_top_components()[0]);
+        assert!(orders.iter().any(|(source, _)| source.starts_with("orders_filter_gate")), "{:?}", orders);
+        let users = connections(&design.get_top_components()[1]);
+        assert!(!users.iter().any(|(source, _)| source.contains("filter")), "{:?}", users);
+
+        let id_widths: Vec<_> = design.get_streamlets().iter()
+            .filter(|streamlet| design.get_json_path(streamlet.get_name()) == Some("$.id") && streamlet.get_name().contains("int_parser"))
+            .map(|streamlet| match streamlet.get_streams().get_generics().iter().find(|generic| generic.get_name() == "BITWIDTH").unwrap().get_type() {
+                GenericType::Positive(width) => (streamlet.get_name().to_string(), *width),
+                _ => panic!("BITWIDTH is not positive"),
+            })
+            .collect();
+        assert_eq!(id_widths.len(), 2, "{:?}", id_widths);
+        assert!(id_widths.iter().all(|(name, width)| *width == if name.starts_with("users") { 32 } else { 64 }), "{:?}", id_widths);
+
+        // The stream types the schemas share are declared once
+        let mut stream_types: Vec<_> = design.get_stream_types().iter().map(|stream_type| stream_type.get_name()).collect();
+        let declared = stream_types.len();
+        stream_types.dedup();
+        assert_eq!(stream_types.len(), declared);
+
+        assert!(checker::check_design(&design).is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_schemas() {
+        let config = SchemaConfig::default();
+        let mut generator = Generator::new("test", 4, 64);
+        generator.add_schema("orders", &[r#"{"id": 1}"#], &config).unwrap();
+
+        for (name, error) in [("orders", "used by another schema"), ("2fa", "not a valid name"), ("a__b", "not a valid name")] {
+            let err = generator.add_schema(name, &[r#"{"id": 1}"#], &config).unwrap_err();
+            assert!(matches!(&err, GeneratorError::SchemaError(schema, _) if schema == name));
+            assert!(err.to_string().contains(error), "'{}' failed with '{}'", name, err);
+        }
+
+        // Errors in the samples of a schema are reported for the schema
+        let err = generator.add_schema("users", &[r#"{"id": "#], &config).unwrap_err();
+        assert!(matches!(err, GeneratorError::SchemaError(schema, inner) if schema == "users" && matches!(*inner, GeneratorError::JsonError(_))));
+    }
+}
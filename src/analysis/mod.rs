@@ -1,17 +1,31 @@
-use self::analyzer::Analyzer;
+use self::{analyzer::{Analyzer, type_manager::StreamShape}, backend::BackendRegistry};
 
 mod components;
 mod visualization;
 pub mod generator;
 pub mod analyzer;
 pub mod types;
-mod til;
-mod td;
+pub mod backend;
+pub mod syntax;
+pub mod checker;
+pub mod schema;
+pub mod filter;
+pub mod aggregate;
+pub mod format;
+pub mod validate;
+pub mod config;
+pub mod framing;
+pub mod params;
+#[cfg(test)]
+pub mod testing;
 
 
 pub struct Generator {
-    analyzer: Analyzer,
+    // An analyzer per schema, each of which has a top component of its own
+    analyzers: Vec<Analyzer>,
     gen_params: GeneratorParams,
+    backends: BackendRegistry,
+    selected_backends: Vec<String>,
 }
 
 #[derive(Default, Clone)]
@@ -23,6 +37,161 @@ pub struct GeneratorParams {
     project_name: String,
     namespace: String,
     comp_namespace: String,
+    // How the documents are delimited in the input of the top component
+    framing: framing::InputFraming,
+    // The name of a schema of several in the project, which names its top component and prefixes its components
+    schema_name: Option<String>,
+    netlist: NetlistParams,
+    schema: SchemaParams,
+    formats: FormatParams,
+    outputs: OutputParams,
+    tuning: TuningParams,
+    lanes: LaneParams,
+    shapes: ShapeParams,
+}
+
+/// How the components are connected into a design
+#[derive(Clone)]
+struct NetlistParams {
+    insert_duplicators: bool,
+    hierarchical: bool,
+    group_records: bool,
+}
+
+impl Default for NetlistParams {
+    fn default() -> Self {
+        NetlistParams {
+            insert_duplicators: true,
+            hierarchical: false,
+            group_records: false,
+        }
+    }
+}
+
+impl NetlistParams {
+    fn merge(&mut self, other: NetlistParams) {
+        self.insert_duplicators &= other.insert_duplicators;
+        self.hierarchical |= other.hierarchical;
+        self.group_records |= other.group_records;
+    }
+}
+
+/// Values that are parsed otherwise than their type in the samples suggests
+#[derive(Default, Clone)]
+struct SchemaParams {
+    map_paths: Vec<String>,
+    map_min_keys: Option<usize>,
+    tuple_paths: Vec<String>,
+    raw_paths: Vec<String>,
+    raw_depth: Option<usize>,
+    // Fields that are not parsed
+    skip_paths: Vec<String>,
+    // The path of an integer and the width it is parsed into
+    int_widths: Vec<(String, usize)>,
+}
+
+impl SchemaParams {
+    fn merge(&mut self, other: SchemaParams) {
+        self.map_paths.extend(other.map_paths);
+        self.map_min_keys = self.map_min_keys.or(other.map_min_keys);
+        self.tuple_paths.extend(other.tuple_paths);
+        self.raw_paths.extend(other.raw_paths);
+        self.raw_depth = self.raw_depth.or(other.raw_depth);
+        self.skip_paths.extend(other.skip_paths);
+        self.int_widths.extend(other.int_widths);
+    }
+}
+
+/// Strings that are parsed into something else than their characters
+#[derive(Default, Clone)]
+struct FormatParams {
+    // The path of a string and the values it is encoded as
+    enum_values: Vec<(String, Vec<String>)>,
+    enum_max_values: Option<usize>,
+    // The path of a string and the format it is parsed in
+    formats: Vec<(String, format::StringFormat)>,
+    detect_formats: bool,
+    decode_paths: Vec<String>,
+    keep_quotes: bool,
+}
+
+impl FormatParams {
+    fn merge(&mut self, other: FormatParams) {
+        self.enum_values.extend(other.enum_values);
+        self.enum_max_values = self.enum_max_values.or(other.enum_max_values);
+        self.formats.extend(other.formats);
+        self.detect_formats |= other.detect_formats;
+        self.decode_paths.extend(other.decode_paths);
+        self.keep_quotes |= other.keep_quotes;
+    }
+}
+
+/// What leaves the design next to the values of the parsers, and which of those values do
+#[derive(Default, Clone)]
+struct OutputParams {
+    array_length: bool,
+    array_index: bool,
+    filters: Vec<filter::Filter>,
+    aggregations: Vec<aggregate::Aggregation>,
+    validate: bool,
+    // The path of a value and the name of its outputs
+    output_names: Vec<(String, String)>,
+}
+
+impl OutputParams {
+    fn merge(&mut self, other: OutputParams) {
+        self.array_length |= other.array_length;
+        self.array_index |= other.array_index;
+        self.filters.extend(other.filters);
+        self.aggregations.extend(other.aggregations);
+        self.validate |= other.validate;
+        self.output_names.extend(other.output_names);
+    }
+}
+
+/// Tuning parameters of the templates for all components and for the components at a path
+#[derive(Default, Clone)]
+struct TuningParams {
+    component_params: Vec<(params::ComponentParam, usize)>,
+    path_params: Vec<(String, params::ComponentParam, usize)>,
+}
+
+impl TuningParams {
+    fn merge(&mut self, other: TuningParams) {
+        self.component_params.extend(other.component_params);
+        self.path_params.extend(other.path_params);
+    }
+}
+
+/// The number of lanes of the parsers of subtrees, where it differs from the EPC of the design
+#[derive(Default, Clone)]
+struct LaneParams {
+    // The path of a subtree and the number of characters its parsers handle per transfer
+    epcs: Vec<(String, usize)>,
+    // Give subtrees that only hold a small part of the characters of the samples fewer lanes
+    auto_epc: bool,
+}
+
+impl LaneParams {
+    fn merge(&mut self, other: LaneParams) {
+        self.epcs.extend(other.epcs);
+        self.auto_epc |= other.auto_epc;
+    }
+}
+
+/// The synchronicity and complexity of the outputs, where they differ from those of the streams
+#[derive(Default, Clone)]
+struct ShapeParams {
+    // The synchronicity and complexity of the outputs at a path and of the outputs of a stream type
+    output_shapes: Vec<(String, StreamShape)>,
+    type_shapes: Vec<(String, StreamShape)>,
+}
+
+impl ShapeParams {
+    fn merge(&mut self, other: ShapeParams) {
+        self.output_shapes.extend(other.output_shapes);
+        self.type_shapes.extend(other.type_shapes);
+    }
 }
 
 impl GeneratorParams {
@@ -38,9 +207,71 @@ impl GeneratorParams {
             output_dir: output_dir.to_owned(),
             project_name,
             namespace: til_ns,
-            comp_namespace: comp_ns
+            comp_namespace: comp_ns,
+            ..Default::default()
         }
     }
+
+    // Add the features of other parameters (e.g. of a configuration) to these: switches that are set
+    // in either are enabled, lists are joined and these win for other parameters
+    fn merge_features(&mut self, other: GeneratorParams) {
+        self.netlist.merge(other.netlist);
+        self.schema.merge(other.schema);
+        self.formats.merge(other.formats);
+        self.outputs.merge(other.outputs);
+        self.tuning.merge(other.tuning);
+        self.lanes.merge(other.lanes);
+        self.shapes.merge(other.shapes);
+    }
+
+    // The width of the integers at a path
+    pub fn get_int_width(&self, path: &str) -> usize {
+        self.schema.int_widths.iter()
+            .find(|(int_path, _)| int_path == path)
+            .map_or(self.int_width, |(_, width)| *width)
+    }
+
+    // The value of a tuning parameter for the component at a path, the value for the path takes
+    // precedence over the value for all components and the default of the template
+    pub fn get_component_param(&self, param: params::ComponentParam, path: &str) -> usize {
+        self.tuning.path_params.iter()
+            .find(|(param_path, path_param, _)| param_path == path && *path_param == param)
+            .map(|(_, _, value)| *value)
+            .or_else(|| self.tuning.component_params.iter().find(|(global, _)| *global == param).map(|(_, value)| *value))
+            .unwrap_or_else(|| param.get_default())
+    }
+
+    // The number of lanes of the parsers at a path, which is the number of the closest enclosing
+    // subtree that has one of its own
+    pub fn get_epc(&self, path: &str) -> usize {
+        self.lanes.epcs.iter()
+            .filter(|(subtree, _)| path.strip_prefix(subtree.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '['])))
+            .max_by_key(|(subtree, _)| subtree.len())
+            .map_or(self.epc, |(_, epc)| *epc)
+    }
+
+    // The synchronicity and complexity of the outputs at a path, of which the stream has one of the given
+    // type names, the shape for the path takes precedence over the shape for the type
+    pub fn get_stream_shape(&self, path: &str, type_names: &[&str]) -> StreamShape {
+        let path_shape = self.shapes.output_shapes.iter()
+            .find(|(shape_path, _)| shape_path == path)
+            .map_or(StreamShape::default(), |(_, shape)| *shape);
+        let type_shape = self.shapes.type_shapes.iter()
+            .find(|(type_name, _)| type_names.contains(&type_name.as_str()))
+            .map_or(StreamShape::default(), |(_, shape)| *shape);
+
+        path_shape.or(type_shape)
+    }
+
+    // The name of the top component, which is the name of the schema when the project has several
+    pub fn get_top_name(&self) -> &str {
+        self.schema_name.as_deref().unwrap_or("top")
+    }
+
+    // The parameters for the files of components with another number of lanes than the design
+    pub fn with_epc(&self, epc: usize) -> GeneratorParams {
+        GeneratorParams { epc, ..self.clone() }
+    }
 }
 
 fn validate_project_name(project_name: &str) -> Result<String, GeneratorError> {
@@ -104,4 +335,57 @@ pub enum GeneratorError {
     AnalyzerError(analyzer::AnalyzerError),
     InvalidProjectName,
     JsonError(json::JsonError),
+    UnknownBackend(String),
+    // The kind of value that was expected and the path
+    UnknownPath(&'static str, String),
+    // What is wrong with the configuration, of a path as well
+    InvalidConfig(String),
+    InvalidPathConfig(String, String),
+    InvalidFilter(filter::FilterError),
+    InvalidAggregation(aggregate::AggregateError),
+    // An enum that is not given as `<path>=<value>,<value>,...`
+    InvalidEnum(String),
+    // A format that is not given as `<path>=<format>` with a known format
+    InvalidFormat(String),
+    // A framing that is not one of the known framings
+    InvalidFraming(String),
+    // An integer of a sample at a path that does not fit in the integer width
+    IntegerTooWide(String, String, usize),
+    // The name of a schema of several and what is wrong with it
+    SchemaError(String, Box<GeneratorError>),
+    IoError(std::io::Error),
+    SyntaxError(syntax::SyntaxError),
+    DesignRuleViolations(Vec<checker::DesignRuleViolation>),
+}
+
+impl std::fmt::Display for GeneratorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GeneratorError::AnalyzerError(err) => write!(f, "analysis failed: {}", err),
+            GeneratorError::InvalidProjectName => write!(f, "invalid project name"),
+            GeneratorError::JsonError(err) => write!(f, "invalid JSON input: {}", err),
+            GeneratorError::UnknownBackend(name) => write!(f, "unknown backend '{}'", name),
+            GeneratorError::UnknownPath(kind, path) => write!(f, "no {} at path '{}'", kind, path),
+            GeneratorError::InvalidConfig(err) => write!(f, "invalid configuration: {}", err),
+            GeneratorError::InvalidPathConfig(path, err) => write!(f, "invalid configuration of path '{}': {}", path, err),
+            GeneratorError::InvalidFilter(err) => write!(f, "invalid filter: {}", err),
+            GeneratorError::InvalidAggregation(err) => write!(f, "invalid aggregation: {}", err),
+            GeneratorError::InvalidFormat(spec) => write!(f, "invalid format '{}', expected '<path>=<format>' with date, datetime, uuid, ipv4, hex or int", spec),
+            GeneratorError::InvalidFraming(name) => write!(f, "invalid framing '{}', expected document, stream, batches or ndjson", name),
+            GeneratorError::IntegerTooWide(path, value, width) => write!(f, "integer {} at path '{}' does not fit in {} bits, increase --int-width or the int_width of the path", value, path, width),
+            GeneratorError::SchemaError(name, err) => write!(f, "schema '{}': {}", name, err),
+            GeneratorError::InvalidEnum(spec) => write!(f, "invalid enum '{}', expected '<path>=<value>,<value>,...'", spec),
+            GeneratorError::IoError(err) => write!(f, "{}", err),
+            GeneratorError::SyntaxError(err) => write!(f, "invalid output: {}", err),
+            GeneratorError::DesignRuleViolations(violations) => {
+                writeln!(f, "the design violates {} design rule(s):", violations.len())?;
+
+                for violation in violations {
+                    writeln!(f, "  {}", violation)?;
+                }
+
+                Ok(())
+            },
+        }
+    }
 }
\ No newline at end of file
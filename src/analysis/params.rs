@@ -0,0 +1,67 @@
+use super::types::streaming_interface::{Generic, GenericType};
+
+/**********************************************************************************
+ * Tuning parameters of the parser templates. They do not change what is parsed, *
+ * only how, and become generics of the streamlets as well as the defaults of    *
+ * the generics of the generated VHDL entities.                                  *
+ **********************************************************************************/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentParam {
+    // The number of stages the conversion of the integer parser is split into, has to divide its width
+    PipelineStages,
+    // The depth of the buffer in which a key parser holds the value while its key is matched
+    KeyBufferDepth,
+    // The width of the counter with which an array parser tracks its elements
+    ElementCounterWidth,
+}
+
+impl ComponentParam {
+    pub const ALL: [ComponentParam; 3] = [ComponentParam::PipelineStages, ComponentParam::KeyBufferDepth, ComponentParam::ElementCounterWidth];
+
+    /// The name in the configuration and in the templates
+    pub fn get_name(self) -> &'static str {
+        match self {
+            ComponentParam::PipelineStages => "pipeline_stages",
+            ComponentParam::KeyBufferDepth => "key_buffer_depth",
+            ComponentParam::ElementCounterWidth => "element_counter_width",
+        }
+    }
+
+    /// The name of the generic of the VHDL entity
+    pub fn get_generic_name(self) -> &'static str {
+        match self {
+            ComponentParam::PipelineStages => "PIPELINE_STAGES",
+            ComponentParam::KeyBufferDepth => "DLY_COMP_BUFF_DEPTH",
+            ComponentParam::ElementCounterWidth => "ELEMENT_COUNTER_BW",
+        }
+    }
+
+    /// The kind of value the parameter belongs to
+    pub fn get_component_kind(self) -> &'static str {
+        match self {
+            ComponentParam::PipelineStages => "integer",
+            ComponentParam::KeyBufferDepth => "record field",
+            ComponentParam::ElementCounterWidth => "array",
+        }
+    }
+
+    /// The default of the template
+    pub fn get_default(self) -> usize {
+        match self {
+            ComponentParam::PipelineStages => 1,
+            ComponentParam::KeyBufferDepth => 5,
+            ComponentParam::ElementCounterWidth => 4,
+        }
+    }
+
+    pub fn to_generic(self, value: usize) -> Generic {
+        let generic_type = match self {
+            ComponentParam::PipelineStages => GenericType::Natural(value),
+            ComponentParam::KeyBufferDepth => GenericType::Integer(value as isize),
+            ComponentParam::ElementCounterWidth => GenericType::Natural(value),
+        };
+
+        Generic::new(self.get_generic_name(), generic_type)
+    }
+}
@@ -0,0 +1,834 @@
+use std::collections::HashMap;
+
+use json::JsonValue;
+
+use super::format::StringFormat;
+
+/**********************************************************************************
+ * Schema inference: every sample document is turned into a schema and the       *
+ * schemas of all samples (and of all elements of an array) are merged. Values   *
+ * that are seen with different types become a union of those types.             *
+ **********************************************************************************/
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schema {
+    String,
+    Integer,
+    Boolean,
+    Null,
+    // The schema of the elements, none if the array was always empty
+    Array(Option<Box<Schema>>),
+    // An array with a schema per position, every sample has the same length
+    Tuple(Vec<Schema>),
+    Object(Vec<(String, Schema)>),
+    // An object of which the keys are data, with the schema of all values
+    Map(Box<Schema>),
+    // Never nested and never contains two variants of the same kind
+    Union(Vec<Schema>),
+    // A value that is forwarded as JSON text instead of being parsed, with the schema it would have
+    Raw(Box<Schema>),
+    // A string that only takes one of a fixed set of values
+    Enum(Vec<String>),
+    // A string in a format that is parsed into a binary value
+    Formatted(StringFormat),
+    // A string of which the escapes are decoded
+    Decoded,
+}
+
+impl Schema {
+    pub fn from_json(value: &JsonValue) -> Schema {
+        match value {
+            JsonValue::Short(_) | JsonValue::String(_) => Schema::String,
+            JsonValue::Number(_) => Schema::Integer,
+            JsonValue::Boolean(_) => Schema::Boolean,
+            JsonValue::Null => Schema::Null,
+            // Positions are kept apart until all samples are merged, see `detect_tuples`
+            JsonValue::Array(elements) if elements.is_empty() => Schema::Array(None),
+            JsonValue::Array(elements) => Schema::Tuple(elements.iter().map(Schema::from_json).collect()),
+            JsonValue::Object(_) => Schema::Object(
+                value.entries()
+                    .map(|(key, value)| (key.to_string(), Schema::from_json(value)))
+                    .collect()
+            ),
+        }
+    }
+
+    /// Merge the schema of another sample into this one
+    pub fn merge(self, other: Schema) -> Schema {
+        match (self, other) {
+            (Schema::Union(mut variants), other) => {
+                let others = match other {
+                    Schema::Union(others) => others,
+                    other => vec![other],
+                };
+
+                for other in others {
+                    match variants.iter().position(|variant| variant.get_kind() == other.get_kind()) {
+                        Some(idx) => {
+                            let variant = variants.remove(idx);
+                            variants.insert(idx, variant.merge(other));
+                        },
+                        None => variants.push(other),
+                    }
+                }
+
+                Schema::Union(variants)
+            },
+            (schema, Schema::Union(variants)) => Schema::Union(vec![schema]).merge(Schema::Union(variants)),
+            (Schema::Array(element), Schema::Array(other_element)) => Schema::Array(
+                match (element, other_element) {
+                    (Some(element), Some(other_element)) => Some(Box::new(element.merge(*other_element))),
+                    (element, other_element) => element.or(other_element),
+                }
+            ),
+            (Schema::Tuple(positions), Schema::Tuple(other_positions)) if positions.len() == other_positions.len() => Schema::Tuple(
+                positions.into_iter()
+                    .zip(other_positions)
+                    .map(|(position, other)| position.merge(other))
+                    .collect()
+            ),
+            // Arrays of different lengths are not tuples
+            (Schema::Tuple(positions), other) if other.get_kind() == "array" => {
+                Schema::Array(Schema::merge_all(positions).map(Box::new)).merge(other)
+            },
+            (schema, Schema::Tuple(positions)) if schema.get_kind() == "array" => {
+                schema.merge(Schema::Array(Schema::merge_all(positions).map(Box::new)))
+            },
+            (Schema::Map(value), Schema::Map(other_value)) => Schema::Map(Box::new(value.merge(*other_value))),
+            (Schema::Map(value), Schema::Object(fields)) | (Schema::Object(fields), Schema::Map(value)) => Schema::Map(Box::new(
+                fields.into_iter().fold(*value, |value, (_, field)| value.merge(field))
+            )),
+            (Schema::Object(mut fields), Schema::Object(other_fields)) => {
+                // Fields keep the order in which they were first seen
+                for (key, other) in other_fields {
+                    match fields.iter().position(|(field, _)| *field == key) {
+                        Some(idx) => {
+                            let (key, schema) = fields.remove(idx);
+                            fields.insert(idx, (key, schema.merge(other)));
+                        },
+                        None => fields.push((key, other)),
+                    }
+                }
+
+                Schema::Object(fields)
+            },
+            (schema, other) if schema.get_kind() == other.get_kind() => schema,
+            (schema, other) => Schema::Union(vec![schema, other]),
+        }
+    }
+
+    /// Collect the paths of the keys of objects that are missing from some of the objects of a sample
+    pub fn collect_optional_keys(&self, value: &JsonValue, path: &str, optional: &mut Vec<String>) {
+        match (self, value) {
+            (Schema::Object(fields), JsonValue::Object(object)) => {
+                for (key, field) in fields {
+                    let field_path = format!("{}.{}", path, key);
+
+                    match object.get(key) {
+                        Some(field_value) => field.collect_optional_keys(field_value, &field_path, optional),
+                        None if !optional.contains(&field_path) => optional.push(field_path),
+                        None => {},
+                    }
+                }
+            },
+            (Schema::Map(schema), JsonValue::Object(_)) => {
+                for (_, entry) in value.entries() {
+                    schema.collect_optional_keys(entry, &format!("{}.*", path), optional);
+                }
+            },
+            (Schema::Array(Some(schema)), JsonValue::Array(elements)) => {
+                for element in elements {
+                    schema.collect_optional_keys(element, &format!("{}[]", path), optional);
+                }
+            },
+            (Schema::Tuple(positions), JsonValue::Array(elements)) => {
+                for (idx, (schema, element)) in positions.iter().zip(elements).enumerate() {
+                    schema.collect_optional_keys(element, &format!("{}[{}]", path, idx), optional);
+                }
+            },
+            (Schema::Union(variants), value) => {
+                for variant in variants {
+                    variant.collect_optional_keys(value, path, optional);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// The name of the kind of value, also used to name the variants of a union
+    pub fn get_kind(&self) -> &str {
+        match self {
+            Schema::String | Schema::Enum(_) | Schema::Formatted(_) | Schema::Decoded => "string",
+            Schema::Integer => "integer",
+            Schema::Boolean => "boolean",
+            Schema::Null => "null",
+            Schema::Array(_) | Schema::Tuple(_) => "array",
+            Schema::Object(_) | Schema::Map(_) => "object",
+            Schema::Union(_) => "union",
+            Schema::Raw(schema) => schema.get_kind(),
+        }
+    }
+
+    /// The number of nested objects and arrays inside the value
+    pub fn get_depth(&self) -> usize {
+        match self {
+            Schema::Array(element) => 1 + element.as_ref().map_or(0, |element| element.get_depth()),
+            Schema::Tuple(positions) => 1 + positions.iter().map(Schema::get_depth).max().unwrap_or(0),
+            Schema::Object(fields) => 1 + fields.iter().map(|(_, field)| field.get_depth()).max().unwrap_or(0),
+            Schema::Map(value) => 1 + value.get_depth(),
+            Schema::Union(variants) => variants.iter().map(Schema::get_depth).max().unwrap_or(0),
+            Schema::Raw(schema) => schema.get_depth(),
+            _ => 0,
+        }
+    }
+
+    fn merge_all(schemas: Vec<Schema>) -> Option<Schema> {
+        schemas.into_iter().reduce(Schema::merge)
+    }
+
+    /// Keep arrays as tuples when they are configured as one (by path, e.g. `$.location`) or when
+    /// the type of their elements differs by position, all other arrays get a single element schema
+    /// Every configured path that is found is added to `found`
+    pub fn detect_tuples(self, path: &str, tuple_paths: &[String], found: &mut Vec<String>) -> Schema {
+        match self {
+            Schema::Tuple(positions) => {
+                let configured = tuple_paths.iter().any(|tuple_path| tuple_path == path);
+                let heterogeneous = positions.windows(2).any(|pair| pair[0].get_kind() != pair[1].get_kind());
+
+                if configured {
+                    found.push(path.to_string());
+                }
+
+                if configured || heterogeneous {
+                    Schema::Tuple(
+                        positions.into_iter()
+                            .enumerate()
+                            .map(|(idx, position)| position.detect_tuples(&format!("{}[{}]", path, idx), tuple_paths, found))
+                            .collect()
+                    )
+                } else {
+                    Schema::Array(Schema::merge_all(positions).map(Box::new)).detect_tuples(path, tuple_paths, found)
+                }
+            },
+            Schema::Array(Some(element)) => Schema::Array(Some(Box::new(element.detect_tuples(&format!("{}[]", path), tuple_paths, found)))),
+            Schema::Object(fields) => Schema::Object(
+                fields.into_iter()
+                    .map(|(key, field)| {
+                        let field = field.detect_tuples(&format!("{}.{}", path, key), tuple_paths, found);
+                        (key, field)
+                    })
+                    .collect()
+            ),
+            Schema::Map(value) => Schema::Map(Box::new(value.detect_tuples(&format!("{}.*", path), tuple_paths, found))),
+            Schema::Union(variants) => Schema::Union(
+                variants.into_iter()
+                    .map(|variant| variant.detect_tuples(path, tuple_paths, found))
+                    .collect()
+            ),
+            schema => schema,
+        }
+    }
+
+    /// Turn objects into maps when they are configured as one (by path, e.g. `$.counts`) or when
+    /// they have at least `min_keys` keys of which all values have the same type
+    /// Every configured path that is found is added to `found`, and every configured object that
+    /// is empty in all samples (so the schema of its values is unknown) to `empty`
+    pub fn detect_maps(self, path: &str, map_paths: &[String], min_keys: Option<usize>, found: &mut Vec<String>, empty: &mut Vec<String>) -> Schema {
+        match self {
+            Schema::Object(fields) => {
+                let configured = map_paths.iter().any(|map_path| map_path == path);
+
+                let values = fields.iter()
+                    .map(|(_, field)| field.clone())
+                    .reduce(Schema::merge);
+
+                let inferred = match (min_keys, &values) {
+                    (Some(min_keys), Some(values)) => fields.len() >= min_keys && !matches!(values, Schema::Union(_)),
+                    _ => false,
+                };
+
+                if configured {
+                    found.push(path.to_string());
+
+                    if values.is_none() {
+                        empty.push(path.to_string());
+                    }
+                }
+
+                match values {
+                    Some(values) if configured || inferred => Schema::Map(Box::new(
+                        values.detect_maps(&format!("{}.*", path), map_paths, min_keys, found, empty)
+                    )),
+                    _ => Schema::Object(
+                        fields.into_iter()
+                            .map(|(key, field)| {
+                                let field = field.detect_maps(&format!("{}.{}", path, key), map_paths, min_keys, found, empty);
+                                (key, field)
+                            })
+                            .collect()
+                    ),
+                }
+            },
+            Schema::Map(value) => Schema::Map(Box::new(value.detect_maps(&format!("{}.*", path), map_paths, min_keys, found, empty))),
+            Schema::Array(Some(element)) => Schema::Array(Some(Box::new(element.detect_maps(&format!("{}[]", path), map_paths, min_keys, found, empty)))),
+            Schema::Tuple(positions) => Schema::Tuple(
+                positions.into_iter()
+                    .enumerate()
+                    .map(|(idx, position)| position.detect_maps(&format!("{}[{}]", path, idx), map_paths, min_keys, found, empty))
+                    .collect()
+            ),
+            Schema::Union(variants) => Schema::Union(
+                variants.into_iter()
+                    .map(|variant| variant.detect_maps(path, map_paths, min_keys, found, empty))
+                    .collect()
+            ),
+            schema => schema,
+        }
+    }
+
+    /// Forward values as raw JSON text when they are configured as such (by path, e.g. `$.metadata`)
+    /// or when they are objects or arrays nested deeper than `max_depth`, the root being at depth 0
+    /// Every configured path that is found is added to `found`
+    pub fn detect_raw(self, path: &str, raw_paths: &[String], max_depth: Option<usize>, depth: usize, found: &mut Vec<String>) -> Schema {
+        let configured = raw_paths.iter().any(|raw_path| raw_path == path);
+        let too_deep = self.get_depth() > 0 && max_depth.is_some_and(|max_depth| depth > max_depth);
+
+        if configured {
+            found.push(path.to_string());
+        }
+
+        if configured || too_deep {
+            return Schema::Raw(Box::new(self));
+        }
+
+        match self {
+            Schema::Object(fields) => Schema::Object(
+                fields.into_iter()
+                    .map(|(key, field)| {
+                        let field = field.detect_raw(&format!("{}.{}", path, key), raw_paths, max_depth, depth + 1, found);
+                        (key, field)
+                    })
+                    .collect()
+            ),
+            Schema::Map(value) => Schema::Map(Box::new(value.detect_raw(&format!("{}.*", path), raw_paths, max_depth, depth + 1, found))),
+            Schema::Array(Some(element)) => Schema::Array(Some(Box::new(element.detect_raw(&format!("{}[]", path), raw_paths, max_depth, depth + 1, found)))),
+            Schema::Tuple(positions) => Schema::Tuple(
+                positions.into_iter()
+                    .enumerate()
+                    .map(|(idx, position)| position.detect_raw(&format!("{}[{}]", path, idx), raw_paths, max_depth, depth + 1, found))
+                    .collect()
+            ),
+            // Variants are the same value at the same path, so only the values nested in them can be configured or too deep
+            Schema::Union(variants) => Schema::Union(
+                variants.into_iter()
+                    .map(|variant| variant.detect_raw(path, raw_paths, max_depth, depth, found))
+                    .collect()
+            ),
+            schema => schema,
+        }
+    }
+
+    /// Collect the distinct strings of a sample per path, in the order they are first seen
+    pub fn collect_strings(&self, value: &JsonValue, path: &str, strings: &mut HashMap<String, Vec<String>>) {
+        match (self, value) {
+            (Schema::String, JsonValue::Short(_) | JsonValue::String(_)) => {
+                let seen = strings.entry(path.to_string()).or_default();
+                let string = value.as_str().unwrap_or_default();
+
+                if !seen.iter().any(|seen| seen == string) {
+                    seen.push(string.to_string());
+                }
+            },
+            (Schema::Object(fields), JsonValue::Object(_)) => {
+                for (key, field) in fields {
+                    field.collect_strings(&value[key.as_str()], &format!("{}.{}", path, key), strings);
+                }
+            },
+            (Schema::Map(schema), JsonValue::Object(_)) => {
+                for (_, entry) in value.entries() {
+                    schema.collect_strings(entry, &format!("{}.*", path), strings);
+                }
+            },
+            (Schema::Array(Some(schema)), JsonValue::Array(elements)) => {
+                for element in elements {
+                    schema.collect_strings(element, &format!("{}[]", path), strings);
+                }
+            },
+            (Schema::Tuple(positions), JsonValue::Array(elements)) => {
+                for (idx, (schema, element)) in positions.iter().zip(elements).enumerate() {
+                    schema.collect_strings(element, &format!("{}[{}]", path, idx), strings);
+                }
+            },
+            // Only the variant of the same kind can hold strings
+            (Schema::Union(variants), value) => {
+                for variant in variants {
+                    variant.collect_strings(value, path, strings);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Count the characters of the values of a sample per path, e.g. to tell how much of the input a subtree handles
+    pub fn collect_lengths(&self, value: &JsonValue, path: &str, lengths: &mut HashMap<String, usize>) {
+        *lengths.entry(path.to_string()).or_default() += value.dump().len();
+
+        self.collect_nested_lengths(value, path, lengths);
+    }
+
+    fn collect_nested_lengths(&self, value: &JsonValue, path: &str, lengths: &mut HashMap<String, usize>) {
+        match (self, value) {
+            (Schema::Object(fields), JsonValue::Object(object)) => {
+                for (key, field) in fields {
+                    if let Some(field_value) = object.get(key) {
+                        field.collect_lengths(field_value, &format!("{}.{}", path, key), lengths);
+                    }
+                }
+            },
+            (Schema::Map(schema), JsonValue::Object(_)) => {
+                for (_, entry) in value.entries() {
+                    schema.collect_lengths(entry, &format!("{}.*", path), lengths);
+                }
+            },
+            (Schema::Array(Some(schema)), JsonValue::Array(elements)) => {
+                for element in elements {
+                    schema.collect_lengths(element, &format!("{}[]", path), lengths);
+                }
+            },
+            (Schema::Tuple(positions), JsonValue::Array(elements)) => {
+                for (idx, (schema, element)) in positions.iter().zip(elements).enumerate() {
+                    schema.collect_lengths(element, &format!("{}[{}]", path, idx), lengths);
+                }
+            },
+            // The value is already counted for the path of the union
+            (Schema::Union(variants), value) => {
+                for variant in variants {
+                    variant.collect_nested_lengths(value, path, lengths);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Collect the paths of the record fields that hold an object or an array, enclosing fields first
+    pub fn collect_subtrees(&self, path: &str, subtrees: &mut Vec<String>) {
+        match self {
+            Schema::Object(fields) => {
+                for (key, field) in fields {
+                    let field_path = format!("{}.{}", path, key);
+
+                    if matches!(field, Schema::Object(_) | Schema::Map(_) | Schema::Array(Some(_)) | Schema::Tuple(_)) {
+                        subtrees.push(field_path.clone());
+                    }
+
+                    field.collect_subtrees(&field_path, subtrees);
+                }
+            },
+            Schema::Map(schema) => schema.collect_subtrees(&format!("{}.*", path), subtrees),
+            Schema::Array(Some(schema)) => schema.collect_subtrees(&format!("{}[]", path), subtrees),
+            Schema::Tuple(positions) => {
+                for (idx, schema) in positions.iter().enumerate() {
+                    schema.collect_subtrees(&format!("{}[{}]", path, idx), subtrees);
+                }
+            },
+            Schema::Union(variants) => {
+                for variant in variants {
+                    variant.collect_subtrees(path, subtrees);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Find an integer of a sample that is parsed but does not fit in the width of its path,
+    /// as its path and value
+    pub fn find_wide_integer(&self, value: &JsonValue, path: &str, int_width: &dyn Fn(&str) -> usize) -> Option<(String, String)> {
+        match (self, value) {
+            (Schema::Integer, JsonValue::Number(number)) => {
+                // The parser reads the digits and ignores the sign
+                let (_, mantissa, exponent) = number.as_parts();
+
+                // The digits that do not fit in the 64 bit mantissa are dropped when the sample is
+                // read, so the largest value the sample can have is checked
+                let truncated = mantissa.checked_mul(10).and_then(|mantissa| mantissa.checked_add(9)).is_none();
+                let digits = format!("{}{}", mantissa, (if truncated { "9" } else { "0" }).repeat(exponent.max(0) as usize));
+
+                (!fits_in_bits(&digits, int_width(path))).then(|| (path.to_string(), value.dump()))
+            },
+            (Schema::Object(fields), JsonValue::Object(_)) => fields.iter()
+                .find_map(|(key, field)| field.find_wide_integer(&value[key.as_str()], &format!("{}.{}", path, key), int_width)),
+            (Schema::Map(schema), JsonValue::Object(_)) => value.entries()
+                .find_map(|(_, entry)| schema.find_wide_integer(entry, &format!("{}.*", path), int_width)),
+            (Schema::Array(Some(schema)), JsonValue::Array(elements)) => elements.iter()
+                .find_map(|element| schema.find_wide_integer(element, &format!("{}[]", path), int_width)),
+            (Schema::Tuple(positions), JsonValue::Array(elements)) => positions.iter()
+                .zip(elements)
+                .enumerate()
+                .find_map(|(idx, (schema, element))| schema.find_wide_integer(element, &format!("{}[{}]", path, idx), int_width)),
+            (Schema::Union(variants), value) => variants.iter()
+                .find_map(|variant| variant.find_wide_integer(value, path, int_width)),
+            _ => None,
+        }
+    }
+
+    /// Turn strings into enums when their values are configured (by path, e.g. `$.study`) or when
+    /// at most `max_values` distinct values are seen in all samples
+    /// Every configured path that is found is added to `found`
+    pub fn detect_enums(self, path: &str, enum_values: &[(String, Vec<String>)], strings: &HashMap<String, Vec<String>>, max_values: Option<usize>, found: &mut Vec<String>) -> Schema {
+        match self {
+            // A configured enum takes precedence over a detected format
+            schema @ (Schema::String | Schema::Formatted(_)) => {
+                if let Some((_, values)) = enum_values.iter().find(|(enum_path, _)| enum_path == path) {
+                    found.push(path.to_string());
+                    return Schema::Enum(values.clone());
+                }
+
+                match (strings.get(path), max_values) {
+                    (Some(seen), Some(max_values)) if schema == Schema::String && seen.len() <= max_values => Schema::Enum(seen.clone()),
+                    _ => schema,
+                }
+            },
+            Schema::Object(fields) => Schema::Object(
+                fields.into_iter()
+                    .map(|(key, field)| {
+                        let field = field.detect_enums(&format!("{}.{}", path, key), enum_values, strings, max_values, found);
+                        (key, field)
+                    })
+                    .collect()
+            ),
+            Schema::Map(value) => Schema::Map(Box::new(value.detect_enums(&format!("{}.*", path), enum_values, strings, max_values, found))),
+            Schema::Array(Some(element)) => Schema::Array(Some(Box::new(element.detect_enums(&format!("{}[]", path), enum_values, strings, max_values, found)))),
+            Schema::Tuple(positions) => Schema::Tuple(
+                positions.into_iter()
+                    .enumerate()
+                    .map(|(idx, position)| position.detect_enums(&format!("{}[{}]", path, idx), enum_values, strings, max_values, found))
+                    .collect()
+            ),
+            Schema::Union(variants) => Schema::Union(
+                variants.into_iter()
+                    .map(|variant| variant.detect_enums(path, enum_values, strings, max_values, found))
+                    .collect()
+            ),
+            schema => schema,
+        }
+    }
+
+    /// Parse strings in a format when the format is configured for their path (e.g. `$.birthdate`)
+    /// or, when `detect` is set, when all of their values in the samples have that format
+    /// Every configured path that is found is added to `found`
+    pub fn detect_formats(self, path: &str, formats: &[(String, StringFormat)], strings: &HashMap<String, Vec<String>>, detect: bool, int_width: usize, found: &mut Vec<String>) -> Schema {
+        match self {
+            Schema::String => {
+                if let Some((_, format)) = formats.iter().find(|(format_path, _)| format_path == path) {
+                    found.push(path.to_string());
+                    return Schema::Formatted(*format);
+                }
+
+                match strings.get(path) {
+                    Some(seen) if detect => StringFormat::detect(seen, int_width).map_or(Schema::String, Schema::Formatted),
+                    _ => Schema::String,
+                }
+            },
+            Schema::Object(fields) => Schema::Object(
+                fields.into_iter()
+                    .map(|(key, field)| {
+                        let field = field.detect_formats(&format!("{}.{}", path, key), formats, strings, detect, int_width, found);
+                        (key, field)
+                    })
+                    .collect()
+            ),
+            Schema::Map(value) => Schema::Map(Box::new(value.detect_formats(&format!("{}.*", path), formats, strings, detect, int_width, found))),
+            Schema::Array(Some(element)) => Schema::Array(Some(Box::new(element.detect_formats(&format!("{}[]", path), formats, strings, detect, int_width, found)))),
+            Schema::Tuple(positions) => Schema::Tuple(
+                positions.into_iter()
+                    .enumerate()
+                    .map(|(idx, position)| position.detect_formats(&format!("{}[{}]", path, idx), formats, strings, detect, int_width, found))
+                    .collect()
+            ),
+            Schema::Union(variants) => Schema::Union(
+                variants.into_iter()
+                    .map(|variant| variant.detect_formats(path, formats, strings, detect, int_width, found))
+                    .collect()
+            ),
+            schema => schema,
+        }
+    }
+
+    /// Decode the escapes of the strings at the given paths (e.g. `$.name`)
+    /// Every path that is found is added to `found`
+    pub fn detect_decoded(self, path: &str, decode_paths: &[String], found: &mut Vec<String>) -> Schema {
+        match self {
+            Schema::String if decode_paths.iter().any(|decode_path| decode_path == path) => {
+                found.push(path.to_string());
+                Schema::Decoded
+            },
+            Schema::Object(fields) => Schema::Object(
+                fields.into_iter()
+                    .map(|(key, field)| {
+                        let field = field.detect_decoded(&format!("{}.{}", path, key), decode_paths, found);
+                        (key, field)
+                    })
+                    .collect()
+            ),
+            Schema::Map(value) => Schema::Map(Box::new(value.detect_decoded(&format!("{}.*", path), decode_paths, found))),
+            Schema::Array(Some(element)) => Schema::Array(Some(Box::new(element.detect_decoded(&format!("{}[]", path), decode_paths, found)))),
+            Schema::Tuple(positions) => Schema::Tuple(
+                positions.into_iter()
+                    .enumerate()
+                    .map(|(idx, position)| position.detect_decoded(&format!("{}[{}]", path, idx), decode_paths, found))
+                    .collect()
+            ),
+            Schema::Union(variants) => Schema::Union(
+                variants.into_iter()
+                    .map(|variant| variant.detect_decoded(path, decode_paths, found))
+                    .collect()
+            ),
+            schema => schema,
+        }
+    }
+
+    /// Leave out the fields at the given paths (e.g. `$.metadata`), so they are not parsed at all
+    /// Every path that is found is added to `found`, and every object that has no fields left to `emptied`
+    pub fn deselect(self, path: &str, skip_paths: &[String], found: &mut Vec<String>, emptied: &mut Vec<String>) -> Schema {
+        match self {
+            Schema::Object(fields) => {
+                let had_fields = !fields.is_empty();
+
+                let mut selected = Vec::new();
+                for (key, field) in fields {
+                    let field_path = format!("{}.{}", path, key);
+
+                    if skip_paths.contains(&field_path) {
+                        found.push(field_path);
+                    } else {
+                        selected.push((key, field.deselect(&field_path, skip_paths, found, emptied)));
+                    }
+                }
+
+                if had_fields && selected.is_empty() {
+                    emptied.push(path.to_string());
+                }
+
+                Schema::Object(selected)
+            },
+            Schema::Map(value) => Schema::Map(Box::new(value.deselect(&format!("{}.*", path), skip_paths, found, emptied))),
+            Schema::Array(Some(element)) => Schema::Array(Some(Box::new(element.deselect(&format!("{}[]", path), skip_paths, found, emptied)))),
+            Schema::Tuple(positions) => Schema::Tuple(
+                positions.into_iter()
+                    .enumerate()
+                    .map(|(idx, position)| position.deselect(&format!("{}[{}]", path, idx), skip_paths, found, emptied))
+                    .collect()
+            ),
+            Schema::Union(variants) => Schema::Union(
+                variants.into_iter()
+                    .map(|variant| variant.deselect(path, skip_paths, found, emptied))
+                    .collect()
+            ),
+            schema => schema,
+        }
+    }
+}
+
+// Whether a decimal number is smaller than 2^width
+fn fits_in_bits(digits: &str, width: usize) -> bool {
+    // The decimal digits of 2^width, least significant first
+    let mut limit = vec![1u8];
+    for _ in 0..width {
+        let mut carry = 0;
+        for digit in limit.iter_mut() {
+            let doubled = *digit * 2 + carry;
+            *digit = doubled % 10;
+            carry = doubled / 10;
+        }
+        if carry > 0 {
+            limit.push(carry);
+        }
+    }
+
+    let digits = digits.trim_start_matches('0');
+    if digits.len() != limit.len() {
+        return digits.len() < limit.len();
+    }
+
+    digits.bytes().map(|digit| digit - b'0').lt(limit.into_iter().rev())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge_samples(samples: &[&str]) -> Schema {
+        samples.iter()
+            .map(|sample| Schema::from_json(&json::parse(sample).unwrap()))
+            .reduce(Schema::merge)
+            .unwrap()
+    }
+
+    fn schema_of(samples: &[&str]) -> Schema {
+        merge_samples(samples).detect_tuples("$", &[], &mut Vec::new())
+    }
+
+    #[test]
+    fn merges_samples_into_unions() {
+        let schema = schema_of(&[r#"{"a": 1, "b": {"x": 1}}"#, r#"{"a": "one", "b": null}"#, r#"{"a": 2, "c": true}"#]);
+
+        // Variants keep the order in which they were first seen and are never repeated
+        assert_eq!(schema, Schema::Object(vec![
+            ("a".to_string(), Schema::Union(vec![Schema::Integer, Schema::String])),
+            ("b".to_string(), Schema::Union(vec![Schema::Object(vec![("x".to_string(), Schema::Integer)]), Schema::Null])),
+            ("c".to_string(), Schema::Boolean),
+        ]));
+
+        // Arrays of different lengths are no tuples, their elements are merged like samples
+        let array = schema_of(&[r#"[[1, 2], "x", {"y": 1}]"#, r#"[[3], {"z": 2}]"#]);
+        assert_eq!(array, Schema::Array(Some(Box::new(Schema::Union(vec![
+            Schema::Array(Some(Box::new(Schema::Integer))),
+            Schema::String,
+            Schema::Object(vec![("y".to_string(), Schema::Integer), ("z".to_string(), Schema::Integer)]),
+        ])))));
+    }
+
+    #[test]
+    fn collects_the_keys_that_some_objects_lack() {
+        let samples = [r#"{"a": 1, "b": [{"x": 1, "y": 2}, {"x": 3}]}"#, r#"{"b": [], "c": true}"#];
+        let schema = schema_of(&samples);
+
+        let mut optional = Vec::new();
+        for sample in samples {
+            schema.collect_optional_keys(&json::parse(sample).unwrap(), "$", &mut optional);
+        }
+
+        assert_eq!(optional, vec!["$.b[].y", "$.c", "$.a"]);
+    }
+
+    #[test]
+    fn detects_configured_and_inferred_tuples() {
+        let schema = merge_samples(&[r#"{"point": [1, 2], "pair": ["a", 1], "list": [1, 2]}"#, r#"{"point": [3, 4], "pair": ["b", 2], "list": [3]}"#]);
+
+        let mut found = Vec::new();
+        let tuples = schema.detect_tuples("$", &["$.point".to_string(), "$.list".to_string()], &mut found);
+
+        // Positions with different types make a tuple by themselves, arrays of different lengths never are one
+        assert_eq!(tuples, Schema::Object(vec![
+            ("point".to_string(), Schema::Tuple(vec![Schema::Integer, Schema::Integer])),
+            ("pair".to_string(), Schema::Tuple(vec![Schema::String, Schema::Integer])),
+            ("list".to_string(), Schema::Array(Some(Box::new(Schema::Integer)))),
+        ]));
+        assert_eq!(found, vec!["$.point".to_string()]);
+    }
+
+    #[test]
+    fn detects_configured_and_inferred_maps() {
+        let schema = schema_of(&[r#"{"counts": {"apple": 3, "pear": 5}, "point": {"x": 1, "y": true}, "id": 7}"#]);
+
+        let (mut found, mut empty) = (Vec::new(), Vec::new());
+        let maps = schema.clone().detect_maps("$", &["$.counts".to_string()], None, &mut found, &mut empty);
+        assert_eq!(maps, Schema::Object(vec![
+            ("counts".to_string(), Schema::Map(Box::new(Schema::Integer))),
+            ("point".to_string(), Schema::Object(vec![("x".to_string(), Schema::Integer), ("y".to_string(), Schema::Boolean)])),
+            ("id".to_string(), Schema::Integer),
+        ]));
+        assert_eq!(found, vec!["$.counts".to_string()]);
+
+        // Values of different types are no map, whatever the number of keys
+        let inferred = schema.detect_maps("$", &[], Some(2), &mut Vec::new(), &mut empty);
+        assert!(matches!(&inferred, Schema::Object(fields) if fields[0].1 == Schema::Map(Box::new(Schema::Integer)) && matches!(fields[1].1, Schema::Object(_))));
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn reports_maps_that_are_always_empty() {
+        let schema = schema_of(&[r#"{"counts": {}}"#, r#"{"counts": {}}"#]);
+
+        let (mut found, mut empty) = (Vec::new(), Vec::new());
+        schema.detect_maps("$", &["$.counts".to_string()], None, &mut found, &mut empty);
+        assert_eq!(found, vec!["$.counts".to_string()]);
+        assert_eq!(empty, vec!["$.counts".to_string()]);
+    }
+
+    #[test]
+    fn forwards_raw_values_in_union_variants() {
+        // `$.a` is an object in one sample and an integer in the other
+        let schema = schema_of(&[r#"{"a": {"meta": {"x": 1}}}"#, r#"{"a": 5}"#]);
+
+        let mut found = Vec::new();
+        let raw = schema.detect_raw("$", &["$.a.meta".to_string()], None, 0, &mut found);
+        assert_eq!(found, vec!["$.a.meta".to_string()]);
+
+        let Schema::Object(fields) = raw else { panic!("the root is no object") };
+        let Schema::Union(variants) = &fields[0].1 else { panic!("$.a is no union") };
+        assert!(variants.contains(&Schema::Object(vec![
+            ("meta".to_string(), Schema::Raw(Box::new(Schema::Object(vec![("x".to_string(), Schema::Integer)])))),
+        ])));
+    }
+
+    #[test]
+    fn forwards_values_nested_too_deep() {
+        let schema = schema_of(&[r#"{"a": {"b": {"c": 1}}, "d": 2}"#]);
+
+        let mut found = Vec::new();
+        let raw = schema.detect_raw("$", &["$.e".to_string()], Some(1), 0, &mut found);
+        assert!(found.is_empty());
+
+        // Scalars are never too deep, objects below the maximum depth are forwarded as a whole
+        assert_eq!(raw, Schema::Object(vec![
+            ("a".to_string(), Schema::Object(vec![("b".to_string(), Schema::Raw(Box::new(Schema::Object(vec![("c".to_string(), Schema::Integer)]))))])),
+            ("d".to_string(), Schema::Integer),
+        ]));
+    }
+
+    fn strings_of(schema: &Schema, samples: &[&str]) -> HashMap<String, Vec<String>> {
+        let mut strings = HashMap::new();
+        for sample in samples {
+            schema.collect_strings(&json::parse(sample).unwrap(), "$", &mut strings);
+        }
+
+        strings
+    }
+
+    #[test]
+    fn detects_configured_and_inferred_enums() {
+        let samples = [r#"{"study": "CS", "name": "a", "city": "Delft"}"#, r#"{"study": "EE", "name": "b", "city": "Delft"}"#, r#"{"study": "CS", "name": "c", "city": "Delft"}"#];
+        let schema = schema_of(&samples);
+        let strings = strings_of(&schema, &samples);
+        assert_eq!(strings["$.study"], vec!["CS".to_string(), "EE".to_string()]);
+
+        let mut found = Vec::new();
+        let values = vec!["Delft".to_string(), "Leiden".to_string()];
+        let enums = schema.detect_enums("$", &[("$.city".to_string(), values.clone())], &strings, Some(2), &mut found);
+
+        // Configured values are kept even when they are not seen, inferred ones are in the order they are seen
+        assert_eq!(enums, Schema::Object(vec![
+            ("study".to_string(), Schema::Enum(vec!["CS".to_string(), "EE".to_string()])),
+            ("name".to_string(), Schema::String),
+            ("city".to_string(), Schema::Enum(values)),
+        ]));
+        assert_eq!(found, vec!["$.city".to_string()]);
+    }
+
+    #[test]
+    fn checks_integers_against_their_width() {
+        assert!(fits_in_bits("255", 8));
+        assert!(fits_in_bits("0", 1));
+        assert!(fits_in_bits("00255", 8));
+        assert!(!fits_in_bits("256", 8));
+        assert!(fits_in_bits("18446744073709551615", 64));
+        assert!(!fits_in_bits("18446744073709551616", 64));
+        assert!(fits_in_bits("340282366920938463463374607431768211456", 129));
+        assert!(!fits_in_bits("340282366920938463463374607431768211456", 128));
+    }
+
+    #[test]
+    fn finds_integers_that_are_too_wide_for_their_path() {
+        let sample = json::parse(r#"{"a": [1, 70000], "b": {"c": 300}}"#).unwrap();
+        let schema = schema_of(&[&sample.dump()]);
+
+        let widths = |path: &str| if path == "$.a[]" { 32 } else { 8 };
+        assert_eq!(schema.find_wide_integer(&sample, "$", &widths), Some(("$.b.c".to_string(), "300".to_string())));
+
+        let widths = |path: &str| if path == "$.a[]" { 16 } else { 9 };
+        assert_eq!(schema.find_wide_integer(&sample, "$", &widths), Some(("$.a[]".to_string(), "70000".to_string())));
+
+        assert_eq!(schema.find_wide_integer(&sample, "$", &|_| 17), None);
+    }
+}
@@ -0,0 +1,270 @@
+use std::fmt::{Display, Formatter};
+
+use super::SyntaxError;
+
+/**********************************************************************************
+ * Tokenizer shared by the TIL and TydiLang parsers                               *
+ **********************************************************************************/
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Ident(String),
+    Integer(u64),
+    Float(f64),
+    Str(String),
+    // Multi-character operators: `--`, `=>` and `::`
+    Connect,
+    Arrow,
+    PathSep,
+    Punct(char),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(ident) => write!(f, "{}", ident),
+            Token::Integer(value) => write!(f, "{}", value),
+            Token::Float(value) => write!(f, "{:?}", value),
+            Token::Str(value) => write!(f, "\"{}\"", value),
+            Token::Connect => write!(f, "--"),
+            Token::Arrow => write!(f, "=>"),
+            Token::PathSep => write!(f, "::"),
+            Token::Punct(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+pub fn tokenize(source: &str) -> Result<Vec<Token>, SyntaxError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let c = chars[pos];
+
+        if c.is_whitespace() {
+            pos += 1;
+        } else if c == '/' && chars.get(pos + 1) == Some(&'/') {
+            // Line comment
+            while pos < chars.len() && chars[pos] != '\n' {
+                pos += 1;
+            }
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = pos;
+            while pos < chars.len() && (chars[pos].is_ascii_alphanumeric() || chars[pos] == '_') {
+                pos += 1;
+            }
+            tokens.push(Token::Ident(chars[start..pos].iter().collect()));
+        } else if c.is_ascii_digit() {
+            let start = pos;
+            while pos < chars.len() && chars[pos].is_ascii_digit() {
+                pos += 1;
+            }
+
+            // A dot followed by a digit continues the number as a float
+            if chars.get(pos) == Some(&'.') && chars.get(pos + 1).is_some_and(|c| c.is_ascii_digit()) {
+                pos += 1;
+                while pos < chars.len() && chars[pos].is_ascii_digit() {
+                    pos += 1;
+                }
+                let text: String = chars[start..pos].iter().collect();
+                tokens.push(Token::Float(text.parse().map_err(|_| SyntaxError::InvalidNumber(text))?));
+            } else {
+                let text: String = chars[start..pos].iter().collect();
+                tokens.push(Token::Integer(text.parse().map_err(|_| SyntaxError::InvalidNumber(text))?));
+            }
+        } else if c == '"' {
+            let start = pos + 1;
+            pos += 1;
+            while pos < chars.len() && chars[pos] != '"' {
+                pos += 1;
+            }
+            if pos == chars.len() {
+                return Err(SyntaxError::UnterminatedString);
+            }
+            tokens.push(Token::Str(chars[start..pos].iter().collect()));
+            pos += 1;
+        } else {
+            let next = chars.get(pos + 1).copied();
+            let token = match (c, next) {
+                ('-', Some('-')) => Token::Connect,
+                ('=', Some('>')) => Token::Arrow,
+                (':', Some(':')) => Token::PathSep,
+                _ => Token::Punct(c),
+            };
+
+            pos += match token {
+                Token::Punct(_) => 1,
+                _ => 2,
+            };
+            tokens.push(token);
+        }
+    }
+
+    Ok(tokens)
+}
+
+pub struct TokenStream {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl TokenStream {
+    pub fn new(source: &str) -> Result<TokenStream, SyntaxError> {
+        Ok(TokenStream {
+            tokens: tokenize(source)?,
+            pos: 0,
+        })
+    }
+
+    pub fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    pub fn peek_nth(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n)
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    pub fn next_token(&mut self) -> Result<Token, SyntaxError> {
+        let token = self.tokens.get(self.pos).cloned().ok_or(SyntaxError::UnexpectedEnd)?;
+        self.pos += 1;
+
+        Ok(token)
+    }
+
+    fn unexpected(&self, token: Token, expected: &str) -> SyntaxError {
+        SyntaxError::UnexpectedToken { found: token.to_string(), expected: expected.to_string() }
+    }
+
+    pub fn expect(&mut self, expected: Token) -> Result<(), SyntaxError> {
+        let token = self.next_token()?;
+
+        if token == expected {
+            Ok(())
+        } else {
+            Err(self.unexpected(token, &expected.to_string()))
+        }
+    }
+
+    pub fn expect_punct(&mut self, c: char) -> Result<(), SyntaxError> {
+        self.expect(Token::Punct(c))
+    }
+
+    // Consume the punctuation character if it is next
+    pub fn eat_punct(&mut self, c: char) -> bool {
+        if self.peek() == Some(&Token::Punct(c)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn expect_keyword(&mut self, keyword: &str) -> Result<(), SyntaxError> {
+        self.expect(Token::Ident(keyword.to_string()))
+    }
+
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(ident)) if ident == keyword)
+    }
+
+    pub fn expect_ident(&mut self) -> Result<String, SyntaxError> {
+        match self.next_token()? {
+            Token::Ident(ident) => Ok(ident),
+            token => Err(self.unexpected(token, "identifier")),
+        }
+    }
+
+    pub fn expect_integer(&mut self) -> Result<u64, SyntaxError> {
+        match self.next_token()? {
+            Token::Integer(value) => Ok(value),
+            token => Err(self.unexpected(token, "integer")),
+        }
+    }
+
+    // Integers that may be preceded by a minus sign
+    pub fn expect_signed(&mut self) -> Result<i64, SyntaxError> {
+        if self.eat_punct('-') {
+            Ok(-(self.expect_integer()? as i64))
+        } else {
+            Ok(self.expect_integer()? as i64)
+        }
+    }
+
+    // Numbers written either as integer or as float (`4` or `4.0`)
+    pub fn expect_number(&mut self) -> Result<f64, SyntaxError> {
+        match self.next_token()? {
+            Token::Integer(value) => Ok(value as f64),
+            Token::Float(value) => Ok(value),
+            token => Err(self.unexpected(token, "number")),
+        }
+    }
+
+    pub fn expect_string(&mut self) -> Result<String, SyntaxError> {
+        match self.next_token()? {
+            Token::Str(value) => Ok(value),
+            token => Err(self.unexpected(token, "string")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenizes_floats_and_integers() {
+        let tokens = tokenize("throughput: 2.5, complexity: 8, bits: 4.").unwrap();
+
+        assert_eq!(tokens[2], Token::Float(2.5));
+        assert_eq!(tokens[6], Token::Integer(8));
+        // A dot without a digit behind it does not belong to the number
+        assert_eq!(tokens[10..], [Token::Integer(4), Token::Punct('.')]);
+    }
+
+    #[test]
+    fn skips_line_comments() {
+        let tokens = tokenize("// the type of the input\ninput // trailing\n: in").unwrap();
+
+        assert_eq!(tokens, vec![Token::Ident("input".to_string()), Token::Punct(':'), Token::Ident("in".to_string())]);
+    }
+
+    #[test]
+    fn tokenizes_generics_and_operators() {
+        let tokens = tokenize("JSONStream<NESTING_LEVEL+1> a.output -- b.input; self.x => y::z").unwrap();
+
+        assert_eq!(tokens[..5], [
+            Token::Ident("JSONStream".to_string()),
+            Token::Punct('<'),
+            Token::Ident("NESTING_LEVEL".to_string()),
+            Token::Punct('+'),
+            Token::Integer(1),
+        ]);
+        assert!(tokens.contains(&Token::Connect));
+        assert!(tokens.contains(&Token::Arrow));
+        assert!(tokens.contains(&Token::PathSep));
+    }
+
+    #[test]
+    fn signed_integers() {
+        let mut tokens = TokenStream::new("-3 3").unwrap();
+
+        assert_eq!(tokens.expect_signed().unwrap(), -3);
+        assert_eq!(tokens.expect_signed().unwrap(), 3);
+        assert!(tokens.is_at_end());
+    }
+
+    #[test]
+    fn rejects_unterminated_strings() {
+        assert!(matches!(tokenize("impl: \"./vhdl_dir"), Err(SyntaxError::UnterminatedString)));
+    }
+
+    #[test]
+    fn rejects_numbers_that_do_not_fit() {
+        assert!(matches!(tokenize("99999999999999999999999"), Err(SyntaxError::InvalidNumber(_))));
+    }
+}
@@ -0,0 +1,157 @@
+use std::{fmt::{Display, Formatter}, cmp::Ordering};
+
+mod lexer;
+pub mod til;
+pub mod td;
+
+/**********************************************************************************
+ * Typed syntax trees of the generated languages. Backends build a tree, print    *
+ * it and parse the printed text back to make sure the output is well formed.     *
+ **********************************************************************************/
+
+/// A dimensionality argument such as `NESTING_LEVEL+1` or `2`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimExpr {
+    name: Option<String>,
+    offset: isize,
+}
+
+impl DimExpr {
+    pub fn new(name: Option<String>, offset: isize) -> DimExpr {
+        DimExpr {
+            name,
+            offset,
+        }
+    }
+
+    pub fn fixed(value: usize) -> DimExpr {
+        DimExpr::new(None, value as isize)
+    }
+
+    pub fn generic(name: &str) -> DimExpr {
+        DimExpr::new(Some(name.to_string()), 0)
+    }
+
+    fn parse(tokens: &mut lexer::TokenStream) -> Result<DimExpr, SyntaxError> {
+        match tokens.peek() {
+            Some(lexer::Token::Ident(_)) => {
+                let name = tokens.expect_ident()?;
+
+                let offset = if tokens.eat_punct('+') {
+                    tokens.expect_integer()? as isize
+                } else if tokens.eat_punct('-') {
+                    -(tokens.expect_integer()? as isize)
+                } else {
+                    0
+                };
+
+                Ok(DimExpr::new(Some(name), offset))
+            },
+            _ => Ok(DimExpr::fixed(tokens.expect_integer()? as usize)),
+        }
+    }
+}
+
+impl Display for DimExpr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => match self.offset.cmp(&0) {
+                Ordering::Greater => write!(f, "{}+{}", name, self.offset),
+                Ordering::Less => write!(f, "{}{}", name, self.offset),
+                Ordering::Equal => write!(f, "{}", name),
+            },
+            None => write!(f, "{}", self.offset),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    In,
+    Out,
+}
+
+impl Direction {
+    fn parse(tokens: &mut lexer::TokenStream) -> Result<Direction, SyntaxError> {
+        match tokens.expect_ident()?.as_str() {
+            "in" => Ok(Direction::In),
+            "out" => Ok(Direction::Out),
+            other => Err(SyntaxError::UnexpectedToken { found: other.to_string(), expected: "in or out".to_string() }),
+        }
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::In => write!(f, "in"),
+            Direction::Out => write!(f, "out"),
+        }
+    }
+}
+
+// Print the text of a syntax tree and check that it parses back into the same tree
+pub fn print_validated<T: Display + PartialEq + std::fmt::Debug>(tree: &T, parse: fn(&str) -> Result<T, SyntaxError>) -> Result<String, SyntaxError> {
+    let text = tree.to_string();
+    let parsed = parse(&text)?;
+
+    if &parsed != tree {
+        return Err(SyntaxError::RoundTripMismatch);
+    }
+
+    Ok(text)
+}
+
+#[derive(Debug)]
+pub enum SyntaxError {
+    UnexpectedToken { found: String, expected: String },
+    UnexpectedEnd,
+    InvalidNumber(String),
+    UnterminatedString,
+    RoundTripMismatch,
+}
+
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyntaxError::UnexpectedToken { found, expected } => write!(f, "unexpected '{}', expected {}", found, expected),
+            SyntaxError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            SyntaxError::InvalidNumber(number) => write!(f, "invalid number '{}'", number),
+            SyntaxError::UnterminatedString => write!(f, "unterminated string literal"),
+            SyntaxError::RoundTripMismatch => write!(f, "printed output does not parse back into the same syntax tree"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_dim(source: &str) -> Result<DimExpr, SyntaxError> {
+        DimExpr::parse(&mut lexer::TokenStream::new(source)?)
+    }
+
+    #[test]
+    fn dim_expressions_round_trip() {
+        for source in ["NESTING_LEVEL+1", "NESTING_LEVEL-2", "d", "3"] {
+            let dim = parse_dim(source).unwrap();
+            assert_eq!(dim.to_string(), source);
+        }
+
+        assert_eq!(parse_dim("NESTING_LEVEL+1").unwrap(), DimExpr::new(Some("NESTING_LEVEL".to_string()), 1));
+    }
+
+    #[test]
+    fn rejects_invalid_dim_expressions() {
+        assert!(matches!(parse_dim("+1"), Err(SyntaxError::UnexpectedToken { .. })));
+        assert!(matches!(parse_dim("NESTING_LEVEL+"), Err(SyntaxError::UnexpectedEnd)));
+    }
+
+    #[test]
+    fn print_validated_detects_mismatches() {
+        // A tree that prints as something else than it is does not pass
+        let dim = DimExpr::new(Some("d".to_string()), 0);
+        assert!(print_validated(&dim, |_| Ok(DimExpr::fixed(2))).is_err());
+        assert_eq!(print_validated(&dim, parse_dim).unwrap(), "d");
+    }
+}
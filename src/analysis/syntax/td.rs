@@ -0,0 +1,548 @@
+use std::fmt::{Display, Formatter};
+
+use super::{DimExpr, Direction, SyntaxError, lexer::{Token, TokenStream}};
+
+/**********************************************************************************
+ * Syntax tree, printer and parser of TydiLang (TD)                               *
+ **********************************************************************************/
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Package {
+    pub name: String,
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item {
+    Alias(Alias),
+    Group(Group),
+    TypeContainer(TypeContainer),
+    Streamlet(Streamlet),
+    Impl(Impl),
+}
+
+/// An element type such as `byte_t = Bit(8);`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alias {
+    pub name: String,
+    pub bits: usize,
+}
+
+/// An element type that bundles several element types, e.g. `Group exam_t { grade: integer_t; }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    pub name: String,
+    pub fields: Vec<GroupField>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupField {
+    pub name: String,
+    pub element: String,
+}
+
+/// A template streamlet that only holds the stream types, parameterized on their dimension
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeContainer {
+    pub name: String,
+    pub param: String,
+    pub streams: Vec<StreamDef>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamDef {
+    pub name: String,
+    pub element: String,
+    pub throughput: f64,
+    pub dimension: DimExpr,
+    pub synchronicity: String,
+    pub complexity: u8,
+}
+
+/// A streamlet, which is a template when it has parameters (e.g. `streamlet a <NESTING_LEVEL: int>`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Streamlet {
+    pub name: String,
+    pub params: Vec<String>,
+    pub constants: Vec<Constant>,
+    pub ports: Vec<Port>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constant {
+    pub name: String,
+    pub value: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Port {
+    pub name: String,
+    pub container: String,
+    pub dim: DimExpr,
+    pub type_name: String,
+    pub direction: Direction,
+}
+
+/// An implementation, which passes its parameters on to the streamlet it implements
+#[derive(Debug, Clone, PartialEq)]
+pub struct Impl {
+    pub name: String,
+    pub params: Vec<String>,
+    pub streamlet: String,
+    pub external: bool,
+    pub instances: Vec<Instance>,
+    pub connections: Vec<Connection>,
+}
+
+/// An instance of an implementation, with a value for every parameter of the implementation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instance {
+    pub name: String,
+    pub implementation: String,
+    pub args: Vec<DimExpr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Connection {
+    pub source: PortRef,
+    pub sink: PortRef,
+}
+
+/// A port of an instance, or of the implemented streamlet itself (`self`) when `instance` is `None`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortRef {
+    pub instance: Option<String>,
+    pub port: String,
+}
+
+/**********************************************************************************
+ * Printer                                                                        *
+ **********************************************************************************/
+
+const INDENT: &str = "    ";
+
+impl Display for Package {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "package {};", self.name)?;
+
+        let mut previous: Option<&Item> = None;
+        for item in &self.items {
+            // Consecutive aliases are grouped, everything else is separated by a blank line
+            match (previous, item) {
+                (Some(Item::Alias(_)), Item::Alias(_)) => {},
+                _ => writeln!(f)?,
+            }
+
+            write!(f, "{}", item)?;
+            previous = Some(item);
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Item {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Item::Alias(alias) => writeln!(f, "{} = Bit({});", alias.name, alias.bits),
+            Item::Group(group) => write!(f, "{}", group),
+            Item::TypeContainer(container) => write!(f, "{}", container),
+            Item::Streamlet(streamlet) => write!(f, "{}", streamlet),
+            Item::Impl(implementation) => write!(f, "{}", implementation),
+        }
+    }
+}
+
+impl Display for Group {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Group {} {{", self.name)?;
+
+        for field in &self.fields {
+            writeln!(f, "{}{}: {};", INDENT, field.name, field.element)?;
+        }
+
+        writeln!(f, "}}")
+    }
+}
+
+impl Display for TypeContainer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "streamlet {} <{}: int> {{", self.name, self.param)?;
+
+        for (idx, stream) in self.streams.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+
+            for line in stream.to_string().lines() {
+                writeln!(f, "{}{}", INDENT, line)?;
+            }
+        }
+
+        writeln!(f, "}}")
+    }
+}
+
+impl Display for StreamDef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} = Stream(", self.name)?;
+        writeln!(f, "{}{},", INDENT, self.element)?;
+        writeln!(f, "{}throughput = {:?},", INDENT, self.throughput)?;
+        writeln!(f, "{}dimension = {},", INDENT, self.dimension)?;
+        writeln!(f, "{}synchronicity = \"{}\",", INDENT, self.synchronicity)?;
+        writeln!(f, "{}complexity = {}", INDENT, self.complexity)?;
+        writeln!(f, ");")
+    }
+}
+
+// Template parameters as they are declared, e.g. ` <NESTING_LEVEL: int>`
+fn format_params(params: &[String]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+
+    let params: Vec<String> = params.iter().map(|param| format!("{}: int", param)).collect();
+    format!(" <{}>", params.join(", "))
+}
+
+impl Display for Streamlet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "streamlet {}{} {{", self.name, format_params(&self.params))?;
+
+        for constant in &self.constants {
+            writeln!(f, "{}{} = {};", INDENT, constant.name, constant.value)?;
+        }
+
+        if !self.constants.is_empty() && !self.ports.is_empty() {
+            writeln!(f)?;
+        }
+
+        for port in &self.ports {
+            writeln!(f, "{}{}", INDENT, port)?;
+        }
+
+        writeln!(f, "}}")
+    }
+}
+
+impl Display for Port {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}<{}>.{} {};", self.name, self.container, self.dim, self.type_name, self.direction)
+    }
+}
+
+impl Display for Impl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "impl {}{} of {}", self.name, format_params(&self.params), self.streamlet)?;
+
+        if !self.params.is_empty() {
+            write!(f, "<{}>", self.params.join(", "))?;
+        }
+
+        if self.external {
+            write!(f, " @External")?;
+        }
+
+        if self.instances.is_empty() && self.connections.is_empty() {
+            return writeln!(f, " {{ }}");
+        }
+
+        writeln!(f, " {{")?;
+
+        for instance in &self.instances {
+            writeln!(f, "{}{}", INDENT, instance)?;
+        }
+
+        if !self.instances.is_empty() && !self.connections.is_empty() {
+            writeln!(f)?;
+        }
+
+        for connection in &self.connections {
+            writeln!(f, "{}{} => {};", INDENT, connection.source, connection.sink)?;
+        }
+
+        writeln!(f, "}}")
+    }
+}
+
+impl Display for Instance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "instance {}({}", self.name, self.implementation)?;
+
+        if !self.args.is_empty() {
+            let args: Vec<String> = self.args.iter().map(|arg| arg.to_string()).collect();
+            write!(f, "<{}>", args.join(", "))?;
+        }
+
+        write!(f, ");")
+    }
+}
+
+impl Display for PortRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.instance {
+            Some(instance) => write!(f, "{}.{}", instance, self.port),
+            None => write!(f, "self.{}", self.port),
+        }
+    }
+}
+
+/**********************************************************************************
+ * Parser                                                                         *
+ **********************************************************************************/
+
+pub fn parse(source: &str) -> Result<Package, SyntaxError> {
+    let mut tokens = TokenStream::new(source)?;
+
+    tokens.expect_keyword("package")?;
+    let name = tokens.expect_ident()?;
+    tokens.expect_punct(';')?;
+
+    let mut items = Vec::new();
+    while !tokens.is_at_end() {
+        items.push(parse_item(&mut tokens)?);
+    }
+
+    Ok(Package { name, items })
+}
+
+fn parse_item(tokens: &mut TokenStream) -> Result<Item, SyntaxError> {
+    if tokens.is_keyword("impl") {
+        return Ok(Item::Impl(parse_impl(tokens)?));
+    }
+
+    if tokens.is_keyword("streamlet") {
+        tokens.expect_keyword("streamlet")?;
+        let name = tokens.expect_ident()?;
+        let params = parse_params(tokens)?;
+        tokens.expect_punct('{')?;
+
+        // The type container only holds stream definitions, e.g. `JSONStream = Stream(...)`
+        let is_container = tokens.peek() == Some(&Token::Punct('}')) ||
+            (tokens.peek_nth(1) == Some(&Token::Punct('=')) && tokens.peek_nth(2) == Some(&Token::Ident("Stream".to_string())));
+
+        if is_container {
+            return Ok(Item::TypeContainer(parse_type_container(tokens, name, params)?));
+        }
+
+        return Ok(Item::Streamlet(parse_streamlet(tokens, name, params)?));
+    }
+
+    if tokens.is_keyword("Group") {
+        return Ok(Item::Group(parse_group(tokens)?));
+    }
+
+    let name = tokens.expect_ident()?;
+    tokens.expect_punct('=')?;
+    tokens.expect_keyword("Bit")?;
+    tokens.expect_punct('(')?;
+    let bits = tokens.expect_integer()? as usize;
+    tokens.expect_punct(')')?;
+    tokens.expect_punct(';')?;
+
+    Ok(Item::Alias(Alias { name, bits }))
+}
+
+fn parse_group(tokens: &mut TokenStream) -> Result<Group, SyntaxError> {
+    tokens.expect_keyword("Group")?;
+    let name = tokens.expect_ident()?;
+    tokens.expect_punct('{')?;
+
+    let mut fields = Vec::new();
+    while !tokens.eat_punct('}') {
+        let name = tokens.expect_ident()?;
+        tokens.expect_punct(':')?;
+        let element = tokens.expect_ident()?;
+        tokens.expect_punct(';')?;
+
+        fields.push(GroupField { name, element });
+    }
+
+    Ok(Group { name, fields })
+}
+
+// Template parameters, e.g. `<NESTING_LEVEL: int>`, if there are any
+fn parse_params(tokens: &mut TokenStream) -> Result<Vec<String>, SyntaxError> {
+    let mut params = Vec::new();
+
+    if tokens.eat_punct('<') {
+        while !tokens.eat_punct('>') {
+            params.push(tokens.expect_ident()?);
+            tokens.expect_punct(':')?;
+            tokens.expect_keyword("int")?;
+            tokens.eat_punct(',');
+        }
+    }
+
+    Ok(params)
+}
+
+// The streams of a type container, of which the opening brace has been read
+fn parse_type_container(tokens: &mut TokenStream, name: String, params: Vec<String>) -> Result<TypeContainer, SyntaxError> {
+    // The container has a single parameter for the dimension of its streams
+    if params.len() != 1 {
+        return Err(SyntaxError::UnexpectedToken { found: format!("{} parameters", params.len()), expected: "a dimension parameter".to_string() });
+    }
+    let param = params[0].clone();
+
+    let mut streams = Vec::new();
+    while !tokens.eat_punct('}') {
+        streams.push(parse_stream_def(tokens)?);
+    }
+
+    Ok(TypeContainer { name, param, streams })
+}
+
+fn parse_stream_def(tokens: &mut TokenStream) -> Result<StreamDef, SyntaxError> {
+    let name = tokens.expect_ident()?;
+    tokens.expect_punct('=')?;
+    tokens.expect_keyword("Stream")?;
+    tokens.expect_punct('(')?;
+
+    let element = tokens.expect_ident()?;
+
+    let mut throughput = 1.0;
+    let mut dimension = DimExpr::fixed(0);
+    let mut synchronicity = "Sync".to_string();
+    let mut complexity = 1;
+
+    while tokens.eat_punct(',') {
+        let property = tokens.expect_ident()?;
+        tokens.expect_punct('=')?;
+
+        match property.as_str() {
+            "throughput" => throughput = tokens.expect_number()?,
+            "dimension" => dimension = DimExpr::parse(tokens)?,
+            "synchronicity" => synchronicity = tokens.expect_string()?,
+            "complexity" => complexity = tokens.expect_integer()? as u8,
+            other => return Err(SyntaxError::UnexpectedToken { found: other.to_string(), expected: "stream property".to_string() }),
+        }
+    }
+
+    tokens.expect_punct(')')?;
+    tokens.expect_punct(';')?;
+
+    Ok(StreamDef { name, element, throughput, dimension, synchronicity, complexity })
+}
+
+// The members of a streamlet, of which the opening brace has been read
+fn parse_streamlet(tokens: &mut TokenStream, name: String, params: Vec<String>) -> Result<Streamlet, SyntaxError> {
+    let mut constants = Vec::new();
+    let mut ports = Vec::new();
+
+    while !tokens.eat_punct('}') {
+        let member = tokens.expect_ident()?;
+
+        if tokens.eat_punct('=') {
+            let value = tokens.expect_signed()?;
+            constants.push(Constant { name: member, value });
+        } else {
+            tokens.expect_punct(':')?;
+            let container = tokens.expect_ident()?;
+            tokens.expect_punct('<')?;
+            let dim = DimExpr::parse(tokens)?;
+            tokens.expect_punct('>')?;
+            tokens.expect_punct('.')?;
+            let type_name = tokens.expect_ident()?;
+            let direction = Direction::parse(tokens)?;
+
+            ports.push(Port { name: member, container, dim, type_name, direction });
+        }
+
+        tokens.expect_punct(';')?;
+    }
+
+    Ok(Streamlet { name, params, constants, ports })
+}
+
+fn parse_impl(tokens: &mut TokenStream) -> Result<Impl, SyntaxError> {
+    tokens.expect_keyword("impl")?;
+    let name = tokens.expect_ident()?;
+    let params = parse_params(tokens)?;
+    tokens.expect_keyword("of")?;
+    let streamlet = tokens.expect_ident()?;
+
+    // The parameters are passed on to the streamlet in the same order
+    if !params.is_empty() {
+        tokens.expect_punct('<')?;
+        for param in &params {
+            tokens.expect(Token::Ident(param.clone()))?;
+            tokens.eat_punct(',');
+        }
+        tokens.expect_punct('>')?;
+    }
+
+    let external = tokens.eat_punct('@');
+    if external {
+        tokens.expect_keyword("External")?;
+    }
+
+    tokens.expect_punct('{')?;
+
+    let mut instances = Vec::new();
+    let mut connections = Vec::new();
+
+    while !tokens.eat_punct('}') {
+        if tokens.is_keyword("instance") {
+            tokens.expect_keyword("instance")?;
+            let name = tokens.expect_ident()?;
+            tokens.expect_punct('(')?;
+            let implementation = tokens.expect_ident()?;
+
+            let mut args = Vec::new();
+            if tokens.eat_punct('<') {
+                while !tokens.eat_punct('>') {
+                    args.push(DimExpr::parse(tokens)?);
+                    tokens.eat_punct(',');
+                }
+            }
+            tokens.expect_punct(')')?;
+
+            instances.push(Instance { name, implementation, args });
+        } else {
+            let source = parse_port_ref(tokens)?;
+            tokens.expect(Token::Arrow)?;
+            let sink = parse_port_ref(tokens)?;
+
+            connections.push(Connection { source, sink });
+        }
+
+        tokens.expect_punct(';')?;
+    }
+
+    Ok(Impl { name, params, streamlet, external, instances, connections })
+}
+
+fn parse_port_ref(tokens: &mut TokenStream) -> Result<PortRef, SyntaxError> {
+    let owner = tokens.expect_ident()?;
+    tokens.expect_punct('.')?;
+    let port = tokens.expect_ident()?;
+
+    if owner == "self" {
+        Ok(PortRef { instance: None, port })
+    } else {
+        Ok(PortRef { instance: Some(owner), port })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{backend::generate_td, testing::example_designs};
+
+    #[test]
+    fn examples_round_trip() {
+        for (name, design, gen_params) in example_designs() {
+            let package = generate_td(&design, &gen_params);
+            let parsed = parse(&package.to_string()).unwrap_or_else(|err| panic!("{}: {}", name, err));
+
+            assert_eq!(parsed, package, "{}", name);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_packages() {
+        assert!(matches!(parse("package a"), Err(SyntaxError::UnexpectedEnd)));
+        assert!(matches!(parse("package a; byte_t = Bits(8);"), Err(SyntaxError::UnexpectedToken { .. })));
+    }
+}
@@ -0,0 +1,606 @@
+use std::fmt::{Display, Formatter};
+
+use super::{DimExpr, Direction, SyntaxError, lexer::{Token, TokenStream}};
+
+/**********************************************************************************
+ * Syntax tree, printer and parser of the Tydi Intermediate Language (TIL)        *
+ **********************************************************************************/
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Namespace {
+    pub path: Vec<String>,
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item {
+    Type(TypeDef),
+    Streamlet(Streamlet),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeDef {
+    pub name: String,
+    pub params: Vec<GenericParam>,
+    pub stream: Stream,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stream {
+    pub data: DataType,
+    pub throughput: f64,
+    pub dimensionality: DimExpr,
+    pub synchronicity: String,
+    pub complexity: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataType {
+    Bits(usize),
+    Group(Vec<Field>),
+}
+
+/// A named field of a `Group`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub data: DataType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GenericKind {
+    Integer,
+    Natural,
+    Positive,
+    Dimensionality,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericParam {
+    pub name: String,
+    pub kind: GenericKind,
+    pub default: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Streamlet {
+    pub name: String,
+    pub params: Vec<GenericParam>,
+    pub ports: Vec<Port>,
+    pub implementation: Option<Implementation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Port {
+    pub name: String,
+    pub direction: Direction,
+    pub type_name: String,
+    pub dim: Option<DimExpr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Implementation {
+    Path(String),
+    Inline { instances: Vec<Instance>, connections: Vec<Connection> },
+}
+
+/// An instance of a streamlet, with the values of the generics that differ from their defaults
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instance {
+    pub name: String,
+    pub streamlet: String,
+    pub args: Vec<(String, DimExpr)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Connection {
+    pub source: PortRef,
+    pub sink: PortRef,
+}
+
+/// A port of an instance, or a port of the enclosing streamlet when `instance` is `None`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortRef {
+    pub instance: Option<String>,
+    pub port: String,
+}
+
+/**********************************************************************************
+ * Printer                                                                        *
+ **********************************************************************************/
+
+const INDENT: &str = "    ";
+
+impl Display for Namespace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "namespace {} {{", self.path.join("::"))?;
+
+        for (idx, item) in self.items.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+
+            // Indent every line of the item by one level
+            for line in item.to_string().lines() {
+                if line.is_empty() {
+                    writeln!(f)?;
+                } else {
+                    writeln!(f, "{}{}", INDENT, line)?;
+                }
+            }
+        }
+
+        writeln!(f, "}}")
+    }
+}
+
+impl Display for Item {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Item::Type(type_def) => write!(f, "{}", type_def),
+            Item::Streamlet(streamlet) => write!(f, "{}", streamlet),
+        }
+    }
+}
+
+impl Display for TypeDef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "type {}", self.name)?;
+
+        if !self.params.is_empty() {
+            let params: Vec<String> = self.params.iter().map(|param| param.to_string()).collect();
+            write!(f, "<{}>", params.join(", "))?;
+        }
+
+        writeln!(f, " = {};", self.stream)
+    }
+}
+
+impl Display for Stream {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Stream (")?;
+        writeln!(f, "{}data: {},", INDENT, self.data)?;
+        writeln!(f, "{}throughput: {},", INDENT, format_throughput(self.throughput))?;
+        writeln!(f, "{}dimensionality: {},", INDENT, self.dimensionality)?;
+        writeln!(f, "{}synchronicity: {},", INDENT, self.synchronicity)?;
+        writeln!(f, "{}complexity: {},", INDENT, self.complexity)?;
+        write!(f, ")")
+    }
+}
+
+// Whole throughputs are printed without a fraction
+fn format_throughput(throughput: f64) -> String {
+    if throughput.fract() == 0.0 {
+        format!("{}", throughput as u64)
+    } else {
+        format!("{}", throughput)
+    }
+}
+
+impl Display for DataType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataType::Bits(bits) => write!(f, "Bits({})", bits),
+            DataType::Group(fields) => {
+                let fields: Vec<String> = fields.iter().map(|field| format!("{}: {}", field.name, field.data)).collect();
+                write!(f, "Group({})", fields.join(", "))
+            },
+        }
+    }
+}
+
+impl Display for GenericKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenericKind::Integer => write!(f, "integer"),
+            GenericKind::Natural => write!(f, "natural"),
+            GenericKind::Positive => write!(f, "positive"),
+            GenericKind::Dimensionality => write!(f, "dimensionality"),
+        }
+    }
+}
+
+impl Display for GenericParam {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} = {}", self.name, self.kind, self.default)
+    }
+}
+
+impl Display for Streamlet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "streamlet {} = ", self.name)?;
+
+        if !self.params.is_empty() {
+            writeln!(f, "<")?;
+            for param in &self.params {
+                writeln!(f, "{}{},", INDENT, param)?;
+            }
+            write!(f, "> ")?;
+        }
+
+        writeln!(f, "(")?;
+        for port in &self.ports {
+            writeln!(f, "{}{},", INDENT, port)?;
+        }
+        write!(f, ")")?;
+
+        if let Some(implementation) = &self.implementation {
+            write!(f, " {}", implementation)?;
+        }
+
+        writeln!(f, ";")
+    }
+}
+
+impl Display for Port {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} {}", self.name, self.direction, self.type_name)?;
+
+        if let Some(dim) = &self.dim {
+            write!(f, "<{}>", dim)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Implementation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{{")?;
+
+        match self {
+            Implementation::Path(path) => writeln!(f, "{}impl: \"{}\"", INDENT, path)?,
+            Implementation::Inline { instances, connections } => {
+                writeln!(f, "{}impl: {{", INDENT)?;
+
+                for instance in instances {
+                    writeln!(f, "{}{}{}", INDENT, INDENT, instance)?;
+                }
+
+                if !instances.is_empty() && !connections.is_empty() {
+                    writeln!(f)?;
+                }
+
+                for connection in connections {
+                    writeln!(f, "{}{}{} -- {};", INDENT, INDENT, connection.source, connection.sink)?;
+                }
+
+                writeln!(f, "{}}}", INDENT)?;
+            }
+        }
+
+        write!(f, "}}")
+    }
+}
+
+impl Display for Instance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {}", self.name, self.streamlet)?;
+
+        if !self.args.is_empty() {
+            let args: Vec<String> = self.args.iter().map(|(name, value)| format!("{} = {}", name, value)).collect();
+            write!(f, "<{}>", args.join(", "))?;
+        }
+
+        write!(f, ";")
+    }
+}
+
+impl Display for PortRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.instance {
+            Some(instance) => write!(f, "{}.{}", instance, self.port),
+            None => write!(f, "{}", self.port),
+        }
+    }
+}
+
+/**********************************************************************************
+ * Parser                                                                         *
+ **********************************************************************************/
+
+pub fn parse(source: &str) -> Result<Namespace, SyntaxError> {
+    let mut tokens = TokenStream::new(source)?;
+
+    let namespace = parse_namespace(&mut tokens)?;
+
+    if let Some(token) = tokens.peek() {
+        return Err(SyntaxError::UnexpectedToken { found: token.to_string(), expected: "end of input".to_string() });
+    }
+
+    Ok(namespace)
+}
+
+fn parse_namespace(tokens: &mut TokenStream) -> Result<Namespace, SyntaxError> {
+    tokens.expect_keyword("namespace")?;
+
+    let mut path = vec![tokens.expect_ident()?];
+    while tokens.peek() == Some(&Token::PathSep) {
+        tokens.next_token()?;
+        path.push(tokens.expect_ident()?);
+    }
+
+    tokens.expect_punct('{')?;
+
+    let mut items = Vec::new();
+    while !tokens.eat_punct('}') {
+        if tokens.is_keyword("type") {
+            items.push(Item::Type(parse_type_def(tokens)?));
+        } else {
+            items.push(Item::Streamlet(parse_streamlet(tokens)?));
+        }
+    }
+
+    Ok(Namespace { path, items })
+}
+
+fn parse_type_def(tokens: &mut TokenStream) -> Result<TypeDef, SyntaxError> {
+    tokens.expect_keyword("type")?;
+    let name = tokens.expect_ident()?;
+
+    let mut params = Vec::new();
+    if tokens.eat_punct('<') {
+        while !tokens.eat_punct('>') {
+            params.push(parse_generic_param(tokens)?);
+            tokens.eat_punct(',');
+        }
+    }
+
+    tokens.expect_punct('=')?;
+    let stream = parse_stream(tokens)?;
+    tokens.expect_punct(';')?;
+
+    Ok(TypeDef { name, params, stream })
+}
+
+fn parse_stream(tokens: &mut TokenStream) -> Result<Stream, SyntaxError> {
+    tokens.expect_keyword("Stream")?;
+    tokens.expect_punct('(')?;
+
+    let mut data = None;
+    let mut throughput = None;
+    let mut dimensionality = None;
+    let mut synchronicity = None;
+    let mut complexity = None;
+
+    while !tokens.eat_punct(')') {
+        let property = tokens.expect_ident()?;
+        tokens.expect_punct(':')?;
+
+        match property.as_str() {
+            "data" => data = Some(parse_data_type(tokens)?),
+            "throughput" => throughput = Some(tokens.expect_number()?),
+            "dimensionality" => dimensionality = Some(DimExpr::parse(tokens)?),
+            "synchronicity" => synchronicity = Some(tokens.expect_ident()?),
+            "complexity" => complexity = Some(tokens.expect_integer()? as u8),
+            other => return Err(SyntaxError::UnexpectedToken { found: other.to_string(), expected: "stream property".to_string() }),
+        }
+
+        tokens.eat_punct(',');
+    }
+
+    Ok(Stream {
+        data: data.ok_or_else(|| missing_property("data"))?,
+        throughput: throughput.unwrap_or(1.0),
+        dimensionality: dimensionality.unwrap_or_else(|| DimExpr::fixed(0)),
+        synchronicity: synchronicity.unwrap_or_else(|| "Sync".to_string()),
+        complexity: complexity.unwrap_or(1),
+    })
+}
+
+fn missing_property(property: &str) -> SyntaxError {
+    SyntaxError::UnexpectedToken { found: ")".to_string(), expected: format!("stream property '{}'", property) }
+}
+
+fn parse_data_type(tokens: &mut TokenStream) -> Result<DataType, SyntaxError> {
+    if tokens.is_keyword("Group") {
+        tokens.expect_keyword("Group")?;
+        tokens.expect_punct('(')?;
+
+        let mut fields = Vec::new();
+        while !tokens.eat_punct(')') {
+            let name = tokens.expect_ident()?;
+            tokens.expect_punct(':')?;
+            fields.push(Field { name, data: parse_data_type(tokens)? });
+
+            // The closing parenthesis may follow a trailing comma
+            if !tokens.eat_punct(',') {
+                tokens.expect_punct(')')?;
+                break;
+            }
+        }
+
+        return Ok(DataType::Group(fields));
+    }
+
+    tokens.expect_keyword("Bits")?;
+    tokens.expect_punct('(')?;
+    let bits = tokens.expect_integer()? as usize;
+    tokens.expect_punct(')')?;
+
+    Ok(DataType::Bits(bits))
+}
+
+fn parse_generic_param(tokens: &mut TokenStream) -> Result<GenericParam, SyntaxError> {
+    let name = tokens.expect_ident()?;
+    tokens.expect_punct(':')?;
+
+    let kind = match tokens.expect_ident()?.as_str() {
+        "integer" => GenericKind::Integer,
+        "natural" => GenericKind::Natural,
+        "positive" => GenericKind::Positive,
+        "dimensionality" => GenericKind::Dimensionality,
+        other => return Err(SyntaxError::UnexpectedToken { found: other.to_string(), expected: "generic kind".to_string() }),
+    };
+
+    tokens.expect_punct('=')?;
+    let default = tokens.expect_signed()?;
+
+    Ok(GenericParam { name, kind, default })
+}
+
+fn parse_streamlet(tokens: &mut TokenStream) -> Result<Streamlet, SyntaxError> {
+    tokens.expect_keyword("streamlet")?;
+    let name = tokens.expect_ident()?;
+    tokens.expect_punct('=')?;
+
+    let mut params = Vec::new();
+    if tokens.eat_punct('<') {
+        while !tokens.eat_punct('>') {
+            params.push(parse_generic_param(tokens)?);
+            tokens.eat_punct(',');
+        }
+    }
+
+    tokens.expect_punct('(')?;
+    let mut ports = Vec::new();
+    while !tokens.eat_punct(')') {
+        ports.push(parse_port(tokens)?);
+        tokens.eat_punct(',');
+    }
+
+    let implementation = if tokens.eat_punct('{') {
+        let implementation = parse_implementation(tokens)?;
+        tokens.expect_punct('}')?;
+        Some(implementation)
+    } else {
+        None
+    };
+
+    tokens.expect_punct(';')?;
+
+    Ok(Streamlet { name, params, ports, implementation })
+}
+
+fn parse_port(tokens: &mut TokenStream) -> Result<Port, SyntaxError> {
+    let name = tokens.expect_ident()?;
+    tokens.expect_punct(':')?;
+    let direction = Direction::parse(tokens)?;
+    let type_name = tokens.expect_ident()?;
+
+    let dim = if tokens.eat_punct('<') {
+        let dim = DimExpr::parse(tokens)?;
+        tokens.expect_punct('>')?;
+        Some(dim)
+    } else {
+        None
+    };
+
+    Ok(Port { name, direction, type_name, dim })
+}
+
+fn parse_implementation(tokens: &mut TokenStream) -> Result<Implementation, SyntaxError> {
+    tokens.expect_keyword("impl")?;
+    tokens.expect_punct(':')?;
+
+    if let Some(Token::Str(_)) = tokens.peek() {
+        return Ok(Implementation::Path(tokens.expect_string()?));
+    }
+
+    tokens.expect_punct('{')?;
+
+    let mut instances = Vec::new();
+    let mut connections = Vec::new();
+
+    while !tokens.eat_punct('}') {
+        // An instance is `name = streamlet<GENERIC = value>;`, a connection is `a.b -- c.d;`
+        if tokens.peek_nth(1) == Some(&Token::Punct('=')) {
+            let name = tokens.expect_ident()?;
+            tokens.expect_punct('=')?;
+            let streamlet = tokens.expect_ident()?;
+
+            let mut args = Vec::new();
+            if tokens.eat_punct('<') {
+                while !tokens.eat_punct('>') {
+                    let generic = tokens.expect_ident()?;
+                    tokens.expect_punct('=')?;
+                    args.push((generic, DimExpr::parse(tokens)?));
+                    tokens.eat_punct(',');
+                }
+            }
+
+            instances.push(Instance { name, streamlet, args });
+        } else {
+            let source = parse_port_ref(tokens)?;
+            tokens.expect(Token::Connect)?;
+            let sink = parse_port_ref(tokens)?;
+            connections.push(Connection { source, sink });
+        }
+
+        tokens.expect_punct(';')?;
+    }
+
+    Ok(Implementation::Inline { instances, connections })
+}
+
+fn parse_port_ref(tokens: &mut TokenStream) -> Result<PortRef, SyntaxError> {
+    let first = tokens.expect_ident()?;
+
+    if tokens.eat_punct('.') {
+        Ok(PortRef { instance: Some(first), port: tokens.expect_ident()? })
+    } else {
+        Ok(PortRef { instance: None, port: first })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{backend::generate_til, testing::example_designs};
+
+    #[test]
+    fn examples_round_trip() {
+        for (name, design, gen_params) in example_designs() {
+            let namespace = generate_til(&design, &gen_params);
+            let parsed = parse(&namespace.to_string()).unwrap_or_else(|err| panic!("{}: {}", name, err));
+
+            assert_eq!(parsed, namespace, "{}", name);
+        }
+    }
+
+    #[test]
+    fn parses_generics_floats_and_comments() {
+        let namespace = parse("
+            // Types of the parsers
+            namespace a::b {
+                type S<d: dimensionality = 2> = Stream (
+                    data: Group(x: Bits(8), y: Bits(1)),
+                    throughput: 2.5,
+                    dimensionality: d,
+                    synchronicity: Sync,
+                    complexity: 8,
+                );
+
+                streamlet p = <
+                    NESTING_LEVEL: dimensionality = 3,
+                    OFFSET: integer = -1,
+                > (
+                    input: in S<NESTING_LEVEL+1>, // with a comment
+                    output: out S<NESTING_LEVEL>,
+                ) {
+                    impl: \"./vhdl_dir\"
+                };
+            }
+        ").unwrap();
+
+        assert_eq!(namespace.path, vec!["a", "b"]);
+
+        let Item::Type(type_def) = &namespace.items[0] else { panic!("expected a type") };
+        assert_eq!(type_def.stream.throughput, 2.5);
+        assert_eq!(type_def.params[0].kind, GenericKind::Dimensionality);
+
+        let Item::Streamlet(streamlet) = &namespace.items[1] else { panic!("expected a streamlet") };
+        assert_eq!(streamlet.params[1].default, -1);
+        assert_eq!(streamlet.ports[0].dim, Some(DimExpr::new(Some("NESTING_LEVEL".to_string()), 1)));
+
+        // The printed namespace parses back into the same tree
+        assert_eq!(parse(&namespace.to_string()).unwrap(), namespace);
+    }
+
+    #[test]
+    fn rejects_malformed_namespaces() {
+        assert!(matches!(parse("namespace a { type S = Stream ( data: Bits(8) ) }"), Err(SyntaxError::UnexpectedToken { .. })));
+        assert!(matches!(parse("namespace a {"), Err(SyntaxError::UnexpectedEnd)));
+        assert!(matches!(parse("namespace a { } b"), Err(SyntaxError::UnexpectedToken { .. })));
+    }
+}
@@ -0,0 +1,93 @@
+use super::{Generator, GeneratorError, GeneratorParams, backend::Design, config::Config, types::{TilStreamlet, til_streamlet::TilImplementationType}};
+
+/**********************************************************************************
+ * Fixtures shared by the tests. Every test starts from the generator that the    *
+ * command line creates by default, and only sets the options it is about.        *
+ **********************************************************************************/
+
+// Options to set on a generator before it analyzes a document
+type Setup = fn(&mut Generator);
+
+// The ways in which the example documents are analyzed, every one changes the shape of the output
+const VARIANTS: [(&str, Setup); 4] = [
+    ("flat", |_| {}),
+    ("hierarchical", |generator| generator.set_hierarchical(true)),
+    ("grouped", |generator| generator.set_group_records(true)),
+    ("batches", |generator| generator.set_framing("batches").unwrap()),
+];
+
+// Analyze the samples of a document with a generator that `setup` configured first
+pub fn generator_of_samples(samples: &[&str], setup: impl FnOnce(&mut Generator)) -> Result<Generator, GeneratorError> {
+    let mut generator = Generator::new("test", 4, 64);
+    setup(&mut generator);
+
+    generator.analyze_samples(samples)?;
+
+    Ok(generator)
+}
+
+// Analyze a single sample with a generator that `setup` configured first
+pub fn generator_of(json: &str, setup: impl FnOnce(&mut Generator)) -> Result<Generator, GeneratorError> {
+    generator_of_samples(&[json], setup)
+}
+
+// The design of the samples of a document, analyzed by a generator that `setup` configured first
+pub fn design_of_samples(samples: &[&str], setup: impl FnOnce(&mut Generator)) -> Result<Design, GeneratorError> {
+    generator_of_samples(samples, setup)?.get_design()
+}
+
+// The design of a single sample, analyzed by a generator that `setup` configured first
+pub fn design_of(json: &str, setup: impl FnOnce(&mut Generator)) -> Result<Design, GeneratorError> {
+    design_of_samples(&[json], setup)
+}
+
+// The design of a single sample, analyzed by a generator with a project configuration
+pub fn design_of_config(json: &str, config: &str) -> Result<Design, GeneratorError> {
+    let mut generator = Generator::new("test", 4, 64);
+    generator.apply_config(&Config::parse(config).unwrap())?;
+    generator.analyze_samples(&[json])?;
+
+    generator.get_design()
+}
+
+// The connections of the netlist of a streamlet as `instance.port` pairs, the ports of the streamlet itself have no instance
+pub fn connections(streamlet: &TilStreamlet) -> Vec<(String, String)> {
+    let Some(TilImplementationType::Inline(implementation)) = streamlet.get_implementation() else {
+        return Vec::new();
+    };
+
+    let port = |instance: Option<&String>, port: &str| match instance {
+        Some(instance) => format!("{}.{}", instance, port),
+        None => port.to_string(),
+    };
+
+    implementation.get_signals().iter()
+        .map(|signal| (
+            port(signal.get_source_inst_name(), signal.get_source_stream_name()),
+            port(signal.get_dest_inst_name(), signal.get_dest_stream_name()),
+        ))
+        .collect()
+}
+
+// The designs of the example documents, with the parameters they were generated with
+pub fn example_designs() -> Vec<(String, Design, GeneratorParams)> {
+    let mut designs = Vec::new();
+
+    for entry in std::fs::read_dir("examples").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|extension| extension != "json") {
+            continue;
+        }
+
+        let json = std::fs::read_to_string(&path).unwrap();
+
+        for (variant, setup) in VARIANTS {
+            let mut generator = generator_of(&json, setup).unwrap();
+
+            let design = generator.get_design().unwrap();
+            designs.push((format!("{} ({})", path.display(), variant), design, generator.gen_params));
+        }
+    }
+
+    designs
+}
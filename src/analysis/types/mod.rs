@@ -10,6 +10,8 @@ pub struct TilStreamlet {
     name: String,
     streams: TilStreamingInterface,
     implementation: Option<TilImplementationType>,
+    // Whether the instances pass the dimensionality generics, which makes the streamlet a template in TD
+    parameterized: bool,
 }
 
 #[derive(Clone)]
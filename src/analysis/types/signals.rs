@@ -1,34 +1,7 @@
-use std::fmt::{Display, Formatter};
+use crate::analysis::syntax::{til, td};
 
 use super::TilSignal;
 
-impl Display for TilSignal {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut signal = String::new();
-
-        // If the signal is from an instance, add the instance name
-        if let Some(source_inst_name) = self.get_source_inst_name() {
-            signal.push_str(&format!("{}.", source_inst_name));
-        }
-
-        // Add stream name
-        signal.push_str(self.get_source_stream_name());
-
-        // Connector
-        signal.push_str(" -- ");
-
-        // If the signal is to an instance, add the instance name
-        if let Some(dest_inst_name) = self.get_dest_inst_name() {
-            signal.push_str(&format!("{}.", dest_inst_name));
-        }
-
-        // Add stream name
-        signal.push_str(self.get_dest_stream_name());
-
-        write!(f, "{};", signal)
-    }
-}
-
 impl TilSignal {
     pub fn get_source_inst_name(&self) -> Option<&String> {
         match self {
@@ -62,32 +35,17 @@ impl TilSignal {
         }
     }
 
-    pub fn td(&self) -> String {
-        let mut signal = String::new();
-
-        // If the signal is from an instance, add the instance name
-        if let Some(source_inst_name) = self.get_source_inst_name() {
-            signal.push_str(&format!("    {}.", source_inst_name));
-        } else {
-            signal.push_str(&"self.".to_string());
+    pub fn to_til(&self) -> til::Connection {
+        til::Connection {
+            source: til::PortRef { instance: self.get_source_inst_name().cloned(), port: self.get_source_stream_name().clone() },
+            sink: til::PortRef { instance: self.get_dest_inst_name().cloned(), port: self.get_dest_stream_name().clone() },
         }
+    }
 
-        // Add stream name
-        signal.push_str(self.get_source_stream_name());
-
-        // Connector
-        signal.push_str(" => ");
-
-        // If the signal is to an instance, add the instance name
-        if let Some(dest_inst_name) = self.get_dest_inst_name() {
-            signal.push_str(&format!("{}.", dest_inst_name));
-        } else {
-            signal.push_str(&"self.".to_string());
+    pub fn to_td(&self) -> td::Connection {
+        td::Connection {
+            source: td::PortRef { instance: self.get_source_inst_name().cloned(), port: self.get_source_stream_name().clone() },
+            sink: td::PortRef { instance: self.get_dest_inst_name().cloned(), port: self.get_dest_stream_name().clone() },
         }
-
-        // Add stream name
-        signal.push_str(self.get_dest_stream_name());
-
-        format!("{};", signal)
     }
-}
\ No newline at end of file
+}
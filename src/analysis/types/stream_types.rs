@@ -1,6 +1,4 @@
-use std::{fmt::{Formatter, Display}, cmp::Ordering};
-
-use crate::analysis::analyzer::type_manager::StreamType;
+use crate::analysis::{analyzer::type_manager::StreamType, syntax::DimExpr};
 
 #[derive(Clone)]
 pub struct StreamTypeDecl {
@@ -20,21 +18,12 @@ impl StreamTypeDecl {
         self.stream_type.get_name()
     }
 
-    pub fn get_stream_dim(&self) -> &Option<StreamDim> {
-        &self.stream_dim
+    pub fn get_stream_type(&self) -> StreamType {
+        self.stream_type.clone()
     }
-}
-
-impl Display for StreamTypeDecl {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut inst_str = String::new();
-        inst_str.push_str(self.get_name());
 
-        if let Some(dim) = &self.stream_dim {
-            inst_str.push_str(&dim.to_string());
-        }
-
-        write!(f, "{}", inst_str)
+    pub fn get_stream_dim(&self) -> &Option<StreamDim> {
+        &self.stream_dim
     }
 }
 
@@ -69,33 +58,12 @@ impl StreamDim {
     pub fn get_true_value(&self) -> usize {
         (self.value as isize + self.additive) as usize
     }
-}
-
-impl Display for StreamDim {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut dim = String::new();
-
-        let mut has_name = false;
-
-        dim.push('<');
 
-        if let Some(name) = &self.name {
-            dim.push_str(&name.to_string());
-            has_name = true;
+    // Named dimensions refer to a generic of the streamlet, others are printed as their value
+    pub fn to_dim_expr(&self) -> DimExpr {
+        match &self.name {
+            Some(name) => DimExpr::new(Some(name.clone()), self.additive),
+            None => DimExpr::fixed(self.get_true_value()),
         }
-
-        if has_name {
-            match self.additive.cmp(&0) {
-                Ordering::Greater => dim.push_str(&format!("+{}", self.additive)),
-                Ordering::Less => dim.push_str(&format!("{}", self.additive)),
-                Ordering::Equal => {}
-            }
-        } else {
-            dim.push_str(&format!("{}", self.get_true_value()));
-        }        
-
-        dim.push('>');
-
-        write!(f, "{}", dim)
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,4 @@
-use std::fmt::{Display, Formatter};
+use crate::analysis::syntax::{til, td, DimExpr, Direction};
 
 use super::{TilStreamingInterface, stream_types::StreamTypeDecl};
 
@@ -72,32 +72,42 @@ impl TilStream {
         &self.stream_type
     }
 
-    pub fn td(&self) -> String {
-        let type_dim = self.get_type().get_stream_dim();
-        if (type_dim.is_some()) {
-            return format!("{}: t{}.{} {};", self.get_name(), type_dim.as_ref().unwrap(), self.get_type().get_name(), self.direction);
+    pub fn get_direction(&self) -> TilStreamDirection {
+        self.direction
+    }
+
+    pub fn to_til(&self) -> til::Port {
+        til::Port {
+            name: self.name.clone(),
+            direction: self.direction.into(),
+            type_name: self.stream_type.get_name().to_string(),
+            dim: self.stream_type.get_stream_dim().as_ref().map(|dim| dim.to_dim_expr()),
         }
-        format!("{}: t<0>.{} {};", self.get_name(), self.get_type().get_name(), self.direction)
     }
-}
 
-impl Display for TilStream {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {} {}", self.get_name(), self.direction, self.get_type())
+    pub fn to_td(&self, container: &str) -> td::Port {
+        td::Port {
+            name: self.name.clone(),
+            container: container.to_string(),
+            // Streams without dimensionality are instantiated with zero dimensions
+            dim: self.stream_type.get_stream_dim().as_ref().map_or(DimExpr::fixed(0), |dim| dim.to_dim_expr()),
+            type_name: self.stream_type.get_name().to_string(),
+            direction: self.direction.into(),
+        }
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum TilStreamDirection {
     Input,
     Output,
 }
 
-impl Display for TilStreamDirection {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            TilStreamDirection::Input => write!(f, "in"),
-            TilStreamDirection::Output => write!(f, "out"),
+impl From<TilStreamDirection> for Direction {
+    fn from(direction: TilStreamDirection) -> Self {
+        match direction {
+            TilStreamDirection::Input => Direction::In,
+            TilStreamDirection::Output => Direction::Out,
         }
     }
 }
@@ -124,24 +134,31 @@ impl Generic {
         &self.generic_type
     }
 
-    pub fn td(&self) -> String {
-        let value = match self.get_type() {
+    pub fn get_value(&self) -> i64 {
+        match self.get_type() {
             GenericType::Integer(value) => *value as i64,
             GenericType::Natural(value) => *value as i64,
             GenericType::Positive(value) => *value as i64,
             GenericType::Dimensionality(value) => *value as i64
+        }
+    }
+
+    pub fn to_til(&self) -> til::GenericParam {
+        let kind = match self.get_type() {
+            GenericType::Integer(_) => til::GenericKind::Integer,
+            GenericType::Natural(_) => til::GenericKind::Natural,
+            GenericType::Positive(_) => til::GenericKind::Positive,
+            GenericType::Dimensionality(_) => til::GenericKind::Dimensionality,
         };
-        format!("    {} = {};\n", self.get_name(), value)
+
+        til::GenericParam { name: self.name.clone(), kind, default: self.get_value() }
     }
-}
 
-impl Display for Generic {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {}", self.get_name(), self.get_type())
+    pub fn to_td(&self) -> td::Constant {
+        td::Constant { name: self.name.clone(), value: self.get_value() }
     }
 }
 
-
 #[derive(Clone, Copy)]
 pub enum GenericType {
     Integer(isize),
@@ -149,14 +166,3 @@ pub enum GenericType {
     Positive(usize),
     Dimensionality(usize),
 }
-
-impl Display for GenericType {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            GenericType::Integer(value) => write!(f, "integer = {}", value),
-            GenericType::Natural(value) => write!(f, "natural = {}", value),
-            GenericType::Positive(value) => write!(f, "positive = {}", value),
-            GenericType::Dimensionality(value) => write!(f, "dimensionality = {}", value),
-        }
-    }
-}
\ No newline at end of file
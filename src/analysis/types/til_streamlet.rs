@@ -1,8 +1,6 @@
-use std::fmt::{Display, Formatter};
+use crate::analysis::syntax::{til, td};
 
-use indoc::{formatdoc, writedoc};
-
-use super::{TilStreamlet, TilStreamingInterface, TilSignal};
+use super::{TilStreamlet, TilStreamingInterface, TilSignal, stream_types::StreamDim, streaming_interface::{Generic, GenericType}};
 
 impl TilStreamlet {
     pub fn new(name: &str) -> TilStreamlet {
@@ -10,6 +8,7 @@ impl TilStreamlet {
             name: String::from(name),
             streams: TilStreamingInterface::default(),
             implementation: None,
+            parameterized: false,
         }
     }
 
@@ -37,89 +36,47 @@ impl TilStreamlet {
         &self.implementation
     }
 
-    pub fn td (&self) -> String {
-        let mut comp_def = String::new();
-        let mut generics = String::new();
-        let mut stream_defs = String::new();
-
-        if !self.get_streams().get_generics().is_empty() {
-            let mut generic_defs = "\n".to_string();
-
-            let str = self.get_streams().get_generics().iter().map(|e| e.td()).collect::<Vec<_>>().join("");
-            generic_defs.push_str(&str);
-
-            generics = generic_defs;
-        }
-
-        for stream in self.get_streams().get_streams() {
-            stream_defs.push_str(
-                &format!("    {}\n", stream.td())
-            );
-        }
-
-        comp_def.push_str(
-            &formatdoc!(
-                "streamlet {} {{{generics}\n{stream_defs}}}",
-                self.get_name(),
-            )
-        );
-
-        if let Some(implementation) = &self.implementation {
-            let temp_str = implementation.td(self.get_name().to_string());
-            comp_def += &temp_str;
-        }
+    pub fn set_parameterized(&mut self, parameterized: bool) {
+        self.parameterized = parameterized;
+    }
 
-        comp_def
+    /// The generics that are passed at instantiation: the dimensionalities of a parameterized streamlet
+    pub fn get_params(&self) -> Vec<&Generic> {
+        self.get_streams().get_generics().iter()
+            .filter(|generic| self.parameterized && matches!(generic.get_type(), GenericType::Dimensionality(_)))
+            .collect()
     }
-}
 
-impl Display for TilStreamlet {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut comp_def = String::new();
-        let mut generics = String::new();
-        let mut stream_defs = String::new();
-
-        if !self.get_streams().get_generics().is_empty() {
-            let mut generic_defs = String::new();
-
-            for generic in self.get_streams().get_generics() {
-                generic_defs.push_str(
-                    &format!("{},\n", generic)
-                );
-            }
-
-            generics = formatdoc!(
-                "
-                <
-                {}
-                >",
-                generic_defs
-            );
+    pub fn to_til(&self) -> til::Streamlet {
+        til::Streamlet {
+            name: self.name.clone(),
+            params: self.get_streams().get_generics().iter().map(|generic| generic.to_til()).collect(),
+            ports: self.get_streams().get_streams().iter().map(|stream| stream.to_til()).collect(),
+            implementation: self.implementation.as_ref().map(|implementation| implementation.to_til()),
         }
+    }
 
-        for stream in self.get_streams().get_streams() {
-            stream_defs.push_str(
-                &format!("    {},\n", stream)
-            );
-        }
+    /// Generates the streamlet and, if present, its implementation
+    pub fn to_td(&self, container: &str) -> Vec<td::Item> {
+        let params: Vec<String> = self.get_params().iter().map(|generic| generic.get_name().to_string()).collect();
 
-        comp_def.push_str(
-            &formatdoc!(
-                "
-                streamlet {} = {} (
-                    {}
-                )",
-                self.get_name(),
-                generics,
-                stream_defs
-            )
-        );
+        let mut items = vec![
+            td::Item::Streamlet(td::Streamlet {
+                name: self.name.clone(),
+                params: params.clone(),
+                constants: self.get_streams().get_generics().iter()
+                    .filter(|generic| !params.iter().any(|param| param == generic.get_name()))
+                    .map(|generic| generic.to_td())
+                    .collect(),
+                ports: self.get_streams().get_streams().iter().map(|stream| stream.to_td(container)).collect(),
+            })
+        ];
 
         if let Some(implementation) = &self.implementation {
-            comp_def.push_str(&implementation.to_string());
+            items.push(td::Item::Impl(implementation.to_td(self.get_name(), &params)));
         }
 
-        write!(f, "{};", comp_def)
+        items
     }
 }
 
@@ -130,28 +87,35 @@ pub enum TilImplementationType {
 }
 
 impl TilImplementationType {
-    pub fn td(&self, name: String) -> String {
+    pub fn to_til(&self) -> til::Implementation {
+        match self {
+            TilImplementationType::Inline(inline) => til::Implementation::Inline {
+                instances: inline.get_instances().iter().map(|instance| instance.to_til()).collect(),
+                connections: inline.get_signals().iter().map(|signal| signal.to_til()).collect(),
+            },
+            TilImplementationType::Path(path) => til::Implementation::Path(path.clone()),
+        }
+    }
+
+    pub fn to_td(&self, name: &str, params: &[String]) -> td::Impl {
         match self {
-            TilImplementationType::Inline(inline) => {
-                let mut impl_til = String::new();
-
-                for instance in inline.get_instances() {
-                    impl_til.push_str(&instance.td());
-                    impl_til.push('\n');
-                }
-
-                impl_til.push_str("\n    ");
-
-                for signal in inline.get_signals() {
-                    impl_til.push_str(&signal.td());
-                    impl_til.push('\n');
-                }
-                
-                format!("\n\nimpl {name}_impl of {name} {{\n{impl_til}}}")
+            TilImplementationType::Inline(inline) => td::Impl {
+                name: format!("{}_impl", name),
+                params: params.to_vec(),
+                streamlet: name.to_string(),
+                external: false,
+                instances: inline.get_instances().iter().map(|instance| instance.to_td()).collect(),
+                connections: inline.get_signals().iter().map(|signal| signal.to_td()).collect(),
+            },
+            // TydiLang has no notion of an implementation path, the VHDL is linked in externally
+            TilImplementationType::Path(_) => td::Impl {
+                name: format!("{}_impl", name),
+                params: params.to_vec(),
+                streamlet: name.to_string(),
+                external: true,
+                instances: Vec::new(),
+                connections: Vec::new(),
             },
-            TilImplementationType::Path(path) => {
-                format!("\n\nimpl {name}_impl of {name} @External {{ }}")
-            }
         }
     }
 }
@@ -169,6 +133,10 @@ impl TilInlineImplementation {
         instance_name
     }
 
+    pub fn add_til_instance(&mut self, instance: TilInstance) {
+        self.instances.push(instance);
+    }
+
     pub fn add_signal(&mut self, signal: TilSignal) {
         self.signals.push(signal);
     }
@@ -186,52 +154,12 @@ impl TilInlineImplementation {
     }
 }
 
-impl Display for TilImplementationType {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            TilImplementationType::Inline(inline) => {
-                let mut impl_til = String::new();
-
-                for instance in inline.get_instances() {
-                    impl_til.push_str(&instance.to_string());
-                    impl_til.push('\n');
-                }
-
-                impl_til.push('\n');
-
-                for signal in inline.get_signals() {
-                    impl_til.push_str(&signal.to_string());
-                    impl_til.push('\n');
-                }
-
-                
-                writedoc!(f,
-                    "
-                    {{
-                        impl: {{
-                            {}
-                        }}
-                    }}",
-                    impl_til
-                )
-            },
-            TilImplementationType::Path(path) => {
-                writedoc!(f,
-                    "
-                    {{
-                        impl: \"{}\"
-                    }}",
-                    path
-                )
-            }
-        }
-    }
-}
-
 #[derive(Clone)]
 pub struct TilInstance {
     component_name: String,
     instance_name: String,
+    // The values of the dimensionality generics of a parameterized streamlet
+    generics: Vec<(String, StreamDim)>,
 }
 
 impl TilInstance {
@@ -239,16 +167,39 @@ impl TilInstance {
         TilInstance {
             component_name: String::from(component_name),
             instance_name: String::from(instance_name),
+            generics: Vec::new(),
         }
     }
 
-    pub fn td(&self) -> String {
-        format!("    instance {}({}_impl);", self.instance_name, self.component_name)
+    pub fn add_generic(&mut self, name: &str, value: StreamDim) {
+        self.generics.push((name.to_owned(), value));
+    }
+
+    pub fn get_generic(&self, name: &str) -> Option<&StreamDim> {
+        self.generics.iter().find(|(generic, _)| generic == name).map(|(_, value)| value)
+    }
+
+    pub fn get_component_name(&self) -> &str {
+        &self.component_name
+    }
+
+    pub fn get_instance_name(&self) -> &str {
+        &self.instance_name
     }
-}
 
-impl Display for TilInstance {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} = {};", self.instance_name, self.component_name)
+    pub fn to_til(&self) -> til::Instance {
+        til::Instance {
+            name: self.instance_name.clone(),
+            streamlet: self.component_name.clone(),
+            args: self.generics.iter().map(|(name, value)| (name.clone(), value.to_dim_expr())).collect(),
+        }
     }
-}
\ No newline at end of file
+
+    pub fn to_td(&self) -> td::Instance {
+        td::Instance {
+            name: self.instance_name.clone(),
+            implementation: format!("{}_impl", self.component_name),
+            args: self.generics.iter().map(|(_, value)| value.to_dim_expr()).collect(),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+/**********************************************************************************
+ * Schema validation in hardware: documents that do not match the structure of    *
+ * the samples are reported on an error stream instead of silently producing      *
+ * garbage on the outputs.                                                        *
+ **********************************************************************************/
+
+/// The kind of value a parser expects, which is decided by the first character of the value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Object,
+    Array,
+    String,
+    Integer,
+    Boolean,
+}
+
+impl ValueKind {
+    pub fn get_name(self) -> &'static str {
+        match self {
+            ValueKind::Object => "object",
+            ValueKind::Array => "array",
+            ValueKind::String => "string",
+            ValueKind::Integer => "integer",
+            ValueKind::Boolean => "boolean",
+        }
+    }
+
+    /// The characters a value of this kind can start with
+    pub fn get_first_chars(self) -> &'static [u8] {
+        match self {
+            ValueKind::Object => b"{",
+            ValueKind::Array => b"[",
+            ValueKind::String => b"\"",
+            ValueKind::Integer => b"-0123456789",
+            ValueKind::Boolean => b"tf",
+        }
+    }
+}
+
+/// The kind of a reported error, encoded as its code on the error stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    // A value of another kind than the one in the samples, e.g. a string where an integer was expected
+    TypeMismatch,
+    // A key of the samples that is missing from an object
+    MissingKey,
+    // A key that none of the samples had
+    UnknownKey,
+    // Objects or arrays that are nested deeper than in the samples
+    TooDeep,
+}
+
+impl ErrorKind {
+    pub const ALL: [ErrorKind; 4] = [ErrorKind::TypeMismatch, ErrorKind::MissingKey, ErrorKind::UnknownKey, ErrorKind::TooDeep];
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            ErrorKind::TypeMismatch => "type mismatch",
+            ErrorKind::MissingKey => "missing key",
+            ErrorKind::UnknownKey => "unknown key",
+            ErrorKind::TooDeep => "nesting too deep",
+        }
+    }
+
+    pub fn get_code(self) -> usize {
+        match self {
+            ErrorKind::TypeMismatch => 1,
+            ErrorKind::MissingKey => 2,
+            ErrorKind::UnknownKey => 3,
+            ErrorKind::TooDeep => 4,
+        }
+    }
+}
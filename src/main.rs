@@ -1,15 +1,15 @@
 mod analysis;
 
-use analysis::Generator;
+use analysis::{Generator, config::{Config, SchemaConfig}};
 use clap::Parser;
 use std::fs;
 use std::io::{self, Read};
 
 #[derive(Parser)]
 struct Args {
-    /// The input JSON file (if not provided, will read from stdin)
+    /// The input JSON file, can be given several times to merge the schema of several samples (if not provided, will read from stdin)
     #[arg(short, long)]
-    input: Option<String>,
+    input: Vec<String>,
 
     /// The output directory for generated files
     #[arg(short, long, default_value = "output")]
@@ -19,44 +19,271 @@ struct Args {
     #[arg(short, long)]
     visualize: bool,
 
+    /// A TOML file with the parameters of the project and overrides per path, the options given here take precedence
+    #[arg(short, long)]
+    config: Option<String>,
+
     /// The name of the parser (default: "schema_parser")
-    #[arg(long, default_value = "schema_parser")]
-    parser_name: String,
+    #[arg(long)]
+    parser_name: Option<String>,
 
     /// The number of entries per chunk (epc) (default: 4)
-    #[arg(long, default_value_t = 4)]
-    epc: usize,
+    #[arg(long)]
+    epc: Option<usize>,
+
+    /// Give subtrees that hold a small part of the characters of the samples fewer lanes than the EPC
+    #[arg(long)]
+    auto_epc: bool,
 
     /// The integer width (default: 64)
-    #[arg(long, default_value_t = 64)]
-    int_width: usize,
+    #[arg(long)]
+    int_width: Option<usize>,
+
+    /// Do not insert duplicators for streams that drive several components
+    #[arg(long)]
+    no_duplicators: bool,
+
+    /// Generate a streamlet per record and array instead of one flat top component
+    #[arg(long)]
+    hierarchical: bool,
+
+    /// Bundle the integer, boolean, formatted and enum fields of a record into a single group-typed output stream
+    #[arg(long)]
+    group_records: bool,
+
+    /// Path of an object with dynamic keys (e.g. `$.counts`) to parse as a map, can be given several times
+    #[arg(long)]
+    map: Vec<String>,
+
+    /// Parse every object with at least this many keys of the same value type as a map
+    #[arg(long)]
+    map_min_keys: Option<usize>,
+
+    /// Path of an array (e.g. `$.location`) to parse as a tuple with a parser per position, can be given several times
+    #[arg(long)]
+    tuple: Vec<String>,
+
+    /// Path of a value (e.g. `$.metadata`) to forward as JSON text instead of parsing it, can be given several times
+    #[arg(long)]
+    raw: Vec<String>,
+
+    /// Forward every object and array nested deeper than this as JSON text, the root is at depth 0
+    #[arg(long)]
+    raw_depth: Option<usize>,
+
+    /// Strings of which the escapes are decoded into UTF-8 (e.g. `$.name`), can be given several times
+    #[arg(long)]
+    decode: Vec<String>,
+
+    /// Keep the quotes around decoded strings
+    #[arg(long)]
+    keep_quotes: bool,
+
+    /// Strings to encode as enum codes, given as `<path>=<value>,<value>,...` (e.g. `$.study=Computer Science,Mathematics`), can be given several times
+    #[arg(long = "enum")]
+    enums: Vec<String>,
+
+    /// Encode every string that takes at most this many distinct values over all samples as an enum
+    #[arg(long)]
+    enum_max_values: Option<usize>,
+
+    /// Strings to parse in a format, given as `<path>=<format>` with date, datetime, uuid, ipv4, hex or int (e.g. `$.birthdate=date`), can be given several times
+    #[arg(long)]
+    format: Vec<String>,
+
+    /// Parse every string of which all sample values are dates, datetimes, UUIDs, IPv4 addresses, `0x` prefixed hex numbers or integers
+    #[arg(long)]
+    detect_formats: bool,
+
+    /// Add an output with the number of elements of every array
+    #[arg(long)]
+    array_length: bool,
+
+    /// Add an output with the index of every array element
+    #[arg(long)]
+    array_index: bool,
+
+    /// Only output the records that match, e.g. `$.students[].grade >= 60 && $.students[].active == true`, can be given several times
+    #[arg(long)]
+    filter: Vec<String>,
+
+    /// Output a reduction of the elements of an array, e.g. `sum($.students[].grades[])`, with count, sum, min or max, can be given several times
+    #[arg(long)]
+    aggregate: Vec<String>,
+
+    /// Add an output that reports the documents that do not match the structure of the samples
+    #[arg(long)]
+    validate: bool,
+
+    /// How the documents are delimited in the input: document, stream, batches or ndjson (default: stream)
+    #[arg(long)]
+    framing: Option<String>,
+
+    /// A schema of a project with several top streamlets, given as `<name>=<file>,<file>,...` (e.g. `orders=orders.json`), of which the top streamlet is named after it, can be given several times
+    #[arg(long)]
+    schema: Vec<String>,
+
+    /// The backends to generate output with (default: til,td,vhdl,toml)
+    #[arg(long, value_delimiter = ',')]
+    backend: Option<Vec<String>>,
 }
 
 fn main() {
     let args = Args::parse();
 
-    // Read the JSON input, either from a file or from stdin.
-    let input_string = if let Some(input_path) = args.input {
-        // Read from the provided input file
-        fs::read_to_string(input_path).expect("Failed to read input file")
-    } else {
+    // Read the project configuration, if any
+    let config = match &args.config {
+        Some(path) => Config::from_file(path).unwrap_or_else(|err| {
+            eprintln!("invalid configuration file '{}': {}", path, err);
+            std::process::exit(1);
+        }),
+        None => Config::default(),
+    };
+
+    // The schemas of a project with several top components, the samples of a schema that are given
+    // on the command line replace those in the configuration
+    let mut schemas: Vec<(String, Vec<String>)> = config.get_schemas().iter()
+        .map(|(name, schema)| (name.clone(), schema.get_input().clone()))
+        .collect();
+
+    for spec in &args.schema {
+        let (name, files) = match spec.split_once('=') {
+            Some((name, files)) if !files.is_empty() => (name.trim().to_string(), files.split(',').map(|file| file.trim().to_string()).collect()),
+            _ => {
+                eprintln!("invalid schema '{}', expected '<name>=<file>,<file>,...'", spec);
+                std::process::exit(1);
+            },
+        };
+
+        match schemas.iter_mut().find(|(schema_name, _)| *schema_name == name) {
+            Some((_, schema_files)) => *schema_files = files,
+            None => schemas.push((name, files)),
+        }
+    }
+
+    if !schemas.is_empty() && !args.input.is_empty() {
+        eprintln!("the samples are given either with --input or per schema, not both");
+        std::process::exit(1);
+    }
+
+    // Read the JSON input, either from the files or from stdin.
+    let input_strings: Vec<String> = if !args.input.is_empty() {
+        // Read from the provided input files
+        read_files(&args.input)
+    } else if schemas.is_empty() {
         // Read from stdin if no input file is provided
         let mut buffer = String::new();
         io::stdin().read_to_string(&mut buffer).expect("Failed to read from stdin");
-        buffer
+        vec![buffer]
+    } else {
+        Vec::new()
     };
 
     // Create a new generator
-    let mut generator = Generator::new(&args.parser_name, args.epc, args.int_width);
+    let parser_name = args.parser_name.as_deref().or(config.get_parser_name()).unwrap_or("schema_parser");
+    let epc = args.epc.or(config.get_epc()).unwrap_or(4);
+    let int_width = args.int_width.or(config.get_int_width()).unwrap_or(64);
+    let mut generator = Generator::new(parser_name, epc, int_width);
+
+    generator.set_insert_duplicators(!args.no_duplicators);
+    generator.set_hierarchical(args.hierarchical);
+    generator.set_group_records(args.group_records);
+    generator.set_maps(&args.map, args.map_min_keys);
+    generator.set_tuples(&args.tuple);
+    generator.set_raw(&args.raw, args.raw_depth);
+    generator.set_array_side_band(args.array_length, args.array_index);
+    generator.set_validation(args.validate);
+    generator.set_decoded_strings(&args.decode, args.keep_quotes);
+    generator.set_enum_max_values(args.enum_max_values);
+    generator.set_detect_formats(args.detect_formats);
+    generator.set_auto_epc(args.auto_epc);
+
+    if let Some(framing) = args.framing.as_deref().or(config.get_framing()) {
+        if let Err(err) = generator.set_framing(framing) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
 
-    // Analyze the JSON string
-    generator.analyze(&input_string).unwrap();
+    if let Err(err) = generator.apply_config(&config) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+
+    // Select the output formats, the command line takes precedence over the configuration
+    if let Some(backends) = &args.backend {
+        if let Err(err) = generator.select_backends(backends) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+
+    for spec in &args.format {
+        if let Err(err) = generator.add_format(spec) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+
+    for spec in &args.enums {
+        if let Err(err) = generator.add_enum(spec) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+
+    for filter in &args.filter {
+        if let Err(err) = generator.add_filter(filter) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+
+    for aggregation in &args.aggregate {
+        if let Err(err) = generator.add_aggregation(aggregation) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+
+    // Analyze the JSON samples, of the project or of every schema
+    if schemas.is_empty() {
+        let samples: Vec<&str> = input_strings.iter().map(|input| input.as_str()).collect();
+        if let Err(err) = generator.analyze_samples(&samples) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+
+    let default_schema = SchemaConfig::default();
+    for (name, files) in &schemas {
+        if files.is_empty() {
+            eprintln!("schema '{}' has no samples", name);
+            std::process::exit(1);
+        }
+
+        let schema_strings = read_files(files);
+        let samples: Vec<&str> = schema_strings.iter().map(|input| input.as_str()).collect();
+        let schema_config = config.get_schemas().get(name).unwrap_or(&default_schema);
+
+        if let Err(err) = generator.add_schema(name, &samples, schema_config) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
 
     if args.visualize {
         // Visualize the JSON string
         generator.visualize("output/schema.dot").unwrap();
     }
 
-    // Generate TIL and TL code
-    generator.generate(&args.output).unwrap();
-}
\ No newline at end of file
+    // Generate the output of the selected backends
+    if let Err(err) = generator.generate(&args.output) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+fn read_files(paths: &[String]) -> Vec<String> {
+    paths.iter().map(|input_path| fs::read_to_string(input_path).expect("Failed to read input file")).collect()
+}